@@ -0,0 +1,163 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generator of TypeScript and Python client modules for transactions of Exonum services.
+//!
+//! The generator takes the layouts of the service transactions, as returned by
+//! [`Service::message_schemas`] and served by the node at `/api/system/v1/schemas`,
+//! and emits modules that serialize, sign and verify the transactions.
+//!
+//! Besides the modules, the generator produces [golden vectors](struct.GoldenVector.html):
+//! sample transactions encoded and signed by the Rust implementation. The generated tests
+//! check that the client modules produce the same bytes.
+//!
+//! # Limitations
+//!
+//! Only fields of primitive kinds listed in [`FieldKind`] are supported: `bool`, integers
+//! up to 64 bits, `&PublicKey`, `&Hash`, `&Signature`, `&str`, `&[u8]` and `Vec<u8>`.
+//! Messages with fields of other types, such as nested structures, enumerations or
+//! vectors of non-byte items, are rejected with [`Error::UnsupportedType`].
+//!
+//! Messages declared with `protobuf_transactions!` are rejected with
+//! [`Error::ProtobufMessage`]; use the standard protobuf tooling for them instead.
+//!
+//! Optional fields of versioned messages are ignored: the generated code produces
+//! the first version of the layout consisting of the required fields only, which
+//! the nodes accept as a valid message of the first version.
+//!
+//! [`FieldKind`]: enum.FieldKind.html
+//! [`Error::UnsupportedType`]: enum.Error.html#variant.UnsupportedType
+//! [`Error::ProtobufMessage`]: enum.Error.html#variant.ProtobufMessage
+//! [`Service::message_schemas`]: https://docs.rs/exonum/0.6.0/exonum/blockchain/trait.Service.html#method.message_schemas
+
+#![deny(missing_debug_implementations, missing_docs)]
+
+#[macro_use]
+extern crate failure;
+#[macro_use]
+extern crate exonum;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+
+#[cfg(test)]
+#[macro_use]
+extern crate pretty_assertions;
+
+pub use self::types::FieldKind;
+pub use self::vectors::{golden_vector, golden_vectors, GoldenVector};
+
+use exonum::api::public::ServiceSchemaInfo;
+use exonum::encoding::schema::{FieldSchema, MessageSchema};
+
+pub mod python;
+pub mod typescript;
+mod types;
+mod vectors;
+
+/// Errors of the code generation.
+#[derive(Debug, Fail)]
+pub enum Error {
+    /// The type of a field cannot be represented in the generated code.
+    #[fail(display = "Unsupported type `{}` of field `{}` in message `{}`", type_name, field,
+           message)]
+    UnsupportedType {
+        /// Name of the message.
+        message: String,
+        /// Name of the field.
+        field: String,
+        /// Type of the field.
+        type_name: String,
+    },
+    /// The body of the message is encoded with protobuf.
+    #[fail(display = "Message `{}` is encoded with protobuf as `{}`", message, protobuf_message)]
+    ProtobufMessage {
+        /// Name of the message.
+        message: String,
+        /// Full name of the protobuf message in the body.
+        protobuf_message: String,
+    },
+    /// The size of a field in the layout does not match its type.
+    #[fail(display = "Field `{}` in message `{}` takes {} bytes, but its type requires {}", field,
+           message, actual_size, expected_size)]
+    IncorrectFieldSize {
+        /// Name of the message.
+        message: String,
+        /// Name of the field.
+        field: String,
+        /// Size of the field in the layout.
+        actual_size: u32,
+        /// Size of the field according to its type.
+        expected_size: u32,
+    },
+}
+
+/// Layout of a field with the resolved kind.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedField<'a> {
+    /// Description of the field.
+    pub schema: &'a FieldSchema,
+    /// Kind of the field.
+    pub kind: FieldKind,
+}
+
+/// Resolves kinds of all fields of the message checking that their sizes match the layout.
+///
+/// Only the required fields are resolved; optional fields of versioned messages
+/// are not supported by the generated code and are skipped.
+pub fn resolve_fields(message: &MessageSchema) -> Result<Vec<ResolvedField>, Error> {
+    if let Some(ref protobuf_message) = message.protobuf_message {
+        return Err(Error::ProtobufMessage {
+            message: message.name.clone(),
+            protobuf_message: protobuf_message.clone(),
+        });
+    }
+    message
+        .fields
+        .iter()
+        .map(|field| {
+            let kind = FieldKind::from_type_name(&field.type_name).ok_or_else(|| {
+                Error::UnsupportedType {
+                    message: message.name.clone(),
+                    field: field.name.clone(),
+                    type_name: field.type_name.clone(),
+                }
+            })?;
+            if field.to - field.from != kind.size() {
+                return Err(Error::IncorrectFieldSize {
+                    message: message.name.clone(),
+                    field: field.name.clone(),
+                    actual_size: field.to - field.from,
+                    expected_size: kind.size(),
+                });
+            }
+            Ok(ResolvedField {
+                schema: field,
+                kind,
+            })
+        })
+        .collect()
+}
+
+/// Returns the header comment of the generated files.
+fn generated_notice(service: &ServiceSchemaInfo) -> String {
+    format!(
+        "Generated by exonum-codegen from the transactions of the `{}` service (id {}).\n\
+         Do not edit this file manually.",
+        service.service_name,
+        service.service_id
+    )
+}
@@ -0,0 +1,167 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+extern crate clap;
+extern crate exonum;
+extern crate exonum_codegen;
+extern crate serde_json;
+
+use clap::{App, Arg};
+use exonum::api::public::ServiceSchemaInfo;
+use exonum_codegen::{golden_vectors, python, typescript};
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process;
+
+fn main() {
+    let matches = App::new("exonum-codegen")
+        .about("Generates client modules for transactions of an Exonum service")
+        .arg(
+            Arg::with_name("SCHEMAS")
+                .help(
+                    "File with the output of the `/api/system/v1/schemas` endpoint, \
+                     `-` to read from stdin",
+                )
+                .required(true)
+                .index(1),
+        )
+        .arg(
+            Arg::with_name("SERVICE")
+                .long("service")
+                .short("s")
+                .takes_value(true)
+                .help("Name of the service, required if the file describes several services"),
+        )
+        .arg(
+            Arg::with_name("LANG")
+                .long("lang")
+                .short("l")
+                .takes_value(true)
+                .possible_values(&["typescript", "python"])
+                .default_value("typescript")
+                .help("Language of the generated module"),
+        )
+        .arg(
+            Arg::with_name("OUT")
+                .long("out")
+                .short("o")
+                .takes_value(true)
+                .default_value(".")
+                .help("Output directory"),
+        )
+        .arg(
+            Arg::with_name("MODULE")
+                .long("module")
+                .short("m")
+                .takes_value(true)
+                .help("Name of the generated module, the service name by default"),
+        )
+        .get_matches();
+
+    let result = run(
+        matches.value_of("SCHEMAS").unwrap(),
+        matches.value_of("SERVICE"),
+        matches.value_of("LANG").unwrap(),
+        Path::new(matches.value_of("OUT").unwrap()),
+        matches.value_of("MODULE"),
+    );
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(
+    schemas: &str,
+    service: Option<&str>,
+    lang: &str,
+    out: &Path,
+    module_name: Option<&str>,
+) -> Result<(), Box<std::error::Error>> {
+    let mut input = String::new();
+    if schemas == "-" {
+        io::stdin().read_to_string(&mut input)?;
+    } else {
+        File::open(schemas)?.read_to_string(&mut input)?;
+    }
+    let services: Vec<ServiceSchemaInfo> = serde_json::from_str(&input)?;
+    let service = select_service(services, service)?;
+    let module_name = module_name.map_or_else(
+        || service.service_name.replace('-', "_"),
+        str::to_owned,
+    );
+
+    fs::create_dir_all(out)?;
+    let vectors = golden_vectors(&service.messages).map_err(|e| e.to_string())?;
+    let (module_file, module, tests_file, tests) = match lang {
+        "typescript" => (
+            format!("{}.ts", module_name),
+            typescript::module(&service).map_err(|e| e.to_string())?,
+            format!("{}.test.ts", module_name),
+            typescript::tests(&service, &module_name),
+        ),
+        "python" => (
+            format!("{}.py", module_name),
+            python::module(&service).map_err(|e| e.to_string())?,
+            format!("test_{}.py", module_name),
+            python::tests(&service, &module_name),
+        ),
+        _ => unreachable!("Unknown language {}", lang),
+    };
+
+    write_file(&out.join(module_file), &module)?;
+    write_file(&out.join(tests_file), &tests)?;
+    write_file(
+        &out.join(format!("{}.vectors.json", module_name)),
+        &serde_json::to_string_pretty(&vectors)?,
+    )?;
+    Ok(())
+}
+
+fn select_service(
+    services: Vec<ServiceSchemaInfo>,
+    name: Option<&str>,
+) -> Result<ServiceSchemaInfo, String> {
+    match name {
+        Some(name) => {
+            services
+                .into_iter()
+                .find(|service| service.service_name == name)
+                .ok_or_else(|| format!("Service `{}` is not found", name))
+        }
+        None => {
+            if services.len() == 1 {
+                Ok(services.into_iter().next().unwrap())
+            } else {
+                let names: Vec<_> = services.iter().map(|s| s.service_name.as_str()).collect();
+                Err(format!(
+                    "Specify one of the services with `--service`: {}",
+                    names.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+fn write_file(path: &Path, content: &str) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(content.as_bytes())?;
+    if !content.ends_with('\n') {
+        file.write_all(b"\n")?;
+    }
+    println!("Written {}", path.display());
+    Ok(())
+}
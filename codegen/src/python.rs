@@ -0,0 +1,300 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of Python modules.
+//!
+//! For each transaction `TxName` the module defines the `serialize_tx_name` and
+//! `sign_tx_name` functions accepting the JSON representation of the transaction body
+//! as a `dict`. Generic `sign_message` and `verify_message` functions work with any message
+//! of the service. Signing uses [`PyNaCl`](https://pypi.org/project/PyNaCl/).
+
+use std::fmt::Write;
+
+use exonum::api::public::ServiceSchemaInfo;
+
+use super::{generated_notice, resolve_fields, Error, FieldKind};
+
+const PRELUDE: &str = r#"import struct
+
+from nacl.exceptions import BadSignatureError
+from nacl.signing import SigningKey, VerifyKey
+
+PROTOCOL_VERSION = 0
+HEADER_LENGTH = 10
+SIGNATURE_LENGTH = 64
+
+
+class _MessageWriter:
+    def __init__(self, message_id, body_size):
+        self.buffer = bytearray(HEADER_LENGTH + body_size)
+        self.buffer[1] = PROTOCOL_VERSION
+        struct.pack_into('<HH', self.buffer, 2, message_id, SERVICE_ID)
+
+    def write_int(self, offset, value, size, signed):
+        # Integers are JSON numbers, or decimal strings for 64-bit fields.
+        if isinstance(value, bool) or not isinstance(value, (int, str)):
+            raise TypeError('Expected integer, got {!r}'.format(value))
+        value = int(value, 10) if isinstance(value, str) else value
+        bits = 8 * size
+        low = -(1 << (bits - 1)) if signed else 0
+        high = (1 << (bits - 1)) - 1 if signed else (1 << bits) - 1
+        if not low <= value <= high:
+            raise ValueError('Value {} does not fit into {} bytes'.format(value, size))
+        self.buffer[offset:offset + size] = value.to_bytes(size, 'little', signed=signed)
+
+    def write_bool(self, offset, value):
+        if not isinstance(value, bool):
+            raise TypeError('Expected bool, got {!r}'.format(value))
+        self.buffer[offset] = 1 if value else 0
+
+    def write_fixed(self, offset, hex_value, size):
+        data = bytes.fromhex(hex_value)
+        if len(data) != size:
+            raise ValueError('Expected {} bytes, got {}'.format(size, len(data)))
+        self.buffer[offset:offset + size] = data
+
+    def write_segment(self, offset, data):
+        struct.pack_into('<II', self.buffer, offset, len(self.buffer), len(data))
+        self.buffer.extend(data)
+
+    def finish(self):
+        struct.pack_into('<I', self.buffer, 6, len(self.buffer) + SIGNATURE_LENGTH)
+        return bytes(self.buffer)
+
+
+def sign_message(message: bytes, signing_key: SigningKey) -> bytes:
+    """Appends the Ed25519 signature of the message."""
+    return message + signing_key.sign(message).signature
+
+
+def verify_message(signed: bytes, public_key: bytes) -> bool:
+    """Verifies the signature of the signed message with the 32-byte public key."""
+    if len(signed) < HEADER_LENGTH + SIGNATURE_LENGTH:
+        return False
+    try:
+        VerifyKey(public_key).verify(signed[:-SIGNATURE_LENGTH], signed[-SIGNATURE_LENGTH:])
+        return True
+    except BadSignatureError:
+        return False
+"#;
+
+/// Converts a `CamelCase` name into `snake_case`.
+pub fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn write_statement(kind: FieldKind, offset: u32, name: &str) -> String {
+    let value = format!("body['{}']", name);
+    let offset = format!("HEADER_LENGTH + {}", offset);
+    match kind {
+        FieldKind::Bool => format!("writer.write_bool({}, {})", offset, value),
+        FieldKind::Integer { bytes, signed } => {
+            let signed = if signed { "True" } else { "False" };
+            format!("writer.write_int({}, {}, {}, {})", offset, value, bytes, signed)
+        }
+        FieldKind::FixedBytes { bytes } => {
+            format!("writer.write_fixed({}, {}, {})", offset, value, bytes)
+        }
+        FieldKind::Str => format!("writer.write_segment({}, {}.encode('utf-8'))", offset, value),
+        FieldKind::Bytes => format!("writer.write_segment({}, bytes.fromhex({}))", offset, value),
+    }
+}
+
+/// Generates the Python module for the transactions of the service.
+pub fn module(service: &ServiceSchemaInfo) -> Result<String, Error> {
+    let mut out = String::new();
+    for line in generated_notice(service).lines() {
+        writeln!(out, "# {}", line).unwrap();
+    }
+    writeln!(
+        out,
+        "\"\"\"Client for the transactions of the `{}` service.\"\"\"\n",
+        service.service_name
+    ).unwrap();
+    out.push_str(PRELUDE);
+    writeln!(out, "\nSERVICE_ID = {}", service.service_id).unwrap();
+
+    writeln!(out, "\nMESSAGE_IDS = {{").unwrap();
+    for message in &service.messages {
+        writeln!(out, "    '{}': {},", message.name, message.message_id).unwrap();
+    }
+    writeln!(out, "}}").unwrap();
+
+    for message in &service.messages {
+        let fields = resolve_fields(message)?;
+        let name = snake_case(&message.name);
+
+        writeln!(out, "\n\ndef serialize_{}(body: dict) -> bytes:", name).unwrap();
+        writeln!(
+            out,
+            "    \"\"\"Serializes the `{}` transaction (message id {}) without the signature.",
+            message.name,
+            message.message_id
+        ).unwrap();
+        writeln!(out, "\n    Fields of the body:\n").unwrap();
+        for field in &fields {
+            writeln!(out, "    - `{}`: {}", field.schema.name, field.schema.type_name).unwrap();
+        }
+        writeln!(out, "    \"\"\"").unwrap();
+        writeln!(
+            out,
+            "    writer = _MessageWriter({}, {})",
+            message.message_id,
+            message.body_size
+        ).unwrap();
+        for field in &fields {
+            let statement = write_statement(field.kind, field.schema.from, &field.schema.name);
+            writeln!(out, "    {}", statement).unwrap();
+        }
+        writeln!(out, "    return writer.finish()").unwrap();
+
+        writeln!(
+            out,
+            "\n\ndef sign_{}(body: dict, signing_key: SigningKey) -> bytes:",
+            name
+        ).unwrap();
+        writeln!(
+            out,
+            "    \"\"\"Serializes the `{}` transaction and signs it.\"\"\"",
+            message.name
+        ).unwrap();
+        writeln!(out, "    return sign_message(serialize_{}(body), signing_key)", name).unwrap();
+    }
+    Ok(out)
+}
+
+/// Generates `unittest` tests of the Python module against the golden vectors.
+///
+/// The tests expect the module to be named `module_name` and the vectors to be stored
+/// in `<module_name>.vectors.json` in the same directory.
+pub fn tests(service: &ServiceSchemaInfo, module_name: &str) -> String {
+    let mut out = String::new();
+    for line in generated_notice(service).lines() {
+        writeln!(out, "# {}", line).unwrap();
+    }
+    writeln!(
+        out,
+        r#"import json
+import os
+import unittest
+
+from nacl.signing import SigningKey
+
+import {0} as client
+
+with open(os.path.join(os.path.dirname(__file__), '{0}.vectors.json')) as f:
+    VECTORS = json.load(f)
+
+SIGNERS = {{"#,
+        module_name
+    ).unwrap();
+    for message in &service.messages {
+        writeln!(
+            out,
+            "    '{}': client.sign_{},",
+            message.name,
+            snake_case(&message.name)
+        ).unwrap();
+    }
+    writeln!(
+        out,
+        r#"}}
+
+
+class GoldenVectorsTest(unittest.TestCase):
+    def test_golden_vectors(self):
+        for index, vector in enumerate(VECTORS):
+            with self.subTest(message=vector['message'], index=index):
+                signing_key = SigningKey(bytes.fromhex(vector['seed']))
+                public_key = bytes(signing_key.verify_key)
+                self.assertEqual(public_key.hex(), vector['public_key'])
+
+                signed = SIGNERS[vector['message']](vector['body'], signing_key)
+                self.assertEqual(signed.hex(), vector['hex'])
+                self.assertTrue(client.verify_message(signed, public_key))
+
+                tampered = bytearray(signed)
+                tampered[-65] ^= 1
+                self.assertFalse(client.verify_message(bytes(tampered), public_key))
+
+
+if __name__ == '__main__':
+    unittest.main()"#
+    ).unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum::encoding::schema::{FieldSchema, MessageSchema};
+
+    use super::*;
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("TxCreateWallet"), "tx_create_wallet");
+        assert_eq!(snake_case("transfer"), "transfer");
+    }
+
+    #[test]
+    fn test_python_module() {
+        let service = ServiceSchemaInfo {
+            service_id: 128,
+            service_name: "cryptocurrency".to_owned(),
+            messages: vec![
+                MessageSchema {
+                    name: "TxCreateWallet".to_owned(),
+                    service_id: 128,
+                    message_id: 0,
+                    body_size: 41,
+                    version: None,
+                    fields: vec![
+                        FieldSchema::new("pub_key", "&PublicKey", 0, 32),
+                        FieldSchema::new("name", "&str", 32, 40),
+                        FieldSchema::new("active", "bool", 40, 41),
+                    ],
+                    optional_fields: Vec::new(),
+                    structs: Vec::new(),
+                    enums: Vec::new(),
+                    protobuf_message: None,
+                },
+            ],
+        };
+
+        let module = module(&service).unwrap();
+        assert!(module.contains("\nSERVICE_ID = 128\n"));
+        assert!(module.contains("def serialize_tx_create_wallet(body: dict) -> bytes:"));
+        assert!(module.contains("    writer = _MessageWriter(0, 41)\n"));
+        assert!(module.contains(
+            "    writer.write_segment(HEADER_LENGTH + 32, body['name'].encode('utf-8'))\n",
+        ));
+        assert!(module.contains("    writer.write_bool(HEADER_LENGTH + 40, body['active'])\n"));
+        assert!(module.contains("raise ValueError('Value {} does not fit into {} bytes'"));
+
+        let tests = tests(&service, "cryptocurrency");
+        assert!(tests.contains("import cryptocurrency as client"));
+        assert!(tests.contains("    'TxCreateWallet': client.sign_tx_create_wallet,\n"));
+    }
+}
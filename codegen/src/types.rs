@@ -0,0 +1,116 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// Kind of a field supported by the generated code.
+///
+/// Kinds determine both the binary layout of the field and its JSON representation
+/// accepted by the generated modules, which follows the JSON representation used by Exonum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    /// `bool` stored as a single byte; JSON boolean.
+    Bool,
+    /// Little-endian integer; JSON number for integers up to 32 bits
+    /// and a decimal string for 64-bit integers.
+    Integer {
+        /// Size of the integer in bytes.
+        bytes: u32,
+        /// Is the integer signed?
+        signed: bool,
+    },
+    /// Fixed-size byte array such as `&PublicKey`, `&Hash` or `&Signature`
+    /// stored in place; JSON hex string.
+    FixedBytes {
+        /// Size of the array in bytes.
+        bytes: u32,
+    },
+    /// UTF-8 string stored as a segment; JSON string.
+    Str,
+    /// Byte slice or vector stored as a segment; JSON hex string.
+    Bytes,
+}
+
+impl FieldKind {
+    /// Returns the kind corresponding to the Rust type name from a field schema
+    /// or `None` if the type is not supported. Paths in the type name are ignored,
+    /// e.g. `&exonum::crypto::PublicKey` is the same as `&PublicKey`.
+    pub fn from_type_name(type_name: &str) -> Option<FieldKind> {
+        let (is_ref, name) = if type_name.starts_with('&') {
+            (true, &type_name[1..])
+        } else {
+            (false, type_name)
+        };
+        let name = name.rsplit("::").next().unwrap_or(name);
+
+        let kind = match (is_ref, name) {
+            (false, "bool") => FieldKind::Bool,
+            (false, "u8") => FieldKind::Integer { bytes: 1, signed: false },
+            (false, "i8") => FieldKind::Integer { bytes: 1, signed: true },
+            (false, "u16") => FieldKind::Integer { bytes: 2, signed: false },
+            (false, "i16") => FieldKind::Integer { bytes: 2, signed: true },
+            (false, "u32") => FieldKind::Integer { bytes: 4, signed: false },
+            (false, "i32") => FieldKind::Integer { bytes: 4, signed: true },
+            (false, "u64") => FieldKind::Integer { bytes: 8, signed: false },
+            (false, "i64") => FieldKind::Integer { bytes: 8, signed: true },
+            (true, "PublicKey") | (true, "Hash") => FieldKind::FixedBytes { bytes: 32 },
+            (true, "Signature") => FieldKind::FixedBytes { bytes: 64 },
+            (true, "str") => FieldKind::Str,
+            (true, "[u8]") | (false, "Vec<u8>") => FieldKind::Bytes,
+            _ => return None,
+        };
+        Some(kind)
+    }
+
+    /// Returns the size of the field in the header.
+    pub fn size(&self) -> u32 {
+        match *self {
+            FieldKind::Bool => 1,
+            FieldKind::Integer { bytes, .. } | FieldKind::FixedBytes { bytes } => bytes,
+            FieldKind::Str | FieldKind::Bytes => 8,
+        }
+    }
+
+    /// Is the field stored as a segment?
+    pub fn is_segment(&self) -> bool {
+        match *self {
+            FieldKind::Str | FieldKind::Bytes => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldKind;
+
+    #[test]
+    fn test_kind_from_type_name() {
+        assert_eq!(FieldKind::from_type_name("bool"), Some(FieldKind::Bool));
+        assert_eq!(
+            FieldKind::from_type_name("i64"),
+            Some(FieldKind::Integer {
+                bytes: 8,
+                signed: true,
+            })
+        );
+        assert_eq!(
+            FieldKind::from_type_name("&exonum::crypto::PublicKey"),
+            Some(FieldKind::FixedBytes { bytes: 32 })
+        );
+        assert_eq!(FieldKind::from_type_name("&str"), Some(FieldKind::Str));
+        assert_eq!(FieldKind::from_type_name("&[u8]"), Some(FieldKind::Bytes));
+        assert_eq!(FieldKind::from_type_name("PublicKey"), None);
+        assert_eq!(FieldKind::from_type_name("Vec<u8>"), Some(FieldKind::Bytes));
+        assert_eq!(FieldKind::from_type_name("Vec<u64>"), None);
+    }
+}
@@ -0,0 +1,360 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of TypeScript modules.
+//!
+//! For each transaction `Tx` the module exports the `Tx` interface describing
+//! the JSON representation of the transaction body and the `serializeTx` and `signTx`
+//! functions. Generic `signMessage` and `verifyMessage` functions work with any message
+//! of the service. Signing uses [`tweetnacl`](https://www.npmjs.com/package/tweetnacl).
+
+use std::fmt::Write;
+
+use exonum::api::public::ServiceSchemaInfo;
+
+use super::{generated_notice, resolve_fields, Error, FieldKind};
+
+const PRELUDE: &str = r#"import * as nacl from 'tweetnacl';
+
+const PROTOCOL_VERSION = 0;
+const HEADER_LENGTH = 10;
+const SIGNATURE_LENGTH = 64;
+
+/** Converts a hex string into bytes. */
+export function hexToBytes(hex: string): Uint8Array {
+  if (hex.length % 2 !== 0 || !/^[0-9a-fA-F]*$/.test(hex)) {
+    throw new TypeError(`Invalid hex string: ${hex}`);
+  }
+  const bytes = new Uint8Array(hex.length / 2);
+  for (let i = 0; i < bytes.length; i++) {
+    bytes[i] = parseInt(hex.substr(2 * i, 2), 16);
+  }
+  return bytes;
+}
+
+/** Converts bytes into a lowercase hex string. */
+export function bytesToHex(bytes: Uint8Array): string {
+  return Array.from(bytes, (b) => b.toString(16).padStart(2, '0')).join('');
+}
+
+class MessageWriter {
+  private readonly buffer: number[];
+
+  constructor(messageId: number, bodySize: number) {
+    this.buffer = new Array(HEADER_LENGTH + bodySize).fill(0);
+    this.buffer[1] = PROTOCOL_VERSION;
+    this.writeInt(2, messageId, 2, false);
+    this.writeInt(4, SERVICE_ID, 2, false);
+  }
+
+  writeInt(offset: number, value: number | string | bigint, size: number, signed: boolean) {
+    const bits = BigInt(size * 8);
+    let v = BigInt(value);
+    const min = signed ? -(1n << (bits - 1n)) : 0n;
+    const max = signed ? (1n << (bits - 1n)) - 1n : (1n << bits) - 1n;
+    if (v < min || v > max) {
+      throw new RangeError(`Value ${value} does not fit into ${size} bytes`);
+    }
+    v = BigInt.asUintN(size * 8, v);
+    for (let i = 0; i < size; i++) {
+      this.buffer[offset + i] = Number(v & 0xffn);
+      v >>= 8n;
+    }
+  }
+
+  writeBool(offset: number, value: boolean) {
+    if (typeof value !== 'boolean') {
+      throw new TypeError(`Expected boolean, got ${value}`);
+    }
+    this.buffer[offset] = value ? 1 : 0;
+  }
+
+  writeFixed(offset: number, hex: string, size: number) {
+    const data = hexToBytes(hex);
+    if (data.length !== size) {
+      throw new RangeError(`Expected ${size} bytes, got ${data.length}`);
+    }
+    data.forEach((b, i) => { this.buffer[offset + i] = b; });
+  }
+
+  writeSegment(offset: number, data: Uint8Array) {
+    this.writeInt(offset, this.buffer.length, 4, false);
+    this.writeInt(offset + 4, data.length, 4, false);
+    data.forEach((b) => this.buffer.push(b));
+  }
+
+  finish(): Uint8Array {
+    this.writeInt(6, this.buffer.length + SIGNATURE_LENGTH, 4, false);
+    return Uint8Array.from(this.buffer);
+  }
+}
+
+/** Appends the Ed25519 signature of the message made with the 64-byte secret key. */
+export function signMessage(message: Uint8Array, secretKey: Uint8Array): Uint8Array {
+  const signed = new Uint8Array(message.length + SIGNATURE_LENGTH);
+  signed.set(message);
+  signed.set(nacl.sign.detached(message, secretKey), message.length);
+  return signed;
+}
+
+/** Verifies the signature of the signed message with the 32-byte public key. */
+export function verifyMessage(signed: Uint8Array, publicKey: Uint8Array): boolean {
+  if (signed.length < HEADER_LENGTH + SIGNATURE_LENGTH) {
+    return false;
+  }
+  const length = signed.length - SIGNATURE_LENGTH;
+  return nacl.sign.detached.verify(
+    signed.subarray(0, length),
+    signed.subarray(length),
+    publicKey,
+  );
+}
+"#;
+
+fn field_type(kind: FieldKind) -> &'static str {
+    match kind {
+        FieldKind::Bool => "boolean",
+        FieldKind::Integer { bytes: 8, .. } => "string",
+        FieldKind::Integer { .. } => "number",
+        FieldKind::FixedBytes { .. } | FieldKind::Str | FieldKind::Bytes => "string",
+    }
+}
+
+fn write_statement(kind: FieldKind, offset: u32, name: &str) -> String {
+    let value = format!("body.{}", name);
+    let offset = format!("HEADER_LENGTH + {}", offset);
+    match kind {
+        FieldKind::Bool => format!("writer.writeBool({}, {});", offset, value),
+        FieldKind::Integer { bytes, signed } => {
+            format!("writer.writeInt({}, {}, {}, {});", offset, value, bytes, signed)
+        }
+        FieldKind::FixedBytes { bytes } => {
+            format!("writer.writeFixed({}, {}, {});", offset, value, bytes)
+        }
+        FieldKind::Str => {
+            format!(
+                "writer.writeSegment({}, new TextEncoder().encode({}));",
+                offset,
+                value
+            )
+        }
+        FieldKind::Bytes => format!("writer.writeSegment({}, hexToBytes({}));", offset, value),
+    }
+}
+
+/// Generates the TypeScript module for the transactions of the service.
+pub fn module(service: &ServiceSchemaInfo) -> Result<String, Error> {
+    let mut out = String::new();
+    for line in generated_notice(service).lines() {
+        writeln!(out, "// {}", line).unwrap();
+    }
+    out.push('\n');
+    writeln!(out, "/** Identifier of the `{}` service. */", service.service_name).unwrap();
+    writeln!(out, "export const SERVICE_ID = {};", service.service_id).unwrap();
+    out.push('\n');
+    out.push_str(PRELUDE);
+
+    out.push('\n');
+    writeln!(out, "/** Identifiers of the service transactions. */").unwrap();
+    writeln!(out, "export const MESSAGE_IDS = {{").unwrap();
+    for message in &service.messages {
+        writeln!(out, "  {}: {},", message.name, message.message_id).unwrap();
+    }
+    writeln!(out, "}};").unwrap();
+
+    for message in &service.messages {
+        let fields = resolve_fields(message)?;
+        let name = &message.name;
+
+        out.push('\n');
+        writeln!(
+            out,
+            "/** Body of the `{}` transaction (message id {}). */",
+            name,
+            message.message_id
+        ).unwrap();
+        writeln!(out, "export interface {} {{", name).unwrap();
+        for field in &fields {
+            writeln!(
+                out,
+                "  {}: {}; // {}",
+                field.schema.name,
+                field_type(field.kind),
+                field.schema.type_name
+            ).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+
+        out.push('\n');
+        writeln!(out, "/** Serializes the `{}` transaction without the signature. */", name)
+            .unwrap();
+        writeln!(out, "export function serialize{0}(body: {0}): Uint8Array {{", name).unwrap();
+        writeln!(
+            out,
+            "  const writer = new MessageWriter({}, {});",
+            message.message_id,
+            message.body_size
+        ).unwrap();
+        for field in &fields {
+            let statement = write_statement(field.kind, field.schema.from, &field.schema.name);
+            writeln!(out, "  {}", statement).unwrap();
+        }
+        writeln!(out, "  return writer.finish();").unwrap();
+        writeln!(out, "}}").unwrap();
+
+        out.push('\n');
+        writeln!(
+            out,
+            "/** Serializes the `{}` transaction and signs it with the 64-byte secret key. */",
+            name
+        ).unwrap();
+        writeln!(
+            out,
+            "export function sign{0}(body: {0}, secretKey: Uint8Array): Uint8Array {{",
+            name
+        ).unwrap();
+        writeln!(out, "  return signMessage(serialize{}(body), secretKey);", name).unwrap();
+        writeln!(out, "}}").unwrap();
+    }
+    Ok(out)
+}
+
+/// Generates tests of the TypeScript module against the golden vectors.
+///
+/// The tests use the `node:test` runner and expect the module to be named `module_name`
+/// and the vectors to be stored in `<module_name>.vectors.json` in the same directory.
+pub fn tests(service: &ServiceSchemaInfo, module_name: &str) -> String {
+    let mut out = String::new();
+    for line in generated_notice(service).lines() {
+        writeln!(out, "// {}", line).unwrap();
+    }
+    out.push('\n');
+    writeln!(
+        out,
+        r#"import {{ test }} from 'node:test';
+import * as assert from 'node:assert/strict';
+import {{ readFileSync }} from 'node:fs';
+import {{ join }} from 'node:path';
+import * as nacl from 'tweetnacl';
+import * as client from './{0}';
+
+interface GoldenVector {{
+  message: string;
+  seed: string;
+  public_key: string;
+  body: any;
+  hex: string;
+}}
+
+const vectors: GoldenVector[] = JSON.parse(
+  readFileSync(join(__dirname, '{0}.vectors.json'), 'utf8'),
+);
+
+const signers: {{ [name: string]: (body: any, secretKey: Uint8Array) => Uint8Array }} = {{"#,
+        module_name
+    ).unwrap();
+    for message in &service.messages {
+        writeln!(out, "  {0}: client.sign{0},", message.name).unwrap();
+    }
+    writeln!(
+        out,
+        r#"}};
+
+vectors.forEach((vector, index) => {{
+  test(`${{vector.message}} #${{index}}`, () => {{
+    const keyPair = nacl.sign.keyPair.fromSeed(client.hexToBytes(vector.seed));
+    assert.equal(client.bytesToHex(keyPair.publicKey), vector.public_key);
+
+    const signed = signers[vector.message](vector.body, keyPair.secretKey);
+    assert.equal(client.bytesToHex(signed), vector.hex);
+    assert.ok(client.verifyMessage(signed, keyPair.publicKey));
+
+    const tampered = signed.slice();
+    tampered[tampered.length - 65] ^= 1;
+    assert.ok(!client.verifyMessage(tampered, keyPair.publicKey));
+  }});
+}});"#
+    ).unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum::encoding::schema::{FieldSchema, MessageSchema};
+
+    use super::*;
+
+    fn service() -> ServiceSchemaInfo {
+        ServiceSchemaInfo {
+            service_id: 128,
+            service_name: "cryptocurrency".to_owned(),
+            messages: vec![
+                MessageSchema {
+                    name: "TxTransfer".to_owned(),
+                    service_id: 128,
+                    message_id: 1,
+                    body_size: 48,
+                    version: None,
+                    fields: vec![
+                        FieldSchema::new("from", "&PublicKey", 0, 32),
+                        FieldSchema::new("amount", "u64", 32, 40),
+                        FieldSchema::new("memo", "&str", 40, 48),
+                    ],
+                    optional_fields: Vec::new(),
+                    structs: Vec::new(),
+                    enums: Vec::new(),
+                    protobuf_message: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_typescript_module() {
+        let module = module(&service()).unwrap();
+        assert!(module.contains("export const SERVICE_ID = 128;"));
+        assert!(module.contains("export interface TxTransfer {\n  from: string; // &PublicKey\n"));
+        assert!(module.contains("  const writer = new MessageWriter(1, 48);\n"));
+        assert!(module.contains("  writer.writeFixed(HEADER_LENGTH + 0, body.from, 32);\n"));
+        assert!(module.contains("  writer.writeInt(HEADER_LENGTH + 32, body.amount, 8, false);\n"));
+        assert!(module.contains(
+            "  writer.writeSegment(HEADER_LENGTH + 40, new TextEncoder().encode(body.memo));\n",
+        ));
+        assert!(module.contains("export function signTxTransfer(body: TxTransfer"));
+
+        let tests = tests(&service(), "cryptocurrency");
+        assert!(tests.contains("import * as client from './cryptocurrency';"));
+        assert!(tests.contains("  TxTransfer: client.signTxTransfer,\n"));
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        let mut service = service();
+        service.messages[0].fields[1] = FieldSchema::new("amount", "Vec<u64>", 32, 40);
+        match module(&service) {
+            Err(Error::UnsupportedType { ref field, .. }) if field == "amount" => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_protobuf_message() {
+        let mut service = service();
+        service.messages[0].protobuf_message = Some("example.Transfer".to_owned());
+        match module(&service) {
+            Err(Error::ProtobufMessage { ref message, .. }) if message == "TxTransfer" => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
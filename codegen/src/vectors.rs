@@ -0,0 +1,228 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use exonum::crypto::{self, Hash, Seed, Signature};
+use exonum::encoding::schema::MessageSchema;
+use exonum::encoding::serialize::encode_hex;
+use exonum::messages::{MessageWriter, PROTOCOL_MAJOR_VERSION};
+use serde_json::{Map, Value};
+
+use super::{resolve_fields, Error, FieldKind};
+
+/// Number of golden vectors produced for each message.
+const VARIANTS: u8 = 2;
+
+/// Sample message encoded and signed by the Rust implementation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenVector {
+    /// Name of the message.
+    pub message: String,
+    /// Hex-encoded Ed25519 seed of the key pair which signed the message.
+    pub seed: String,
+    /// Hex-encoded public key corresponding to the seed.
+    pub public_key: String,
+    /// Body of the message in JSON.
+    pub body: Value,
+    /// Hex-encoded signed message.
+    pub hex: String,
+}
+
+/// Produces a golden vector for the message. Variant `0` contains typical field values,
+/// other variants contain edge cases: extreme integers, non-ASCII strings
+/// and empty segments.
+pub fn golden_vector(message: &MessageSchema, variant: u8) -> Result<GoldenVector, Error> {
+    let fields = resolve_fields(message)?;
+    let mut writer = MessageWriter::new(
+        PROTOCOL_MAJOR_VERSION,
+        message.service_id,
+        message.message_id,
+        message.body_size as usize,
+    );
+    let mut body = Map::new();
+
+    for field in &fields {
+        let (from, to) = (field.schema.from, field.schema.to);
+        let value = match field.kind {
+            FieldKind::Bool => {
+                let value = variant == 0;
+                writer.write(value, from, to);
+                json!(value)
+            }
+            FieldKind::Integer { bytes, signed } => {
+                write_integer(&mut writer, bytes, signed, variant, from, to)
+            }
+            FieldKind::FixedBytes { bytes } => {
+                let data = sample_bytes(&field.schema.name, variant, bytes as usize);
+                if bytes == 64 {
+                    writer.write(&Signature::from_slice(&data).unwrap(), from, to);
+                } else {
+                    writer.write(&Hash::from_slice(&data).unwrap(), from, to);
+                }
+                json!(encode_hex(&data))
+            }
+            FieldKind::Str => {
+                let value = if variant == 0 {
+                    "Hello, Exonum!"
+                } else {
+                    "Привет, мир! \u{1F600}"
+                };
+                writer.write(value, from, to);
+                json!(value)
+            }
+            FieldKind::Bytes => {
+                let value: &[u8] = if variant == 0 { &[1, 2, 3, 255] } else { &[] };
+                writer.write(value, from, to);
+                json!(encode_hex(value))
+            }
+        };
+        body.insert(field.schema.name.clone(), value);
+    }
+
+    let seed = sample_bytes(&message.name, variant, 32);
+    let (public_key, secret_key) = crypto::gen_keypair_from_seed(&Seed::from_slice(&seed).unwrap());
+    let signed = writer.sign(&secret_key);
+
+    Ok(GoldenVector {
+        message: message.name.clone(),
+        seed: encode_hex(&seed),
+        public_key: encode_hex(&public_key),
+        body: Value::Object(body),
+        hex: encode_hex(signed.as_ref()),
+    })
+}
+
+/// Produces golden vectors for all variants of all messages.
+pub fn golden_vectors(messages: &[MessageSchema]) -> Result<Vec<GoldenVector>, Error> {
+    let mut vectors = Vec::new();
+    for message in messages {
+        for variant in 0..VARIANTS {
+            vectors.push(golden_vector(message, variant)?);
+        }
+    }
+    Ok(vectors)
+}
+
+/// Returns deterministic pseudo-random bytes derived from the name and the variant.
+fn sample_bytes(name: &str, variant: u8, len: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(len);
+    let mut data = name.as_bytes().to_vec();
+    data.push(variant);
+    while bytes.len() < len {
+        let hash = crypto::hash(&data);
+        bytes.extend_from_slice(hash.as_ref());
+        data = hash.as_ref().to_vec();
+    }
+    bytes.truncate(len);
+    bytes
+}
+
+fn write_integer(
+    writer: &mut MessageWriter,
+    bytes: u32,
+    signed: bool,
+    variant: u8,
+    from: u32,
+    to: u32,
+) -> Value {
+    let typical = variant == 0;
+    match (bytes, signed) {
+        (1, false) => {
+            let value = if typical { 42_u8 } else { u8::max_value() };
+            writer.write(value, from, to);
+            json!(value)
+        }
+        (1, true) => {
+            let value = if typical { -42_i8 } else { i8::min_value() };
+            writer.write(value, from, to);
+            json!(value)
+        }
+        (2, false) => {
+            let value = if typical { 1_000_u16 } else { u16::max_value() };
+            writer.write(value, from, to);
+            json!(value)
+        }
+        (2, true) => {
+            let value = if typical { -1_000_i16 } else { i16::min_value() };
+            writer.write(value, from, to);
+            json!(value)
+        }
+        (4, false) => {
+            let value = if typical { 100_000_u32 } else { u32::max_value() };
+            writer.write(value, from, to);
+            json!(value)
+        }
+        (4, true) => {
+            let value = if typical { -100_000_i32 } else { i32::min_value() };
+            writer.write(value, from, to);
+            json!(value)
+        }
+        (8, false) => {
+            let value = if typical { 9_007_199_254_740_993_u64 } else { u64::max_value() };
+            writer.write(value, from, to);
+            json!(value.to_string())
+        }
+        (8, true) => {
+            let value = if typical { -9_007_199_254_740_993_i64 } else { i64::min_value() };
+            writer.write(value, from, to);
+            json!(value.to_string())
+        }
+        _ => unreachable!("Unsupported integer size {}", bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use exonum::crypto::{self, PublicKey, Seed};
+    use exonum::encoding::serialize::FromHex;
+    use exonum::encoding::serialize::json::ExonumJsonDeserialize;
+    use exonum::messages::Message;
+
+    use super::*;
+
+    messages! {
+        const SERVICE_ID = 42;
+
+        struct TxSample {
+            from: &PublicKey,
+            amount: u64,
+            delta: i16,
+            memo: &str,
+            data: &[u8],
+            flag: bool,
+        }
+    }
+
+    #[test]
+    fn test_golden_vectors_match_rust_encoding() {
+        let vectors = golden_vectors(&[TxSample::schema()]).unwrap();
+        assert_eq!(vectors.len(), 2);
+
+        for vector in vectors {
+            let tx = TxSample::from_hex(&vector.hex).unwrap();
+            let seed = Seed::from_slice(&Vec::<u8>::from_hex(&vector.seed).unwrap()).unwrap();
+            let (public_key, _) = crypto::gen_keypair_from_seed(&seed);
+            assert_eq!(encode_hex(&public_key), vector.public_key);
+            assert!(tx.verify_signature(&public_key));
+
+            let json = json!({
+                "body": vector.body,
+                "signature": encode_hex(tx.raw().signature()),
+                "message_id": 0,
+                "service_id": 42,
+                "protocol_version": 0,
+            });
+            assert_eq!(TxSample::deserialize(&json).unwrap(), tx);
+        }
+    }
+}
@@ -0,0 +1,33 @@
+#!/bin/bash
+
+#
+# Bash script checking the TypeScript and Python modules generated for the sample
+# service described in `schemas.json` against the golden vectors produced by
+# the Rust implementation.
+#
+# Requires Node.js 18+ with npm, and Python 3.6+ with PyNaCl.
+#
+
+set -e
+
+cd "$(dirname "$0")"
+OUT=$(mktemp -d)
+trap 'rm -rf "$OUT"' EXIT
+
+cargo run -p exonum-codegen -- schemas.json --lang typescript --out "$OUT/ts"
+cargo run -p exonum-codegen -- schemas.json --lang python --out "$OUT/py"
+
+# Compiles the TypeScript module with its tests and runs them with the built-in runner.
+(
+    cd "$OUT/ts"
+    npm init -y > /dev/null
+    npm install --silent tweetnacl typescript @types/node
+    ./node_modules/.bin/tsc --target es2020 --module commonjs --moduleResolution node \
+        sample.ts sample.test.ts
+    node --test sample.test.js
+)
+
+(
+    cd "$OUT/py"
+    python3 -m unittest -v test_sample
+)
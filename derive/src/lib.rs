@@ -0,0 +1,379 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Procedural derive of the Exonum binary encoding for ordinary Rust structures.
+//!
+//! `#[derive(EncodingStruct)]` is an alternative to the [`encoding_struct!`] macro.
+//! The structure stays an ordinary Rust structure with public fields, so it may have
+//! doc comments and attributes on its fields and other derives, and IDEs can navigate
+//! to its declaration. The derive implements [`Field`], [`StorageValue`], [`CryptoHash`],
+//! [`ExonumJson`] and [`ExonumJsonDeserialize`] traits, as well as `Serialize` and
+//! `Deserialize` traits of `serde` using the Exonum JSON representation.
+//!
+//! The binary layout is the same as the one of `encoding_struct!` with the same fields,
+//! with owned types used in place of the borrowed ones:
+//!
+//! | `encoding_struct!` | `#[derive(EncodingStruct)]` |
+//! |--------------------|-----------------------------|
+//! | `&str`             | `String`                    |
+//! | `&Hash`            | `Hash`                      |
+//! | `&PublicKey`       | `PublicKey`                 |
+//! | `&Signature`       | `Signature`                 |
+//! | `&[u8]`            | `Vec<u8>`                   |
+//! | `&[Hash]`          | `Vec<Hash>`                 |
+//!
+//! Unlike `encoding_struct!`, which reads fields lazily from the byte buffer,
+//! the derived `StorageValue::from_bytes` decodes all fields at once.
+//!
+//! The derive requires `exonum` to be declared in the crate root. Do not derive
+//! `Serialize` and `Deserialize` for the same structure, as they are already implemented.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! #[macro_use]
+//! extern crate exonum_derive;
+//! extern crate exonum;
+//!
+//! use exonum::crypto::PublicKey;
+//!
+//! /// Wallet of the cryptocurrency service.
+//! #[derive(Debug, Clone, PartialEq, EncodingStruct)]
+//! pub struct Wallet {
+//!     /// Public key of the owner.
+//!     pub pub_key: PublicKey,
+//!     /// Name of the owner.
+//!     pub name: String,
+//!     /// Current balance.
+//!     pub balance: u64,
+//! }
+//! ```
+//!
+//! [`encoding_struct!`]: https://docs.rs/exonum/0.6.0/exonum/macro.encoding_struct.html
+//! [`Field`]: https://docs.rs/exonum/0.6.0/exonum/encoding/trait.Field.html
+//! [`StorageValue`]: https://docs.rs/exonum/0.6.0/exonum/storage/trait.StorageValue.html
+//! [`CryptoHash`]: https://docs.rs/exonum/0.6.0/exonum/crypto/trait.CryptoHash.html
+//! [`ExonumJson`]: https://docs.rs/exonum/0.6.0/exonum/encoding/serialize/json/trait.ExonumJson.html
+//! [`ExonumJsonDeserialize`]: https://docs.rs/exonum/0.6.0/exonum/encoding/serialize/json/trait.ExonumJsonDeserialize.html
+
+#![recursion_limit = "256"]
+#![deny(missing_debug_implementations, missing_docs)]
+
+extern crate proc_macro;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use quote::Tokens;
+use syn::{Data, DeriveInput, Fields, Ident, Type};
+
+/// Field of the structure with its type.
+struct StructField<'a> {
+    name: &'a Ident,
+    ty: &'a Type,
+}
+
+/// Derives the Exonum binary encoding for a structure with named fields.
+///
+/// See the [crate documentation](index.html) for details.
+#[proc_macro_derive(EncodingStruct)]
+pub fn derive_encoding_struct(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = syn::parse(input).expect("Unable to parse the structure");
+    if !input.generics.params.is_empty() {
+        panic!("`EncodingStruct` cannot be derived for generic structures");
+    }
+    let fields = struct_fields(&input.data);
+    implement_encoding(&input.ident, &fields).into()
+}
+
+fn struct_fields(data: &Data) -> Vec<StructField> {
+    match *data {
+        Data::Struct(ref data) => {
+            match data.fields {
+                Fields::Named(ref fields) => {
+                    fields
+                        .named
+                        .iter()
+                        .map(|field| {
+                            StructField {
+                                name: field.ident.as_ref().unwrap(),
+                                ty: &field.ty,
+                            }
+                        })
+                        .collect()
+                }
+                Fields::Unit => Vec::new(),
+                Fields::Unnamed(_) => {
+                    panic!("`EncodingStruct` can only be derived for structures with named fields")
+                }
+            }
+        }
+        _ => panic!("`EncodingStruct` can only be derived for structures"),
+    }
+}
+
+/// Generates the code processing the fields one by one. Each step is given the offsets
+/// of the field in the `__from` and `__to` variables.
+fn for_each_field<F>(fields: &[StructField], mut step: F) -> Tokens
+where
+    F: FnMut(&StructField) -> Tokens,
+{
+    let steps: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let ty = field.ty;
+            let body = step(field);
+            quote! {
+                let __to = __from + <#ty as ::exonum::encoding::Field>::field_size();
+                #body
+                let __from = __to;
+            }
+        })
+        .collect();
+    quote! {
+        let __from: ::exonum::encoding::Offset = 0;
+        #(#steps)*
+    }
+}
+
+fn implement_encoding(name: &Ident, fields: &[StructField]) -> Tokens {
+    let name_str = name.as_ref();
+    let types: Vec<_> = fields.iter().map(|field| field.ty).collect();
+    let names: Vec<_> = fields.iter().map(|field| field.name).collect();
+    let header_size = quote! {
+        0 #(+ <#types as ::exonum::encoding::Field>::field_size())*
+    };
+
+    let write_fields = for_each_field(fields, |field| {
+        let field_name = field.name;
+        quote! {
+            ::exonum::encoding::Field::write(&self.#field_name, &mut __buffer, __from, __to);
+        }
+    });
+    let read_fields = for_each_field(fields, |field| {
+        let field_name = field.name;
+        quote! {
+            let #field_name = ::exonum::encoding::Field::read(__raw, __from, __to);
+        }
+    });
+    let check_fields = for_each_field(fields, |field| {
+        let (field_name, ty) = (field.name.as_ref(), field.ty);
+        quote! {
+            let __latest_segment = <#ty as ::exonum::encoding::Field>::check(
+                __raw,
+                __from.into(),
+                __to.into(),
+                __latest_segment,
+            ).map_err(|e| e.in_field(#name_str, #field_name))?;
+        }
+    });
+    let deserialize_fields = for_each_field(fields, |field| {
+        let (field_name, ty) = (field.name.as_ref(), field.ty);
+        quote! {
+            let __value = __object.get(#field_name).ok_or("Can't get object from json.")?;
+            <#ty as ::exonum::encoding::serialize::json::ExonumJson>::deserialize_field(
+                __value,
+                &mut __buffer,
+                __from,
+                __to,
+            )?;
+        }
+    });
+    let serialize_fields: Vec<_> = fields
+        .iter()
+        .map(|field| {
+            let (field_name, field_str) = (field.name, field.name.as_ref());
+            quote! {
+                __map.insert(
+                    #field_str.to_owned(),
+                    ::exonum::encoding::serialize::json::ExonumJson::serialize_field(
+                        &self.#field_name,
+                    )?,
+                );
+            }
+        })
+        .collect();
+    let serialize_error = format!("Can not serialize structure: {}", name_str);
+
+    quote! {
+        #[allow(unused_variables, unused_mut)]
+        impl #name {
+            fn __ex_header_size() -> ::exonum::encoding::Offset {
+                #header_size
+            }
+
+            fn __ex_to_bytes(&self) -> Vec<u8> {
+                let mut __buffer = vec![0; #name::__ex_header_size() as usize];
+                #write_fields
+                __buffer
+            }
+
+            fn __ex_check(__raw: &[u8]) -> ::exonum::encoding::Result {
+                let __header_size = #name::__ex_header_size();
+                if __raw.len() < __header_size as usize {
+                    return Err(::exonum::encoding::Error::UnexpectedlyShortPayload {
+                        actual_size: __raw.len() as ::exonum::encoding::Offset,
+                        minimum_size: __header_size,
+                    });
+                }
+                let __latest_segment: ::exonum::encoding::CheckedOffset =
+                    __header_size.into();
+                #check_fields
+                Ok(__latest_segment)
+            }
+        }
+
+        #[allow(unsafe_code)]
+        impl<'a> ::exonum::encoding::Field<'a> for #name {
+            fn field_size() -> ::exonum::encoding::Offset {
+                // Structures are written as segments, like in `encoding_struct!`.
+                8
+            }
+
+            unsafe fn read(
+                buffer: &'a [u8],
+                from: ::exonum::encoding::Offset,
+                to: ::exonum::encoding::Offset,
+            ) -> Self {
+                let raw: &[u8] = ::exonum::encoding::Field::read(buffer, from, to);
+                ::exonum::storage::StorageValue::from_bytes(::std::borrow::Cow::Borrowed(raw))
+            }
+
+            fn write(
+                &self,
+                buffer: &mut Vec<u8>,
+                from: ::exonum::encoding::Offset,
+                to: ::exonum::encoding::Offset,
+            ) {
+                ::exonum::encoding::Field::write(&self.__ex_to_bytes(), buffer, from, to);
+            }
+
+            fn check(
+                buffer: &'a [u8],
+                from: ::exonum::encoding::CheckedOffset,
+                to: ::exonum::encoding::CheckedOffset,
+                latest_segment: ::exonum::encoding::CheckedOffset,
+            ) -> ::exonum::encoding::Result {
+                let latest_segment = <&[u8] as ::exonum::encoding::Field>::check(
+                    buffer,
+                    from,
+                    to,
+                    latest_segment,
+                )?;
+                let raw: &[u8] = unsafe {
+                    ::exonum::encoding::Field::read(
+                        buffer,
+                        from.unchecked_offset(),
+                        to.unchecked_offset(),
+                    )
+                };
+                #name::__ex_check(raw)?;
+                Ok(latest_segment)
+            }
+        }
+
+        impl ::exonum::storage::StorageValue for #name {
+            fn into_bytes(self) -> Vec<u8> {
+                self.__ex_to_bytes()
+            }
+
+            #[allow(unsafe_code, unused_variables)]
+            fn from_bytes(__bytes: ::std::borrow::Cow<[u8]>) -> Self {
+                let __raw: &[u8] = __bytes.as_ref();
+                unsafe {
+                    #read_fields
+                    #name { #(#names),* }
+                }
+            }
+        }
+
+        impl ::exonum::crypto::CryptoHash for #name {
+            fn hash(&self) -> ::exonum::crypto::Hash {
+                ::exonum::crypto::hash(&self.__ex_to_bytes())
+            }
+        }
+
+        impl ::exonum::encoding::serialize::json::ExonumJson for #name {
+            fn deserialize_field<B>(
+                value: &::exonum::encoding::serialize::json::reexport::Value,
+                buffer: &mut B,
+                from: ::exonum::encoding::Offset,
+                to: ::exonum::encoding::Offset,
+            ) -> Result<(), Box<::std::error::Error>>
+            where
+                B: ::exonum::encoding::serialize::WriteBufferWrapper,
+            {
+                use ::exonum::encoding::serialize::json::ExonumJsonDeserialize;
+                let structure = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+                buffer.write(from, to, structure);
+                Ok(())
+            }
+
+            #[allow(unused_mut)]
+            fn serialize_field(
+                &self,
+            ) -> Result<
+                ::exonum::encoding::serialize::json::reexport::Value,
+                Box<::std::error::Error + Send + Sync>,
+            > {
+                let mut __map = ::exonum::encoding::serialize::json::reexport::Map::new();
+                #(#serialize_fields)*
+                Ok(::exonum::encoding::serialize::json::reexport::Value::Object(__map))
+            }
+        }
+
+        impl ::exonum::encoding::serialize::json::ExonumJsonDeserialize for #name {
+            #[allow(unused_variables, unused_mut)]
+            fn deserialize(
+                value: &::exonum::encoding::serialize::json::reexport::Value,
+            ) -> Result<Self, Box<::std::error::Error>> {
+                let __object = value.as_object().ok_or("Can't cast json as object.")?;
+                let mut __buffer = vec![0; #name::__ex_header_size() as usize];
+                #deserialize_fields
+                #name::__ex_check(&__buffer)?;
+                Ok(::exonum::storage::StorageValue::from_bytes(
+                    ::std::borrow::Cow::Owned(__buffer),
+                ))
+            }
+        }
+
+        impl<'de> ::exonum::encoding::serialize::reexport::Deserialize<'de> for #name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: ::exonum::encoding::serialize::reexport::Deserializer<'de>,
+            {
+                use ::exonum::encoding::serialize::json::reexport::Value;
+                use ::exonum::encoding::serialize::reexport::{DeError, Deserialize};
+                let value = <Value as Deserialize>::deserialize(deserializer)?;
+                <Self as ::exonum::encoding::serialize::json::ExonumJsonDeserialize>::deserialize(
+                    &value,
+                ).map_err(|_| D::Error::custom("Can not deserialize value."))
+            }
+        }
+
+        impl ::exonum::encoding::serialize::reexport::Serialize for #name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: ::exonum::encoding::serialize::reexport::Serializer,
+            {
+                use ::exonum::encoding::serialize::reexport::SerError;
+                use ::exonum::encoding::serialize::json::ExonumJson;
+                self.serialize_field()
+                    .map_err(|_| S::Error::custom(#serialize_error))?
+                    .serialize(serializer)
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests checking that the derived encoding is the same as the one of `encoding_struct!`.
+
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate exonum_derive;
+
+use exonum::crypto::{self, CryptoHash, Hash, PublicKey};
+use exonum::encoding::{Error, Field};
+use exonum::encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize};
+use exonum::storage::StorageValue;
+
+use std::collections::BTreeMap;
+
+/// Declarations with the `encoding_struct!` macro.
+mod macros {
+    use exonum::crypto::{Hash, PublicKey};
+
+    use std::collections::BTreeMap;
+
+    encoding_struct! {
+        struct Entry {
+            flag: bool,
+            amount: u64,
+        }
+    }
+
+    encoding_struct! {
+        struct Wallet {
+            pub_key: &PublicKey,
+            name: &str,
+            balance: u64,
+            frozen: bool,
+            memo: &[u8],
+            history: &[Hash],
+            entries: Vec<Entry>,
+            limit: Option<u32>,
+            labels: BTreeMap<u16, u64>,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, EncodingStruct)]
+struct Entry {
+    flag: bool,
+    amount: u64,
+}
+
+/// Wallet with doc comments on its fields.
+#[derive(Debug, Clone, PartialEq, EncodingStruct)]
+struct Wallet {
+    /// Public key of the owner.
+    pub_key: PublicKey,
+    /// Name of the owner.
+    name: String,
+    balance: u64,
+    frozen: bool,
+    memo: Vec<u8>,
+    /// Hashes of transactions involving the wallet.
+    history: Vec<Hash>,
+    entries: Vec<Entry>,
+    limit: Option<u32>,
+    labels: BTreeMap<u16, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, EncodingStruct)]
+struct Empty {}
+
+fn wallets() -> (Wallet, macros::Wallet) {
+    let (pub_key, _) = crypto::gen_keypair();
+    let history = vec![crypto::hash(&[1]), crypto::hash(&[2])];
+    let mut labels = BTreeMap::new();
+    labels.insert(3, 30);
+    labels.insert(1, 10);
+
+    let wallet = Wallet {
+        pub_key,
+        name: "Alice".to_owned(),
+        balance: 100,
+        frozen: true,
+        memo: vec![1, 2, 3],
+        history: history.clone(),
+        entries: vec![
+            Entry { flag: true, amount: 5 },
+            Entry { flag: false, amount: 7 },
+        ],
+        limit: Some(1_000),
+        labels: labels.clone(),
+    };
+    let macro_wallet = macros::Wallet::new(
+        &pub_key,
+        "Alice",
+        100,
+        true,
+        &[1, 2, 3],
+        &history,
+        vec![macros::Entry::new(true, 5), macros::Entry::new(false, 7)],
+        Some(1_000),
+        labels,
+    );
+    (wallet, macro_wallet)
+}
+
+#[test]
+fn test_same_bytes() {
+    let (wallet, macro_wallet) = wallets();
+    assert_eq!(wallet.clone().into_bytes(), macro_wallet.clone().into_bytes());
+    assert_eq!(wallet.hash(), macro_wallet.hash());
+    assert_eq!(
+        Entry { flag: true, amount: 5 }.into_bytes(),
+        macros::Entry::new(true, 5).into_bytes()
+    );
+    assert_eq!(Empty {}.into_bytes(), Vec::<u8>::new());
+}
+
+#[test]
+fn test_read_macro_bytes() {
+    let (wallet, macro_wallet) = wallets();
+    let decoded = Wallet::from_bytes(macro_wallet.into_bytes().into());
+    assert_eq!(decoded, wallet);
+}
+
+#[test]
+fn test_same_field_layout() {
+    let (wallet, macro_wallet) = wallets();
+
+    let mut buffer = vec![0; 8];
+    Field::write(&wallet, &mut buffer, 0, 8);
+    let mut macro_buffer = vec![0; 8];
+    Field::write(&macro_wallet, &mut macro_buffer, 0, 8);
+    assert_eq!(buffer, macro_buffer);
+
+    <Wallet as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap();
+    let decoded: Wallet = unsafe { Field::read(&buffer, 0, 8) };
+    assert_eq!(decoded, wallet);
+}
+
+#[test]
+fn test_same_json() {
+    let (wallet, macro_wallet) = wallets();
+    let json = wallet.serialize_field().unwrap();
+    assert_eq!(json, macro_wallet.serialize_field().unwrap());
+
+    let decoded = <Wallet as ExonumJsonDeserialize>::deserialize(&json).unwrap();
+    assert_eq!(decoded, wallet);
+    let macro_decoded = <macros::Wallet as ExonumJsonDeserialize>::deserialize(&json).unwrap();
+    assert_eq!(macro_decoded, macro_wallet);
+}
+
+#[test]
+fn test_check_errors() {
+    let (wallet, _) = wallets();
+    let mut buffer = vec![0; 8];
+    Field::write(&wallet, &mut buffer, 0, 8);
+
+    // The name of the wallet follows the header of the wallet (89 bytes)
+    // after the segment pointer (8 bytes).
+    buffer[8 + 89] = 0xff;
+    let error = <Wallet as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap_err();
+    assert_eq!(error.field_path().unwrap().to_string(), "Wallet.name");
+    match *error.root_cause() {
+        Error::Utf8 { .. } => {}
+        ref other => panic!("Unexpected error: {:?}", other),
+    }
+
+    let mut buffer = vec![0; 8];
+    Field::write(&vec![0_u8; 4], &mut buffer, 0, 8);
+    let error = <Wallet as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap_err();
+    match error {
+        Error::UnexpectedlyShortPayload { actual_size: 4, minimum_size: 89 } => {}
+        ref other => panic!("Unexpected error: {:?}", other),
+    }
+}
@@ -28,6 +28,7 @@ fn node_config() -> NodeConfig {
     let validator_keys = ValidatorKeys {
         consensus_key: consensus_public_key,
         service_key: service_public_key,
+        voting_power: None,
     };
     let genesis = GenesisConfig::new(vec![validator_keys].into_iter());
 
@@ -54,6 +55,7 @@ fn node_config() -> NodeConfig {
         mempool: Default::default(),
         services_configs: Default::default(),
         database: Default::default(),
+        consensus_trace: None,
     }
 }
 
@@ -280,14 +280,15 @@ pub mod api {
     use exonum::node::{TransactionSend, ApiSender};
     use exonum::crypto::{Hash, PublicKey};
     use exonum::api::{Api, ApiError};
+    use exonum::api::openapi::{self, DocumentedRouter, Endpoint};
     use iron::prelude::*;
     use iron::status::Status;
     use iron::headers::ContentType;
     use iron::modifiers::Header;
-    use router::Router;
 
     use bodyparser;
     use serde_json;
+    use serde_json::Value as JsonValue;
     use schema::{Wallet, CurrencySchema};
     use transactions::CurrencyTransactions;
 
@@ -370,7 +371,7 @@ pub mod api {
     /// endpoints; for example, it parses `POST`ed JSON into the binary transaction
     /// representation used in Exonum internally.
     impl Api for CryptocurrencyApi {
-        fn wire(&self, router: &mut Router) {
+        fn wire_documented(&self, router: &mut DocumentedRouter) {
             let self_ = self.clone();
             let post_create_wallet = move |req: &mut Request| self_.post_transaction(req);
             let self_ = self.clone();
@@ -380,13 +381,64 @@ pub mod api {
             let self_ = self.clone();
             let get_wallet = move |req: &mut Request| self_.get_wallet(req);
 
-            // Bind handlers to specific routes.
-            router.post("/v1/wallets", post_create_wallet, "post_create_wallet");
-            router.post("/v1/wallets/transfer", post_transfer, "post_transfer");
-            router.get("/v1/wallets", get_wallets, "get_wallets");
-            router.get("/v1/wallet/:pub_key", get_wallet, "get_wallet");
+            // Bind handlers to specific routes, describing each endpoint
+            // for the OpenAPI document of the node.
+            let tx_response = || openapi::object(&[("tx_hash", openapi::hash())]);
+            router.route(
+                Endpoint::post("/v1/wallets", "Creates a new wallet")
+                    .request(transaction_schema(&[
+                        ("pub_key", openapi::hash()),
+                        ("name", openapi::string()),
+                    ]))
+                    .response(tx_response()),
+                post_create_wallet,
+                "post_create_wallet",
+            );
+            router.route(
+                Endpoint::post("/v1/wallets/transfer", "Transfers tokens between wallets")
+                    .request(transaction_schema(&[
+                        ("from", openapi::hash()),
+                        ("to", openapi::hash()),
+                        ("amount", openapi::integer_string()),
+                        ("seed", openapi::integer_string()),
+                    ]))
+                    .response(tx_response()),
+                post_transfer,
+                "post_transfer",
+            );
+            router.route(
+                Endpoint::get("/v1/wallets", "Returns all wallets")
+                    .response(openapi::array(wallet_schema())),
+                get_wallets,
+                "get_wallets",
+            );
+            router.route(
+                Endpoint::get("/v1/wallet/:pub_key", "Returns the wallet with the given key")
+                    .path_param("pub_key", "Public key of the wallet owner", openapi::hash())
+                    .response(wallet_schema()),
+                get_wallet,
+                "get_wallet",
+            );
         }
     }
+
+    fn wallet_schema() -> JsonValue {
+        openapi::object(&[
+            ("pub_key", openapi::hash()),
+            ("name", openapi::string()),
+            ("balance", openapi::integer_string()),
+        ])
+    }
+
+    fn transaction_schema(body: &[(&str, JsonValue)]) -> JsonValue {
+        openapi::object(&[
+            ("body", openapi::object(body)),
+            ("protocol_version", openapi::integer()),
+            ("service_id", openapi::integer()),
+            ("message_id", openapi::integer()),
+            ("signature", openapi::hash()),
+        ])
+    }
 }
 
 /// Service declaration.
@@ -396,7 +448,9 @@ pub mod service {
     use exonum::storage::Snapshot;
     use exonum::crypto::Hash;
     use exonum::encoding;
+    use exonum::encoding::schema::MessageSchema;
     use exonum::api::Api;
+    use exonum::api::openapi::Endpoint;
     use iron::Handler;
     use router::Router;
 
@@ -461,6 +515,11 @@ pub mod service {
             Ok(tx.into())
         }
 
+        // Describe the binary layout of the transactions for generic tooling.
+        fn message_schemas(&self) -> Vec<MessageSchema> {
+            CurrencyTransactions::message_schemas()
+        }
+
         // Hashes for the service tables that will be included into the state hash.
         // To simplify things, we don't have [Merkelized tables][merkle] in the service storage
         // for now, so we return an empty vector.
@@ -477,5 +536,11 @@ pub mod service {
             api.wire(&mut router);
             Some(Box::new(router))
         }
+
+        // Describe the REST endpoints for the OpenAPI document of the node.
+        fn public_api_endpoints(&self, ctx: &ApiContext) -> Vec<Endpoint> {
+            let api = CryptocurrencyApi::new(ctx.node_channel().clone(), ctx.blockchain().clone());
+            api.endpoints()
+        }
     }
 }
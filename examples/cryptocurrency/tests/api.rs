@@ -205,6 +205,29 @@ fn test_malformed_wallet_request() {
     );
 }
 
+/// Check that a transaction with a malformed field is rejected with the path to the field.
+#[test]
+fn test_malformed_transaction() {
+    use exonum::api::public::TransactionHex;
+    use exonum::encoding::serialize::ToHex;
+    use exonum::messages::Message;
+
+    let (_testkit, api) = create_testkit();
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx = TxCreateWallet::new(&pubkey, ALICE_NAME, &key);
+
+    // The name follows the message header (10 bytes) and the header of the body (40 bytes).
+    let mut bytes = tx.raw().as_ref().to_vec();
+    bytes[50] = 0xff;
+    let body = TransactionHex { tx_body: bytes.to_hex() };
+    let error: serde_json::Value = api.inner.post(ApiKind::Explorer, "v1/transactions", &body);
+    let description = error["description"].as_str().unwrap();
+    assert!(description.starts_with(
+        "Bad request: Error in field `TxCreateWallet.name` of message 0 of service 1: \
+         Utf8 error in parsing string",
+    ));
+}
+
 #[test]
 fn test_unknown_wallet_request() {
     let (_testkit, api) = create_testkit();
@@ -222,6 +245,17 @@ fn test_unknown_wallet_request() {
     );
 }
 
+#[test]
+fn test_documented_endpoints() {
+    let (_testkit, api) = create_testkit();
+
+    let document: serde_json::Value = api.inner.get(ApiKind::Root, "openapi.json");
+    let wallet = &document["paths"]["/api/services/cryptocurrency/v1/wallet/{pub_key}"];
+    assert_eq!(wallet["get"]["parameters"][0]["name"], "pub_key");
+
+    api.inner.check_documented_endpoints();
+}
+
 /// Wrapper for the cryptocurrency service API allowing to easily use it
 /// (compared to `TestKitApi` calls).
 struct CryptocurrencyApi {
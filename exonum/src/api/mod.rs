@@ -19,7 +19,9 @@ use std::marker::PhantomData;
 use std::io;
 use std::collections::BTreeMap;
 use std::fmt;
+use std::io::Read;
 use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use iron::IronError;
 use iron::prelude::*;
@@ -27,6 +29,7 @@ use iron::status;
 use iron::headers::Cookie;
 use hyper::header::{ContentType, SetCookie};
 use cookie::Cookie as CookiePair;
+use mount::OriginalUrl;
 use router::Router;
 use params;
 use serde_json;
@@ -35,10 +38,12 @@ use serde::de::{self, Visitor, Deserialize, Deserializer};
 use failure::Fail;
 use bodyparser;
 
-use crypto::{PublicKey, SecretKey};
+use crypto::{self, PublicKey, SecretKey, Signature};
 use encoding::serialize::{FromHex, FromHexError, ToHex, encode_hex};
 use storage;
+use self::openapi::{DocumentedRouter, Endpoint};
 
+pub mod openapi;
 pub mod public;
 pub mod private;
 #[cfg(test)]
@@ -118,6 +123,112 @@ impl From<ApiError> for IronError {
     }
 }
 
+/// Name of the header with the hex-encoded public key of the signer of a request.
+pub const PUBLIC_KEY_HEADER: &str = "X-Exonum-Public-Key";
+/// Name of the header with the hex-encoded signature of a request.
+pub const SIGNATURE_HEADER: &str = "X-Exonum-Signature";
+/// Name of the header with the time of signing of a request, in seconds since the Unix epoch.
+pub const TIMESTAMP_HEADER: &str = "X-Exonum-Timestamp";
+/// Maximal difference in seconds between the time of signing of a request and the time
+/// of its verification. Signed requests outside this window are rejected, which limits
+/// the time a captured request can be replayed.
+pub const SIGNED_REQUEST_WINDOW: u64 = 300;
+/// Maximal length of the body of a signed request.
+pub const MAX_SIGNED_REQUEST_BODY_LENGTH: u64 = 1024 * 1024;
+
+/// Returns the data signed by the client for the request with the given method, path,
+/// time of signing and body.
+///
+/// The path is the full path of the request with the query string, if any,
+/// e.g. `/api/services/cryptocurrency/v1/wallets?count=10`. The timestamp is
+/// the number of seconds since the Unix epoch, which is passed in the
+/// [`X-Exonum-Timestamp`] header. The data is the method and the path separated by a space,
+/// the timestamp and the body, each separated by a newline, so that a signature
+/// cannot be replayed against a different endpoint or after the acceptance window.
+///
+/// [`X-Exonum-Timestamp`]: constant.TIMESTAMP_HEADER.html
+pub fn signed_request_data(method: &str, path: &str, timestamp: u64, body: &[u8]) -> Vec<u8> {
+    let mut data = format!("{} {}\n{}\n", method.to_uppercase(), path, timestamp).into_bytes();
+    data.extend_from_slice(body);
+    data
+}
+
+/// Signs the request with the given method, path, time of signing and body.
+/// The signature should be passed hex-encoded in the [`X-Exonum-Signature`] header along with
+/// the public key in the [`X-Exonum-Public-Key`] header and the timestamp
+/// in the [`X-Exonum-Timestamp`] header.
+///
+/// [`X-Exonum-Signature`]: constant.SIGNATURE_HEADER.html
+/// [`X-Exonum-Public-Key`]: constant.PUBLIC_KEY_HEADER.html
+/// [`X-Exonum-Timestamp`]: constant.TIMESTAMP_HEADER.html
+///
+/// # Examples
+///
+/// ```
+/// use exonum::api::{sign_request, verify_request};
+/// use exonum::crypto;
+///
+/// let (public_key, secret_key) = crypto::gen_keypair();
+/// let path = "/api/services/wallets/v1/issue";
+/// let timestamp = 1_500_000_000;
+/// let body = br#"{ "amount": "10" }"#;
+/// let signature = sign_request("POST", path, timestamp, body, &secret_key);
+/// assert!(verify_request("POST", path, timestamp, body, &public_key, &signature));
+/// ```
+pub fn sign_request(
+    method: &str,
+    path: &str,
+    timestamp: u64,
+    body: &[u8],
+    secret_key: &SecretKey,
+) -> Signature {
+    crypto::sign(
+        &signed_request_data(method, path, timestamp, body),
+        secret_key,
+    )
+}
+
+/// Verifies the signature of the request with the given method, path, time of signing
+/// and body. The timestamp is not compared with the current time.
+pub fn verify_request(
+    method: &str,
+    path: &str,
+    timestamp: u64,
+    body: &[u8],
+    public_key: &PublicKey,
+    signature: &Signature,
+) -> bool {
+    crypto::verify(
+        signature,
+        &signed_request_data(method, path, timestamp, body),
+        public_key,
+    )
+}
+
+/// Checks that the time of signing of a request is within `SIGNED_REQUEST_WINDOW`
+/// from the current time.
+fn is_within_signed_request_window(timestamp: u64, now: u64) -> bool {
+    let difference = if timestamp > now {
+        timestamp - now
+    } else {
+        now - timestamp
+    };
+    difference <= SIGNED_REQUEST_WINDOW
+}
+
+/// Reads the request body, which must not be longer than `limit` bytes.
+pub(crate) fn read_body(req: &mut Request, limit: u64) -> Result<Vec<u8>, ApiError> {
+    let mut bytes = Vec::new();
+    (&mut req.body).take(limit + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > limit {
+        return Err(ApiError::BadRequest(format!(
+            "Request body is too long, maximum allowed length is {} bytes",
+            limit
+        )));
+    }
+    Ok(bytes)
+}
+
 /// `Field` that is serialized/deserialized from/to hex.
 #[derive(Clone, Debug)]
 struct HexField<T: AsRef<[u8]> + Clone>(pub T);
@@ -246,7 +357,84 @@ pub trait Api {
         }
     }
 
+    /// Verifies the signature of the request and returns the public key of the signer.
+    ///
+    /// The request must carry the hex-encoded public key and signature in the
+    /// [`X-Exonum-Public-Key`] and [`X-Exonum-Signature`] headers and the time of signing
+    /// in the [`X-Exonum-Timestamp`] header; the signature is verified against
+    /// [`signed_request_data`] of the request. Requests signed more than
+    /// [`SIGNED_REQUEST_WINDOW`] seconds before or after the current time are rejected.
+    ///
+    /// The body is read as bytes and may be up to [`MAX_SIGNED_REQUEST_BODY_LENGTH`] bytes
+    /// long. If the body is a UTF-8 string, it remains available
+    /// to [`parse_body`](#method.parse_body).
+    ///
+    /// [`X-Exonum-Public-Key`]: constant.PUBLIC_KEY_HEADER.html
+    /// [`X-Exonum-Signature`]: constant.SIGNATURE_HEADER.html
+    /// [`X-Exonum-Timestamp`]: constant.TIMESTAMP_HEADER.html
+    /// [`signed_request_data`]: fn.signed_request_data.html
+    /// [`SIGNED_REQUEST_WINDOW`]: constant.SIGNED_REQUEST_WINDOW.html
+    /// [`MAX_SIGNED_REQUEST_BODY_LENGTH`]: constant.MAX_SIGNED_REQUEST_BODY_LENGTH.html
+    fn verify_request_signature(&self, request: &mut Request) -> Result<PublicKey, ApiError> {
+        fn header<'a>(request: &'a Request, name: &str) -> Result<&'a str, ApiError> {
+            request
+                .headers
+                .get_raw(name)
+                .and_then(|values| values.first())
+                .and_then(|value| ::std::str::from_utf8(value).ok())
+                .map(str::trim)
+                .ok_or(ApiError::Unauthorized)
+        }
+
+        fn hex_header<T: FromHex>(request: &Request, name: &str) -> Result<T, ApiError> {
+            T::from_hex(header(request, name)?).map_err(|_| {
+                ApiError::BadRequest(format!("Invalid hex in '{}' header", name))
+            })
+        }
+
+        let public_key: PublicKey = hex_header(request, PUBLIC_KEY_HEADER)?;
+        let signature: Signature = hex_header(request, SIGNATURE_HEADER)?;
+        let timestamp: u64 = header(request, TIMESTAMP_HEADER)?.parse().map_err(|_| {
+            ApiError::BadRequest(format!("Invalid timestamp in '{}' header", TIMESTAMP_HEADER))
+        })?;
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time is before the Unix epoch")
+            .as_secs();
+        if !is_within_signed_request_window(timestamp, now) {
+            return Err(ApiError::Unauthorized);
+        }
+
+        let path = {
+            let url = request.extensions.get::<OriginalUrl>().unwrap_or(
+                &request.url,
+            );
+            let mut path = format!("/{}", url.path().join("/"));
+            if let Some(query) = url.query() {
+                path.push('?');
+                path.push_str(query);
+            }
+            path
+        };
+        let method = request.method.to_string();
+        let body = read_body(request, MAX_SIGNED_REQUEST_BODY_LENGTH)?;
+
+        if !verify_request(&method, &path, timestamp, &body, &public_key, &signature) {
+            return Err(ApiError::Unauthorized);
+        }
+        // The body is consumed, so it is cached for `parse_body`.
+        if let Ok(body) = String::from_utf8(body) {
+            let body = if body.is_empty() { None } else { Some(body) };
+            request.extensions.insert::<bodyparser::Raw>(body);
+        }
+        Ok(public_key)
+    }
+
     /// Loads hex value from the cookies.
+    #[deprecated(since = "0.7.0",
+                 note = "sending secret keys to the node is insecure, \
+                         use `verify_request_signature` instead")]
     fn load_hex_value_from_cookie<'a>(
         &self,
         request: &'a Request,
@@ -269,6 +457,10 @@ pub trait Api {
     }
 
     /// Loads public and secret key from the cookies.
+    #[deprecated(since = "0.7.0",
+                 note = "sending secret keys to the node is insecure, \
+                         use `verify_request_signature` instead")]
+    #[allow(deprecated)]
     fn load_keypair_from_cookies(
         &self,
         request: &Request,
@@ -328,6 +520,21 @@ pub trait Api {
         self.not_found_response_with_cookies(json, None)
     }
 
-    /// Used to extend Api.
-    fn wire<'b>(&self, router: &'b mut Router);
+    /// Used to extend Api. By default, adds the routes of
+    /// [`wire_documented`](#method.wire_documented).
+    fn wire<'b>(&self, router: &'b mut Router) {
+        self.wire_documented(&mut DocumentedRouter::new(router));
+    }
+
+    /// Adds the routes together with the descriptions of their endpoints. APIs implementing
+    /// this method instead of [`wire`](#method.wire) are described in the OpenAPI document
+    /// served by the node.
+    #[allow(unused_variables)]
+    fn wire_documented(&self, router: &mut DocumentedRouter) {}
+
+    /// Returns descriptions of the endpoints added by
+    /// [`wire_documented`](#method.wire_documented).
+    fn endpoints(&self) -> Vec<Endpoint> {
+        openapi::endpoints_of(|router| self.wire_documented(router))
+    }
 }
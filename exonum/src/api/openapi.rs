@@ -0,0 +1,600 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Declarative descriptions of API endpoints and their aggregation into
+//! an [OpenAPI](https://swagger.io/specification/) document.
+//!
+//! Routes are registered together with their descriptions through [`DocumentedRouter`]
+//! in [`Api::wire_documented`], so that every route is described; the descriptions are
+//! returned by [`Api::endpoints`] for the core APIs and by [`Service::public_api_endpoints`] /
+//! [`Service::private_api_endpoints`] for services. The node serves the aggregated document
+//! at `/api/openapi.json` of both its public and private listen addresses.
+//!
+//! Schemas of parameters and bodies are [JSON schemas] represented as `serde_json`
+//! values; the functions in this module cover the types used across the core APIs.
+//!
+//! [`DocumentedRouter`]: struct.DocumentedRouter.html
+//! [`Api::wire_documented`]: ../trait.Api.html#method.wire_documented
+//! [`Api::endpoints`]: ../trait.Api.html#method.endpoints
+//! [`Service::public_api_endpoints`]: ../../blockchain/trait.Service.html#method.public_api_endpoints
+//! [`Service::private_api_endpoints`]: ../../blockchain/trait.Service.html#method.private_api_endpoints
+//! [JSON schemas]: http://json-schema.org/
+//!
+//! # Examples
+//!
+//! ```
+//! use exonum::api::openapi::{self, ApiDocument, Endpoint};
+//!
+//! let endpoints = vec![
+//!     Endpoint::get("/v1/wallets/:pub_key", "Returns the wallet with the given key")
+//!         .path_param("pub_key", "Public key of the wallet owner", openapi::hash())
+//!         .response(openapi::object(&[("balance", openapi::integer())])),
+//! ];
+//!
+//! let mut document = ApiDocument::new("Cryptocurrency API");
+//! document.add_endpoints("/api/services/cryptocurrency", &endpoints);
+//! let json = document.to_json();
+//! assert!(json["paths"]["/api/services/cryptocurrency/v1/wallets/{pub_key}"]["get"].is_object());
+//! ```
+
+use iron::Handler;
+use router::Router;
+use serde_json::Value as JsonValue;
+use serde_json::map::Map;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Version of the OpenAPI specification the generated documents conform to.
+pub const OPENAPI_VERSION: &str = "3.0.0";
+
+/// HTTP method of an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Method {
+    /// `GET` request.
+    Get,
+    /// `POST` request.
+    Post,
+}
+
+impl Method {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            Method::Get => "get",
+            Method::Post => "post",
+        }
+    }
+}
+
+/// Location of an endpoint parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterLocation {
+    /// Parameter is a fragment of the url path, e.g. `:height` in `/v1/blocks/:height`.
+    Path,
+    /// Parameter is passed in the query string.
+    Query,
+}
+
+impl ParameterLocation {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            ParameterLocation::Path => "path",
+            ParameterLocation::Query => "query",
+        }
+    }
+}
+
+/// Endpoint parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    /// Name of the parameter.
+    pub name: String,
+    /// Location of the parameter.
+    pub location: ParameterLocation,
+    /// Human-readable description.
+    pub description: String,
+    /// Whether the parameter is required. Path parameters are always required.
+    pub required: bool,
+    /// JSON schema of the parameter value.
+    pub schema: JsonValue,
+}
+
+impl Parameter {
+    fn to_json(&self) -> JsonValue {
+        let mut object = Map::new();
+        object.insert("name".into(), self.name.clone().into());
+        object.insert("in".into(), self.location.as_str().into());
+        object.insert("description".into(), self.description.clone().into());
+        object.insert("required".into(), self.required.into());
+        object.insert("schema".into(), self.schema.clone());
+        JsonValue::Object(object)
+    }
+}
+
+/// Description of a single API endpoint.
+///
+/// Paths use the same syntax as the `router` crate, i.e., `/v1/blocks/:height`;
+/// they are relative to the prefix the API is mounted at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoint {
+    /// HTTP method of the endpoint.
+    pub method: Method,
+    /// Path of the endpoint relative to the mount point of the API.
+    pub path: String,
+    /// Short summary of what the endpoint does.
+    pub summary: String,
+    /// Path and query parameters.
+    pub parameters: Vec<Parameter>,
+    /// JSON schema of the request body, if the endpoint accepts one.
+    pub request: Option<JsonValue>,
+    /// JSON schema of the successful response.
+    pub response: Option<JsonValue>,
+}
+
+impl Endpoint {
+    /// Creates a description of an endpoint with the given method, path and summary.
+    pub fn new<P: Into<String>, S: Into<String>>(method: Method, path: P, summary: S) -> Self {
+        Endpoint {
+            method,
+            path: path.into(),
+            summary: summary.into(),
+            parameters: Vec::new(),
+            request: None,
+            response: None,
+        }
+    }
+
+    /// Creates a description of a `GET` endpoint.
+    pub fn get<P: Into<String>, S: Into<String>>(path: P, summary: S) -> Self {
+        Endpoint::new(Method::Get, path, summary)
+    }
+
+    /// Creates a description of a `POST` endpoint.
+    pub fn post<P: Into<String>, S: Into<String>>(path: P, summary: S) -> Self {
+        Endpoint::new(Method::Post, path, summary)
+    }
+
+    /// Adds a path parameter.
+    pub fn path_param(mut self, name: &str, description: &str, schema: JsonValue) -> Self {
+        self.parameters.push(Parameter {
+            name: name.to_owned(),
+            location: ParameterLocation::Path,
+            description: description.to_owned(),
+            required: true,
+            schema,
+        });
+        self
+    }
+
+    /// Adds a query parameter.
+    pub fn query_param(
+        mut self,
+        name: &str,
+        description: &str,
+        required: bool,
+        schema: JsonValue,
+    ) -> Self {
+        self.parameters.push(Parameter {
+            name: name.to_owned(),
+            location: ParameterLocation::Query,
+            description: description.to_owned(),
+            required,
+            schema,
+        });
+        self
+    }
+
+    /// Sets the JSON schema of the request body.
+    pub fn request(mut self, schema: JsonValue) -> Self {
+        self.request = Some(schema);
+        self
+    }
+
+    /// Sets the JSON schema of the successful response.
+    pub fn response(mut self, schema: JsonValue) -> Self {
+        self.response = Some(schema);
+        self
+    }
+
+    fn to_json(&self) -> JsonValue {
+        let mut object = Map::new();
+        object.insert("summary".into(), self.summary.clone().into());
+        if !self.parameters.is_empty() {
+            let parameters = self.parameters.iter().map(Parameter::to_json).collect();
+            object.insert("parameters".into(), JsonValue::Array(parameters));
+        }
+        if let Some(ref schema) = self.request {
+            let mut body = Map::new();
+            body.insert("required".into(), true.into());
+            body.insert("content".into(), json_content(schema.clone()));
+            object.insert("requestBody".into(), JsonValue::Object(body));
+        }
+
+        let mut ok = Map::new();
+        ok.insert("description".into(), "Successful response".into());
+        if let Some(ref schema) = self.response {
+            ok.insert("content".into(), json_content(schema.clone()));
+        }
+        let mut responses = Map::new();
+        responses.insert("200".into(), JsonValue::Object(ok));
+        responses.insert("default".into(), error_response());
+        object.insert("responses".into(), JsonValue::Object(responses));
+        JsonValue::Object(object)
+    }
+}
+
+/// Router wrapper registering each route together with the description of its endpoint.
+///
+/// The method and the path of a route are taken from the description, so the routes
+/// and the OpenAPI document cannot diverge.
+pub struct DocumentedRouter<'a> {
+    router: &'a mut Router,
+    endpoints: Vec<Endpoint>,
+}
+
+impl<'a> DocumentedRouter<'a> {
+    /// Creates a wrapper adding routes to the given router.
+    pub fn new(router: &'a mut Router) -> Self {
+        DocumentedRouter {
+            router,
+            endpoints: Vec::new(),
+        }
+    }
+
+    /// Routes requests matching the method and the path of the endpoint to the handler
+    /// and records the description of the endpoint.
+    pub fn route<H: Handler>(&mut self, endpoint: Endpoint, handler: H, route_id: &str) {
+        match endpoint.method {
+            Method::Get => self.router.get(&endpoint.path, handler, route_id),
+            Method::Post => self.router.post(&endpoint.path, handler, route_id),
+        };
+        self.endpoints.push(endpoint);
+    }
+
+    /// Returns descriptions of the endpoints routed so far.
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+
+    /// Converts the wrapper into the descriptions of the routed endpoints.
+    pub fn into_endpoints(self) -> Vec<Endpoint> {
+        self.endpoints
+    }
+}
+
+impl<'a> fmt::Debug for DocumentedRouter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DocumentedRouter")
+            .field("endpoints", &self.endpoints)
+            .finish()
+    }
+}
+
+/// Returns descriptions of the endpoints routed by the given function. The routes
+/// themselves are added to a scratch router and discarded.
+pub fn endpoints_of<F>(wire: F) -> Vec<Endpoint>
+where
+    F: FnOnce(&mut DocumentedRouter),
+{
+    let mut router = Router::new();
+    let mut documented = DocumentedRouter::new(&mut router);
+    wire(&mut documented);
+    documented.into_endpoints()
+}
+
+/// OpenAPI document aggregating endpoints of several APIs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiDocument {
+    title: String,
+    version: String,
+    paths: BTreeMap<String, BTreeMap<Method, Endpoint>>,
+}
+
+impl ApiDocument {
+    /// Creates an empty document with the given title. The version of the document
+    /// is the version of the `exonum` crate.
+    pub fn new<S: Into<String>>(title: S) -> Self {
+        ApiDocument {
+            title: title.into(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            paths: BTreeMap::new(),
+        }
+    }
+
+    /// Adds endpoints of an API mounted at the given prefix, e.g. `/api/explorer`.
+    ///
+    /// If several endpoints share the same path and method, the one added last wins.
+    pub fn add_endpoints(&mut self, prefix: &str, endpoints: &[Endpoint]) {
+        for endpoint in endpoints {
+            let path = openapi_path(prefix, &endpoint.path);
+            self.paths.entry(path).or_insert_with(BTreeMap::new).insert(
+                endpoint.method,
+                endpoint.clone(),
+            );
+        }
+    }
+
+    /// Returns the number of described endpoints.
+    pub fn len(&self) -> usize {
+        self.paths.values().map(BTreeMap::len).sum()
+    }
+
+    /// Returns `true` if the document does not describe any endpoints.
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Converts the document into its JSON representation.
+    pub fn to_json(&self) -> JsonValue {
+        let mut info = Map::new();
+        info.insert("title".into(), self.title.clone().into());
+        info.insert("version".into(), self.version.clone().into());
+
+        let mut paths = Map::new();
+        for (path, endpoints) in &self.paths {
+            let mut item = Map::new();
+            for (method, endpoint) in endpoints {
+                item.insert(method.as_str().into(), endpoint.to_json());
+            }
+            paths.insert(path.clone(), JsonValue::Object(item));
+        }
+
+        let mut document = Map::new();
+        document.insert("openapi".into(), OPENAPI_VERSION.into());
+        document.insert("info".into(), JsonValue::Object(info));
+        document.insert("paths".into(), JsonValue::Object(paths));
+        JsonValue::Object(document)
+    }
+}
+
+/// Schema of a string.
+pub fn string() -> JsonValue {
+    schema_of_type("string")
+}
+
+/// Schema of a string with the given format, e.g. `"hex"`.
+pub fn formatted_string(format: &str) -> JsonValue {
+    let mut object = type_map("string");
+    object.insert("format".into(), format.into());
+    JsonValue::Object(object)
+}
+
+/// Schema of a hex-encoded hash, public key or signature.
+pub fn hash() -> JsonValue {
+    formatted_string("hex")
+}
+
+/// Schema of an unsigned integer.
+pub fn integer() -> JsonValue {
+    let mut object = type_map("integer");
+    object.insert("minimum".into(), 0.into());
+    JsonValue::Object(object)
+}
+
+/// Schema of an unsigned integer serialized as a string, e.g. `u64` values in
+/// storage types.
+pub fn integer_string() -> JsonValue {
+    formatted_string("uint64")
+}
+
+/// Schema of a floating-point number.
+pub fn number() -> JsonValue {
+    schema_of_type("number")
+}
+
+/// Schema of a boolean.
+pub fn boolean() -> JsonValue {
+    schema_of_type("boolean")
+}
+
+/// Schema of any JSON value.
+pub fn any() -> JsonValue {
+    JsonValue::Object(Map::new())
+}
+
+/// Schema of a string that takes one of the given values.
+pub fn enumeration(values: &[&str]) -> JsonValue {
+    let mut object = type_map("string");
+    let values = values.iter().map(|&value| value.into()).collect();
+    object.insert("enum".into(), JsonValue::Array(values));
+    JsonValue::Object(object)
+}
+
+/// Schema of an array with the given item schema.
+pub fn array(items: JsonValue) -> JsonValue {
+    let mut object = type_map("array");
+    object.insert("items".into(), items);
+    JsonValue::Object(object)
+}
+
+/// Schema of an object with the given required properties.
+pub fn object(properties: &[(&str, JsonValue)]) -> JsonValue {
+    let mut object = type_map("object");
+    let mut props = Map::new();
+    for &(name, ref schema) in properties {
+        props.insert(name.into(), schema.clone());
+    }
+    let required = properties.iter().map(|&(name, _)| name.into()).collect();
+    object.insert("properties".into(), JsonValue::Object(props));
+    object.insert("required".into(), JsonValue::Array(required));
+    JsonValue::Object(object)
+}
+
+/// Schema of an object with arbitrary keys and values matching the given schema.
+pub fn dictionary(values: JsonValue) -> JsonValue {
+    let mut object = type_map("object");
+    object.insert("additionalProperties".into(), values);
+    JsonValue::Object(object)
+}
+
+/// Schema of a value that may be `null`.
+pub fn nullable(mut schema: JsonValue) -> JsonValue {
+    if let JsonValue::Object(ref mut object) = schema {
+        object.insert("nullable".into(), true.into());
+    }
+    schema
+}
+
+/// Schema of a value matching any of the given schemas.
+pub fn one_of(schemas: Vec<JsonValue>) -> JsonValue {
+    let mut object = Map::new();
+    object.insert("oneOf".into(), JsonValue::Array(schemas));
+    JsonValue::Object(object)
+}
+
+/// Adds a description to the schema.
+pub fn described(mut schema: JsonValue, description: &str) -> JsonValue {
+    if let JsonValue::Object(ref mut object) = schema {
+        object.insert("description".into(), description.into());
+    }
+    schema
+}
+
+fn type_map(ty: &str) -> Map<String, JsonValue> {
+    let mut object = Map::new();
+    object.insert("type".into(), ty.into());
+    object
+}
+
+fn schema_of_type(ty: &str) -> JsonValue {
+    JsonValue::Object(type_map(ty))
+}
+
+fn json_content(schema: JsonValue) -> JsonValue {
+    let mut media_type = Map::new();
+    media_type.insert("schema".into(), schema);
+    let mut content = Map::new();
+    content.insert("application/json".into(), JsonValue::Object(media_type));
+    JsonValue::Object(content)
+}
+
+// Errors are reported by `ApiError` as `{ "debug": ..., "description": ... }`.
+fn error_response() -> JsonValue {
+    let mut response = Map::new();
+    response.insert("description".into(), "Error".into());
+    response.insert(
+        "content".into(),
+        json_content(object(&[("debug", string()), ("description", string())])),
+    );
+    JsonValue::Object(response)
+}
+
+// Converts a `router` path with `:name` fragments into an OpenAPI path with `{name}`.
+fn openapi_path(prefix: &str, path: &str) -> String {
+    let segments = prefix
+        .split('/')
+        .chain(path.split('/'))
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| if segment.starts_with(':') {
+            format!("{{{}}}", &segment[1..])
+        } else {
+            segment.to_owned()
+        });
+
+    let mut result = String::new();
+    for segment in segments {
+        result.push('/');
+        result.push_str(&segment);
+    }
+    if result.is_empty() {
+        result.push('/');
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_openapi_path() {
+        assert_eq!(openapi_path("/api/explorer", "/v1/blocks"), "/api/explorer/v1/blocks");
+        assert_eq!(
+            openapi_path("api/explorer/", "/v1/blocks/:height/transactions"),
+            "/api/explorer/v1/blocks/{height}/transactions"
+        );
+        assert_eq!(openapi_path("", ""), "/");
+    }
+
+    #[test]
+    fn test_documented_router() {
+        use iron::{IronResult, Request, Response};
+        use iron::status;
+
+        fn handler(_: &mut Request) -> IronResult<Response> {
+            Ok(Response::with(status::Ok))
+        }
+
+        let endpoints = endpoints_of(|router| {
+            router.route(
+                Endpoint::get("/v1/blocks/:height", "Returns a block"),
+                handler,
+                "block",
+            );
+            router.route(
+                Endpoint::post("/v1/blocks", "Adds a block"),
+                handler,
+                "post_block",
+            );
+        });
+        assert_eq!(endpoints.len(), 2);
+        assert_eq!(endpoints[0].method, Method::Get);
+        assert_eq!(endpoints[0].path, "/v1/blocks/:height");
+        assert_eq!(endpoints[1].method, Method::Post);
+    }
+
+    #[test]
+    fn test_api_document() {
+        let endpoints = vec![
+            Endpoint::get("/v1/blocks/:height", "Returns a block")
+                .path_param("height", "Height of the block", integer())
+                .response(object(&[("block", any())])),
+            Endpoint::post("/v1/blocks/:height", "Does something with a block")
+                .path_param("height", "Height of the block", integer())
+                .request(object(&[("value", boolean())])),
+            Endpoint::get("/v1/blocks", "Returns blocks")
+                .query_param("count", "Number of blocks", true, integer())
+                .response(array(any())),
+        ];
+        let mut document = ApiDocument::new("Test API");
+        assert!(document.is_empty());
+        document.add_endpoints("/api/explorer", &endpoints);
+        assert_eq!(document.len(), 3);
+
+        let json = document.to_json();
+        assert_eq!(json["openapi"], OPENAPI_VERSION);
+        assert_eq!(json["info"]["title"], "Test API");
+
+        let paths = json["paths"].as_object().unwrap();
+        assert_eq!(paths.len(), 2);
+        let block = &paths["/api/explorer/v1/blocks/{height}"];
+        assert_eq!(block["get"]["summary"], "Returns a block");
+        assert_eq!(block["get"]["parameters"][0]["in"], "path");
+        assert_eq!(block["get"]["parameters"][0]["required"], true);
+        assert_eq!(
+            block["get"]["responses"]["200"]["content"]["application/json"]["schema"]["type"],
+            "object"
+        );
+        assert!(block["get"].get("requestBody").is_none());
+        assert_eq!(
+            block["post"]["requestBody"]["content"]["application/json"]["schema"]["properties"]
+                ["value"]["type"],
+            "boolean"
+        );
+        assert!(block["post"]["responses"]["200"].get("content").is_none());
+
+        let blocks = &paths["/api/explorer/v1/blocks"];
+        assert_eq!(blocks["get"]["parameters"][0]["in"], "query");
+    }
+}
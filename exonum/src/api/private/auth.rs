@@ -0,0 +1,323 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Token-based authentication of the private API requests.
+//!
+//! Clients pass a token in the `Authorization: Bearer <token>` header. The node
+//! configuration stores only SHA-256 hashes of the tokens, together with the list of
+//! endpoints each token is allowed to access:
+//!
+//! ```toml
+//! [[api.private_api_auth.tokens]]
+//! name = "monitoring"
+//! token_hash = "..."
+//! allow = ["GET /api/system/*", "GET /api/services/configuration/*"]
+//!
+//! [[api.private_api_auth.tokens]]
+//! name = "admin"
+//! token_hash = "..."
+//! allow = ["* /api/*"]
+//! ```
+//!
+//! The hash of a token can be computed as `echo -n "<token>" | sha256sum`.
+
+use iron::{BeforeMiddleware, IronError, IronResult, Request, status};
+use iron::headers::{Authorization, Bearer};
+use iron::method::Method;
+use failure::Fail;
+use serde::{de, ser};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crypto::{self, Hash};
+
+/// Rule allowing access to one or several endpoints.
+///
+/// Rules are written as `<METHOD> <path>`, e.g. `GET /api/system/v1/peers`.
+/// `*` in place of the method matches any method, and a path ending with `*`
+/// matches all paths starting with the preceding prefix, e.g. `* /api/system/*`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EndpointRule {
+    method: Option<Method>,
+    path: String,
+    is_prefix: bool,
+}
+
+impl EndpointRule {
+    /// Returns `true` if the rule allows a request with the given method and path.
+    pub fn matches(&self, method: &Method, path: &str) -> bool {
+        if let Some(ref rule_method) = self.method {
+            if rule_method != method {
+                return false;
+            }
+        }
+        let path = path.trim_right_matches('/');
+        if self.is_prefix {
+            path.starts_with(&self.path)
+        } else {
+            path == self.path
+        }
+    }
+}
+
+impl FromStr for EndpointRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split_whitespace().collect();
+        if parts.len() != 2 {
+            return Err(format!(
+                "Invalid endpoint rule '{}', expected '<METHOD> <path>'",
+                s
+            ));
+        }
+
+        let method = match parts[0] {
+            "*" => None,
+            method => Some(method.to_uppercase().parse::<Method>().map_err(|e| {
+                format!("Invalid method in endpoint rule '{}': {}", s, e)
+            })?),
+        };
+        let path = parts[1];
+        if !path.starts_with('/') {
+            return Err(format!(
+                "Path in endpoint rule '{}' must start with '/'",
+                s
+            ));
+        }
+        let (path, is_prefix) = if path.ends_with('*') {
+            (&path[..path.len() - 1], true)
+        } else {
+            (path.trim_right_matches('/'), false)
+        };
+
+        Ok(EndpointRule {
+            method,
+            path: path.to_owned(),
+            is_prefix,
+        })
+    }
+}
+
+impl fmt::Display for EndpointRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.method {
+            Some(ref method) => write!(f, "{} ", method)?,
+            None => write!(f, "* ")?,
+        }
+        write!(f, "{}", self.path)?;
+        if self.is_prefix {
+            write!(f, "*")?;
+        }
+        Ok(())
+    }
+}
+
+impl ser::Serialize for EndpointRule {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for EndpointRule {
+    fn deserialize<D>(d: D) -> Result<EndpointRule, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(d)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Access token of the private API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccessToken {
+    /// Name of the token used in logs.
+    pub name: String,
+    /// SHA-256 hash of the token.
+    pub token_hash: Hash,
+    /// Endpoints accessible with the token.
+    pub allow: Vec<EndpointRule>,
+}
+
+/// Authentication options of the private API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuthConfig {
+    /// Tokens allowed to access the private API.
+    #[serde(default)]
+    pub tokens: Vec<AccessToken>,
+}
+
+/// Middleware rejecting requests to the private API without a valid token.
+///
+/// Requests without a known token are rejected with `401 Unauthorized`,
+/// requests to the endpoints the token is not allowed to access - with `403 Forbidden`.
+#[derive(Debug, Clone)]
+pub struct AuthMiddleware {
+    config: AuthConfig,
+}
+
+impl AuthMiddleware {
+    /// Creates a new middleware with the given options.
+    pub fn new(config: AuthConfig) -> Self {
+        AuthMiddleware { config }
+    }
+
+    /// Checks access of the given token to the endpoint.
+    pub fn authorize(&self, token: &str, method: &Method, path: &str) -> Result<(), AuthError> {
+        let token_hash = crypto::hash(token.as_bytes());
+        let token = self.config
+            .tokens
+            .iter()
+            .find(|t| t.token_hash == token_hash)
+            .ok_or(AuthError::UnknownToken)?;
+
+        if token.allow.iter().any(|rule| rule.matches(method, path)) {
+            Ok(())
+        } else {
+            warn!(
+                "Token '{}' is not allowed to access {} {}",
+                token.name,
+                method,
+                path
+            );
+            Err(AuthError::Forbidden)
+        }
+    }
+}
+
+/// Reasons of the rejection of a private API request.
+#[derive(Fail, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthError {
+    /// Request does not contain a token.
+    #[fail(display = "Missing bearer token")]
+    MissingToken,
+    /// Token is not present in the configuration.
+    #[fail(display = "Unknown bearer token")]
+    UnknownToken,
+    /// Token is not allowed to access the endpoint.
+    #[fail(display = "Token is not allowed to access the endpoint")]
+    Forbidden,
+}
+
+impl From<AuthError> for IronError {
+    fn from(e: AuthError) -> IronError {
+        let code = match e {
+            AuthError::MissingToken | AuthError::UnknownToken => status::Unauthorized,
+            AuthError::Forbidden => status::Forbidden,
+        };
+        let body = {
+            let mut map = BTreeMap::new();
+            map.insert("debug", format!("{:?}", e));
+            map.insert("description", e.to_string());
+            ::serde_json::to_string_pretty(&map).unwrap()
+        };
+        let mut error = IronError::new(e.compat(), (code, body));
+        if code == status::Unauthorized {
+            error.response.headers.set_raw(
+                "WWW-Authenticate",
+                vec![b"Bearer".to_vec()],
+            );
+        }
+        error
+    }
+}
+
+impl BeforeMiddleware for AuthMiddleware {
+    fn before(&self, req: &mut Request) -> IronResult<()> {
+        let token = match req.headers.get::<Authorization<Bearer>>() {
+            Some(&Authorization(Bearer { ref token })) => token.clone(),
+            None => return Err(AuthError::MissingToken.into()),
+        };
+        let path = format!("/{}", req.url.path().join("/"));
+        self.authorize(&token, &req.method, &path).map_err(
+            IronError::from,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn middleware() -> AuthMiddleware {
+        AuthMiddleware::new(AuthConfig {
+            tokens: vec![
+                AccessToken {
+                    name: "monitoring".to_owned(),
+                    token_hash: crypto::hash(b"monitoring-token"),
+                    allow: vec!["GET /api/system/*".parse().unwrap()],
+                },
+                AccessToken {
+                    name: "admin".to_owned(),
+                    token_hash: crypto::hash(b"admin-token"),
+                    allow: vec!["* /api/*".parse().unwrap()],
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn endpoint_rule_parse() {
+        let rule: EndpointRule = "GET /api/system/v1/peers/".parse().unwrap();
+        assert_eq!(rule.to_string(), "GET /api/system/v1/peers");
+        assert!(rule.matches(&Method::Get, "/api/system/v1/peers"));
+        assert!(rule.matches(&Method::Get, "/api/system/v1/peers/"));
+        assert!(!rule.matches(&Method::Post, "/api/system/v1/peers"));
+        assert!(!rule.matches(&Method::Get, "/api/system/v1/peers/1"));
+
+        let rule: EndpointRule = "* /api/system/*".parse().unwrap();
+        assert_eq!(rule.to_string(), "* /api/system/*");
+        assert!(rule.matches(&Method::Post, "/api/system/v1/shutdown"));
+        assert!(!rule.matches(&Method::Get, "/api/services/configuration/v1/configs"));
+
+        assert!("/api/system".parse::<EndpointRule>().is_err());
+        assert!("GET api/system".parse::<EndpointRule>().is_err());
+    }
+
+    #[test]
+    fn endpoint_rule_serde() {
+        let rule: EndpointRule = "post /api/system/v1/shutdown".parse().unwrap();
+        let json = ::serde_json::to_string(&rule).unwrap();
+        assert_eq!(json, "\"POST /api/system/v1/shutdown\"");
+        let rule2: EndpointRule = ::serde_json::from_str(&json).unwrap();
+        assert_eq!(rule, rule2);
+    }
+
+    #[test]
+    fn authorize() {
+        let auth = middleware();
+        assert_eq!(
+            auth.authorize("monitoring-token", &Method::Get, "/api/system/v1/peers"),
+            Ok(())
+        );
+        assert_eq!(
+            auth.authorize("monitoring-token", &Method::Post, "/api/system/v1/shutdown"),
+            Err(AuthError::Forbidden)
+        );
+        assert_eq!(
+            auth.authorize("admin-token", &Method::Post, "/api/system/v1/shutdown"),
+            Ok(())
+        );
+        assert_eq!(
+            auth.authorize("other-token", &Method::Get, "/api/system/v1/peers"),
+            Err(AuthError::UnknownToken)
+        );
+    }
+}
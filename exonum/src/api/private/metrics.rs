@@ -0,0 +1,31 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use iron::prelude::*;
+use iron::status;
+
+use helpers::metrics::Registry;
+
+/// Content type of the Prometheus text format.
+const CONTENT_TYPE: &[u8] = b"text/plain; version=0.0.4";
+
+/// Handler returning the metrics of the [global registry] in the Prometheus text format.
+/// The node mounts it at `/metrics` of the private API.
+///
+/// [global registry]: ../../helpers/metrics/struct.Registry.html#method.global
+pub fn metrics_handler(_: &mut Request) -> IronResult<Response> {
+    let mut response = Response::with((status::Ok, Registry::global().encode()));
+    response.headers.set_raw("Content-Type", vec![CONTENT_TYPE.to_vec()]);
+    Ok(response)
+}
@@ -14,6 +14,10 @@
 
 //! Private part of the Exonum rest api.
 
+pub use self::auth::{AccessToken, AuthConfig, AuthError, AuthMiddleware, EndpointRule};
+pub use self::metrics::metrics_handler;
 pub use self::system::{SystemApi, NodeInfo};
 
+mod auth;
+mod metrics;
 mod system;
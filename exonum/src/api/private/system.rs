@@ -12,8 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use serde_json;
-use router::Router;
+use serde_json::{self, Value as JsonValue};
 use iron::prelude::*;
 
 use std::net::SocketAddr;
@@ -23,6 +22,7 @@ use crypto::PublicKey;
 use node::{ExternalMessage, ApiSender};
 use blockchain::{Service, Blockchain, SharedNodeState};
 use api::{Api, ApiError};
+use api::openapi::{self, DocumentedRouter, Endpoint};
 use messages::PROTOCOL_MAJOR_VERSION;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -141,16 +141,23 @@ impl SystemApi {
         }
     }
 
-    fn handle_peers_info(self, router: &mut Router) {
+    fn handle_peers_info(self, router: &mut DocumentedRouter) {
         let peers_info = move |_: &mut Request| -> IronResult<Response> {
             let info = self.peers_info();
             self.ok_response(&serde_json::to_value(info).unwrap())
         };
 
-        router.get("/v1/peers", peers_info, "peers_info");
+        let endpoint = Endpoint::get(
+            "/v1/peers",
+            "Returns incoming and outgoing connections of the node",
+        ).response(openapi::object(&[
+            ("incoming_connections", openapi::array(openapi::string())),
+            ("outgoing_connections", openapi::dictionary(openapi::any())),
+        ]));
+        router.route(endpoint, peers_info, "peers_info");
     }
 
-    fn handle_peer_add(self, router: &mut Router) {
+    fn handle_peer_add(self, router: &mut DocumentedRouter) {
         let peer_add = move |request: &mut Request| -> IronResult<Response> {
             #[derive(Serialize, Deserialize, Clone, Debug)]
             struct PeerAddInfo {
@@ -162,32 +169,50 @@ impl SystemApi {
             self.ok_response(&serde_json::to_value("Ok").unwrap())
         };
 
-        router.post("/v1/peers", peer_add, "peer_add");
+        let endpoint = Endpoint::post(
+            "/v1/peers",
+            "Connects the node to the peer with the given address",
+        ).request(openapi::object(&[("ip", openapi::string())]))
+            .response(ok_schema());
+        router.route(endpoint, peer_add, "peer_add");
     }
 
-    fn handle_network(self, router: &mut Router) {
+    fn handle_network(self, router: &mut DocumentedRouter) {
         let network = move |_: &mut Request| -> IronResult<Response> {
             let info = self.info.clone();
             self.ok_response(&serde_json::to_value(info).unwrap())
         };
 
-        router.get("/v1/network", network, "network_info");
+        let endpoint = Endpoint::get(
+            "/v1/network",
+            "Returns the protocol version and the services",
+        ).response(openapi::object(&[
+            ("protocol_version", openapi::integer()),
+            (
+                "services",
+                openapi::array(openapi::object(&[
+                    ("name", openapi::string()),
+                    ("id", openapi::integer()),
+                ])),
+            ),
+        ]));
+        router.route(endpoint, network, "network_info");
     }
 
-    fn handle_is_consensus_enabled(self, router: &mut Router) {
+    fn handle_is_consensus_enabled(self, router: &mut DocumentedRouter) {
         let consensus_enabled_info = move |_: &mut Request| -> IronResult<Response> {
             let info = self.shared_api_state.is_enabled();
             self.ok_response(&serde_json::to_value(info).unwrap())
         };
 
-        router.get(
+        let endpoint = Endpoint::get(
             "/v1/consensus_enabled",
-            consensus_enabled_info,
-            "consensus_enabled_info",
-        );
+            "Returns whether the consensus is enabled",
+        ).response(openapi::boolean());
+        router.route(endpoint, consensus_enabled_info, "consensus_enabled_info");
     }
 
-    fn handle_set_consensus_enabled(self, router: &mut Router) {
+    fn handle_set_consensus_enabled(self, router: &mut DocumentedRouter) {
         let consensus_enabled_set = move |request: &mut Request| -> IronResult<Response> {
             #[derive(Serialize, Deserialize, Clone, Debug)]
             struct EnabledInfo {
@@ -202,14 +227,15 @@ impl SystemApi {
             self.ok_response(&serde_json::to_value("Ok").unwrap())
         };
 
-        router.post(
+        let endpoint = Endpoint::post(
             "/v1/consensus_enabled",
-            consensus_enabled_set,
-            "consensus_enabled_set",
-        );
+            "Enables or disables the consensus",
+        ).request(openapi::object(&[("enabled", openapi::boolean())]))
+            .response(ok_schema());
+        router.route(endpoint, consensus_enabled_set, "consensus_enabled_set");
     }
 
-    fn handle_shutdown(self, router: &mut Router) {
+    fn handle_shutdown(self, router: &mut DocumentedRouter) {
         let shutdown = move |_: &mut Request| -> IronResult<Response> {
             self.node_channel
                 .send_external_message(ExternalMessage::Shutdown)
@@ -217,12 +243,13 @@ impl SystemApi {
             self.ok_response(&serde_json::to_value("Ok").unwrap())
         };
 
-        router.post("/v1/shutdown", shutdown, "shutdown");
+        let endpoint = Endpoint::post("/v1/shutdown", "Shuts the node down").response(ok_schema());
+        router.route(endpoint, shutdown, "shutdown");
     }
 }
 
 impl Api for SystemApi {
-    fn wire(&self, router: &mut Router) {
+    fn wire_documented(&self, router: &mut DocumentedRouter) {
         self.clone().handle_peers_info(router);
         self.clone().handle_peer_add(router);
         self.clone().handle_network(router);
@@ -231,3 +258,8 @@ impl Api for SystemApi {
         self.clone().handle_shutdown(router);
     }
 }
+
+// Body of the responses of the endpoints changing the node state.
+fn ok_schema() -> JsonValue {
+    openapi::enumeration(&["Ok"])
+}
@@ -12,23 +12,36 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use router::Router;
+use byteorder::{ByteOrder, LittleEndian};
 use serde_json::Value as JsonValue;
 use iron::prelude::*;
+use hyper::header::ContentType;
+use hyper::mime::{Mime, SubLevel, TopLevel};
 
 use std::ops::Range;
 use std::cmp;
 use std::error::Error;
+use std::str::FromStr;
 
-use api::{Api, ApiError};
-use blockchain::{Transaction, Block, Blockchain, TxLocation, Schema, TransactionErrorType,
-                 TransactionResult};
-use crypto::Hash;
+use api::{read_body, Api, ApiError};
+use api::openapi::{self, DocumentedRouter, Endpoint};
+use blockchain::{Transaction, Block, Blockchain, Equivocation, ExecutionStatus, TxLocation, Schema,
+                 TransactionErrorType, TransactionResult, ValidatorsStatistics};
+use crypto::{Hash, SIGNATURE_LENGTH};
+use encoding::Error as MessageError;
+use encoding::serialize::FromHex;
 use helpers::Height;
-use messages::Precommit;
-use storage::{ListProof, Snapshot};
+use messages::{HEADER_LENGTH, Precommit, RawMessage};
+use node::TransactionSend;
+use storage::{ListIndex, ListProof, Snapshot};
 
 const MAX_BLOCKS_PER_REQUEST: u64 = 1000;
+const MAX_TRANSACTIONS_PER_REQUEST: usize = 10_000;
+// Maximum length of the binary body with a batch of transactions.
+const MAX_TRANSACTIONS_BODY_LENGTH: u64 = 16 * 1024 * 1024;
+const MAX_ITEMS_PER_PAGE: u64 = 1000;
+// Maximum number of index entries looked through to fill a single page of filtered items.
+const MAX_SCANNED_ITEMS_PER_PAGE: u64 = 10_000;
 
 /// Block information.
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -55,7 +68,7 @@ pub struct TxInfo {
 }
 
 /// Transaction execution status. Simplified version of `TransactionResult`.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "kebab-case")]
 pub enum TxStatus {
     /// Successful transaction execution.
@@ -74,6 +87,101 @@ pub enum TxStatus {
     },
 }
 
+/// Collects the page of items with positions in `0..len`, starting from the `cursor`
+/// position. `f` returns `None` for the positions that do not match the filter.
+fn paginate<T, F>(
+    len: u64,
+    cursor: Option<u64>,
+    limit: u64,
+    direction: Direction,
+    mut f: F,
+) -> Result<Page<T>, ApiError>
+where
+    F: FnMut(u64) -> Result<Option<T>, ApiError>,
+{
+    let positions: Box<Iterator<Item = u64>> = match direction {
+        Direction::Forward => Box::new(cursor.unwrap_or(0)..len),
+        Direction::Backward => {
+            let end = cursor.map_or(len, |cursor| cmp::min(cursor.saturating_add(1), len));
+            Box::new((0..end).rev())
+        }
+    };
+
+    let mut items = Vec::new();
+    let mut scanned = 0;
+    for position in positions {
+        if items.len() as u64 == limit || scanned == MAX_SCANNED_ITEMS_PER_PAGE {
+            return Ok(Page {
+                items,
+                next_cursor: Some(position),
+            });
+        }
+        scanned += 1;
+        if let Some(item) = f(position)? {
+            items.push(item);
+        }
+    }
+    Ok(Page {
+        items,
+        next_cursor: None,
+    })
+}
+
+/// Returns `true` if the request body contains binary data rather than JSON.
+fn is_binary(req: &Request) -> bool {
+    match req.headers.get::<ContentType>() {
+        Some(&ContentType(Mime(TopLevel::Application, SubLevel::OctetStream, _))) => true,
+        _ => false,
+    }
+}
+
+/// Splits the stream of transactions, each prefixed with its length as a little-endian `u32`.
+fn split_length_prefixed(mut bytes: &[u8]) -> Result<Vec<Vec<u8>>, ApiError> {
+    let mut txs = Vec::new();
+    while !bytes.is_empty() {
+        if txs.len() == MAX_TRANSACTIONS_PER_REQUEST {
+            return Err(too_many_transactions());
+        }
+        if bytes.len() < 4 {
+            return Err(ApiError::BadRequest(
+                "Unexpected end of the transactions stream".to_owned(),
+            ));
+        }
+        let len = LittleEndian::read_u32(&bytes[..4]) as usize;
+        bytes = &bytes[4..];
+        if bytes.len() < len {
+            return Err(ApiError::BadRequest(
+                "Unexpected end of the transactions stream".to_owned(),
+            ));
+        }
+        txs.push(bytes[..len].to_vec());
+        bytes = &bytes[len..];
+    }
+    Ok(txs)
+}
+
+fn too_many_transactions() -> ApiError {
+    ApiError::BadRequest(format!(
+        "Max transaction count per request exceeded ({})",
+        MAX_TRANSACTIONS_PER_REQUEST
+    ))
+}
+
+impl<'a> From<&'a TransactionResult> for TxStatus {
+    fn from(result: &'a TransactionResult) -> Self {
+        match *result {
+            Ok(()) => TxStatus::Success,
+            Err(ref e) => {
+                let description = e.description().unwrap_or_default().to_owned();
+                match e.error_type() {
+                    TransactionErrorType::Panic => TxStatus::Panic { description },
+                    TransactionErrorType::Code(code) => TxStatus::Error { code, description },
+                }
+            }
+        }
+    }
+}
+
 /// Information on blocks coupled with the corresponding range in the blockchain.
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 pub struct BlocksRange {
@@ -98,6 +206,106 @@ pub enum TransactionInfo {
     Committed(TxInfo),
 }
 
+/// Response to the transaction submission.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct TransactionHash {
+    /// Hash of the submitted transaction.
+    pub tx_hash: Hash,
+}
+
+/// Hex-encoded signed transaction submitted via the explorer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionHex {
+    /// Hex representation of the transaction, including the header and the signature.
+    pub tx_body: String,
+}
+
+impl TransactionHex {
+    fn decode(&self) -> Result<Vec<u8>, String> {
+        Vec::<u8>::from_hex(&self.tx_body).map_err(|e| {
+            format!("Invalid hex in 'tx_body': {}", e)
+        })
+    }
+}
+
+/// Result of the submission of a single transaction from a batch.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum SubmissionResult {
+    /// Transaction is valid and has been sent to the node.
+    Accepted {
+        /// Hash of the transaction.
+        tx_hash: Hash,
+    },
+    /// Transaction has been rejected.
+    Rejected {
+        /// Reason of the rejection.
+        description: String,
+    },
+}
+
+/// Direction in which paginated listings are traversed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    /// From older items to newer ones.
+    Forward,
+    /// From newer items to older ones.
+    Backward,
+}
+
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "forward" => Ok(Direction::Forward),
+            "backward" => Ok(Direction::Backward),
+            _ => Err(format!(
+                "Unknown direction '{}', expected 'forward' or 'backward'",
+                s
+            )),
+        }
+    }
+}
+
+/// Page of a paginated listing.
+///
+/// Cursors are positions in the underlying index: a block height for blocks, a position
+/// in the block for its transactions, and a position in the corresponding secondary index
+/// for filtered transactions. Pass `next_cursor` to get the next page.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct Page<T> {
+    /// Items on the page.
+    pub items: Vec<T>,
+    /// Cursor of the next page or `None` if there are no more items.
+    pub next_cursor: Option<u64>,
+}
+
+/// Committed transaction with its location and execution status.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct CommittedTransaction {
+    /// Hash of the transaction.
+    pub tx_hash: Hash,
+    /// `JSON` serialized transaction.
+    pub content: JsonValue,
+    /// Transaction location in block.
+    pub location: TxLocation,
+    /// Status of the transaction execution.
+    pub status: TxStatus,
+}
+
+/// Filter of committed transactions. Empty filter matches all transactions.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionFilter {
+    /// Identifier of the service.
+    pub service_id: Option<u16>,
+    /// Message type of the transaction. Requires `service_id` to be set.
+    pub message_type: Option<u16>,
+    /// Execution status of the transaction.
+    pub status: Option<ExecutionStatus>,
+}
+
 /// Public explorer API.
 #[derive(Clone, Debug)]
 pub struct ExplorerApi {
@@ -129,6 +337,30 @@ impl ExplorerApi {
         Ok(self.explorer().blocks_range(count, from, skip_empty_blocks))
     }
 
+    fn validators_statistics(
+        &self,
+        count: u64,
+        from: Option<u64>,
+    ) -> Result<ValidatorsStatistics, ApiError> {
+        if count > MAX_BLOCKS_PER_REQUEST {
+            return Err(ApiError::BadRequest(format!(
+                "Max block count per request exceeded ({})",
+                MAX_BLOCKS_PER_REQUEST
+            )));
+        }
+        Ok(self.explorer().validators_statistics(count, from))
+    }
+
+    fn check_page_limit(limit: u64) -> Result<(), ApiError> {
+        if limit > MAX_ITEMS_PER_PAGE {
+            return Err(ApiError::BadRequest(format!(
+                "Max items count per request exceeded ({})",
+                MAX_ITEMS_PER_PAGE
+            )));
+        }
+        Ok(())
+    }
+
     fn block(&self, height: Height) -> Option<BlockInfo> {
         self.explorer().block_info(height)
     }
@@ -162,7 +394,93 @@ impl ExplorerApi {
         }
     }
 
-    fn set_blocks_response(self, router: &mut Router) {
+    fn raw_transaction(&self, req: &mut Request) -> Result<Vec<u8>, ApiError> {
+        if is_binary(req) {
+            read_body(req, u64::from(self.max_message_len()))
+        } else {
+            let raw: TransactionHex = self.parse_body(req)?;
+            raw.decode().map_err(ApiError::BadRequest)
+        }
+    }
+
+    fn raw_transactions(
+        &self,
+        req: &mut Request,
+    ) -> Result<Vec<Result<Vec<u8>, String>>, ApiError> {
+        if is_binary(req) {
+            let bytes = read_body(req, MAX_TRANSACTIONS_BODY_LENGTH)?;
+            Ok(split_length_prefixed(&bytes)?.into_iter().map(Ok).collect())
+        } else {
+            let raw: Vec<TransactionHex> = self.parse_body(req)?;
+            if raw.len() > MAX_TRANSACTIONS_PER_REQUEST {
+                return Err(too_many_transactions());
+            }
+            Ok(raw.iter().map(TransactionHex::decode).collect())
+        }
+    }
+
+    fn max_message_len(&self) -> u32 {
+        Schema::new(self.blockchain.snapshot())
+            .actual_configuration()
+            .consensus
+            .max_message_len
+    }
+
+    fn check_transaction(&self, bytes: Vec<u8>) -> Result<Box<Transaction>, String> {
+        if bytes.len() < HEADER_LENGTH + SIGNATURE_LENGTH {
+            return Err(format!("Transaction is too short: {} bytes", bytes.len()));
+        }
+        let raw = RawMessage::from_vec(bytes);
+        if raw.payload_length() != raw.len() {
+            return Err(format!(
+                "Transaction length ({}) does not match the length in the header ({})",
+                raw.len(),
+                raw.payload_length()
+            ));
+        }
+
+        let service_id = raw.service_id();
+        let tx = self.blockchain.tx_from_raw(raw).map_err(|e| match e {
+            // Errors in the body already contain the service and message identifiers
+            // as well as the path to the incorrect field.
+            MessageError::InMessage { .. } => e.to_string(),
+            _ => format!("{}, service_id={}", e.description(), service_id),
+        })?;
+        if !tx.verify() {
+            return Err("Transaction verification failed".to_owned());
+        }
+        Ok(tx)
+    }
+
+    fn submit_transaction(&self, bytes: Vec<u8>) -> Result<TransactionHash, ApiError> {
+        let tx = self.check_transaction(bytes).map_err(ApiError::BadRequest)?;
+        let tx_hash = tx.hash();
+        self.blockchain.api_sender().send(tx)?;
+        Ok(TransactionHash { tx_hash })
+    }
+
+    fn submit_transactions(
+        &self,
+        raw_txs: Vec<Result<Vec<u8>, String>>,
+    ) -> Result<Vec<SubmissionResult>, ApiError> {
+        let mut accepted = Vec::with_capacity(raw_txs.len());
+        let results: Vec<_> = raw_txs
+            .into_iter()
+            .map(|bytes| match bytes.and_then(|bytes| self.check_transaction(bytes)) {
+                Ok(tx) => {
+                    let tx_hash = tx.hash();
+                    accepted.push(tx);
+                    SubmissionResult::Accepted { tx_hash }
+                }
+                Err(description) => SubmissionResult::Rejected { description },
+            })
+            .collect();
+
+        self.blockchain.api_sender().send_batch(accepted)?;
+        Ok(results)
+    }
+
+    fn set_blocks_response(self, router: &mut DocumentedRouter) {
         let blocks = move |req: &mut Request| -> IronResult<Response> {
             let count: u64 = self.required_param(req, "count")?;
             let latest: Option<u64> = self.optional_param(req, "latest")?;
@@ -172,20 +490,136 @@ impl ExplorerApi {
             self.ok_response(&::serde_json::to_value(info).unwrap())
         };
 
-        router.get("/v1/blocks", blocks, "blocks");
+        let endpoint = Endpoint::get("/v1/blocks", "Returns the range of the latest blocks")
+            .query_param("count", "Number of blocks to return", true, openapi::integer())
+            .query_param(
+                "latest",
+                "Height of the latest block in the range",
+                false,
+                openapi::integer(),
+            )
+            .query_param(
+                "skip_empty_blocks",
+                "Whether to skip blocks without transactions",
+                false,
+                openapi::boolean(),
+            )
+            .response(openapi::object(&[
+                ("range", range_schema()),
+                ("blocks", openapi::array(block_schema())),
+            ]));
+        router.route(endpoint, blocks, "blocks");
     }
 
-    fn set_block_response(self, router: &mut Router) {
+    fn set_block_response(self, router: &mut DocumentedRouter) {
         let block = move |req: &mut Request| -> IronResult<Response> {
             let height: Height = self.url_fragment(req, "height")?;
             let info = self.block(height);
             self.ok_response(&::serde_json::to_value(info).unwrap())
         };
 
-        router.get("/v1/blocks/:height", block, "height");
+        let endpoint = Endpoint::get("/v1/blocks/:height", "Returns the block with precommits")
+            .path_param("height", "Height of the block", openapi::integer())
+            .response(openapi::nullable(openapi::object(&[
+                ("block", block_schema()),
+                ("precommits", openapi::array(openapi::any())),
+                ("txs", openapi::array(openapi::hash())),
+            ])));
+        router.route(endpoint, block, "height");
     }
 
-    fn set_transaction_info_response(self, router: &mut Router) {
+    fn set_blocks_page_response(self, router: &mut DocumentedRouter) {
+        let blocks = move |req: &mut Request| -> IronResult<Response> {
+            let limit: u64 = self.required_param(req, "limit")?;
+            let cursor: Option<u64> = self.optional_param(req, "cursor")?;
+            let direction: Direction = self.optional_param(req, "direction")?
+                .unwrap_or(Direction::Backward);
+            Self::check_page_limit(limit)?;
+            let page = self.explorer().blocks_page(cursor, limit, direction);
+            self.ok_response(&::serde_json::to_value(page).unwrap())
+        };
+
+        let endpoint = page_params(
+            Endpoint::get("/v1/blocks/page", "Returns a page of blocks"),
+            "backward",
+        ).response(page_schema(block_schema()));
+        router.route(endpoint, blocks, "blocks_page");
+    }
+
+    fn set_block_transactions_response(self, router: &mut DocumentedRouter) {
+        let transactions = move |req: &mut Request| -> IronResult<Response> {
+            let height: Height = self.url_fragment(req, "height")?;
+            let limit: u64 = self.required_param(req, "limit")?;
+            let cursor: Option<u64> = self.optional_param(req, "cursor")?;
+            let direction: Direction = self.optional_param(req, "direction")?
+                .unwrap_or(Direction::Forward);
+            Self::check_page_limit(limit)?;
+            let page = self.explorer().block_transactions_page(
+                height,
+                cursor,
+                limit,
+                direction,
+            )?;
+            self.ok_response(&::serde_json::to_value(page).unwrap())
+        };
+
+        let endpoint = page_params(
+            Endpoint::get(
+                "/v1/blocks/:height/transactions",
+                "Returns a page of transactions of the block",
+            ).path_param("height", "Height of the block", openapi::integer()),
+            "forward",
+        ).response(page_schema(committed_transaction_schema()));
+        router.route(endpoint, transactions, "block_transactions");
+    }
+
+    fn set_transactions_response(self, router: &mut DocumentedRouter) {
+        let transactions = move |req: &mut Request| -> IronResult<Response> {
+            let filter = TransactionFilter {
+                service_id: self.optional_param(req, "service_id")?,
+                message_type: self.optional_param(req, "message_type")?,
+                status: self.optional_param(req, "status")?,
+            };
+            let limit: u64 = self.required_param(req, "limit")?;
+            let cursor: Option<u64> = self.optional_param(req, "cursor")?;
+            let direction: Direction = self.optional_param(req, "direction")?
+                .unwrap_or(Direction::Backward);
+            Self::check_page_limit(limit)?;
+            let page = self.explorer().transactions_page(
+                &filter,
+                cursor,
+                limit,
+                direction,
+            )?;
+            self.ok_response(&::serde_json::to_value(page).unwrap())
+        };
+
+        let endpoint = page_params(
+            Endpoint::get("/v1/transactions", "Returns a page of committed transactions")
+                .query_param(
+                    "service_id",
+                    "Identifier of the service",
+                    false,
+                    openapi::integer(),
+                )
+                .query_param(
+                    "message_type",
+                    "Message type of the transaction, requires `service_id`",
+                    false,
+                    openapi::integer(),
+                )
+                .query_param(
+                    "status",
+                    "Execution status of the transaction",
+                    false,
+                    openapi::enumeration(&["success", "error", "panic"]),
+                ),
+            "backward",
+        ).response(page_schema(committed_transaction_schema()));
+        router.route(endpoint, transactions, "transactions");
+    }
+
+    fn set_transaction_info_response(self, router: &mut DocumentedRouter) {
         let transaction = move |req: &mut Request| -> IronResult<Response> {
             let hash: Hash = self.url_fragment(req, "hash")?;
             let info = self.transaction_info(&hash)?;
@@ -196,18 +630,166 @@ impl ExplorerApi {
             result(&self, &::serde_json::to_value(info).unwrap())
         };
 
-        router.get("/v1/transactions/:hash", transaction, "hash");
+        let endpoint = Endpoint::get(
+            "/v1/transactions/:hash",
+            "Returns information about the transaction",
+        ).path_param("hash", "Hash of the transaction", openapi::hash())
+            .response(openapi::object(&[
+                ("type", openapi::enumeration(&["unknown", "in-pool", "committed"])),
+            ]));
+        router.route(endpoint, transaction, "hash");
+    }
+
+    fn set_post_transaction_response(self, router: &mut DocumentedRouter) {
+        let transaction = move |req: &mut Request| -> IronResult<Response> {
+            let bytes = self.raw_transaction(req)?;
+            let info = self.submit_transaction(bytes)?;
+            self.ok_response(&::serde_json::to_value(info).unwrap())
+        };
+
+        let endpoint = Endpoint::post("/v1/transactions", "Submits a signed transaction")
+            .request(transaction_hex_schema())
+            .response(openapi::object(&[("tx_hash", openapi::hash())]));
+        router.route(endpoint, transaction, "post_transaction");
+    }
+
+    fn set_post_transactions_batch_response(self, router: &mut DocumentedRouter) {
+        let transactions = move |req: &mut Request| -> IronResult<Response> {
+            let raw_txs = self.raw_transactions(req)?;
+            let results = self.submit_transactions(raw_txs)?;
+            self.ok_response(&::serde_json::to_value(results).unwrap())
+        };
+
+        let endpoint = Endpoint::post(
+            "/v1/transactions/batch",
+            "Submits a batch of signed transactions",
+        ).request(openapi::array(transaction_hex_schema()))
+            .response(openapi::array(openapi::object(&[
+                ("type", openapi::enumeration(&["accepted", "rejected"])),
+            ])));
+        router.route(endpoint, transactions, "post_transactions_batch");
+    }
+
+    fn set_validators_statistics_response(self, router: &mut DocumentedRouter) {
+        let statistics = move |req: &mut Request| -> IronResult<Response> {
+            let count: u64 = self.required_param(req, "count")?;
+            let latest: Option<u64> = self.optional_param(req, "latest")?;
+            let info = self.validators_statistics(count, latest)?;
+            self.ok_response(&::serde_json::to_value(info).unwrap())
+        };
+
+        let endpoint = Endpoint::get(
+            "/v1/validators/statistics",
+            "Returns participation statistics of validators",
+        ).query_param("count", "Number of blocks to analyze", true, openapi::integer())
+            .query_param(
+                "latest",
+                "Height of the latest analyzed block",
+                false,
+                openapi::integer(),
+            )
+            .response(openapi::object(&[
+                ("range", range_schema()),
+                ("average_rounds", openapi::number()),
+                ("validators", openapi::array(openapi::any())),
+            ]));
+        router.route(endpoint, statistics, "validators_statistics");
+    }
+
+    fn set_equivocations_response(self, router: &mut DocumentedRouter) {
+        let equivocations = move |_: &mut Request| -> IronResult<Response> {
+            let evidence = self.explorer().equivocations();
+            self.ok_response(&::serde_json::to_value(evidence).unwrap())
+        };
+
+        let endpoint = Endpoint::get("/v1/equivocations", "Returns evidence of equivocations")
+            .response(openapi::array(openapi::any()));
+        router.route(endpoint, equivocations, "equivocations");
     }
 }
 
 impl Api for ExplorerApi {
-    fn wire(&self, router: &mut Router) {
+    fn wire_documented(&self, router: &mut DocumentedRouter) {
         self.clone().set_blocks_response(router);
         self.clone().set_block_response(router);
+        self.clone().set_blocks_page_response(router);
+        self.clone().set_block_transactions_response(router);
+        self.clone().set_transactions_response(router);
         self.clone().set_transaction_info_response(router);
+        self.clone().set_post_transaction_response(router);
+        self.clone().set_post_transactions_batch_response(router);
+        self.clone().set_equivocations_response(router);
+        self.clone().set_validators_statistics_response(router);
     }
 }
 
+fn page_params(endpoint: Endpoint, default_direction: &str) -> Endpoint {
+    endpoint
+        .query_param(
+            "limit",
+            "Maximum number of items on the page",
+            true,
+            openapi::integer(),
+        )
+        .query_param(
+            "cursor",
+            "Cursor returned as `next_cursor` with the previous page",
+            false,
+            openapi::integer(),
+        )
+        .query_param(
+            "direction",
+            &format!("Direction of the traversal, `{}` by default", default_direction),
+            false,
+            openapi::enumeration(&["forward", "backward"]),
+        )
+}
+
+fn range_schema() -> JsonValue {
+    openapi::object(&[("start", openapi::integer()), ("end", openapi::integer())])
+}
+
+fn block_schema() -> JsonValue {
+    openapi::object(&[
+        ("schema_version", openapi::integer()),
+        ("proposer_id", openapi::integer()),
+        ("height", openapi::integer_string()),
+        ("tx_count", openapi::integer()),
+        ("prev_hash", openapi::hash()),
+        ("tx_hash", openapi::hash()),
+        ("state_hash", openapi::hash()),
+    ])
+}
+
+fn page_schema(items: JsonValue) -> JsonValue {
+    openapi::object(&[
+        ("items", openapi::array(items)),
+        ("next_cursor", openapi::nullable(openapi::integer())),
+    ])
+}
+
+fn committed_transaction_schema() -> JsonValue {
+    openapi::object(&[
+        ("tx_hash", openapi::hash()),
+        ("content", openapi::any()),
+        (
+            "location",
+            openapi::object(&[
+                ("block_height", openapi::integer_string()),
+                ("position_in_block", openapi::integer_string()),
+            ]),
+        ),
+        (
+            "status",
+            openapi::object(&[("type", openapi::enumeration(&["success", "error", "panic"]))]),
+        ),
+    ])
+}
+
+fn transaction_hex_schema() -> JsonValue {
+    openapi::object(&[("tx_body", openapi::formatted_string("hex"))])
+}
+
 /// Blockchain explorer.
 #[derive(Debug)]
 pub struct BlockchainExplorer<'a> {
@@ -254,16 +836,7 @@ impl<'a> BlockchainExplorer<'a> {
             .get_proof(location.position_in_block());
 
         // Unwrap is OK here, because we already know that transaction is committed.
-        let status = match schema.transaction_results().get(tx_hash).unwrap() {
-            Ok(()) => TxStatus::Success,
-            Err(e) => {
-                let description = e.description().unwrap_or_default().to_owned();
-                match e.error_type() {
-                    TransactionErrorType::Panic => TxStatus::Panic { description },
-                    TransactionErrorType::Code(code) => TxStatus::Error { code, description },
-                }
-            }
-        };
+        let status = TxStatus::from(&schema.transaction_results().get(tx_hash).unwrap());
 
         Ok(Some(TxInfo {
             content,
@@ -347,9 +920,148 @@ impl<'a> BlockchainExplorer<'a> {
         }
     }
 
+    /// Returns the page of blocks starting from the `cursor` height in the given direction.
+    pub fn blocks_page(
+        &self,
+        cursor: Option<u64>,
+        limit: u64,
+        direction: Direction,
+    ) -> Page<Block> {
+        let schema = Schema::new(self.blockchain.snapshot());
+        let hashes = schema.block_hashes_by_height();
+        let blocks = schema.blocks();
+        let page: Result<_, ApiError> = paginate(hashes.len(), cursor, limit, direction, |height| {
+            let block_hash = hashes.get(height).expect(&format!(
+                "Block not found, height:{:?}",
+                height
+            ));
+            let block = blocks.get(&block_hash).expect(&format!(
+                "Block not found, hash:{:?}",
+                block_hash
+            ));
+            Ok(Some(block))
+        });
+        page.unwrap()
+    }
+
+    /// Returns the page of transactions in the block at the given height starting from
+    /// the `cursor` position in the block.
+    pub fn block_transactions_page(
+        &self,
+        height: Height,
+        cursor: Option<u64>,
+        limit: u64,
+        direction: Direction,
+    ) -> Result<Page<CommittedTransaction>, ApiError> {
+        let schema = Schema::new(self.blockchain.snapshot());
+        let block_transactions = schema.block_transactions(height);
+        paginate(block_transactions.len(), cursor, limit, direction, |position| {
+            let tx_hash = block_transactions.get(position).unwrap();
+            self.committed_transaction(&schema, tx_hash).map(Some)
+        })
+    }
+
+    /// Returns the page of committed transactions matching the filter starting from the `cursor`
+    /// position.
+    ///
+    /// The most specific secondary index is chosen based on the filter; the execution status
+    /// is checked on the fly if the service is specified as well. In this case the page may
+    /// contain less than `limit` items even if there are more matching transactions, since
+    /// a limited number of index entries is looked through per request.
+    pub fn transactions_page(
+        &self,
+        filter: &TransactionFilter,
+        cursor: Option<u64>,
+        limit: u64,
+        direction: Direction,
+    ) -> Result<Page<CommittedTransaction>, ApiError> {
+        let schema = Schema::new(self.blockchain.snapshot());
+        let (index, status): (ListIndex<_, Hash>, _) =
+            match (filter.service_id, filter.message_type, filter.status) {
+                (Some(service_id), Some(message_type), status) => {
+                    (schema.transactions_by_message_type(service_id, message_type), status)
+                }
+                (Some(service_id), None, status) => {
+                    (schema.transactions_by_service(service_id), status)
+                }
+                (None, None, Some(status)) => (schema.transactions_by_status(status), None),
+                (None, None, None) => (schema.committed_transactions(), None),
+                (None, Some(_), _) => {
+                    return Err(ApiError::BadRequest(
+                        "Filter by 'message_type' requires 'service_id'".to_owned(),
+                    ))
+                }
+            };
+        let results = schema.transaction_results();
+
+        paginate(index.len(), cursor, limit, direction, |position| {
+            let tx_hash = index.get(position).unwrap();
+            if let Some(status) = status {
+                let result = results.get(&tx_hash).unwrap();
+                if ExecutionStatus::from(&result) != status {
+                    return Ok(None);
+                }
+            }
+            self.committed_transaction(&schema, tx_hash).map(Some)
+        })
+    }
+
+    fn committed_transaction<T: AsRef<Snapshot>>(
+        &self,
+        schema: &Schema<T>,
+        tx_hash: Hash,
+    ) -> Result<CommittedTransaction, ApiError> {
+        let raw_tx = schema.transactions().get(&tx_hash).expect(&format!(
+            "Transaction not found, hash: {:?}",
+            tx_hash
+        ));
+        let content = self.blockchain
+            .tx_from_raw(raw_tx)
+            .map_err(|error| {
+                ApiError::InternalError(
+                    format!("{}, tx: {:?}", error.description(), tx_hash).into(),
+                )
+            })?
+            .serialize_field()
+            .map_err(ApiError::InternalError)?;
+        let location = schema.transactions_locations().get(&tx_hash).expect(
+            &format!(
+                "Not found tx_hash location: {:?}",
+                tx_hash
+            ),
+        );
+        // Unwrap is OK here, because we already know that transaction is committed.
+        let status = TxStatus::from(&schema.transaction_results().get(&tx_hash).unwrap());
+
+        Ok(CommittedTransaction {
+            tx_hash,
+            content,
+            location,
+            status,
+        })
+    }
+
     /// Returns transaction result.
     pub fn transaction_result(&self, hash: &Hash) -> Option<TransactionResult> {
         let schema = Schema::new(self.blockchain.snapshot());
         schema.transaction_results().get(hash)
     }
+
+    /// Returns participation statistics of validators over the `count` latest blocks
+    /// up to and including the `upper` height.
+    pub fn validators_statistics(&self, count: u64, upper: Option<u64>) -> ValidatorsStatistics {
+        let snapshot = self.blockchain.snapshot();
+        let max_height: u64 = Schema::new(&*snapshot).height().into();
+        let upper = upper.map(|x| cmp::min(x, max_height)).unwrap_or(max_height);
+        let lower = (upper + 1).saturating_sub(count);
+        ValidatorsStatistics::compute(&*snapshot, lower..upper + 1)
+    }
+
+    /// Returns evidence of equivocation observed by the node.
+    pub fn equivocations(&self) -> Vec<Equivocation> {
+        let schema = Schema::new(self.blockchain.snapshot());
+        let equivocations = schema.equivocations();
+        let it = equivocations.values();
+        it.collect()
+    }
 }
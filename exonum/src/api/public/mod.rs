@@ -14,9 +14,14 @@
 
 //! Public part of the Exonum rest api.
 
-pub use self::system::{HealthCheckInfo, SystemApi};
-pub use self::blockchain_explorer::{BlockInfo, BlockchainExplorer, BlocksRange, ExplorerApi,
-                                    TransactionInfo, TxInfo, TxStatus};
+pub use self::system::{HealthCheckInfo, ServiceSchemaInfo, SystemApi};
+pub use self::blockchain_explorer::{BlockInfo, BlockchainExplorer, BlocksRange,
+                                    CommittedTransaction, Direction, ExplorerApi, Page,
+                                    SubmissionResult, TransactionFilter, TransactionHash,
+                                    TransactionHex, TransactionInfo, TxInfo, TxStatus};
+pub use self::websocket::{start_websocket_api, ClientMessage, Notification, Notifier,
+                          Subscription, TransactionStatus, WebSocketConfig, WEBSOCKET_API_PATH};
 
 mod system;
 mod blockchain_explorer;
+mod websocket;
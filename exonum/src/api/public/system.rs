@@ -12,23 +12,104 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use router::Router;
 use iron::prelude::*;
-use serde_json;
+use iron::status;
+use hyper::header::ContentType;
+use serde_json::{self, Value as JsonValue};
+
+use std::time::SystemTime;
 
 use blockchain::{Schema, Blockchain, SharedNodeState};
+use encoding::schema::MessageSchema;
 use api::Api;
-use helpers::user_agent;
+use api::openapi::{self, DocumentedRouter, Endpoint};
+use helpers::{user_agent, Height, Round};
 
 #[derive(Serialize, Deserialize, PartialEq)]
 struct MemPoolInfo {
     pub size: usize,
 }
 
-#[doc(hidden)]
+/// Health of the node returned by the `v1/healthcheck` endpoint.
+///
+/// The endpoint responds with `200 OK` if the node is healthy and with
+/// `503 Service Unavailable` otherwise, so it can be used by load balancer probes.
+/// Consensus-related fields are updated by the node every `state_update_timeout`
+/// milliseconds.
 #[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
 pub struct HealthCheckInfo {
+    /// Does the node have connected peers?
     pub connectivity: bool,
+    /// Current height of the node.
+    pub height: Height,
+    /// Maximum height reported by the peers of the node.
+    pub max_known_height: Height,
+    /// Is the node in sync with the peers, i.e., no peer is on a bigger height?
+    pub in_sync: bool,
+    /// Current round at the current height.
+    pub round: Round,
+    /// Milliseconds passed since the last commit or since the node start,
+    /// `None` if the node has not reported its state yet.
+    pub since_last_commit: Option<u64>,
+    /// Does the node participate in the consensus?
+    pub consensus_enabled: bool,
+    /// Did the last write of the node to the database succeed? The health check itself
+    /// never writes to the database.
+    pub database_writable: bool,
+    /// Is the node healthy? A node is healthy if it is in sync, participates
+    /// in the consensus and its database is writable.
+    pub healthy: bool,
+}
+
+impl HealthCheckInfo {
+    fn new(state: &SharedNodeState) -> Self {
+        let height = state.height();
+        let max_known_height = state.max_known_height();
+        let in_sync = max_known_height <= height;
+        let since_last_commit = state.height_start_time().map(|start| {
+            let elapsed = SystemTime::now().duration_since(start).unwrap_or_default();
+            elapsed.as_secs() * 1_000 + u64::from(elapsed.subsec_nanos() / 1_000_000)
+        });
+        let consensus_enabled = state.is_enabled();
+        let database_writable = state.is_database_writable();
+
+        HealthCheckInfo {
+            connectivity: !state.peers_info().is_empty(),
+            height,
+            max_known_height,
+            in_sync,
+            round: state.round(),
+            since_last_commit,
+            consensus_enabled,
+            database_writable,
+            healthy: in_sync && consensus_enabled && database_writable,
+        }
+    }
+}
+
+/// Returns the health of the node with `200 OK` if the node is healthy and
+/// with `503 Service Unavailable` otherwise.
+fn healthcheck_response(info: &HealthCheckInfo) -> Response {
+    let code = if info.healthy {
+        status::Ok
+    } else {
+        status::ServiceUnavailable
+    };
+    let body = serde_json::to_string_pretty(info).unwrap();
+    let mut response = Response::with((code, body));
+    response.headers.set(ContentType::json());
+    response
+}
+
+/// Transaction layouts of a service returned by the `v1/schemas` endpoint.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ServiceSchemaInfo {
+    /// Identifier of the service.
+    pub service_id: u16,
+    /// Name of the service.
+    pub service_name: String,
+    /// Layouts of the service transactions.
+    pub messages: Vec<MessageSchema>,
 }
 
 /// Public system API.
@@ -47,38 +128,240 @@ impl SystemApi {
         }
     }
 
-    fn mempool_info(self, router: &mut Router) {
+    fn mempool_info(self, router: &mut DocumentedRouter) {
         let mempool = move |_: &mut Request| -> IronResult<Response> {
             let snapshot = self.blockchain.snapshot();
             let schema = Schema::new(&snapshot);
             let info = MemPoolInfo { size: schema.transactions_pool_len() };
             self.ok_response(&serde_json::to_value(info).unwrap())
         };
-        router.get("/v1/mempool", mempool, "mempool");
+        let endpoint = Endpoint::get(
+            "/v1/mempool",
+            "Returns the number of transactions in the pool",
+        ).response(openapi::object(&[("size", openapi::integer())]));
+        router.route(endpoint, mempool, "mempool");
     }
 
-    fn healthcheck_info(self, router: &mut Router) {
+    fn healthcheck_info(self, router: &mut DocumentedRouter) {
         let healthcheck = move |_: &mut Request| -> IronResult<Response> {
-            let info =
-                HealthCheckInfo { connectivity: !self.shared_api_state.peers_info().is_empty() };
+            let info = HealthCheckInfo::new(&self.shared_api_state);
+            Ok(healthcheck_response(&info))
+        };
+        let endpoint = Endpoint::get("/v1/healthcheck", "Returns the health of the node")
+            .response(openapi::object(
+                &[
+                    ("connectivity", openapi::boolean()),
+                    ("height", openapi::integer()),
+                    ("max_known_height", openapi::integer()),
+                    ("in_sync", openapi::boolean()),
+                    ("round", openapi::integer()),
+                    ("since_last_commit", openapi::nullable(openapi::integer())),
+                    ("consensus_enabled", openapi::boolean()),
+                    ("database_writable", openapi::boolean()),
+                    ("healthy", openapi::boolean()),
+                ],
+            ));
+        router.route(endpoint, healthcheck, "healthcheck");
+    }
+
+    fn schemas_info(self, router: &mut DocumentedRouter) {
+        let schemas = move |_: &mut Request| -> IronResult<Response> {
+            let info: Vec<_> = self.blockchain
+                .service_map()
+                .values()
+                .map(|service| {
+                    ServiceSchemaInfo {
+                        service_id: service.service_id(),
+                        service_name: service.service_name().to_owned(),
+                        messages: service.message_schemas(),
+                    }
+                })
+                .collect();
             self.ok_response(&serde_json::to_value(info).unwrap())
         };
-        router.get("/v1/healthcheck", healthcheck, "healthcheck");
+        let endpoint = Endpoint::get(
+            "/v1/schemas",
+            "Returns the binary layouts of the transactions of all services",
+        ).response(openapi::array(openapi::object(
+            &[
+                ("service_id", openapi::integer()),
+                ("service_name", openapi::string()),
+                ("messages", openapi::array(message_schema())),
+            ],
+        )));
+        router.route(endpoint, schemas, "schemas");
     }
 
-    fn user_agent_info(self, router: &mut Router) {
+    fn user_agent_info(self, router: &mut DocumentedRouter) {
         let user_agent = move |_: &mut Request| -> IronResult<Response> {
             let info = user_agent::get();
             self.ok_response(&serde_json::to_value(info).unwrap())
         };
-        router.get("/v1/user_agent", user_agent, "user_agent");
+        let endpoint = Endpoint::get("/v1/user_agent", "Returns the user agent of the node")
+            .response(openapi::string());
+        router.route(endpoint, user_agent, "user_agent");
     }
 }
 
 impl Api for SystemApi {
-    fn wire(&self, router: &mut Router) {
+    fn wire_documented(&self, router: &mut DocumentedRouter) {
         self.clone().mempool_info(router);
         self.clone().healthcheck_info(router);
+        self.clone().schemas_info(router);
         self.clone().user_agent_info(router);
     }
 }
+
+fn message_schema() -> JsonValue {
+    let field_schemas = openapi::array(openapi::object(
+        &[
+            ("name", openapi::string()),
+            ("type", openapi::string()),
+            ("from", openapi::integer()),
+            ("to", openapi::integer()),
+        ],
+    ));
+    let struct_schema = openapi::object(
+        &[
+            ("name", openapi::string()),
+            ("header_size", openapi::integer()),
+            ("version", openapi::nullable(openapi::integer())),
+            ("fields", field_schemas.clone()),
+            ("optional_fields", field_schemas.clone()),
+        ],
+    );
+    let enum_schema = openapi::object(
+        &[
+            ("name", openapi::string()),
+            (
+                "variants",
+                openapi::array(openapi::object(
+                    &[
+                        ("name", openapi::string()),
+                        ("tag", openapi::integer()),
+                        ("type", openapi::nullable(openapi::string())),
+                    ],
+                )),
+            ),
+        ],
+    );
+    openapi::object(
+        &[
+            ("name", openapi::string()),
+            ("service_id", openapi::integer()),
+            ("message_id", openapi::integer()),
+            ("body_size", openapi::integer()),
+            ("version", openapi::nullable(openapi::integer())),
+            ("fields", field_schemas.clone()),
+            ("optional_fields", field_schemas),
+            ("structs", openapi::array(struct_schema)),
+            ("enums", openapi::array(enum_schema)),
+            ("protobuf_message", openapi::nullable(openapi::string())),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::sync::mpsc;
+    use iron::status;
+
+    use std::sync::Arc;
+
+    use blockchain::{Blockchain, SharedNodeState};
+    use crypto::gen_keypair;
+    use node::ApiSender;
+    use storage::{Database, Error, MemoryDB, Patch, Snapshot};
+    use super::{healthcheck_response, HealthCheckInfo};
+
+    /// Database rejecting all writes, e.g., because the disk is full.
+    struct ReadOnlyDatabase(MemoryDB);
+
+    impl Database for ReadOnlyDatabase {
+        fn snapshot(&self) -> Box<Snapshot> {
+            self.0.snapshot()
+        }
+
+        fn merge(&self, _: Patch) -> Result<(), Error> {
+            Err(Error::new("Database is read-only"))
+        }
+
+        fn merge_sync(&self, _: Patch) -> Result<(), Error> {
+            Err(Error::new("Database is read-only"))
+        }
+    }
+
+    fn create_blockchain<D: Database>(db: D) -> Blockchain {
+        let db: Arc<Database> = Arc::new(db);
+        let (public_key, secret_key) = gen_keypair();
+        let api_channel = mpsc::channel(1);
+        Blockchain::new(
+            db,
+            Vec::new(),
+            public_key,
+            secret_key,
+            ApiSender::new(api_channel.0),
+        )
+    }
+
+    #[test]
+    fn test_healthcheck_healthy() {
+        let state = SharedNodeState::new(1_000);
+
+        let info = HealthCheckInfo::new(&state);
+        assert!(info.in_sync);
+        assert!(info.consensus_enabled);
+        assert!(info.database_writable);
+        assert!(info.healthy);
+        assert_eq!(healthcheck_response(&info).status, Some(status::Ok));
+    }
+
+    #[test]
+    fn test_healthcheck_database_not_writable() {
+        let mut blockchain = create_blockchain(ReadOnlyDatabase(MemoryDB::new()));
+        let state = SharedNodeState::new(1_000);
+        blockchain.set_node_state(state.clone());
+        assert!(HealthCheckInfo::new(&state).database_writable);
+
+        let patch = blockchain.fork().into_patch();
+        assert!(blockchain.merge(patch).is_err());
+        let info = HealthCheckInfo::new(&state);
+        assert!(info.consensus_enabled);
+        assert!(!info.database_writable);
+        assert!(!info.healthy);
+        assert_eq!(
+            healthcheck_response(&info).status,
+            Some(status::ServiceUnavailable)
+        );
+    }
+
+    #[test]
+    fn test_healthcheck_database_writable_again() {
+        let mut blockchain = create_blockchain(MemoryDB::new());
+        let state = SharedNodeState::new(1_000);
+        blockchain.set_node_state(state.clone());
+        state.set_database_writable(false);
+        assert!(!HealthCheckInfo::new(&state).healthy);
+
+        let patch = blockchain.fork().into_patch();
+        blockchain.merge(patch).unwrap();
+        let info = HealthCheckInfo::new(&state);
+        assert!(info.database_writable);
+        assert!(info.healthy);
+    }
+
+    #[test]
+    fn test_healthcheck_consensus_disabled() {
+        let state = SharedNodeState::new(1_000);
+        state.set_enabled(false);
+
+        let info = HealthCheckInfo::new(&state);
+        assert!(!info.consensus_enabled);
+        assert!(info.database_writable);
+        assert!(!info.healthy);
+        assert_eq!(
+            healthcheck_response(&info).status,
+            Some(status::ServiceUnavailable)
+        );
+    }
+}
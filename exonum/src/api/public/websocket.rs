@@ -0,0 +1,598 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! WebSocket API for subscriptions to committed blocks, transaction statuses
+//! and service events.
+//!
+//! Clients send JSON messages of the following form:
+//!
+//! ```text
+//! { "type": "subscribe", "subscription": { "type": "blocks" } }
+//! { "type": "subscribe", "subscription": { "type": "transaction", "hash": "..." } }
+//! { "type": "subscribe",
+//!   "subscription": { "type": "service-events", "service_id": 128, "stream": "transfers" } }
+//! { "type": "unsubscribe", "subscription": { "type": "blocks" } }
+//! ```
+//!
+//! and receive [`Notification`](enum.Notification.html)s serialized to JSON.
+//!
+//! The API is served on a separate `websocket_address` of the node api configuration,
+//! because `iron` cannot upgrade HTTP connections. Handshakes must request the
+//! [`WEBSOCKET_API_PATH`](constant.WEBSOCKET_API_PATH.html) resource; the number of
+//! connections and the number of subscriptions of each connection are limited by
+//! [`WebSocketConfig`](struct.WebSocketConfig.html).
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::thread;
+
+use serde_json::{self, Value as JsonValue};
+use ws;
+
+use blockchain::{Block, Blockchain, Schema, TxLocation};
+use crypto::Hash;
+use helpers::Height;
+use storage::Snapshot;
+use super::TxStatus;
+
+/// Resource requested by WebSocket handshakes. Handshakes for other resources are
+/// rejected with `404 Not Found`.
+pub const WEBSOCKET_API_PATH: &str = "/api/ws";
+
+/// Maximal number of events waiting to be delivered to the WebSocket server.
+/// Events emitted while the queue is full are dropped, so that the node is never
+/// blocked by the clients.
+const EVENTS_QUEUE_CAPACITY: usize = 1024;
+
+/// Limits of the WebSocket api.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WebSocketConfig {
+    /// Maximal number of simultaneous client connections.
+    pub max_connections: usize,
+    /// Maximal number of subscriptions of a single connection.
+    pub max_subscriptions: usize,
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> WebSocketConfig {
+        WebSocketConfig {
+            max_connections: 100,
+            max_subscriptions: 64,
+        }
+    }
+}
+
+/// Subscription of a WebSocket client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Subscription {
+    /// All committed blocks.
+    Blocks,
+    /// Status of the transaction with the given hash. The subscription is removed
+    /// after the transaction is committed.
+    Transaction {
+        /// Hash of the transaction.
+        hash: Hash,
+    },
+    /// Events published by the service into the stream with the given name.
+    ServiceEvents {
+        /// Identifier of the service.
+        service_id: u16,
+        /// Name of the stream.
+        stream: String,
+    },
+}
+
+/// Message sent by a WebSocket client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum ClientMessage {
+    /// Adds the subscription.
+    Subscribe {
+        /// The subscription to add.
+        subscription: Subscription,
+    },
+    /// Removes the subscription.
+    Unsubscribe {
+        /// The subscription to remove.
+        subscription: Subscription,
+    },
+}
+
+/// Status of a transaction reported to subscribers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum TransactionStatus {
+    /// Transaction is unknown to the node.
+    Unknown,
+    /// Transaction is in the memory pool, but not yet committed to the blockchain.
+    InPool,
+    /// Transaction is committed to the blockchain.
+    Committed {
+        /// Transaction location in block.
+        location: TxLocation,
+        /// Status of the transaction execution.
+        result: TxStatus,
+    },
+}
+
+impl TransactionStatus {
+    /// Reads the status of the transaction with the given hash from the snapshot.
+    pub fn get(snapshot: &Snapshot, hash: &Hash) -> Self {
+        let schema = Schema::new(snapshot);
+        if schema.transactions_pool().contains(hash) {
+            return TransactionStatus::InPool;
+        }
+        match schema.transactions_locations().get(hash) {
+            Some(location) => {
+                // Unwrap is OK here, because we already know that transaction is committed.
+                let result = TxStatus::from(&schema.transaction_results().get(hash).unwrap());
+                TransactionStatus::Committed { location, result }
+            }
+            None => TransactionStatus::Unknown,
+        }
+    }
+}
+
+/// Message sent to WebSocket clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Notification {
+    /// New block has been committed.
+    Block {
+        /// Hash of the block.
+        block_hash: Hash,
+        /// Header of the block.
+        block: Block,
+    },
+    /// Status of the transaction has changed.
+    Transaction {
+        /// Hash of the transaction.
+        hash: Hash,
+        /// New status of the transaction.
+        status: TransactionStatus,
+    },
+    /// Event published by a service.
+    ServiceEvent {
+        /// Identifier of the service.
+        service_id: u16,
+        /// Name of the stream.
+        stream: String,
+        /// Service-defined payload of the event.
+        payload: JsonValue,
+    },
+    /// The client message cannot be processed.
+    Error {
+        /// Error description.
+        description: String,
+    },
+}
+
+struct Subscriber {
+    sender: ws::Sender,
+    subscriptions: HashSet<Subscription>,
+}
+
+impl Subscriber {
+    fn send(&self, notification: &Notification) {
+        send_notification(&self.sender, notification);
+    }
+}
+
+fn send_notification(sender: &ws::Sender, notification: &Notification) {
+    let text = serde_json::to_string(notification).unwrap();
+    if let Err(e) = sender.send(text) {
+        warn!("Unable to send notification to websocket client: {}", e);
+    }
+}
+
+/// Event emitted by the node or by a service.
+enum Event {
+    InPool(Hash),
+    Block(Height),
+    ServiceEvent {
+        service_id: u16,
+        stream: String,
+        payload: JsonValue,
+    },
+}
+
+/// Delivers notifications to subscribed WebSocket clients.
+///
+/// Notifications about blocks and transactions are emitted by the node in the commit path.
+/// Services may publish their own events with the [`publish_event`](#method.publish_event)
+/// method, which is available through `ServiceContext::notifier`.
+///
+/// Events are only queued here; the WebSocket server reads the blockchain and sends
+/// the notifications in its own thread. If the server is not started, events are discarded.
+#[derive(Clone, Default)]
+pub struct Notifier {
+    events: Arc<Mutex<Option<SyncSender<Event>>>>,
+}
+
+impl Notifier {
+    /// Creates a notifier that is not connected to a WebSocket server.
+    pub fn new() -> Self {
+        Notifier::default()
+    }
+
+    /// Publishes the service event to the clients subscribed to the given stream.
+    pub fn publish_event(&self, service_id: u16, stream: &str, payload: &JsonValue) {
+        self.send(|| {
+            Event::ServiceEvent {
+                service_id,
+                stream: stream.to_owned(),
+                payload: payload.clone(),
+            }
+        });
+    }
+
+    /// Notifies subscribers that the transaction has been added into the pool.
+    pub(crate) fn notify_in_pool(&self, hash: &Hash) {
+        self.send(|| Event::InPool(*hash));
+    }
+
+    /// Notifies subscribers about the block at the given height and the transactions
+    /// committed in it.
+    pub(crate) fn notify_block(&self, height: Height) {
+        self.send(|| Event::Block(height));
+    }
+
+    fn connect(&self, events: SyncSender<Event>) {
+        *self.events.lock().expect("Expected lock") = Some(events);
+    }
+
+    fn send<F: FnOnce() -> Event>(&self, event: F) {
+        let mut events = self.events.lock().expect("Expected lock");
+        let disconnected = match *events {
+            Some(ref sender) => {
+                match sender.try_send(event()) {
+                    Ok(()) => false,
+                    Err(TrySendError::Full(_)) => {
+                        warn!("Websocket events queue is full, the event is dropped");
+                        false
+                    }
+                    Err(TrySendError::Disconnected(_)) => true,
+                }
+            }
+            None => false,
+        };
+        if disconnected {
+            *events = None;
+        }
+    }
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad("Notifier { .. }")
+    }
+}
+
+/// Subscriptions of the connected clients, shared by the sessions and the thread
+/// delivering the events.
+#[derive(Clone)]
+struct Subscribers {
+    inner: Arc<Mutex<HashMap<u32, Subscriber>>>,
+    max_subscriptions: usize,
+}
+
+impl Subscribers {
+    fn new(max_subscriptions: usize) -> Self {
+        Subscribers {
+            inner: Arc::default(),
+            max_subscriptions,
+        }
+    }
+
+    /// Adds the subscription unless the client already has the maximal number
+    /// of subscriptions. Returns `false` if the subscription is rejected.
+    fn subscribe(&self, sender: &ws::Sender, subscription: Subscription) -> bool {
+        let mut subscribers = self.inner.lock().expect("Expected lock");
+        let subscriptions = &mut subscribers
+            .entry(sender.connection_id())
+            .or_insert_with(|| {
+                Subscriber {
+                    sender: sender.clone(),
+                    subscriptions: HashSet::new(),
+                }
+            })
+            .subscriptions;
+        if subscriptions.len() >= self.max_subscriptions &&
+            !subscriptions.contains(&subscription)
+        {
+            return false;
+        }
+        subscriptions.insert(subscription);
+        true
+    }
+
+    fn unsubscribe(&self, sender: &ws::Sender, subscription: &Subscription) {
+        let mut subscribers = self.inner.lock().expect("Expected lock");
+        if let Some(subscriber) = subscribers.get_mut(&sender.connection_id()) {
+            subscriber.subscriptions.remove(subscription);
+        }
+    }
+
+    fn remove(&self, sender: &ws::Sender) {
+        let mut subscribers = self.inner.lock().expect("Expected lock");
+        subscribers.remove(&sender.connection_id());
+    }
+
+    /// Delivers the events until all the notifiers are dropped.
+    fn dispatch(&self, blockchain: &Blockchain, events: Receiver<Event>) {
+        for event in events {
+            match event {
+                Event::InPool(hash) => self.dispatch_in_pool(hash),
+                Event::Block(height) => self.dispatch_block(&*blockchain.snapshot(), height),
+                Event::ServiceEvent {
+                    service_id,
+                    stream,
+                    payload,
+                } => self.dispatch_service_event(service_id, stream, payload),
+            }
+        }
+    }
+
+    fn dispatch_in_pool(&self, hash: Hash) {
+        let subscribers = self.inner.lock().expect("Expected lock");
+        let subscription = Subscription::Transaction { hash };
+        for subscriber in subscribers.values() {
+            if subscriber.subscriptions.contains(&subscription) {
+                subscriber.send(&Notification::Transaction {
+                    hash,
+                    status: TransactionStatus::InPool,
+                });
+            }
+        }
+    }
+
+    fn dispatch_block(&self, snapshot: &Snapshot, height: Height) {
+        let mut subscribers = self.inner.lock().expect("Expected lock");
+        if subscribers.is_empty() {
+            return;
+        }
+
+        let schema = Schema::new(snapshot);
+        let block_hash = schema.block_hash_by_height(height).expect(&format!(
+            "Block not found, height: {:?}",
+            height
+        ));
+        let block = schema.blocks().get(&block_hash).expect(&format!(
+            "Block not found, hash: {:?}",
+            block_hash
+        ));
+        let notification = Notification::Block { block_hash, block };
+
+        let locations = schema.transactions_locations();
+        for subscriber in subscribers.values_mut() {
+            if subscriber.subscriptions.contains(&Subscription::Blocks) {
+                subscriber.send(&notification);
+            }
+
+            let committed: Vec<Hash> = subscriber
+                .subscriptions
+                .iter()
+                .filter_map(|subscription| match *subscription {
+                    Subscription::Transaction { hash } => Some(hash),
+                    _ => None,
+                })
+                .filter(|hash| {
+                    locations.get(hash).map_or(false, |location| {
+                        location.block_height() == height
+                    })
+                })
+                .collect();
+            for hash in committed {
+                subscriber.send(&Notification::Transaction {
+                    hash,
+                    status: TransactionStatus::get(snapshot, &hash),
+                });
+                subscriber.subscriptions.remove(&Subscription::Transaction { hash });
+            }
+        }
+    }
+
+    fn dispatch_service_event(&self, service_id: u16, stream: String, payload: JsonValue) {
+        let subscribers = self.inner.lock().expect("Expected lock");
+        let subscription = Subscription::ServiceEvents {
+            service_id,
+            stream: stream.clone(),
+        };
+        let notification = Notification::ServiceEvent {
+            service_id,
+            stream,
+            payload,
+        };
+        for subscriber in subscribers.values() {
+            if subscriber.subscriptions.contains(&subscription) {
+                subscriber.send(&notification);
+            }
+        }
+    }
+}
+
+/// Connection of a single WebSocket client.
+struct Session {
+    out: ws::Sender,
+    blockchain: Blockchain,
+    subscribers: Subscribers,
+}
+
+impl Session {
+    fn handle_message(&self, message: ClientMessage) {
+        match message {
+            ClientMessage::Subscribe { subscription } => {
+                // Subscribe before reading the status, so that a commit between the two
+                // steps is not missed. The client may get the committed status twice then.
+                if !self.subscribers.subscribe(&self.out, subscription.clone()) {
+                    let description = format!(
+                        "Too many subscriptions, at most {} are allowed",
+                        self.subscribers.max_subscriptions
+                    );
+                    self.send(&Notification::Error { description });
+                    return;
+                }
+                if let Subscription::Transaction { hash } = subscription {
+                    let status = TransactionStatus::get(&*self.blockchain.snapshot(), &hash);
+                    if let TransactionStatus::Committed { .. } = status {
+                        self.subscribers.unsubscribe(&self.out, &subscription);
+                    }
+                    self.send(&Notification::Transaction { hash, status });
+                }
+            }
+            ClientMessage::Unsubscribe { subscription } => {
+                self.subscribers.unsubscribe(&self.out, &subscription);
+            }
+        }
+    }
+
+    fn send(&self, notification: &Notification) {
+        send_notification(&self.out, notification);
+    }
+}
+
+impl ws::Handler for Session {
+    fn on_request(&mut self, request: &ws::Request) -> ws::Result<ws::Response> {
+        if is_websocket_api_resource(request.resource()) {
+            ws::Response::from_request(request)
+        } else {
+            Ok(ws::Response::new(404, "Not Found", Vec::new()))
+        }
+    }
+
+    fn on_message(&mut self, msg: ws::Message) -> ws::Result<()> {
+        let parsed = msg.as_text().map_err(|e| e.to_string()).and_then(|text| {
+            serde_json::from_str::<ClientMessage>(text).map_err(|e| e.to_string())
+        });
+        match parsed {
+            Ok(message) => self.handle_message(message),
+            Err(description) => self.send(&Notification::Error { description }),
+        }
+        Ok(())
+    }
+
+    fn on_close(&mut self, _: ws::CloseCode, _: &str) {
+        self.subscribers.remove(&self.out);
+    }
+}
+
+/// Checks whether the requested resource is the WebSocket api, ignoring the query string.
+fn is_websocket_api_resource(resource: &str) -> bool {
+    resource.splitn(2, '?').next() == Some(WEBSOCKET_API_PATH)
+}
+
+/// Binds the WebSocket server to the given address and starts it together with
+/// the thread delivering the events of the blockchain notifier.
+pub fn start_websocket_api(
+    blockchain: Blockchain,
+    listen_address: SocketAddr,
+    config: &WebSocketConfig,
+) -> ws::Result<()> {
+    let subscribers = Subscribers::new(config.max_subscriptions);
+    let factory = {
+        let blockchain = blockchain.clone();
+        let subscribers = subscribers.clone();
+        move |out| {
+            Session {
+                out,
+                blockchain: blockchain.clone(),
+                subscribers: subscribers.clone(),
+            }
+        }
+    };
+    let settings = ws::Settings {
+        max_connections: config.max_connections,
+        ..ws::Settings::default()
+    };
+    let server = ws::Builder::new()
+        .with_settings(settings)
+        .build(factory)?
+        .bind(listen_address)?;
+
+    let (sender, receiver) = mpsc::sync_channel(EVENTS_QUEUE_CAPACITY);
+    blockchain.notifier().connect(sender);
+    thread::spawn(move || subscribers.dispatch(&blockchain, receiver));
+    thread::spawn(move || {
+        info!("Websocket exonum api started on {}", listen_address);
+        if let Err(e) = server.run() {
+            error!("Websocket exonum api stopped: {}", e);
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::hash;
+    use super::*;
+
+    #[test]
+    fn test_client_message_json() {
+        let message: ClientMessage = serde_json::from_str(
+            r#"{ "type": "subscribe", "subscription": { "type": "blocks" } }"#,
+        ).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Subscribe { subscription: Subscription::Blocks }
+        );
+
+        let message: ClientMessage = serde_json::from_str(
+            r#"{
+                "type": "unsubscribe",
+                "subscription": { "type": "service-events", "service_id": 128, "stream": "a" }
+            }"#,
+        ).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Unsubscribe {
+                subscription: Subscription::ServiceEvents {
+                    service_id: 128,
+                    stream: "a".to_owned(),
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_websocket_api_resource() {
+        assert!(is_websocket_api_resource("/api/ws"));
+        assert!(is_websocket_api_resource("/api/ws?token=1"));
+        assert!(!is_websocket_api_resource("/"));
+        assert!(!is_websocket_api_resource("/api/ws/other"));
+        assert!(!is_websocket_api_resource("/api/explorer/v1/blocks"));
+    }
+
+    #[test]
+    fn test_notification_json() {
+        let notification = Notification::Transaction {
+            hash: hash(&[1, 2, 3]),
+            status: TransactionStatus::Committed {
+                location: TxLocation::new(Height(5), 1),
+                result: TxStatus::Success,
+            },
+        };
+        let json = serde_json::to_value(&notification).unwrap();
+        assert_eq!(json["type"], "transaction");
+        assert_eq!(json["status"]["type"], "committed");
+        assert_eq!(json["status"]["result"]["type"], "success");
+        assert_eq!(
+            notification,
+            serde_json::from_value::<Notification>(json).unwrap()
+        );
+    }
+}
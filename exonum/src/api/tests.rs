@@ -50,3 +50,42 @@ fn test_json_response_for_complex_val() {
     assert!(result.is_ok());
     print!("{:?}", result);
 }
+
+#[test]
+fn test_request_signature() {
+    let (public_key, secret_key) = ::crypto::gen_keypair();
+    let path = "/api/services/counter/count?value=5";
+    let time = 1_500_000_000;
+    let body = br#"{ "by": 5 }"#;
+    let signature = sign_request("post", path, time, body, &secret_key);
+
+    assert_eq!(
+        signed_request_data("post", path, time, body),
+        b"POST /api/services/counter/count?value=5\n1500000000\n{ \"by\": 5 }".to_vec()
+    );
+    assert!(verify_request("POST", path, time, body, &public_key, &signature));
+    assert!(!verify_request("POST", path, time, b"{ \"by\": 6 }", &public_key, &signature));
+    assert!(!verify_request("POST", path, time + 1, body, &public_key, &signature));
+    let other_path = "/api/services/counter/reset";
+    assert!(!verify_request("POST", other_path, time, body, &public_key, &signature));
+    assert!(!verify_request("GET", path, time, body, &public_key, &signature));
+
+    let (other_key, _) = ::crypto::gen_keypair();
+    assert!(!verify_request("POST", path, time, body, &other_key, &signature));
+
+    // Bodies are signed as bytes and need not be UTF-8 strings.
+    let body = [0xff, 0x00, 0xfe];
+    let signature = sign_request("POST", path, time, &body, &secret_key);
+    assert!(verify_request("POST", path, time, &body, &public_key, &signature));
+}
+
+#[test]
+fn test_signed_request_window() {
+    let now = 1_500_000_000;
+    assert!(is_within_signed_request_window(now, now));
+    assert!(is_within_signed_request_window(now - SIGNED_REQUEST_WINDOW, now));
+    assert!(is_within_signed_request_window(now + SIGNED_REQUEST_WINDOW, now));
+    assert!(!is_within_signed_request_window(now - SIGNED_REQUEST_WINDOW - 1, now));
+    assert!(!is_within_signed_request_window(now + SIGNED_REQUEST_WINDOW + 1, now));
+    assert!(!is_within_signed_request_window(0, now));
+}
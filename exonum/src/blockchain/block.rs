@@ -12,9 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crypto::Hash;
-use messages::Precommit;
+use std::collections::HashSet;
+
+use crypto::{CryptoHash, Hash};
+use messages::{Message, Precommit};
 use helpers::{Height, ValidatorId};
+use super::StoredConfiguration;
 
 /// Current core information schema version.
 pub const SCHEMA_MAJOR_VERSION: u16 = 0;
@@ -56,9 +59,45 @@ pub struct BlockProof {
     pub precommits: Vec<Precommit>,
 }
 
+impl BlockProof {
+    /// Checks that the block is authenticated by validators with +2/3 of the voting power
+    /// according to the given configuration, i.e., that pre-commits are signed by distinct
+    /// validators for this block in the same round.
+    pub fn verify(&self, config: &StoredConfiguration) -> bool {
+        let block_hash = self.block.hash();
+        let round = match self.precommits.first() {
+            Some(precommit) => precommit.round(),
+            None => return false,
+        };
+
+        let mut voters = HashSet::with_capacity(self.precommits.len());
+        for precommit in &self.precommits {
+            let keys = match config.validator_keys.get(usize::from(precommit.validator())) {
+                Some(keys) => keys,
+                None => return false,
+            };
+            if precommit.height() != self.block.height() || precommit.round() != round ||
+                *precommit.block_hash() != block_hash ||
+                !precommit.verify_signature(&keys.consensus_key) ||
+                !voters.insert(precommit.validator())
+            {
+                return false;
+            }
+        }
+
+        let voting_power: u64 = voters.into_iter().map(|id| config.voting_power_of(id)).sum();
+        voting_power >= config.majority_voting_power()
+    }
+}
 #[cfg(test)]
 mod tests {
-    use crypto::hash;
+    use std::collections::BTreeMap;
+
+    use chrono::Utc;
+
+    use crypto::{gen_keypair, hash, SecretKey};
+    use blockchain::{ConsensusConfig, ValidatorKeys};
+    use helpers::Round;
     use super::*;
 
     #[test]
@@ -91,4 +130,71 @@ mod tests {
         let block1: Block = ::serde_json::from_str(&json_str).unwrap();
         assert_eq!(block1, block);
     }
+
+    #[test]
+    fn test_block_proof_weighted_verify() {
+        let keys: Vec<_> = (0..4).map(|_| gen_keypair()).collect();
+        let mut config = StoredConfiguration {
+            previous_cfg_hash: Hash::zero(),
+            actual_from: Height(0),
+            validator_keys: keys.iter()
+                .map(|&(ref public_key, _)| {
+                    ValidatorKeys {
+                        consensus_key: *public_key,
+                        service_key: *public_key,
+                        voting_power: None,
+                    }
+                })
+                .collect(),
+            consensus: ConsensusConfig::default(),
+            majority_count: None,
+            services: BTreeMap::new(),
+        };
+
+        let block = Block::new(
+            SCHEMA_MAJOR_VERSION,
+            ValidatorId(0),
+            Height(1),
+            0,
+            &hash(&[1]),
+            &hash(&[2]),
+            &hash(&[3]),
+        );
+        let precommit = |id: u16, secret_key: &SecretKey| {
+            Precommit::new(
+                ValidatorId(id),
+                Height(1),
+                Round(1),
+                &hash(&[4]),
+                &block.hash(),
+                Utc::now(),
+                secret_key,
+            )
+        };
+
+        // Two validators out of four are not enough with equal voting power...
+        let proof = BlockProof {
+            block: block.clone(),
+            precommits: vec![precommit(0, &keys[0].1), precommit(1, &keys[1].1)],
+        };
+        assert!(!proof.verify(&config));
+
+        // ...but are enough if they have more than 2/3 of the voting power.
+        config.validator_keys[0].voting_power = Some(5);
+        assert!(proof.verify(&config));
+
+        // Duplicated pre-commits are not counted.
+        let proof = BlockProof {
+            block: block.clone(),
+            precommits: vec![precommit(0, &keys[0].1), precommit(0, &keys[0].1)],
+        };
+        assert!(!proof.verify(&config));
+
+        // Pre-commits with wrong signatures are not accepted.
+        let proof = BlockProof {
+            block: block.clone(),
+            precommits: vec![precommit(0, &keys[1].1), precommit(1, &keys[1].1)],
+        };
+        assert!(!proof.verify(&config));
+    }
 }
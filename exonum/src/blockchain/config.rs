@@ -21,7 +21,7 @@ use serde_json::{self, Error as JsonError};
 
 use storage::StorageValue;
 use crypto::{hash, CryptoHash, PublicKey, Hash};
-use helpers::{Height, Milliseconds};
+use helpers::{Height, Milliseconds, ValidatorId};
 
 /// Public keys of a validator.
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -31,6 +31,17 @@ pub struct ValidatorKeys {
     pub consensus_key: PublicKey,
     /// Service key is used for services.
     pub service_key: PublicKey,
+    /// Voting power of the validator. Validators without the explicitly set voting power
+    /// have the power of `1`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voting_power: Option<u64>,
+}
+
+impl ValidatorKeys {
+    /// Returns voting power of the validator.
+    pub fn voting_power(&self) -> u64 {
+        self.voting_power.unwrap_or(1)
+    }
 }
 
 /// Exonum blockchain global configuration.
@@ -46,9 +57,9 @@ pub struct StoredConfiguration {
     pub validator_keys: Vec<ValidatorKeys>,
     /// Consensus algorithm parameters.
     pub consensus: ConsensusConfig,
-    /// Number of votes required to commit new configuration.
-    /// Should be greater than 2/3 and less or equal to the validators count.
-    pub majority_count: Option<u16>,
+    /// Voting power required to commit new configuration.
+    /// Should be greater than 2/3 and less or equal to the total voting power of validators.
+    pub majority_count: Option<u64>,
     /// Services specific variables.
     /// Keys are `service_name` from `Service` trait and values are the serialized json.
     #[serde(default)]
@@ -68,8 +79,21 @@ pub struct ConsensusConfig {
     pub txs_block_limit: u32,
     /// Maximum message length (in bytes).
     pub max_message_len: u32,
+    /// Minimal interval between the start of a height and the propose for this height.
+    /// The leader never proposes earlier, even if the timeout adjuster allows it.
+    /// Zero disables the limit.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub min_block_interval: Milliseconds,
+    /// If set, the leader does not propose empty blocks until the given time passes
+    /// since the start of the height, but proposes as soon as a transaction appears
+    /// in the pool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_idle_time: Option<Milliseconds>,
     /// `TimeoutAdjuster` configuration.
     pub timeout_adjuster: TimeoutAdjusterConfig,
+    /// `LeaderElection` configuration.
+    #[serde(default, skip_serializing_if = "LeaderElectionConfig::is_default")]
+    pub leader_election: LeaderElectionConfig,
 }
 
 impl ConsensusConfig {
@@ -84,6 +108,7 @@ impl ConsensusConfig {
             TimeoutAdjusterConfig::Dynamic { max, .. } |
             TimeoutAdjusterConfig::MovingAverage { max, .. } => max,
         };
+        let propose_timeout = ::std::cmp::max(propose_timeout, self.min_block_interval);
 
         if self.round_timeout <= 2 * propose_timeout {
             warn!(
@@ -96,6 +121,12 @@ impl ConsensusConfig {
     }
 }
 
+/// Used to omit the disabled `min_block_interval`, so that configurations without it
+/// keep their hashes.
+fn is_zero(value: &Milliseconds) -> bool {
+    *value == 0
+}
+
 impl Default for ConsensusConfig {
     fn default() -> Self {
         ConsensusConfig {
@@ -104,12 +135,35 @@ impl Default for ConsensusConfig {
             peers_timeout: 10_000,
             txs_block_limit: 1000,
             max_message_len: Self::DEFAULT_MAX_MESSAGE_LEN,
+            min_block_interval: 0,
+            max_idle_time: None,
             timeout_adjuster: TimeoutAdjusterConfig::Constant { timeout: 500 },
+            leader_election: LeaderElectionConfig::default(),
         }
     }
 }
 
 impl StoredConfiguration {
+    /// Returns the total voting power of validators.
+    pub fn total_voting_power(&self) -> u64 {
+        self.validator_keys.iter().map(ValidatorKeys::voting_power).sum()
+    }
+
+    /// Returns voting power of the validator with the given id, or `0` if there is no
+    /// such validator.
+    pub fn voting_power_of(&self, id: ValidatorId) -> u64 {
+        self.validator_keys.get(usize::from(id)).map_or(
+            0,
+            ValidatorKeys::voting_power,
+        )
+    }
+
+    /// Returns voting power sufficient for the byzantine majority, i.e., more than 2/3
+    /// of the total voting power.
+    pub fn majority_voting_power(&self) -> u64 {
+        self.total_voting_power() * 2 / 3 + 1
+    }
+
     /// Tries to serialize given configuration into the utf8 encoded json.
     pub fn try_serialize(&self) -> Result<Vec<u8>, JsonError> {
         serde_json::to_vec(&self)
@@ -133,6 +187,23 @@ impl StoredConfiguration {
             }
         }
 
+        // Check voting power.
+        {
+            let mut total_voting_power: u64 = 0;
+            for k in &config.validator_keys {
+                if k.voting_power == Some(0) {
+                    return Err(JsonError::custom(
+                        "Voting power of validators must be positive",
+                    ));
+                }
+                // Doubled total voting power must fit into `u64` to compute the majority.
+                total_voting_power = match total_voting_power.checked_add(k.voting_power()) {
+                    Some(power) if power <= u64::max_value() / 2 => power,
+                    _ => return Err(JsonError::custom("Total voting power is too big")),
+                };
+            }
+        }
+
         // Check timeout adjuster.
         let propose_timeout = match config.consensus.timeout_adjuster {
             // There is no need to validate `Constant` timeout adjuster.
@@ -178,6 +249,33 @@ impl StoredConfiguration {
             }
         };
 
+        // Check leader election.
+        match config.consensus.leader_election {
+            LeaderElectionConfig::RoundRobin => {}
+            LeaderElectionConfig::Weighted { ref weights } => {
+                if weights.len() != config.validator_keys.len() {
+                    return Err(JsonError::custom(format!(
+                        "Weighted leader election: number of weights ({}) must be equal \
+                        to the number of validators ({})",
+                        weights.len(),
+                        config.validator_keys.len()
+                    )));
+                }
+                if weights.iter().all(|&weight| weight == 0) {
+                    return Err(JsonError::custom(
+                        "Weighted leader election: at least one weight must be positive",
+                    ));
+                }
+            }
+            LeaderElectionConfig::SkipInactive { window } => {
+                if window == 0 {
+                    return Err(JsonError::custom(
+                        "Skip inactive leader election: window must be positive",
+                    ));
+                }
+            }
+        }
+
         if config.consensus.round_timeout <= propose_timeout {
             return Err(JsonError::custom(format!(
                 "round_timeout({}) must be strictly larger than propose_timeout({})",
@@ -185,6 +283,16 @@ impl StoredConfiguration {
                 propose_timeout
             )));
         }
+        if config.consensus.round_timeout <= config.consensus.min_block_interval {
+            return Err(JsonError::custom(format!(
+                "round_timeout({}) must be strictly larger than min_block_interval({})",
+                config.consensus.round_timeout,
+                config.consensus.min_block_interval
+            )));
+        }
+        if config.consensus.max_idle_time == Some(0) {
+            return Err(JsonError::custom("max_idle_time must be positive"));
+        }
 
         Ok(config)
     }
@@ -238,6 +346,38 @@ pub enum TimeoutAdjusterConfig {
     },
 }
 
+/// `LeaderElection` config.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum LeaderElectionConfig {
+    /// Round-robin leader election config.
+    RoundRobin,
+    /// Weighted leader election configuration.
+    Weighted {
+        /// Weights of validators in the order of `validator_keys`.
+        weights: Vec<u64>,
+    },
+    /// Leader election configuration that skips inactive validators.
+    SkipInactive {
+        /// Number of latest blocks used to find inactive validators.
+        window: u64,
+    },
+}
+
+impl LeaderElectionConfig {
+    /// The default round-robin election is not serialized, so that the hash of
+    /// configurations created before the election became configurable is preserved.
+    fn is_default(&self) -> bool {
+        *self == LeaderElectionConfig::RoundRobin
+    }
+}
+
+impl Default for LeaderElectionConfig {
+    fn default() -> Self {
+        LeaderElectionConfig::RoundRobin
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use toml;
@@ -305,6 +445,7 @@ mod tests {
         configuration.validator_keys.push(ValidatorKeys {
             consensus_key: PublicKey::zero(),
             service_key: PublicKey::zero(),
+            voting_power: None,
         });
         serialize_deserialize(&configuration);
     }
@@ -461,12 +602,132 @@ mod tests {
         serialize_deserialize(&configuration);
     }
 
+    #[test]
+    fn leader_election_config_toml() {
+        check_toml_roundtrip(&LeaderElectionConfig::RoundRobin);
+        check_toml_roundtrip(&LeaderElectionConfig::Weighted { weights: vec![1, 2, 3] });
+        check_toml_roundtrip(&LeaderElectionConfig::SkipInactive { window: 10 });
+    }
+
+    #[test]
+    fn default_leader_election_is_not_serialized() {
+        let configuration = create_test_configuration();
+        let json = serde_json::to_value(&configuration.consensus).unwrap();
+        assert!(json.get("leader_election").is_none());
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+
+        let mut consensus = configuration.consensus;
+        consensus.leader_election = LeaderElectionConfig::SkipInactive { window: 10 };
+        let json = serde_json::to_value(&consensus).unwrap();
+        assert_eq!(json["leader_election"]["type"], "SkipInactive");
+    }
+
+    #[test]
+    fn weighted_leader_election_serialize_deserialize() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election =
+            LeaderElectionConfig::Weighted { weights: vec![1, 0, 5] };
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "Weighted leader election: number of weights (2) must be equal")]
+    fn weighted_leader_election_invalid_weights_count() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election =
+            LeaderElectionConfig::Weighted { weights: vec![1, 2] };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "Weighted leader election: at least one weight must be positive")]
+    fn weighted_leader_election_zero_weights() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election =
+            LeaderElectionConfig::Weighted { weights: vec![0, 0, 0] };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "Skip inactive leader election: window must be positive")]
+    fn skip_inactive_leader_election_zero_window() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.leader_election = LeaderElectionConfig::SkipInactive { window: 0 };
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    fn block_interval_serialize_deserialize() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.min_block_interval = 100;
+        configuration.consensus.max_idle_time = Some(5000);
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+        check_toml_roundtrip(&configuration.consensus);
+
+        configuration.consensus.min_block_interval = 0;
+        configuration.consensus.max_idle_time = None;
+        let json = serde_json::to_value(&configuration.consensus).unwrap();
+        assert!(json.get("min_block_interval").is_none());
+        assert!(json.get("max_idle_time").is_none());
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be strictly larger than min_block_interval(50)")]
+    fn invalid_min_block_interval() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.round_timeout = 50;
+        configuration.consensus.timeout_adjuster = TimeoutAdjusterConfig::Constant { timeout: 10 };
+        configuration.consensus.min_block_interval = 50;
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "max_idle_time must be positive")]
+    fn zero_max_idle_time() {
+        let mut configuration = create_test_configuration();
+        configuration.consensus.max_idle_time = Some(0);
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    fn stored_configuration_voting_power() {
+        let mut configuration = create_test_configuration();
+        assert_eq!(configuration.total_voting_power(), 3);
+        assert_eq!(configuration.majority_voting_power(), 3);
+
+        configuration.validator_keys[0].voting_power = Some(5);
+        assert_eq!(configuration.total_voting_power(), 7);
+        assert_eq!(configuration.majority_voting_power(), 5);
+        assert_eq!(configuration.voting_power_of(ValidatorId(0)), 5);
+        assert_eq!(configuration.voting_power_of(ValidatorId(1)), 1);
+        assert_eq!(configuration.voting_power_of(ValidatorId(3)), 0);
+        assert_eq!(configuration, serialize_deserialize(&configuration));
+    }
+
+    #[test]
+    #[should_panic(expected = "Voting power of validators must be positive")]
+    fn stored_configuration_zero_voting_power() {
+        let mut configuration = create_test_configuration();
+        configuration.validator_keys[1].voting_power = Some(0);
+        serialize_deserialize(&configuration);
+    }
+
+    #[test]
+    #[should_panic(expected = "Total voting power is too big")]
+    fn stored_configuration_too_big_voting_power() {
+        let mut configuration = create_test_configuration();
+        configuration.validator_keys[0].voting_power = Some(u64::max_value() / 2);
+        serialize_deserialize(&configuration);
+    }
+
     fn create_test_configuration() -> StoredConfiguration {
         let validator_keys = (1..4)
             .map(|i| {
                 ValidatorKeys {
                     consensus_key: gen_keypair_from_seed(&Seed::new([i; 32])).0,
                     service_key: gen_keypair_from_seed(&Seed::new([i * 10; 32])).0,
+                    voting_power: None,
                 }
             })
             .collect();
@@ -0,0 +1,194 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Evidence of byzantine behavior of validators.
+
+use crypto::{CryptoHash, PublicKey};
+use messages::{Any, ConsensusMessage, RawMessage};
+use helpers::{Height, Round, ValidatorId};
+
+encoding_struct!(
+    /// Evidence of equivocation: a pair of conflicting consensus messages signed by
+    /// the same validator for the same height and round.
+    ///
+    /// The following pairs of messages are considered conflicting:
+    ///
+    /// - two `Propose`s with different hashes;
+    /// - two `Prevote`s for different proposes;
+    /// - two `Precommit`s for different proposes or blocks.
+    ///
+    /// The evidence is self-contained: it can be checked by anyone knowing the consensus key
+    /// of the validator with the [`verify`](#method.verify) method.
+    struct Equivocation {
+        /// Id of the validator that signed the conflicting messages.
+        validator: ValidatorId,
+        /// The height to which the messages are related.
+        height: Height,
+        /// The round to which the messages are related.
+        round: Round,
+        /// The conflicting messages in the order they were received.
+        messages: Vec<RawMessage>,
+    }
+);
+
+impl Equivocation {
+    /// Creates evidence from the two given conflicting messages.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the messages do not conflict with each other.
+    pub fn from_messages(first: &ConsensusMessage, second: &ConsensusMessage) -> Self {
+        assert!(
+            is_conflicting(first, second),
+            "Messages do not conflict: first={:?}, second={:?}",
+            first,
+            second
+        );
+        Equivocation::new(
+            first.validator(),
+            first.height(),
+            first.round(),
+            vec![first.raw().clone(), second.raw().clone()],
+        )
+    }
+
+    /// Checks that the evidence is valid, i.e., it contains exactly two conflicting
+    /// consensus messages that match the validator, height and round of the evidence
+    /// and are signed with the given consensus key.
+    pub fn verify(&self, consensus_key: &PublicKey) -> bool {
+        let messages = self.messages();
+        if messages.len() != 2 {
+            return false;
+        }
+
+        let mut parsed = Vec::with_capacity(messages.len());
+        for raw in messages {
+            match Any::from_raw(raw) {
+                Ok(Any::Consensus(msg)) => {
+                    if msg.validator() != self.validator() || msg.height() != self.height() ||
+                        msg.round() != self.round() || !msg.verify(consensus_key)
+                    {
+                        return false;
+                    }
+                    parsed.push(msg);
+                }
+                _ => return false,
+            }
+        }
+        is_conflicting(&parsed[0], &parsed[1])
+    }
+}
+
+/// Returns `true` if the messages are signed by the same validator for the same height
+/// and round, but vote for different things.
+pub(crate) fn is_conflicting(first: &ConsensusMessage, second: &ConsensusMessage) -> bool {
+    if first.validator() != second.validator() || first.height() != second.height() ||
+        first.round() != second.round()
+    {
+        return false;
+    }
+
+    match (first, second) {
+        (&ConsensusMessage::Propose(ref a), &ConsensusMessage::Propose(ref b)) => {
+            a.hash() != b.hash()
+        }
+        (&ConsensusMessage::Prevote(ref a), &ConsensusMessage::Prevote(ref b)) => {
+            a.propose_hash() != b.propose_hash()
+        }
+        (&ConsensusMessage::Precommit(ref a), &ConsensusMessage::Precommit(ref b)) => {
+            a.propose_hash() != b.propose_hash() || a.block_hash() != b.block_hash()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto::{gen_keypair, hash};
+    use messages::{Prevote, Precommit, Message};
+    use super::*;
+
+    #[test]
+    fn test_prevote_equivocation() {
+        let (public_key, secret_key) = gen_keypair();
+        let first = Prevote::new(
+            ValidatorId(1),
+            Height(2),
+            Round(3),
+            &hash(&[1]),
+            Round::zero(),
+            &secret_key,
+        );
+        let second = Prevote::new(
+            ValidatorId(1),
+            Height(2),
+            Round(3),
+            &hash(&[2]),
+            Round::zero(),
+            &secret_key,
+        );
+
+        let first = ConsensusMessage::Prevote(first);
+        let second = ConsensusMessage::Prevote(second);
+        assert!(is_conflicting(&first, &second));
+        assert!(!is_conflicting(&first, &first));
+
+        let evidence = Equivocation::from_messages(&first, &second);
+        assert_eq!(evidence.validator(), ValidatorId(1));
+        assert_eq!(evidence.height(), Height(2));
+        assert_eq!(evidence.round(), Round(3));
+        assert!(evidence.verify(&public_key));
+        assert!(!evidence.verify(&gen_keypair().0));
+
+        let json_str = ::serde_json::to_string(&evidence).unwrap();
+        let evidence1: Equivocation = ::serde_json::from_str(&json_str).unwrap();
+        assert_eq!(evidence1, evidence);
+    }
+
+    #[test]
+    fn test_different_rounds_do_not_conflict() {
+        let (public_key, secret_key) = gen_keypair();
+        let first = Precommit::new(
+            ValidatorId(0),
+            Height(1),
+            Round(1),
+            &hash(&[1]),
+            &hash(&[2]),
+            ::chrono::Utc::now(),
+            &secret_key,
+        );
+        let second = Precommit::new(
+            ValidatorId(0),
+            Height(1),
+            Round(2),
+            &hash(&[3]),
+            &hash(&[4]),
+            ::chrono::Utc::now(),
+            &secret_key,
+        );
+        assert!(!is_conflicting(
+            &ConsensusMessage::Precommit(first.clone()),
+            &ConsensusMessage::Precommit(second.clone()),
+        ));
+
+        // Forged evidence must not pass verification.
+        let evidence = Equivocation::new(
+            ValidatorId(0),
+            Height(1),
+            Round(1),
+            vec![first.raw().clone(), second.raw().clone()],
+        );
+        assert!(!evidence.verify(&public_key));
+    }
+}
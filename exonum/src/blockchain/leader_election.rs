@@ -0,0 +1,288 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! `LeaderElection` is used to select the validator that proposes a block in a round.
+
+use std::cmp;
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crypto::hash;
+use helpers::{Height, Round, ValidatorId};
+use storage::Snapshot;
+use super::{ConsensusConfig, LeaderElectionConfig, Schema};
+
+/// `LeaderElection` trait is used to select the leader (i.e., the validator that
+/// proposes a block) for every round.
+///
+/// Leader election must be deterministic: all honest validators must select the same leader
+/// for the same height and round given the same blockchain state.
+///
+/// # Examples
+///
+/// Implementing `LeaderElection`:
+///
+/// ```
+/// use exonum::blockchain::leader_election::LeaderElection;
+/// use exonum::helpers::{Height, Round, ValidatorId};
+///
+/// # #[allow(dead_code)]
+/// # #[derive(Debug)]
+/// struct FirstValidator {}
+///
+/// impl LeaderElection for FirstValidator {
+///     fn leader(&self, _: Height, _: Round, _: usize) -> ValidatorId {
+///         // Simply always select the first validator.
+///         ValidatorId::zero()
+///     }
+/// }
+/// ```
+/// For more examples see `RoundRobin`, `Weighted` and `SkipInactive` implementations.
+pub trait LeaderElection: Send + Debug {
+    /// Called during node initialization and after accepting a new height, before the leader
+    /// for this height is requested. `view` contains all blocks below `height`.
+    fn update(&mut self, _view: &Snapshot, _height: Height) {}
+
+    /// Returns the leader for the given height and round.
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId;
+}
+
+/// `LeaderElection` implementation that selects validators one by one, starting with
+/// a validator depending on the height.
+#[derive(Debug, Default)]
+pub struct RoundRobin {}
+
+impl RoundRobin {
+    /// Creates a new `RoundRobin` leader election instance.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::blockchain::leader_election::RoundRobin;
+    ///
+    /// let election = RoundRobin::new();
+    /// # drop(election);
+    /// ```
+    pub fn new() -> Self {
+        RoundRobin {}
+    }
+}
+
+impl LeaderElection for RoundRobin {
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId {
+        let height: u64 = height.into();
+        let round: u64 = round.into();
+        ValidatorId(((height + round) % (validators_count as u64)) as u16)
+    }
+}
+
+/// `LeaderElection` implementation that selects validators pseudo-randomly with probabilities
+/// proportional to their weights.
+///
+/// Note that validators with bigger weights may be selected several rounds in a row,
+/// so a heavy validator being offline slows the network down more than a light one.
+#[derive(Debug)]
+pub struct Weighted {
+    weights: Vec<u64>,
+    total_weight: u64,
+}
+
+impl Weighted {
+    /// Creates a new `Weighted` leader election instance with the given weights of validators.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::blockchain::leader_election::Weighted;
+    ///
+    /// let election = Weighted::new(vec![1, 2, 1, 4]);
+    /// # drop(election);
+    /// ```
+    pub fn new(weights: Vec<u64>) -> Self {
+        let total_weight = weights.iter().sum();
+        Weighted {
+            weights,
+            total_weight,
+        }
+    }
+}
+
+impl LeaderElection for Weighted {
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId {
+        if self.total_weight == 0 || self.weights.len() != validators_count {
+            return RoundRobin::new().leader(height, round, validators_count);
+        }
+
+        let mut seed = [0; 12];
+        LittleEndian::write_u64(&mut seed[0..8], height.into());
+        LittleEndian::write_u32(&mut seed[8..12], round.into());
+        let mut point = LittleEndian::read_u64(&hash(&seed).as_ref()[0..8]) % self.total_weight;
+
+        for (id, weight) in self.weights.iter().enumerate() {
+            if point < *weight {
+                return ValidatorId(id as u16);
+            }
+            point -= *weight;
+        }
+        unreachable!("Point is always less than the total weight")
+    }
+}
+
+/// `LeaderElection` implementation that works as `RoundRobin`, but moves validators that
+/// failed to take part in the consensus recently to the end of the queue.
+///
+/// A validator is considered inactive if during the last `window` blocks it neither
+/// proposed a block, nor had its precommit included in a block. Inactive validators
+/// still become leaders in later rounds, so a validator coming back online is able
+/// to restore its status.
+#[derive(Debug)]
+pub struct SkipInactive {
+    window: u64,
+    inactive: HashSet<ValidatorId>,
+}
+
+impl SkipInactive {
+    /// Creates a new `SkipInactive` leader election instance that looks at the given
+    /// number of latest blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::blockchain::leader_election::SkipInactive;
+    ///
+    /// let election = SkipInactive::new(10);
+    /// # drop(election);
+    /// ```
+    pub fn new(window: u64) -> Self {
+        SkipInactive {
+            window,
+            inactive: HashSet::new(),
+        }
+    }
+
+    fn leader_impl(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId {
+        let n = validators_count as u64;
+        let offset = u64::from(height.next()) % n;
+        let rotated = (0..n).map(|i| ValidatorId(((offset + i) % n) as u16));
+
+        let (active, inactive): (Vec<_>, Vec<_>) =
+            rotated.partition(|id| !self.inactive.contains(id));
+        let queue: Vec<_> = active.into_iter().chain(inactive).collect();
+
+        let round: u64 = round.into();
+        queue[((round + n - 1) % n) as usize]
+    }
+}
+
+impl LeaderElection for SkipInactive {
+    fn update(&mut self, view: &Snapshot, height: Height) {
+        let schema = Schema::new(view);
+        let validators = schema.configuration_by_height(height).validator_keys;
+
+        let mut active = HashSet::new();
+        let to: u64 = height.into();
+        let from = cmp::max(to.saturating_sub(self.window), 1);
+        for h in from..to {
+            let proof = schema.block_and_precommits(Height(h)).expect(&format!(
+                "Block not found, height: {:?}",
+                h
+            ));
+            let keys = schema.configuration_by_height(Height(h)).validator_keys;
+            let ids = proof
+                .precommits
+                .iter()
+                .map(|precommit| precommit.validator())
+                .chain(Some(proof.block.proposer_id()));
+            for id in ids {
+                if let Some(keys) = keys.get(usize::from(id)) {
+                    active.insert(keys.consensus_key);
+                }
+            }
+        }
+
+        self.inactive = validators
+            .iter()
+            .enumerate()
+            .filter(|&(_, keys)| !active.contains(&keys.consensus_key))
+            .map(|(id, _)| ValidatorId(id as u16))
+            .collect();
+    }
+
+    fn leader(&self, height: Height, round: Round, validators_count: usize) -> ValidatorId {
+        self.leader_impl(height, round, validators_count)
+    }
+}
+
+/// Creates `LeaderElection` instance described by the given consensus configuration.
+pub(crate) fn make_leader_election(config: &ConsensusConfig) -> Box<LeaderElection> {
+    match config.leader_election {
+        LeaderElectionConfig::RoundRobin => Box::new(RoundRobin::new()),
+        LeaderElectionConfig::Weighted { ref weights } => Box::new(Weighted::new(weights.clone())),
+        LeaderElectionConfig::SkipInactive { window } => Box::new(SkipInactive::new(window)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_robin_leader_election() {
+        let election = RoundRobin::new();
+        assert_eq!(ValidatorId(2), election.leader(Height(1), Round(1), 4));
+        assert_eq!(ValidatorId(3), election.leader(Height(1), Round(2), 4));
+        assert_eq!(ValidatorId(0), election.leader(Height(1), Round(3), 4));
+        assert_eq!(ValidatorId(1), election.leader(Height(4), Round(1), 4));
+    }
+
+    #[test]
+    fn weighted_leader_election() {
+        let election = Weighted::new(vec![0, 3, 0, 1]);
+        let mut counts = [0; 4];
+        for height in 0..100 {
+            for round in 1..5 {
+                let leader = election.leader(Height(height), Round(round), 4);
+                assert_eq!(leader, election.leader(Height(height), Round(round), 4));
+                counts[usize::from(leader)] += 1;
+            }
+        }
+        assert_eq!(counts[0], 0);
+        assert_eq!(counts[2], 0);
+        assert!(counts[1] > counts[3]);
+        assert!(counts[3] > 0);
+    }
+
+    #[test]
+    fn skip_inactive_leader_election() {
+        let mut election = SkipInactive::new(10);
+        let round_robin = RoundRobin::new();
+        for height in 0..10 {
+            for round in 1..10 {
+                assert_eq!(
+                    round_robin.leader(Height(height), Round(round), 4),
+                    election.leader_impl(Height(height), Round(round), 4)
+                );
+            }
+        }
+
+        election.inactive.insert(ValidatorId(2));
+        assert_eq!(ValidatorId(3), election.leader_impl(Height(1), Round(1), 4));
+        assert_eq!(ValidatorId(0), election.leader_impl(Height(1), Round(2), 4));
+        assert_eq!(ValidatorId(1), election.leader_impl(Height(1), Round(3), 4));
+        assert_eq!(ValidatorId(2), election.leader_impl(Height(1), Round(4), 4));
+        assert_eq!(ValidatorId(3), election.leader_impl(Height(1), Round(5), 4));
+    }
+}
@@ -41,6 +41,7 @@ use std::iter;
 use std::panic;
 use std::net::SocketAddr;
 use std::error::Error as StdError;
+use std::time::Instant;
 use failure;
 
 use vec_map::VecMap;
@@ -51,19 +52,27 @@ use crypto::{self, CryptoHash, Hash, PublicKey, SecretKey};
 use messages::{CONSENSUS as CORE_SERVICE, Connect, Precommit, RawMessage};
 use storage::{Database, Error, Fork, Patch, Snapshot};
 use helpers::{Height, Round, ValidatorId};
+use helpers::metrics::{self, Histogram};
 use node::ApiSender;
+use api::public::Notifier;
 use encoding::Error as MessageError;
 
 pub use self::block::{Block, BlockProof, SCHEMA_MAJOR_VERSION};
 pub use self::schema::{Schema, TxLocation};
+pub use self::evidence::Equivocation;
+pub use self::statistics::{ValidatorStatistics, ValidatorsStatistics};
 pub use self::genesis::GenesisConfig;
-pub use self::config::{ConsensusConfig, StoredConfiguration, TimeoutAdjusterConfig, ValidatorKeys};
+pub use self::config::{ConsensusConfig, LeaderElectionConfig, StoredConfiguration,
+                       TimeoutAdjusterConfig, ValidatorKeys};
 pub use self::service::{ApiContext, Service, ServiceContext, SharedNodeState};
-pub use self::transaction::{ExecutionError, ExecutionResult, Transaction, TransactionError,
-                            TransactionErrorType, TransactionResult, TransactionSet};
+pub use self::transaction::{ExecutionError, ExecutionResult, ExecutionStatus, Transaction,
+                            TransactionError, TransactionErrorType, TransactionResult,
+                            TransactionSet};
 
 mod block;
 mod schema;
+pub(crate) mod evidence;
+mod statistics;
 mod genesis;
 mod service;
 #[macro_use]
@@ -72,6 +81,7 @@ mod transaction;
 mod tests;
 
 pub mod config;
+pub mod leader_election;
 
 /// Exonum blockchain instance with the concrete services set and data storage.
 /// Only blockchains with the identical set of services and genesis block can be combined
@@ -81,6 +91,9 @@ pub struct Blockchain {
     service_map: Arc<VecMap<Box<Service>>>,
     service_keypair: (PublicKey, SecretKey),
     api_sender: ApiSender,
+    notifier: Notifier,
+    node_state: Option<SharedNodeState>,
+    tx_execution_time: Arc<VecMap<Histogram>>,
 }
 
 impl Blockchain {
@@ -93,6 +106,7 @@ impl Blockchain {
         api_sender: ApiSender,
     ) -> Blockchain {
         let mut service_map = VecMap::new();
+        let mut tx_execution_time = VecMap::new();
         for service in services {
             let id = service.service_id() as usize;
             if service_map.contains_key(id) {
@@ -101,6 +115,8 @@ impl Blockchain {
                     id
                 );
             }
+            let histogram = metrics::node_metrics().tx_execution_time(service.service_id());
+            tx_execution_time.insert(id, histogram);
             service_map.insert(id, service);
         }
 
@@ -109,6 +125,9 @@ impl Blockchain {
             service_map: Arc::new(service_map),
             service_keypair: (service_public_key, service_secret_key),
             api_sender,
+            notifier: Notifier::new(),
+            node_state: None,
+            tx_execution_time: Arc::new(tx_execution_time),
         }
     }
 
@@ -121,6 +140,16 @@ impl Blockchain {
         }
     }
 
+    /// Returns the sender used to pass transactions to the node.
+    pub(crate) fn api_sender(&self) -> &ApiSender {
+        &self.api_sender
+    }
+
+    /// Returns the notifier used to deliver events to WebSocket subscribers.
+    pub fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
+
     /// Returns service `VecMap` for all our services.
     pub fn service_map(&self) -> &Arc<VecMap<Box<Service>>> {
         &self.service_map
@@ -154,7 +183,17 @@ impl Blockchain {
     /// Commits changes from the patch to the blockchain storage.
     /// See [`Fork`](../storage/struct.Fork.html) for details.
     pub fn merge(&mut self, patch: Patch) -> Result<(), Error> {
-        self.db.merge(patch)
+        let result = self.db.merge(patch);
+        if let Some(ref node_state) = self.node_state {
+            node_state.set_database_writable(result.is_ok());
+        }
+        result
+    }
+
+    /// Sets the node state, into which the results of writes to the storage are recorded,
+    /// so that the health check reports them without writing to the storage itself.
+    pub(crate) fn set_node_state(&mut self, node_state: SharedNodeState) {
+        self.node_state = Some(node_state);
     }
 
     /// Returns the hash of latest committed block.
@@ -187,6 +226,21 @@ impl Blockchain {
         if !has_genesis_block {
             self.create_genesis_block(cfg)?;
         }
+        self.rebuild_transaction_indexes()
+    }
+
+    /// Adds blocks committed before the secondary transaction indexes were introduced
+    /// into these indexes.
+    fn rebuild_transaction_indexes(&mut self) -> Result<(), Error> {
+        let (indexed_blocks, patch) = {
+            let mut fork = self.fork();
+            let indexed_blocks = Schema::new(&mut fork).update_transaction_indexes();
+            (indexed_blocks, fork.into_patch())
+        };
+        if indexed_blocks > 0 {
+            info!("Rebuilt transaction indexes for {} block(s)", indexed_blocks);
+            self.merge(patch)?;
+        }
         Ok(())
     }
 
@@ -364,7 +418,12 @@ impl Blockchain {
 
         fork.checkpoint();
 
+        let start = Instant::now();
         let catch_result = panic::catch_unwind(panic::AssertUnwindSafe(|| tx.execute(fork)));
+        let service_id = tx.raw().service_id() as usize;
+        if let Some(histogram) = self.tx_execution_time.get(service_id) {
+            histogram.observe_duration(start.elapsed());
+        }
 
         let tx_result = match catch_result {
             Ok(execution_result) => {
@@ -430,17 +489,21 @@ impl Blockchain {
                 // Consensus messages cache is useful only during one height, so it should be
                 // cleared when a new height is achieved.
                 schema.consensus_messages_cache_mut().clear();
+
+                schema.update_transaction_indexes();
             }
             fork.into_patch()
         };
         self.merge(patch)?;
+        let height = Schema::new(&self.snapshot()).height();
+        self.notifier.notify_block(height);
         // Initializes the context after merge.
         let context = ServiceContext::new(
             self.service_keypair.0,
             self.service_keypair.1.clone(),
             self.api_sender.clone(),
             self.fork(),
-        );
+        ).with_notifier(self.notifier.clone());
         // Invokes `handle_commit` for each service in order of their identifiers
         for service in self.service_map.values() {
             service.handle_commit(&context);
@@ -472,7 +535,7 @@ impl Blockchain {
         mount
     }
 
-    fn api_context(&self) -> ApiContext {
+    pub(crate) fn api_context(&self) -> ApiContext {
         ApiContext::from_parts(
             self,
             self.api_sender.clone(),
@@ -495,6 +558,27 @@ impl Blockchain {
         );
     }
 
+    /// Saves evidence of validator equivocation to the node-local evidence storage.
+    /// Returns `false` without writing to the storage if the evidence is already known.
+    pub fn save_equivocation(&mut self, evidence: Equivocation) -> bool {
+        let hash = evidence.hash();
+        if Schema::new(&self.snapshot()).equivocations().contains(&hash) {
+            return false;
+        }
+
+        let mut fork = self.fork();
+
+        {
+            let mut schema = Schema::new(&mut fork);
+            schema.equivocations_mut().put(&hash, evidence);
+        }
+
+        self.merge(fork.into_patch()).expect(
+            "Unable to save equivocation evidence",
+        );
+        true
+    }
+
     /// Removes peer from the peers cache
     pub fn remove_peer_with_addr(&mut self, addr: &SocketAddr) {
         let mut fork = self.fork();
@@ -559,6 +643,9 @@ impl Clone for Blockchain {
             service_map: Arc::clone(&self.service_map),
             api_sender: self.api_sender.clone(),
             service_keypair: self.service_keypair.clone(),
+            notifier: self.notifier.clone(),
+            node_state: self.node_state.clone(),
+            tx_execution_time: Arc::clone(&self.tx_execution_time),
         }
     }
 }
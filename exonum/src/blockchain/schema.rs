@@ -17,7 +17,7 @@ use messages::{Precommit, RawMessage, Connect};
 use storage::{Entry, Fork, ListIndex, MapIndex, MapProof, ProofListIndex, ProofMapIndex, Snapshot,
               KeySetIndex};
 use helpers::{Height, Round};
-use super::{Block, BlockProof, Blockchain, TransactionResult};
+use super::{Block, BlockProof, Blockchain, Equivocation, ExecutionStatus, TransactionResult};
 use super::config::StoredConfiguration;
 
 /// Defines `&str` constants with given name and value.
@@ -46,6 +46,12 @@ define_names!(
     PEERS_CACHE => "peers_cache";
     CONSENSUS_MESSAGES_CACHE => "consensus_messages_cache";
     CONSENSUS_ROUND => "consensus_round";
+    EQUIVOCATIONS => "equivocations";
+    COMMITTED_TRANSACTIONS => "committed_transactions";
+    TRANSACTIONS_BY_SERVICE => "transactions_by_service";
+    TRANSACTIONS_BY_MESSAGE_TYPE => "transactions_by_message_type";
+    TRANSACTIONS_BY_STATUS => "transactions_by_status";
+    TRANSACTION_INDEXES_BLOCKS => "transaction_indexes_blocks";
 );
 
 encoding_struct! (
@@ -184,6 +190,46 @@ where
             .unwrap_or_else(Round::first)
     }
 
+    /// Returns evidence of equivocation observed by this node, keyed by the hash of the evidence.
+    ///
+    /// This table is local to the node and does not participate in the blockchain state hash.
+    pub fn equivocations(&self) -> MapIndex<&T, Hash, Equivocation> {
+        MapIndex::new(EQUIVOCATIONS, &self.view)
+    }
+
+    /// Returns table that keeps hashes of all committed transactions in the order
+    /// of their commitment.
+    ///
+    /// This and the other secondary transaction indexes are updated when a block is committed.
+    /// They are local to the node and do not participate in the blockchain state hash.
+    pub fn committed_transactions(&self) -> ListIndex<&T, Hash> {
+        ListIndex::new(COMMITTED_TRANSACTIONS, &self.view)
+    }
+
+    /// Returns table that keeps hashes of committed transactions of the given service
+    /// in the order of their commitment.
+    pub fn transactions_by_service(&self, service_id: u16) -> ListIndex<&T, Hash> {
+        ListIndex::new_in_family(TRANSACTIONS_BY_SERVICE, &service_id, &self.view)
+    }
+
+    /// Returns table that keeps hashes of committed transactions with the given service id
+    /// and message type in the order of their commitment.
+    pub fn transactions_by_message_type(
+        &self,
+        service_id: u16,
+        message_type: u16,
+    ) -> ListIndex<&T, Hash> {
+        let key = message_type_key(service_id, message_type);
+        ListIndex::new_in_family(TRANSACTIONS_BY_MESSAGE_TYPE, &key, &self.view)
+    }
+
+    /// Returns table that keeps hashes of committed transactions with the given execution
+    /// status in the order of their commitment.
+    pub fn transactions_by_status(&self, status: ExecutionStatus) -> ListIndex<&T, Hash> {
+        let key = status as u8;
+        ListIndex::new_in_family(TRANSACTIONS_BY_STATUS, &key, &self.view)
+    }
+
     /// Returns block hash for the given height.
     pub fn block_hash_by_height(&self, height: Height) -> Option<Hash> {
         self.block_hashes_by_height().get(height.into())
@@ -353,6 +399,11 @@ where
     }
 }
 
+/// Combines the service id and the message type into a single index key.
+fn message_type_key(service_id: u16, message_type: u16) -> u32 {
+    (u32::from(service_id) << 16) | u32::from(message_type)
+}
+
 impl<'a> Schema<&'a mut Fork> {
     /// Mutable reference to the [`transactions`][1] index.
     ///
@@ -451,6 +502,110 @@ impl<'a> Schema<&'a mut Fork> {
         ListIndex::new(CONSENSUS_MESSAGES_CACHE, self.view)
     }
 
+    /// Mutable reference to the [`equivocations`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.equivocations
+    pub(crate) fn equivocations_mut(&mut self) -> MapIndex<&mut Fork, Hash, Equivocation> {
+        MapIndex::new(EQUIVOCATIONS, self.view)
+    }
+
+    /// Mutable reference to the [`committed_transactions`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.committed_transactions
+    pub(crate) fn committed_transactions_mut(&mut self) -> ListIndex<&mut Fork, Hash> {
+        ListIndex::new(COMMITTED_TRANSACTIONS, self.view)
+    }
+
+    /// Mutable reference to the [`transactions_by_service`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.transactions_by_service
+    pub(crate) fn transactions_by_service_mut(
+        &mut self,
+        service_id: u16,
+    ) -> ListIndex<&mut Fork, Hash> {
+        ListIndex::new_in_family(TRANSACTIONS_BY_SERVICE, &service_id, self.view)
+    }
+
+    /// Mutable reference to the [`transactions_by_message_type`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.transactions_by_message_type
+    pub(crate) fn transactions_by_message_type_mut(
+        &mut self,
+        service_id: u16,
+        message_type: u16,
+    ) -> ListIndex<&mut Fork, Hash> {
+        let key = message_type_key(service_id, message_type);
+        ListIndex::new_in_family(TRANSACTIONS_BY_MESSAGE_TYPE, &key, self.view)
+    }
+
+    /// Mutable reference to the [`transactions_by_status`][1] index.
+    ///
+    /// [1]: struct.Schema.html#method.transactions_by_status
+    pub(crate) fn transactions_by_status_mut(
+        &mut self,
+        status: ExecutionStatus,
+    ) -> ListIndex<&mut Fork, Hash> {
+        let key = status as u8;
+        ListIndex::new_in_family(TRANSACTIONS_BY_STATUS, &key, self.view)
+    }
+
+    /// Adds transactions of all blocks, which are not indexed yet, into the secondary
+    /// transaction indexes and returns the number of the indexed blocks.
+    ///
+    /// Blocks committed before the indexes were introduced are indexed on the first call,
+    /// so the method is used both to rebuild the indexes of an existing database and
+    /// to index each newly committed block.
+    pub(crate) fn update_transaction_indexes(&mut self) -> u64 {
+        let indexed_blocks: u64 = Entry::new(TRANSACTION_INDEXES_BLOCKS, &self.view)
+            .get()
+            .unwrap_or(0);
+        let total_blocks = self.block_hashes_by_height().len();
+        for height in indexed_blocks..total_blocks {
+            self.index_block_transactions(Height(height));
+        }
+        if total_blocks > indexed_blocks {
+            let mut entry: Entry<&mut Fork, u64> =
+                Entry::new(TRANSACTION_INDEXES_BLOCKS, self.view);
+            entry.set(total_blocks);
+        }
+        total_blocks.saturating_sub(indexed_blocks)
+    }
+
+    /// Adds transactions of the block at the given height into the secondary
+    /// transaction indexes.
+    fn index_block_transactions(&mut self, height: Height) {
+        let entries: Vec<(Hash, u16, u16, ExecutionStatus)> = {
+            let transactions = self.transactions();
+            let results = self.transaction_results();
+            let block_transactions = self.block_transactions(height);
+            let it = block_transactions.iter().map(|tx_hash| {
+                let raw = transactions.get(&tx_hash).expect(&format!(
+                    "Transaction not found, hash: {:?}",
+                    tx_hash
+                ));
+                let result = results.get(&tx_hash).expect(&format!(
+                    "Transaction result not found, hash: {:?}",
+                    tx_hash
+                ));
+                (
+                    tx_hash,
+                    raw.service_id(),
+                    raw.message_type(),
+                    ExecutionStatus::from(&result),
+                )
+            });
+            it.collect()
+        };
+
+        for (tx_hash, service_id, message_type, status) in entries {
+            self.committed_transactions_mut().push(tx_hash);
+            self.transactions_by_service_mut(service_id).push(tx_hash);
+            self.transactions_by_message_type_mut(service_id, message_type)
+                .push(tx_hash);
+            self.transactions_by_status_mut(status).push(tx_hash);
+        }
+    }
+
     /// Saves the given consensus round value into storage.
     pub(crate) fn set_consensus_round(&mut self, round: Round) {
         let mut entry: Entry<&mut Fork, _> = Entry::new(CONSENSUS_ROUND, self.view);
@@ -19,6 +19,7 @@ use std::fmt;
 use std::sync::{Arc, RwLock};
 use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::time::SystemTime;
 
 use serde_json::Value;
 use iron::Handler;
@@ -27,9 +28,12 @@ use crypto::{Hash, PublicKey, SecretKey};
 use storage::{Fork, Snapshot};
 use messages::RawTransaction;
 use encoding::Error as MessageError;
+use encoding::schema::MessageSchema;
+use api::openapi::Endpoint;
+use api::public::Notifier;
 use node::{ApiSender, Node, State, TransactionSend};
 use blockchain::{Blockchain, ConsensusConfig, Schema, StoredConfiguration, ValidatorKeys};
-use helpers::{Height, Milliseconds, ValidatorId};
+use helpers::{Height, Milliseconds, Round, ValidatorId};
 use super::transaction::Transaction;
 
 
@@ -166,6 +170,16 @@ pub trait Service: Send + Sync + 'static {
     /// [the `Service` example above](#examples).
     fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<Transaction>, MessageError>;
 
+    /// Returns descriptors of the binary layout of the service transactions. The descriptors
+    /// are served by the node at `/api/system/v1/schemas`, so generic tooling can decode
+    /// transactions of the service without access to its Rust types.
+    ///
+    /// Services declaring transactions with the `transactions!` macro can return
+    /// `<MyTransactions as TransactionSet>::message_schemas()`.
+    fn message_schemas(&self) -> Vec<MessageSchema> {
+        Vec::new()
+    }
+
     /// Initializes the information schema of the service
     /// and generates an initial service configuration.
     /// Called on genesis block creation.
@@ -197,6 +211,35 @@ pub trait Service: Send + Sync + 'static {
     fn private_api_handler(&self, context: &ApiContext) -> Option<Box<Handler>> {
         None
     }
+
+    /// Returns descriptions of the endpoints served by [the public API handler][pub-handler].
+    /// Paths are relative to `/api/services/{service_name}`. The descriptions are included
+    /// into the OpenAPI document served by the node at `/api/openapi.json`.
+    ///
+    /// Services wiring their handler with [`Api::wire_documented`][wire-documented]
+    /// should return [`Api::endpoints`][endpoints] of the same API here.
+    ///
+    /// [pub-handler]: #method.public_api_handler
+    /// [wire-documented]: ../api/trait.Api.html#method.wire_documented
+    /// [endpoints]: ../api/trait.Api.html#method.endpoints
+    fn public_api_endpoints(&self, context: &ApiContext) -> Vec<Endpoint> {
+        Vec::new()
+    }
+
+    /// Returns descriptions of the endpoints served by [the private API handler][private-handler].
+    /// Paths are relative to `/api/services/{service_name}`. The descriptions are included
+    /// into the OpenAPI document served by the node at `/api/openapi.json` of the private
+    /// listen address.
+    ///
+    /// Services wiring their handler with [`Api::wire_documented`][wire-documented]
+    /// should return [`Api::endpoints`][endpoints] of the same API here.
+    ///
+    /// [private-handler]: #method.private_api_handler
+    /// [wire-documented]: ../api/trait.Api.html#method.wire_documented
+    /// [endpoints]: ../api/trait.Api.html#method.endpoints
+    fn private_api_endpoints(&self, context: &ApiContext) -> Vec<Endpoint> {
+        Vec::new()
+    }
 }
 
 /// The current node state on which the blockchain is running, or in other words
@@ -209,6 +252,7 @@ pub struct ServiceContext {
     fork: Fork,
     stored_configuration: StoredConfiguration,
     height: Height,
+    notifier: Notifier,
 }
 
 impl ServiceContext {
@@ -242,9 +286,16 @@ impl ServiceContext {
             fork,
             stored_configuration,
             height,
+            notifier: Notifier::new(),
         }
     }
 
+    /// Sets the notifier used to publish service events.
+    pub(crate) fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
     /// If the current node is validator returns its identifier.
     /// For other nodes return `None`.
     pub fn validator_id(&self) -> Option<ValidatorId> {
@@ -295,9 +346,15 @@ impl ServiceContext {
     pub fn stored_configuration(&self) -> &StoredConfiguration {
         &self.stored_configuration
     }
+
+    /// Returns the notifier that can be used to publish service events to
+    /// WebSocket subscribers.
+    pub fn notifier(&self) -> &Notifier {
+        &self.notifier
+    }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct ApiNodeState {
     incoming_connections: HashSet<SocketAddr>,
     outgoing_connections: HashSet<SocketAddr>,
@@ -305,11 +362,27 @@ pub struct ApiNodeState {
     //TODO: update on event?
     peers_info: HashMap<SocketAddr, PublicKey>,
     is_enabled: bool,
+    height: Height,
+    max_known_height: Height,
+    round: Round,
+    height_start_time: Option<SystemTime>,
+    database_writable: bool,
 }
 
 impl ApiNodeState {
     fn new() -> ApiNodeState {
-        Self::default()
+        ApiNodeState {
+            incoming_connections: HashSet::new(),
+            outgoing_connections: HashSet::new(),
+            reconnects_timeout: HashMap::new(),
+            peers_info: HashMap::new(),
+            is_enabled: true,
+            height: Height::zero(),
+            max_known_height: Height::zero(),
+            round: Round::zero(),
+            height_start_time: None,
+            database_writable: true,
+        }
     }
 }
 
@@ -370,15 +443,58 @@ impl SharedNodeState {
             .map(|(c, e)| (*c, *e))
             .collect()
     }
+    /// Returns the current height of the node as of the last state update.
+    pub fn height(&self) -> Height {
+        self.state.read().expect("Expected read lock.").height
+    }
+
+    /// Returns the maximum height reported by other nodes, or the height
+    /// of the node itself if no node is ahead of it.
+    pub fn max_known_height(&self) -> Height {
+        self.state.read().expect("Expected read lock.").max_known_height
+    }
+
+    /// Returns the current round of the node as of the last state update.
+    pub fn round(&self) -> Round {
+        self.state.read().expect("Expected read lock.").round
+    }
+
+    /// Returns the start time of the current height, i.e., the time of the last commit
+    /// or of the node start. Returns `None` before the first state update.
+    pub fn height_start_time(&self) -> Option<SystemTime> {
+        self.state.read().expect("Expected read lock.").height_start_time
+    }
+
+    /// Returns `false` if the last write of the node to the storage failed.
+    pub fn is_database_writable(&self) -> bool {
+        self.state.read().expect("Expected read lock.").database_writable
+    }
+
+    /// Records the result of the last write of the node to the storage.
+    pub fn set_database_writable(&self, database_writable: bool) {
+        if self.is_database_writable() != database_writable {
+            let mut state = self.state.write().expect("Expected write lock.");
+            state.database_writable = database_writable;
+        }
+    }
+
     /// Update internal state, from `Node` State`
     pub fn update_node_state(&self, state: &State) {
+        let max_known_height = state
+            .nodes_with_bigger_height()
+            .into_iter()
+            .map(|key| state.node_height(key))
+            .max()
+            .unwrap_or_else(|| state.height());
+
+        let mut api_state = self.state.write().expect("Expected write lock.");
         for (p, c) in state.peers().iter() {
-            self.state
-                .write()
-                .expect("Expected write lock.")
-                .peers_info
-                .insert(c.addr(), *p);
+            api_state.peers_info.insert(c.addr(), *p);
         }
+        api_state.height = state.height();
+        api_state.max_known_height = max_known_height;
+        api_state.round = state.round();
+        api_state.height_start_time = Some(state.height_start_time());
     }
 
     /// Is the node enabled?
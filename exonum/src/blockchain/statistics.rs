@@ -0,0 +1,163 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Liveness and participation statistics of validators.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crypto::PublicKey;
+use helpers::{Height, Round};
+use storage::Snapshot;
+use super::Schema;
+use super::leader_election::make_leader_election;
+
+/// Participation statistics of a single validator over a range of blocks.
+///
+/// Validators are identified by their consensus keys, since validator ids may change
+/// between configurations.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorStatistics {
+    /// Consensus key of the validator.
+    pub consensus_key: PublicKey,
+    /// Number of blocks in the range committed while the validator was in the
+    /// validator set.
+    pub blocks: u64,
+    /// Number of those blocks containing a precommit from the validator.
+    pub precommits: u64,
+    /// Share of the blocks containing a precommit from the validator.
+    pub precommit_participation: f64,
+    /// Number of rounds in which the validator was the leader, up to and including
+    /// the round in which the block was committed.
+    pub leader_rounds: u64,
+    /// Number of committed blocks proposed by the validator.
+    pub proposed_blocks: u64,
+    /// Ratio of the proposed blocks to the rounds in which the validator was the leader.
+    pub proposal_success_rate: f64,
+}
+
+impl ValidatorStatistics {
+    fn new(consensus_key: PublicKey) -> Self {
+        ValidatorStatistics {
+            consensus_key,
+            blocks: 0,
+            precommits: 0,
+            precommit_participation: 0.0,
+            leader_rounds: 0,
+            proposed_blocks: 0,
+            proposal_success_rate: 0.0,
+        }
+    }
+
+    fn update_rates(&mut self) {
+        self.precommit_participation = ratio(self.precommits, self.blocks);
+        self.proposal_success_rate = ratio(self.proposed_blocks, self.leader_rounds);
+    }
+}
+
+/// Statistics of all validators over a range of blocks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorsStatistics {
+    /// Exclusive range of block heights the statistics is computed over.
+    /// The genesis block is never included, as it has no precommits.
+    pub range: Range<u64>,
+    /// Average round in which blocks in the range were committed.
+    pub average_rounds: f64,
+    /// Per-validator statistics, in the order validators appear in configurations.
+    pub validators: Vec<ValidatorStatistics>,
+}
+
+impl ValidatorsStatistics {
+    /// Computes statistics over the blocks with heights in the given exclusive range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a block within the range is absent.
+    pub fn compute(snapshot: &Snapshot, range: Range<u64>) -> Self {
+        let schema = Schema::new(snapshot);
+        let range = ::std::cmp::max(range.start, 1)..range.end;
+
+        let mut validators: Vec<ValidatorStatistics> = Vec::new();
+        let mut indices: HashMap<PublicKey, usize> = HashMap::new();
+        let mut total_rounds = 0;
+        let mut blocks_count = 0;
+
+        for height in range.clone() {
+            let height = Height(height);
+            let proof = schema.block_and_precommits(height).expect(&format!(
+                "Block not found, height: {:?}",
+                height
+            ));
+            let config = schema.configuration_by_height(height);
+            let keys: Vec<PublicKey> = config
+                .validator_keys
+                .iter()
+                .map(|keys| keys.consensus_key)
+                .collect();
+
+            let stats: Vec<usize> = keys.iter()
+                .map(|key| {
+                    *indices.entry(*key).or_insert_with(|| {
+                        validators.push(ValidatorStatistics::new(*key));
+                        validators.len() - 1
+                    })
+                })
+                .collect();
+
+            for &idx in &stats {
+                validators[idx].blocks += 1;
+            }
+            for precommit in &proof.precommits {
+                if let Some(&idx) = stats.get(usize::from(precommit.validator())) {
+                    validators[idx].precommits += 1;
+                }
+            }
+            if let Some(&idx) = stats.get(usize::from(proof.block.proposer_id())) {
+                validators[idx].proposed_blocks += 1;
+            }
+
+            let commit_round = proof.precommits.first().map_or_else(
+                Round::first,
+                |precommit| precommit.round(),
+            );
+            let mut leader_election = make_leader_election(&config.consensus);
+            leader_election.update(snapshot, height);
+            for round in Round::first().iter_to(commit_round.next()) {
+                let leader = leader_election.leader(height, round, keys.len());
+                validators[stats[usize::from(leader)]].leader_rounds += 1;
+            }
+
+            total_rounds += u64::from(commit_round);
+            blocks_count += 1;
+        }
+
+        for validator in &mut validators {
+            validator.update_rates();
+        }
+
+        ValidatorsStatistics {
+            range,
+            average_rounds: ratio(total_rounds, blocks_count),
+            validators,
+        }
+    }
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
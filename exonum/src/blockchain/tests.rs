@@ -18,7 +18,8 @@ use rand::{thread_rng, Rng};
 use serde_json;
 use chrono::{DateTime, Utc, TimeZone};
 
-use blockchain::{Blockchain, Service, Snapshot, Schema, Transaction, ExecutionResult};
+use blockchain::{Blockchain, ExecutionResult, ExecutionStatus, GenesisConfig, Schema, Service,
+                 Snapshot, Transaction, ValidatorKeys};
 use crypto::{gen_keypair, Hash, CryptoHash};
 use storage::{Error, Fork, ListIndex};
 use messages::{Message, RawTransaction};
@@ -269,6 +270,120 @@ fn handling_tx_panic_storage_error(blockchain: &mut Blockchain) {
     );
 }
 
+/// Commits blocks the way the nodes did before the secondary transaction indexes were
+/// introduced, i.e., without filling in these indexes. Returns hashes of the committed
+/// transactions in the order of their execution.
+fn create_blocks_without_transaction_indexes(blockchain: &mut Blockchain) -> Vec<Hash> {
+    let (_, sec_key) = gen_keypair();
+
+    let tx_ok1 = Tx::new(3, &sec_key);
+    let tx_ok2 = Tx::new(4, &sec_key);
+    let tx_failed = Tx::new(0, &sec_key);
+
+    let patch = {
+        let mut fork = blockchain.fork();
+        {
+            let mut schema = Schema::new(&mut fork);
+            schema.add_transaction_into_pool(tx_ok1.raw().clone());
+            schema.add_transaction_into_pool(tx_ok2.raw().clone());
+            schema.add_transaction_into_pool(tx_failed.raw().clone());
+        }
+        fork.into_patch()
+    };
+    blockchain.merge(patch).unwrap();
+
+    let (_, patch) = blockchain.create_patch(
+        ValidatorId::zero(),
+        Height::zero(),
+        &[tx_ok1.hash(), tx_failed.hash()],
+    );
+    blockchain.merge(patch).unwrap();
+    let (_, patch) = blockchain.create_patch(ValidatorId::zero(), Height(1), &[tx_ok2.hash()]);
+    blockchain.merge(patch).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    assert!(Schema::new(&snapshot).committed_transactions().is_empty());
+    vec![tx_ok1.hash(), tx_failed.hash(), tx_ok2.hash()]
+}
+
+fn rebuild_transaction_indexes(blockchain: &mut Blockchain, tx_hashes: &[Hash]) {
+    let (consensus_key, _) = gen_keypair();
+    let (service_key, _) = gen_keypair();
+    let genesis = GenesisConfig::new(
+        vec![
+            ValidatorKeys {
+                consensus_key,
+                service_key,
+                voting_power: None,
+            },
+        ].into_iter(),
+    );
+    blockchain.initialize(genesis.clone()).unwrap();
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.height(), Height(1));
+    assert_eq!(
+        schema.committed_transactions().iter().collect::<Vec<_>>(),
+        tx_hashes
+    );
+    assert_eq!(
+        schema
+            .transactions_by_service(TEST_SERVICE_ID)
+            .iter()
+            .collect::<Vec<_>>(),
+        tx_hashes
+    );
+    assert_eq!(
+        schema
+            .transactions_by_status(ExecutionStatus::Success)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![tx_hashes[0], tx_hashes[2]]
+    );
+    assert_eq!(
+        schema
+            .transactions_by_status(ExecutionStatus::Panic)
+            .iter()
+            .collect::<Vec<_>>(),
+        vec![tx_hashes[1]]
+    );
+
+    // Repeated initialization does not index the same blocks twice.
+    blockchain.initialize(genesis).unwrap();
+    let snapshot = blockchain.snapshot();
+    assert_eq!(Schema::new(&snapshot).committed_transactions().len(), 3);
+}
+
+fn save_equivocation_once(blockchain: &mut Blockchain) {
+    use blockchain::Equivocation;
+    use crypto::hash;
+    use helpers::Round;
+    use messages::{ConsensusMessage, Prevote};
+
+    let (_, secret_key) = gen_keypair();
+    let prevote = |propose: &[u8]| {
+        ConsensusMessage::Prevote(Prevote::new(
+            ValidatorId(1),
+            Height(2),
+            Round(3),
+            &hash(propose),
+            Round::zero(),
+            &secret_key,
+        ))
+    };
+    let evidence = Equivocation::from_messages(&prevote(&[1]), &prevote(&[2]));
+
+    assert!(blockchain.save_equivocation(evidence.clone()));
+    // Replayed conflicting messages are not written again.
+    assert!(!blockchain.save_equivocation(evidence.clone()));
+
+    let snapshot = blockchain.snapshot();
+    let schema = Schema::new(&snapshot);
+    assert_eq!(schema.equivocations().get(&evidence.hash()), Some(evidence));
+    assert_eq!(schema.equivocations().values().count(), 1);
+}
+
 mod transactions_tests {
     use blockchain::{Transaction, TransactionSet, ExecutionResult};
     use storage::Fork;
@@ -404,6 +519,23 @@ mod memorydb_tests {
         let mut blockchain = create_blockchain(path);
         super::handling_tx_panic_storage_error(&mut blockchain);
     }
+
+    #[test]
+    fn test_save_equivocation_once() {
+        let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+        let path = dir.path();
+        let mut blockchain = create_blockchain(path);
+        super::save_equivocation_once(&mut blockchain);
+    }
+
+    #[test]
+    fn test_rebuild_transaction_indexes() {
+        let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+        let path = dir.path();
+        let mut blockchain = create_blockchain(path);
+        let tx_hashes = super::create_blocks_without_transaction_indexes(&mut blockchain);
+        super::rebuild_transaction_indexes(&mut blockchain, &tx_hashes);
+    }
 }
 
 mod rocksdb_tests {
@@ -449,4 +581,24 @@ mod rocksdb_tests {
         let mut blockchain = create_blockchain(path);
         super::handling_tx_panic_storage_error(&mut blockchain);
     }
+
+    #[test]
+    fn test_save_equivocation_once() {
+        let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+        let path = dir.path();
+        let mut blockchain = create_blockchain(path);
+        super::save_equivocation_once(&mut blockchain);
+    }
+
+    #[test]
+    fn test_rebuild_transaction_indexes_in_existing_database() {
+        let dir = TempDir::new(super::gen_tempdir_name().as_str()).unwrap();
+        let path = dir.path();
+        let tx_hashes = {
+            let mut blockchain = create_blockchain(path);
+            super::create_blocks_without_transaction_indexes(&mut blockchain)
+        };
+        let mut blockchain = create_blockchain(path);
+        super::rebuild_transaction_indexes(&mut blockchain, &tx_hashes);
+    }
 }
@@ -18,6 +18,7 @@ use std::borrow::Cow;
 use std::any::Any;
 use std::error::Error;
 use std::fmt;
+use std::str::FromStr;
 use std::u8;
 
 use serde::Serialize;
@@ -27,6 +28,7 @@ use messages::{Message, RawTransaction};
 use storage::{Fork, StorageValue};
 use crypto::{Hash, CryptoHash};
 use encoding;
+use encoding::schema::MessageSchema;
 use encoding::serialize::json::ExonumJson;
 
 //  User-defined error codes (`TransactionErrorType::Code(u8)`) have a `0...255` range.
@@ -184,6 +186,52 @@ pub enum TransactionErrorType {
     Code(u8),
 }
 
+/// Coarse execution status of a committed transaction, used to filter transactions.
+///
+/// The discriminants are part of the storage format: they are the keys of
+/// the `transactions_by_status` index family and must never change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u8)]
+pub enum ExecutionStatus {
+    /// Successful transaction execution.
+    Success = 0,
+    /// Transaction execution returned an error.
+    Error = 1,
+    /// Panic occurred during transaction execution.
+    Panic = 2,
+}
+
+impl<'a> From<&'a TransactionResult> for ExecutionStatus {
+    fn from(result: &'a TransactionResult) -> Self {
+        match *result {
+            Ok(()) => ExecutionStatus::Success,
+            Err(ref e) => {
+                match e.error_type() {
+                    TransactionErrorType::Panic => ExecutionStatus::Panic,
+                    TransactionErrorType::Code(_) => ExecutionStatus::Error,
+                }
+            }
+        }
+    }
+}
+
+impl FromStr for ExecutionStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "success" => Ok(ExecutionStatus::Success),
+            "error" => Ok(ExecutionStatus::Error),
+            "panic" => Ok(ExecutionStatus::Panic),
+            _ => Err(format!(
+                "Unknown execution status '{}', expected one of: success, error, panic",
+                s
+            )),
+        }
+    }
+}
+
 /// Result of unsuccessful transaction execution encompassing both service and framework-wide error
 /// handling.
 ///
@@ -350,6 +398,11 @@ pub trait TransactionSet
     : Into<Box<Transaction>> + DeserializeOwned + Serialize + Clone {
     /// Parse a transaction from this set from a `RawMessage`.
     fn tx_from_raw(raw: RawTransaction) -> Result<Self, encoding::Error>;
+
+    /// Returns descriptors of the binary layout of the transactions in this set.
+    fn message_schemas() -> Vec<MessageSchema> {
+        Vec::new()
+    }
 }
 
 /// `transactions!` is used to declare a set of transactions of a particular service.
@@ -376,8 +429,18 @@ pub trait TransactionSet
 ///   and a message [`Signature`].
 ///
 /// Each transaction also implements [`Message`], [`ServiceMessage`], [`SegmentField`],
-/// [`ExonumJson`] and [`StorageValue`] traits for the declared datatype.
+/// [`ExonumJson`] and [`StorageValue`] traits for the declared datatype, and a `schema`
+/// method returning the [descriptor][schema] of its binary layout. Descriptors of all
+/// transactions in the set are returned by [`TransactionSet::message_schemas`].
+///
+/// A transaction may be declared with an `optional` block of fields after the ordinary
+/// ones, just like a versioned [`encoding_struct!`]. Optional fields follow the fields
+/// in the constructors, and transactions signed with the previous layouts of the
+/// transaction remain valid.
 ///
+/// [`encoding_struct!`]: macro.encoding_struct.html#schema-evolution
+/// [schema]: ./encoding/schema/struct.MessageSchema.html
+/// [`TransactionSet::message_schemas`]: ./blockchain/trait.TransactionSet.html#method.message_schemas
 ///
 /// **NB.** `transactions!` uses other macros in the `exonum` crate internally.
 /// Be sure to add them to the global scope.
@@ -439,6 +502,9 @@ macro_rules! transactions {
                 struct $name:ident {
                     $($def:tt)*
                 }
+                $(optional {
+                    $($opt:tt)*
+                })*
             )*
         }
     } => {
@@ -449,6 +515,9 @@ macro_rules! transactions {
                 struct $name {
                     $($def)*
                 }
+                $(optional {
+                    $($opt)*
+                })*
             )*
         }
 
@@ -474,6 +543,9 @@ macro_rules! transactions {
                 struct $name:ident {
                     $($def:tt)*
                 }
+                $(optional {
+                    $($opt:tt)*
+                })*
             )*
         }
     } => {
@@ -484,6 +556,9 @@ macro_rules! transactions {
                 struct $name {
                     $($def)*
                 }
+                $(optional {
+                    $($opt)*
+                })*
             )*
         }
 
@@ -509,6 +584,9 @@ macro_rules! transactions {
                 struct $name:ident {
                     $($def:tt)*
                 }
+                $(optional {
+                    $($opt:tt)*
+                })*
             )*
         }
     } => {
@@ -519,6 +597,9 @@ macro_rules! transactions {
                 struct $name {
                     $($def)*
                 }
+                $(optional {
+                    $($opt)*
+                })*
             )*
         }
 
@@ -535,6 +616,15 @@ macro_rules! transactions {
     };
     // Implementation details
     (@implement $transaction_set:ident, $($name:ident)*) => {
+        transactions!(
+            @implement_set $transaction_set,
+            vec![$($name::schema(),)*],
+            $($name)*
+        );
+    };
+    // Implementation of the set for the given transactions and descriptors of their layouts.
+    // Also used by `protobuf_transactions!`.
+    (@implement_set $transaction_set:ident, $schemas:expr, $($name:ident)*) => {
 
         impl $crate::blockchain::TransactionSet for $transaction_set {
             fn tx_from_raw(
@@ -551,6 +641,10 @@ macro_rules! transactions {
                     _ => return Err($crate::encoding::Error::IncorrectMessageType { message_type })
                 }
             }
+
+            fn message_schemas() -> Vec<$crate::encoding::schema::MessageSchema> {
+                $schemas
+            }
         }
 
         impl Into<Box<$crate::blockchain::Transaction>> for $transaction_set {
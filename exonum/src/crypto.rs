@@ -36,6 +36,7 @@ use std::ops::{Index, Range, RangeFrom, RangeFull, RangeTo};
 use std::fmt;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use encoding::Decimal;
 use encoding::serialize::FromHex;
 use helpers::Round;
 
@@ -602,6 +603,34 @@ impl CryptoHash for i64 {
     }
 }
 
+#[cfg(feature = "int128")]
+impl CryptoHash for u128 {
+    fn hash(&self) -> Hash {
+        let mut v = [0; 16];
+        LittleEndian::write_u128(&mut v, *self);
+        hash(&v)
+    }
+}
+
+#[cfg(feature = "int128")]
+impl CryptoHash for i128 {
+    fn hash(&self) -> Hash {
+        let mut v = [0; 16];
+        LittleEndian::write_i128(&mut v, *self);
+        hash(&v)
+    }
+}
+
+/// Uses the same encoding as `StorageValue` for `Decimal`.
+impl CryptoHash for Decimal {
+    fn hash(&self) -> Hash {
+        let mut v = [0; 9];
+        LittleEndian::write_i64(&mut v[0..8], self.mantissa());
+        v[8] = self.scale();
+        hash(&v)
+    }
+}
+
 const EMPTY_SLICE_HASH: Hash = Hash(DigestSodium(
     [
         227,
@@ -0,0 +1,504 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp::{self, Ordering};
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+
+use byteorder::{ByteOrder, LittleEndian};
+use serde_json::value::Value;
+
+use super::Result as EncodingResult;
+use super::Error as EncodingError;
+use encoding::{CheckedOffset, Field, Offset};
+use encoding::serialize::WriteBufferWrapper;
+use encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize};
+
+const POWERS_OF_TEN: [i64; 19] = [
+    1,
+    10,
+    100,
+    1_000,
+    10_000,
+    100_000,
+    1_000_000,
+    10_000_000,
+    100_000_000,
+    1_000_000_000,
+    10_000_000_000,
+    100_000_000_000,
+    1_000_000_000_000,
+    10_000_000_000_000,
+    100_000_000_000_000,
+    1_000_000_000_000_000,
+    10_000_000_000_000_000,
+    100_000_000_000_000_000,
+    1_000_000_000_000_000_000,
+];
+
+/// Fixed-point decimal number equal to `mantissa * 10^(-scale)`.
+///
+/// Unlike `F32` and `F64`, decimals represent amounts such as `0.1` exactly,
+/// and arithmetic operations either return the exact result or fail. This makes
+/// them suitable for monetary amounts.
+///
+/// The scale is a part of the value: `1.5` and `1.50` are equal numerically,
+/// but they are different values with different binary and JSON representations.
+/// Services usually fix the scale for every kind of amounts they store.
+///
+/// # Examples
+///
+/// ```
+/// use exonum::encoding::Decimal;
+///
+/// let price: Decimal = "12.50".parse().unwrap();
+/// let fee = Decimal::new(5, 1);
+/// assert_eq!(price.checked_add(fee).unwrap().to_string(), "13.00");
+/// assert_eq!(price.checked_mul(Decimal::new(3, 0)).unwrap().to_string(), "37.50");
+/// assert!(Decimal::new(i64::max_value(), 0).checked_add(fee).is_none());
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Decimal {
+    mantissa: i64,
+    scale: u8,
+}
+
+impl Decimal {
+    /// Maximum number of digits after the decimal point.
+    pub const MAX_SCALE: u8 = 18;
+
+    /// Creates a new `Decimal` instance with the given `mantissa` and `scale`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `scale` exceeds `MAX_SCALE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::encoding::Decimal;
+    ///
+    /// let val = Decimal::new(-1205, 2);
+    /// assert_eq!(val.to_string(), "-12.05");
+    /// ```
+    pub fn new(mantissa: i64, scale: u8) -> Self {
+        Self::try_new(mantissa, scale).expect("Unexpected decimal scale")
+    }
+
+    /// Creates a new `Decimal` instance with the given `mantissa` and `scale`.
+    /// Returns `None` if `scale` exceeds `MAX_SCALE`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::encoding::Decimal;
+    ///
+    /// assert!(Decimal::try_new(1, 18).is_some());
+    /// assert!(Decimal::try_new(1, 19).is_none());
+    /// ```
+    pub fn try_new(mantissa: i64, scale: u8) -> Option<Self> {
+        if scale <= Self::MAX_SCALE {
+            Some(Decimal { mantissa, scale })
+        } else {
+            None
+        }
+    }
+
+    /// Checks the scale read from a buffer at the given position.
+    pub(crate) fn check_scale(scale: u8, position: Offset) -> Result<(), EncodingError> {
+        if scale > Self::MAX_SCALE {
+            Err(EncodingError::IncorrectDecimalScale {
+                position,
+                value: scale,
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the mantissa of the number.
+    pub fn mantissa(&self) -> i64 {
+        self.mantissa
+    }
+
+    /// Returns the number of digits after the decimal point.
+    pub fn scale(&self) -> u8 {
+        self.scale
+    }
+
+    /// Converts the number to the given `scale`. Returns `None` if the result
+    /// overflows or if the conversion would lose precision.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use exonum::encoding::Decimal;
+    ///
+    /// let val = Decimal::new(150, 2);
+    /// assert_eq!(val.checked_rescale(4), Some(Decimal::new(15_000, 4)));
+    /// assert_eq!(val.checked_rescale(1), Some(Decimal::new(15, 1)));
+    /// assert_eq!(val.checked_rescale(0), None);
+    /// ```
+    pub fn checked_rescale(self, scale: u8) -> Option<Self> {
+        if scale > Self::MAX_SCALE {
+            return None;
+        }
+        let mantissa = if scale >= self.scale {
+            let factor = POWERS_OF_TEN[(scale - self.scale) as usize];
+            self.mantissa.checked_mul(factor)?
+        } else {
+            let factor = POWERS_OF_TEN[(self.scale - scale) as usize];
+            if self.mantissa % factor != 0 {
+                return None;
+            }
+            self.mantissa / factor
+        };
+        Some(Decimal { mantissa, scale })
+    }
+
+    /// Computes `self + other` with the larger scale of the operands.
+    /// Returns `None` if the result overflows.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let (lhs, rhs) = Self::align(self, other)?;
+        let mantissa = lhs.mantissa.checked_add(rhs.mantissa)?;
+        Some(Decimal { mantissa, ..lhs })
+    }
+
+    /// Computes `self - other` with the larger scale of the operands.
+    /// Returns `None` if the result overflows.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        let (lhs, rhs) = Self::align(self, other)?;
+        let mantissa = lhs.mantissa.checked_sub(rhs.mantissa)?;
+        Some(Decimal { mantissa, ..lhs })
+    }
+
+    /// Computes `self * other` with the sum of the scales of the operands.
+    /// Returns `None` if the result overflows or its scale exceeds `MAX_SCALE`.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let mantissa = self.mantissa.checked_mul(other.mantissa)?;
+        Self::try_new(mantissa, self.scale + other.scale)
+    }
+
+    /// Computes `-self`. Returns `None` if the result overflows.
+    pub fn checked_neg(self) -> Option<Self> {
+        let mantissa = self.mantissa.checked_neg()?;
+        Some(Decimal { mantissa, ..self })
+    }
+
+    /// Converts both operands to the larger of their scales.
+    fn align(lhs: Self, rhs: Self) -> Option<(Self, Self)> {
+        let scale = cmp::max(lhs.scale, rhs.scale);
+        Some((lhs.checked_rescale(scale)?, rhs.checked_rescale(scale)?))
+    }
+}
+
+/// Compares decimals numerically. Numerically equal decimals are ordered by their scales,
+/// e.g., `1.5 < 1.50 < 1.51`.
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let scale = cmp::max(self.scale, other.scale);
+        match (self.checked_rescale(scale), other.checked_rescale(scale)) {
+            (Some(lhs), Some(rhs)) => {
+                lhs.mantissa.cmp(&rhs.mantissa).then(
+                    self.scale.cmp(&other.scale),
+                )
+            }
+            // Rescaling overflows only if the absolute value of the number exceeds
+            // the absolute value of any number with the larger scale.
+            (None, _) => self.mantissa.cmp(&0),
+            (_, None) => 0.cmp(&other.mantissa),
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Formats the number with exactly `scale` digits after the decimal point.
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let scale = self.scale as usize;
+        // `wrapping_abs` leaves `i64::min_value()` intact, and it is then cast
+        // to its absolute value.
+        let digits = format!(
+            "{:0>width$}",
+            self.mantissa.wrapping_abs() as u64,
+            width = scale + 1
+        );
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        if scale == 0 {
+            write!(f, "{}{}", sign, integer)
+        } else {
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        }
+    }
+}
+
+/// Parses the representation produced by `Display`. The scale of the number is equal
+/// to the number of digits after the decimal point.
+impl FromStr for Decimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        let error = || ParseDecimalError { string: string.to_owned() };
+
+        let is_negative = string.starts_with('-');
+        let unsigned = if is_negative { &string[1..] } else { string };
+        let (integer, fraction) = match unsigned.find('.') {
+            Some(pos) => (&unsigned[..pos], &unsigned[pos + 1..]),
+            None => (unsigned, ""),
+        };
+        let is_digits = |part: &str| part.bytes().all(|b| b >= b'0' && b <= b'9');
+        let is_canonical = !integer.is_empty() && is_digits(integer) && is_digits(fraction) &&
+            (integer == "0" || !integer.starts_with('0')) &&
+            (integer.len() == unsigned.len() || !fraction.is_empty());
+        if !is_canonical || fraction.len() > Self::MAX_SCALE as usize {
+            return Err(error());
+        }
+
+        let mut digits = String::with_capacity(string.len());
+        if is_negative {
+            digits.push('-');
+        }
+        digits.push_str(integer);
+        digits.push_str(fraction);
+        let mantissa: i64 = digits.parse().map_err(|_| error())?;
+        // Negative zero is not produced by `Display`.
+        if is_negative && mantissa == 0 {
+            return Err(error());
+        }
+        Ok(Decimal {
+            mantissa,
+            scale: fraction.len() as u8,
+        })
+    }
+}
+
+/// Error returned when a string is not a canonical representation of `Decimal`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError {
+    string: String,
+}
+
+impl fmt::Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "`{}` is not a canonical decimal number", self.string)
+    }
+}
+
+impl Error for ParseDecimalError {
+    fn description(&self) -> &str {
+        "Invalid decimal number"
+    }
+}
+
+/// Mantissa stored in little endian followed by the scale.
+impl<'a> Field<'a> for Decimal {
+    fn field_size() -> Offset {
+        (mem::size_of::<i64>() + mem::size_of::<u8>()) as Offset
+    }
+
+    unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> Self {
+        Self::new(
+            LittleEndian::read_i64(&buffer[from as usize..to as usize - 1]),
+            buffer[to as usize - 1],
+        )
+    }
+
+    fn write(&self, buffer: &mut Vec<u8>, from: Offset, to: Offset) {
+        LittleEndian::write_i64(&mut buffer[from as usize..to as usize - 1], self.mantissa);
+        buffer[to as usize - 1] = self.scale;
+    }
+
+    fn check(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        to: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> EncodingResult {
+        debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
+
+        let position = to.unchecked_offset() - 1;
+        Self::check_scale(buffer[position as usize], position)?;
+        Ok(latest_segment)
+    }
+}
+
+impl ExonumJson for Decimal {
+    fn deserialize_field<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+    ) -> Result<(), Box<Error>> {
+        let string = value.as_str().ok_or("Can't cast json as string")?;
+        buffer.write(from, to, string.parse::<Decimal>()?);
+        Ok(())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        Ok(Value::String(self.to_string()))
+    }
+}
+
+impl ExonumJsonDeserialize for Decimal {
+    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
+        let string = value.as_str().ok_or("Can't cast json as string")?;
+        Ok(string.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Decimal;
+    use encoding::{Error, Field};
+
+    #[test]
+    fn test_decimal_display_round_trip() {
+        let cases = [
+            (0, 0, "0"),
+            (0, 3, "0.000"),
+            (7, 0, "7"),
+            (-7, 2, "-0.07"),
+            (1_250, 2, "12.50"),
+            (-1_000, 1, "-100.0"),
+            (i64::max_value(), 18, "9.223372036854775807"),
+            (i64::min_value(), 0, "-9223372036854775808"),
+            (i64::min_value(), 18, "-9.223372036854775808"),
+        ];
+        for &(mantissa, scale, string) in &cases {
+            let decimal = Decimal::new(mantissa, scale);
+            assert_eq!(decimal.to_string(), string);
+            assert_eq!(string.parse::<Decimal>().unwrap(), decimal);
+        }
+    }
+
+    #[test]
+    fn test_decimal_non_canonical_strings() {
+        let cases = [
+            "",
+            "-",
+            "+1",
+            "01",
+            "-0",
+            "-0.00",
+            ".5",
+            "1.",
+            "1..5",
+            "1.2.3",
+            "1e3",
+            " 1",
+            "1.0000000000000000000",
+            "9223372036854775808",
+        ];
+        for string in &cases {
+            assert!(string.parse::<Decimal>().is_err(), "Parsed {:?}", string);
+        }
+    }
+
+    #[test]
+    fn test_decimal_arithmetic() {
+        let a = Decimal::new(1_050, 2);
+        let b = Decimal::new(-25, 1);
+        assert_eq!(a.checked_add(b), Some(Decimal::new(800, 2)));
+        assert_eq!(a.checked_sub(b), Some(Decimal::new(1_300, 2)));
+        assert_eq!(a.checked_mul(b), Some(Decimal::new(-26_250, 3)));
+        assert_eq!(b.checked_neg(), Some(Decimal::new(25, 1)));
+
+        let max = Decimal::new(i64::max_value(), 0);
+        assert_eq!(max.checked_add(Decimal::new(1, 0)), None);
+        assert_eq!(max.checked_add(Decimal::new(0, 1)), None);
+        assert_eq!(max.checked_mul(Decimal::new(2, 0)), None);
+        assert_eq!(Decimal::new(i64::min_value(), 0).checked_neg(), None);
+        assert_eq!(Decimal::new(1, 10).checked_mul(Decimal::new(1, 9)), None);
+        assert_eq!(a.checked_rescale(19), None);
+    }
+
+    #[test]
+    fn test_decimal_ordering() {
+        let mut values: Vec<Decimal> = [
+            "1.51",
+            "-100",
+            "1.50",
+            "9223372036854775807",
+            "0.000000000000000001",
+            "-9223372036854775808",
+            "1.5",
+            "0",
+            "-0.01",
+        ].iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        values.sort();
+        let sorted: Vec<String> = values.iter().map(Decimal::to_string).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "-9223372036854775808",
+                "-100",
+                "-0.01",
+                "0",
+                "0.000000000000000001",
+                "1.5",
+                "1.50",
+                "1.51",
+                "9223372036854775807",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decimal_field() {
+        let decimal = Decimal::new(-1_234, 3);
+        let mut buffer = vec![0; 9];
+        decimal.write(&mut buffer, 0, 9);
+        assert_eq!(buffer, vec![0x2e, 0xfb, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 3]);
+        <Decimal as Field>::check(&buffer, 0.into(), 9.into(), 9.into()).unwrap();
+        assert_eq!(unsafe { <Decimal as Field>::read(&buffer, 0, 9) }, decimal);
+
+        buffer[8] = 19;
+        match <Decimal as Field>::check(&buffer, 0.into(), 9.into(), 9.into()) {
+            Err(Error::IncorrectDecimalScale { position: 8, value: 19 }) => {}
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn test_decimal_struct() {
+        use encoding::serialize::json::{ExonumJson, ExonumJsonDeserialize};
+
+        encoding_struct!(
+            struct Transfer {
+                amount: Decimal,
+                fees: Vec<Decimal>,
+            }
+        );
+
+        let fees = vec![Decimal::new(1, 2), Decimal::new(5, 1)];
+        let transfer = Transfer::new(Decimal::new(10_000, 2), fees.clone());
+        assert_eq!(transfer.amount(), Decimal::new(10_000, 2));
+        assert_eq!(transfer.fees(), fees);
+
+        let json = transfer.serialize_field().unwrap();
+        assert_eq!(json, json!({ "amount": "100.00", "fees": ["0.01", "0.5"] }));
+        assert_eq!(<Transfer as ExonumJsonDeserialize>::deserialize(&json).unwrap(), transfer);
+    }
+}
@@ -37,6 +37,34 @@ pub enum Error {
         /// value that was parsed as bool
         value: u8,
     },
+    /// Unknown variant tag of an `encoding_enum!` type.
+    IncorrectEnumTag {
+        /// Position in buffer where error appears.
+        position: Offset,
+        /// Value that was parsed as tag.
+        value: u8,
+    },
+    /// Value of an `encoding_enum!` variant does not match the segment size.
+    IncorrectEnumValueSize {
+        /// Position in buffer where error appears.
+        position: Offset,
+        /// Size of the value in the segment.
+        actual_size: Offset,
+        /// Size of the value according to its layout.
+        expected_size: Offset,
+    },
+    /// Keys of a map are not sorted in the ascending order or contain duplicates.
+    IncorrectMapKeyOrder {
+        /// Position in buffer where error appears.
+        position: Offset,
+    },
+    /// Unknown layout version of a versioned `encoding_struct!` type.
+    UnsupportedStructVersion {
+        /// Position in buffer where error appears.
+        position: Offset,
+        /// Version found in the buffer.
+        version: u8,
+    },
     /// Unsupported floating point value (Infinity, NaN or signaling NaN).
     UnsupportedFloat {
         /// Position in buffer where error appears.
@@ -44,6 +72,13 @@ pub enum Error {
         /// Value represented as `f64`.
         value: f64,
     },
+    /// Scale of a `Decimal` value exceeds `Decimal::MAX_SCALE`.
+    IncorrectDecimalScale {
+        /// Position in buffer where error appears.
+        position: Offset,
+        /// Value that was parsed as scale.
+        value: u8,
+    },
     /// Segment reference is incorrect
     IncorrectSegmentReference {
         /// position in buffer where error appears.
@@ -110,6 +145,22 @@ pub enum Error {
         /// what error exact was
         error: ::std::str::Utf8Error,
     },
+    /// Error in a field of a structure or message.
+    InField {
+        /// Path to the field, e.g. `TxTransfer.to` or `Wallet.history[3].amount`.
+        path: FieldPath,
+        /// Error in the field.
+        error: Box<Error>,
+    },
+    /// Error in the body of a message.
+    InMessage {
+        /// Identifier of the service of the message.
+        service_id: u16,
+        /// Identifier of the message.
+        message_id: u16,
+        /// Error in the body.
+        error: Box<Error>,
+    },
     /// Overflow in Offsets
     OffsetOverflow,
     /// Basic error support, for custom fields.
@@ -118,9 +169,155 @@ pub enum Error {
     Other(Box<StdError>),
 }
 
+/// Path to a field of a structure or message in which a decoding error occurred.
+///
+/// The path starts with the name of the outermost structure or message and lists names
+/// of the nested fields and indices of the items of collections,
+/// e.g. `Wallet.history[3].amount`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FieldPath {
+    root: &'static str,
+    items: Vec<FieldPathItem>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldPathItem {
+    Field(&'static str),
+    Index(usize),
+}
+
+impl FieldPath {
+    /// Returns the name of the outermost structure or message, or an empty string
+    /// if the path starts with an item of a collection.
+    pub fn root(&self) -> &str {
+        self.root
+    }
+
+    /// Returns the name of the innermost field in the path.
+    pub fn field(&self) -> Option<&str> {
+        for item in self.items.iter().rev() {
+            if let FieldPathItem::Field(name) = *item {
+                return Some(name);
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.root)?;
+        for item in &self.items {
+            match *item {
+                FieldPathItem::Field(name) => write!(f, ".{}", name)?,
+                FieldPathItem::Index(index) => write!(f, "[{}]", index)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Error {
+    /// Adds the field of the structure to the path of the error.
+    #[doc(hidden)]
+    pub fn in_field(self, structure: &'static str, field: &'static str) -> Self {
+        self.with_path_item(structure, FieldPathItem::Field(field))
+    }
+
+    /// Adds the index of the collection item to the path of the error.
+    #[doc(hidden)]
+    pub fn at_index(self, index: usize) -> Self {
+        self.with_path_item("", FieldPathItem::Index(index))
+    }
+
+    /// Adds the identifiers of the message to the error in its body.
+    #[doc(hidden)]
+    pub fn in_message(self, service_id: u16, message_id: u16) -> Self {
+        Error::InMessage {
+            service_id,
+            message_id,
+            error: Box::new(self),
+        }
+    }
+
+    fn with_path_item(self, root: &'static str, item: FieldPathItem) -> Self {
+        match self {
+            Error::InField { mut path, error } => {
+                path.root = root;
+                path.items.insert(0, item);
+                Error::InField { path, error }
+            }
+            error => Error::InField {
+                path: FieldPath {
+                    root,
+                    items: vec![item],
+                },
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Returns the path to the field in which the error occurred, if it is known.
+    pub fn field_path(&self) -> Option<&FieldPath> {
+        match *self {
+            Error::InField { ref path, .. } => Some(path),
+            Error::InMessage { ref error, .. } => error.field_path(),
+            _ => None,
+        }
+    }
+
+    /// Returns the error without the information about the field and the message
+    /// in which it occurred.
+    pub fn root_cause(&self) -> &Error {
+        match *self {
+            Error::InField { ref error, .. } |
+            Error::InMessage { ref error, .. } => error.root_cause(),
+            _ => self,
+        }
+    }
+}
+
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} = {:?}", self.description(), self)
+        match *self {
+            Error::InField { ref path, ref error } => {
+                write!(f, "Error in field `{}`: {}", path, error)
+            }
+            Error::InMessage {
+                service_id,
+                message_id,
+                ref error,
+            } => {
+                match **error {
+                    Error::InField { ref path, ref error } => {
+                        write!(
+                            f,
+                            "Error in field `{}` of message {} of service {}: {}",
+                            path,
+                            message_id,
+                            service_id,
+                            error
+                        )
+                    }
+                    ref error => {
+                        write!(
+                            f,
+                            "Error in message {} of service {}: {}",
+                            message_id,
+                            service_id,
+                            error
+                        )
+                    }
+                }
+            }
+            _ => write!(f, "{} = {:?}", self.description(), self),
+        }
     }
 }
 
@@ -129,7 +326,12 @@ impl StdError for Error {
         match *self {
             Error::UnexpectedlyShortPayload { .. } => "Unexpectedly short payload",
             Error::IncorrectBoolean { .. } => "Incorrect boolean value",
+            Error::IncorrectEnumTag { .. } => "Incorrect enum tag",
+            Error::IncorrectEnumValueSize { .. } => "Incorrect size of enum value",
+            Error::IncorrectMapKeyOrder { .. } => "Incorrect order of map keys",
+            Error::UnsupportedStructVersion { .. } => "Unsupported structure version",
             Error::UnsupportedFloat { .. } => "Unsupported float value",
+            Error::IncorrectDecimalScale { .. } => "Incorrect decimal scale",
             Error::IncorrectSegmentReference { .. } => "Incorrect segment reference",
             Error::IncorrectSegmentSize { .. } => "Incorrect segment size",
             Error::UnexpectedlyShortRawMessage { .. } => "Unexpectedly short RawMessage",
@@ -140,6 +342,8 @@ impl StdError for Error {
             Error::OverlappingSegment { .. } => "Overlapping segments",
             Error::SpaceBetweenSegments { .. } => "Space between segments",
             Error::Utf8 { .. } => "Utf8 error in parsing string",
+            Error::InField { .. } => "Error in field",
+            Error::InMessage { .. } => "Error in message",
             Error::OffsetOverflow => "Offset pointers overflow",
             Error::Basic(ref x) => x.as_ref(),
             Error::Other(_) => "Other error",
@@ -148,10 +352,11 @@ impl StdError for Error {
 
     fn cause(&self) -> Option<&StdError> {
         use std::ops::Deref;
-        if let Error::Other(ref error) = *self {
-            Some(error.deref())
-        } else {
-            None
+        match *self {
+            Error::Other(ref error) => Some(error.deref()),
+            Error::InField { ref error, .. } |
+            Error::InMessage { ref error, .. } => Some(error.deref()),
+            _ => None,
         }
     }
 }
@@ -25,6 +25,7 @@ use std::net::{SocketAddr, SocketAddrV4, Ipv4Addr};
 use crypto::{Hash, PublicKey, Signature};
 use helpers::{Height, Round, ValidatorId};
 use super::{Error, CheckedOffset, Offset, Result};
+use super::schema::TypeSchemas;
 
 /// Trait for all types that could be a field in `encoding`.
 pub trait Field<'a> {
@@ -52,6 +53,11 @@ pub trait Field<'a> {
         to: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> ::std::result::Result<CheckedOffset, Error>;
+
+    /// Adds descriptors of the structures and enumerations used by the type
+    /// to `schemas`. Does nothing for the types that do not refer to such types.
+    #[allow(unused_variables)]
+    fn collect_schemas(schemas: &mut TypeSchemas) {}
 }
 
 /// implement field for all types that has writer and reader functions
@@ -260,6 +266,10 @@ implement_std_field!{u32 LittleEndian::read_u32; LittleEndian::write_u32}
 implement_std_field!{i32 LittleEndian::read_i32; LittleEndian::write_i32}
 implement_std_field!{u64 LittleEndian::read_u64; LittleEndian::write_u64}
 implement_std_field!{i64 LittleEndian::read_i64; LittleEndian::write_i64}
+#[cfg(feature = "int128")]
+implement_std_field!{u128 LittleEndian::read_u128; LittleEndian::write_u128}
+#[cfg(feature = "int128")]
+implement_std_field!{i128 LittleEndian::read_i128; LittleEndian::write_i128}
 
 implement_std_typedef_field!{Height(u64) LittleEndian::read_u64; LittleEndian::write_u64}
 implement_std_typedef_field!{Round(u32) LittleEndian::read_u32; LittleEndian::write_u32}
@@ -269,6 +279,97 @@ implement_pod_as_ref_field! {Signature}
 implement_pod_as_ref_field! {PublicKey}
 implement_pod_as_ref_field! {Hash}
 
+/// Implements `Field` for owned values of POD types. They have the same
+/// binary representation as the references to them.
+macro_rules! implement_pod_owned_field {
+    ($($name:ident)*) => ($(
+        impl<'a> Field<'a> for $name {
+            fn field_size() -> Offset {
+                <&$name as Field>::field_size()
+            }
+
+            unsafe fn read(buffer: &'a [u8], from: Offset, to: Offset) -> $name {
+                *<&$name as Field>::read(buffer, from, to)
+            }
+
+            fn write(&self, buffer: &mut Vec<u8>, from: Offset, to: Offset) {
+                <&$name as Field>::write(&self, buffer, from, to)
+            }
+
+            fn check(
+                buffer: &'a [u8],
+                from: CheckedOffset,
+                to: CheckedOffset,
+                latest_segment: CheckedOffset,
+            ) -> Result {
+                <&$name as Field>::check(buffer, from, to, latest_segment)
+            }
+        }
+    )*)
+}
+
+implement_pod_owned_field!{Signature PublicKey Hash}
+
+/// Implements `Field` for fixed-size arrays of the given lengths.
+/// Arrays are stored in place as a sequence of their items.
+macro_rules! implement_array_field {
+    ($($len:tt)*) => ($(
+        impl<'a, T> Field<'a> for [T; $len]
+        where
+            T: Field<'a> + Copy + Default,
+        {
+            fn field_size() -> Offset {
+                T::field_size() * $len
+            }
+
+            unsafe fn read(buffer: &'a [u8], from: Offset, _: Offset) -> Self {
+                let mut array = [T::default(); $len];
+                let mut start = from;
+                for item in array.iter_mut() {
+                    *item = T::read(buffer, start, start + T::field_size());
+                    start += T::field_size();
+                }
+                array
+            }
+
+            fn write(&self, buffer: &mut Vec<u8>, from: Offset, _: Offset) {
+                let mut start = from;
+                for item in self.iter() {
+                    item.write(buffer, start, start + T::field_size());
+                    start += T::field_size();
+                }
+            }
+
+            fn check(
+                buffer: &'a [u8],
+                from: CheckedOffset,
+                to: CheckedOffset,
+                latest_segment: CheckedOffset,
+            ) -> Result {
+                debug_assert_eq!((to - from)?.unchecked_offset(), Self::field_size());
+                let mut start = from;
+                let mut latest_segment = latest_segment;
+                for _ in 0..$len {
+                    let end = (start + T::field_size())?;
+                    latest_segment = T::check(buffer, start, end, latest_segment)?;
+                    start = end;
+                }
+                Ok(latest_segment)
+            }
+
+            fn collect_schemas(schemas: &mut TypeSchemas) {
+                T::collect_schemas(schemas);
+            }
+        }
+    )*)
+}
+
+implement_array_field!{
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    64
+}
+
 impl<'a> Field<'a> for DateTime<Utc> {
     fn field_size() -> Offset {
         (mem::size_of::<i64>() + mem::size_of::<u32>()) as Offset
@@ -241,10 +241,11 @@ impl ExonumJson for F32 {
     }
 
     fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        // The shortest decimal representation of `f32` is used instead of the one of
+        // the widened `f64`, e.g., `0.1` rather than `0.10000000149011612`.
+        let number: f64 = self.get().to_string().parse()?;
         Ok(Value::Number(
-            Number::from_f64(f64::from(self.get())).ok_or(
-                "Can't cast float as json",
-            )?,
+            Number::from_f64(number).ok_or("Can't cast float as json")?,
         ))
     }
 }
@@ -78,8 +78,11 @@
 //! | `i32`    | 4    | 32-bit signed integer stored in little endian  |
 //! | `u64`    | 8    | Long unsigned integer stored in little endian  |
 //! | `i64`    | 8    | Long signed integer stored in little endian  |
+//! | `u128`   | 16   | 128-bit unsigned integer stored in little endian \[4\] |
+//! | `i128`   | 16   | 128-bit signed integer stored in little endian \[4\] |
 //! | `F32`    | 4    | 32-bit floating point type stored in little endian \[1\]\[2\] |
 //! | `F64`    | 8    | 64-bit floating point type stored in little endian \[1\]\[2\] |
+//! | `Decimal` | 9   | Fixed-point number: `i64` mantissa followed by `u8` scale \[5\] |
 //! | `bool`   | 1    | Stored as a byte, with `0x01` denoting true and `0x00` false \[3\] |
 //!
 //! \[1\]
@@ -92,11 +95,51 @@
 //! \[3\]
 //! Trying to represent other values as `bool` leads to undefined behavior.
 //!
+//! \[4\]
+//! 128-bit integers are hidden behind the `int128` feature gate, which requires
+//! a nightly compiler until the `i128_type` feature is stabilized.
+//!
+//! \[5\]
+//! The scale, i.e., the number of digits after the decimal point, cannot exceed
+//! `Decimal::MAX_SCALE`.
+//!
 //! ## Segment fields
 //!
 //! All segment types take 8 bytes in the header: 4 for position in the buffer,
 //! and 4 for the segment field size.
 //!
+//! ## Optional fields and enumerations
+//!
+//! `Option<T>` is stored as a segment containing zero or one `T` item and is
+//! represented as `null` or the value itself in JSON.
+//!
+//! Tagged enumerations are declared with the [`encoding_enum!`] macro. They are stored
+//! as a segment with a one-byte tag of the variant followed by the value of the variant.
+//!
+//! [`encoding_enum!`]: ../macro.encoding_enum.html
+//!
+//! ## Maps and arrays
+//!
+//! `BTreeMap<K, V>` is stored as a segment of key-value pairs sorted by keys;
+//! unsorted or duplicate keys are rejected when checking the buffer. In JSON a map is
+//! represented as an array of `[key, value]` pairs.
+//!
+//! Fixed-size arrays `[T; N]` of `Copy` types are stored in place in the header
+//! and take `N` times the size of `T`. Arrays with up to 32 items and with 64 items
+//! are supported.
+//!
+//! ## Versioned structures
+//!
+//! Structures and messages declared with an `optional` block in [`encoding_struct!`]
+//! or [`messages!`] keep the layout of their required fields. Optional fields follow
+//! the data of the required fields in a trailing block that starts with a one-byte
+//! layout version; values without optional fields omit the block entirely. Hence,
+//! optional fields may be appended in later versions of the structure without
+//! breaking values encoded with the previous layouts.
+//!
+//! [`encoding_struct!`]: ../macro.encoding_struct.html
+//! [`messages!`]: ../macro.messages.html
+//!
 //! ## Custom fields
 //!
 //! These types can be implemented as per developer's design,
@@ -104,18 +147,37 @@
 //! write in the header using the [`field_size()`] function.
 //!
 //! [`field_size()`]: ./trait.Field.html#tymethod.field_size
+//!
+//! # JSON representation
+//!
+//! All field types have a canonical JSON representation, in which 64-bit integers
+//! are decimal strings and binary data is hex-encoded. A value converted from
+//! the canonical JSON to the binary form and back yields the same JSON text.
+//! See [`serialize::json`] for details.
+//!
+//! [`serialize::json`]: ./serialize/json/index.html
+//!
+//! # Protobuf
+//!
+//! With the `protobuf` feature, services may encode the bodies of their transactions
+//! and the values in the storage with protobuf instead. See the `encoding::protobuf`
+//! module documentation for details.
 
 use std::convert::From;
 use std::ops::{Add, Sub, Mul, Div};
 
 pub use self::fields::Field;
 pub use self::segments::SegmentField;
-pub use self::error::Error;
+pub use self::error::{Error, FieldPath};
+pub use self::decimal::{Decimal, ParseDecimalError};
 #[cfg(feature = "float_serialize")]
 pub use self::float::{F32, F64};
 
 #[macro_use]
 pub mod serialize;
+pub mod schema;
+#[cfg(feature = "protobuf")]
+pub mod protobuf;
 
 mod error;
 #[macro_use]
@@ -123,6 +185,7 @@ mod fields;
 mod segments;
 #[macro_use]
 mod spec;
+mod decimal;
 #[cfg(feature = "float_serialize")]
 mod float;
 
@@ -0,0 +1,641 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Protobuf encoding of transactions and storage values.
+//!
+//! The module is available with the `protobuf` feature. It allows services to use
+//! messages generated by [`rust-protobuf`] instead of the Exonum binary encoding,
+//! so that clients can be built with the standard protobuf tooling.
+//!
+//! The encoding is selected per service: a service declares its transactions either with
+//! [`transactions!`] or with [`protobuf_transactions!`], and both produce
+//! a [`TransactionSet`] to be used in `Service::tx_from_raw`. Protobuf transactions keep
+//! the Exonum message header and signature; only the body of the message between
+//! the header and the signature is encoded with protobuf. Transactions are dispatched
+//! by the message id in the header, just like ordinary ones.
+//!
+//! Values in the storage are wrapped into [`ProtobufValue`].
+//!
+//! # Deterministic serialization
+//!
+//! Protobuf allows several binary representations of the same message, while hashes
+//! and signatures require a single one. The canonical representation is the one
+//! produced by [`to_bytes`]: known fields in the order of their numbers, scalar fields
+//! of `proto3` messages with default values omitted, and varints in the shortest form.
+//! [`from_bytes`] rejects any other representation, as well as unknown fields
+//! in the message and in the nested messages.
+//!
+//! Map fields are not supported, because their entries are not serialized in
+//! a deterministic order: [`to_bytes`] and [`from_bytes`] fail for messages with
+//! map fields, including the nested ones. Use repeated fields with key-value messages
+//! instead.
+//!
+//! # JSON representation
+//!
+//! `rust-protobuf` does not support JSON, so in JSON the body of a protobuf transaction
+//! is a hex string with its protobuf representation, and other fields are the same
+//! as for ordinary transactions.
+//!
+//! # Schemas
+//!
+//! Descriptors of protobuf transactions returned by `TransactionSet::message_schemas`
+//! contain the full name of the protobuf message in the body instead of the fields.
+//!
+//! # Examples
+//!
+//! ```
+//! #[macro_use]
+//! extern crate exonum;
+//! extern crate protobuf;
+//!
+//! use exonum::blockchain::{ExecutionResult, Transaction, TransactionSet};
+//! use exonum::crypto;
+//! use exonum::messages::Message;
+//! use exonum::storage::Fork;
+//! use protobuf::well_known_types::StringValue;
+//!
+//! protobuf_transactions! {
+//!     pub NoteTransactions {
+//!         const SERVICE_ID = 5;
+//!
+//!         /// Publishes a note.
+//!         struct TxNote(StringValue) = 0;
+//!     }
+//! }
+//!
+//! impl Transaction for TxNote {
+//!     fn verify(&self) -> bool {
+//!         !self.payload().get_value().is_empty()
+//!     }
+//!
+//!     fn execute(&self, _: &mut Fork) -> ExecutionResult {
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # fn main() {
+//! let (_, secret_key) = crypto::gen_keypair();
+//! let mut note = StringValue::new();
+//! note.set_value("Hello".to_owned());
+//! let tx = TxNote::new(note, &secret_key);
+//!
+//! let parsed = NoteTransactions::tx_from_raw(tx.raw().clone()).unwrap();
+//! let tx: Box<Transaction> = parsed.into();
+//! assert!(tx.verify());
+//! # }
+//! ```
+//!
+//! [`rust-protobuf`]: https://github.com/stepancheg/rust-protobuf
+//! [`transactions!`]: ../../macro.transactions.html
+//! [`protobuf_transactions!`]: ../../macro.protobuf_transactions.html
+//! [`TransactionSet`]: ../../blockchain/trait.TransactionSet.html
+//! [`ProtobufValue`]: struct.ProtobufValue.html
+//! [`to_bytes`]: fn.to_bytes.html
+//! [`from_bytes`]: fn.from_bytes.html
+
+use protobuf::{self, Message, MessageStatic};
+use protobuf::descriptor::FieldDescriptorProto_Type;
+
+use std::borrow::Cow;
+use std::error::Error as StdError;
+
+use crypto::{self, CryptoHash, Hash, Signature, SIGNATURE_LENGTH};
+use messages::{MessageWriter, RawMessage, HEADER_LENGTH, PROTOCOL_MAJOR_VERSION};
+use storage::StorageValue;
+use super::{Error, Offset};
+use super::schema::MessageSchema;
+use super::serialize::{encode_hex, FromHex};
+use super::serialize::json::ExonumJson;
+use super::serialize::json::reexport::{from_value, Map, Value};
+
+/// Serializes the protobuf message into its canonical binary representation.
+///
+/// Fails if required fields of a `proto2` message are not set, or if the message
+/// or the nested messages have unknown or map fields.
+pub fn to_bytes<M: Message>(message: &M) -> Result<Vec<u8>, Error> {
+    check_fields(message)?;
+    message.write_to_bytes().map_err(|e| Error::Other(Box::new(e)))
+}
+
+/// Parses the protobuf message from its canonical binary representation.
+///
+/// Fails if the bytes are not a valid message, contain unknown fields of the message
+/// or the nested messages, or differ from the canonical representation of the parsed
+/// message.
+pub fn from_bytes<M: MessageStatic>(bytes: &[u8]) -> Result<M, Error> {
+    let message: M = protobuf::parse_from_bytes(bytes).map_err(|e| Error::Other(Box::new(e)))?;
+    if to_bytes(&message)? != bytes {
+        return Err(Error::Basic("Non-canonical protobuf encoding".into()));
+    }
+    Ok(message)
+}
+
+// Checks that the message and its nested messages have neither unknown fields,
+// which are not part of the canonical representation, nor map fields.
+fn check_fields(message: &Message) -> Result<(), Error> {
+    let descriptor = message.descriptor();
+    if message.get_unknown_fields().iter().next().is_some() {
+        return Err(Error::Basic(
+            format!("Unknown fields in protobuf message {}", descriptor.full_name()).into(),
+        ));
+    }
+
+    for field in descriptor.fields() {
+        if field.proto().get_field_type() != FieldDescriptorProto_Type::TYPE_MESSAGE {
+            continue;
+        }
+        if field.is_repeated() {
+            // Map fields are repeated fields of the entry messages nested in the message.
+            let type_name = field.proto().get_type_name();
+            let is_map = descriptor.get_proto().get_nested_type().iter().any(|nested| {
+                nested.get_options().get_map_entry() &&
+                    type_name.ends_with(&format!(".{}.{}", descriptor.name(), nested.get_name()))
+            });
+            if is_map {
+                return Err(Error::Basic(
+                    format!(
+                        "Map field {} in protobuf message {} is not supported",
+                        field.name(),
+                        descriptor.full_name()
+                    ).into(),
+                ));
+            }
+            for index in 0..field.len_field(message) {
+                check_fields(field.get_rep_message_item(message, index))?;
+            }
+        } else if field.has_field(message) {
+            check_fields(field.get_message(message))?;
+        }
+    }
+    Ok(())
+}
+
+/// Adapter for storing protobuf messages in the storage indices.
+///
+/// The value is stored in the canonical representation, which is also used to compute
+/// its hash.
+///
+/// # Panics
+///
+/// `into_bytes` and `hash` panic if required fields of a `proto2` message are not set.
+/// `from_bytes` panics if the stored bytes are not a canonical representation
+/// of the message.
+///
+/// # Examples
+///
+/// ```
+/// extern crate exonum;
+/// extern crate protobuf;
+///
+/// use exonum::encoding::protobuf::ProtobufValue;
+/// use exonum::storage::{Database, MapIndex, MemoryDB};
+/// use protobuf::well_known_types::Timestamp;
+///
+/// # fn main() {
+/// let db = MemoryDB::new();
+/// let mut fork = db.fork();
+/// let mut index = MapIndex::new("timestamps", &mut fork);
+///
+/// let mut timestamp = Timestamp::new();
+/// timestamp.set_seconds(1_500_000_000);
+/// index.put(&1_u64, ProtobufValue(timestamp.clone()));
+/// assert_eq!(index.get(&1_u64), Some(ProtobufValue(timestamp)));
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProtobufValue<T>(pub T);
+
+impl<T> ProtobufValue<T> {
+    /// Returns the wrapped message.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Message> CryptoHash for ProtobufValue<T> {
+    fn hash(&self) -> Hash {
+        crypto::hash(&to_bytes(&self.0).expect("Unable to serialize protobuf message"))
+    }
+}
+
+impl<T: MessageStatic> StorageValue for ProtobufValue<T> {
+    fn into_bytes(self) -> Vec<u8> {
+        to_bytes(&self.0).expect("Unable to serialize protobuf message")
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        ProtobufValue(from_bytes(&value).expect("Malformed protobuf message in the storage"))
+    }
+}
+
+/// Creates a writer of the message with the given payload.
+#[doc(hidden)]
+pub fn message_writer<M: Message>(service_id: u16, message_id: u16, payload: &M) -> MessageWriter {
+    let bytes = to_bytes(payload).expect("Unable to serialize protobuf message");
+    let mut writer =
+        MessageWriter::new(PROTOCOL_MAJOR_VERSION, service_id, message_id, bytes.len());
+    writer.write_bytes(&bytes, 0);
+    writer
+}
+
+/// Returns the descriptor of the message with the given protobuf payload.
+#[doc(hidden)]
+pub fn message_schema<M: MessageStatic>(
+    name: &str,
+    service_id: u16,
+    message_id: u16,
+) -> MessageSchema {
+    MessageSchema {
+        name: name.to_owned(),
+        service_id,
+        message_id,
+        body_size: 0,
+        version: None,
+        fields: Vec::new(),
+        optional_fields: Vec::new(),
+        structs: Vec::new(),
+        enums: Vec::new(),
+        protobuf_message: Some(M::new().descriptor().full_name().to_owned()),
+    }
+}
+
+/// Checks the header of the raw message and parses its protobuf payload.
+#[doc(hidden)]
+pub fn payload_from_raw<M: MessageStatic>(
+    raw: &RawMessage,
+    service_id: u16,
+    message_id: u16,
+) -> Result<M, Error> {
+    let min_message_size = HEADER_LENGTH + SIGNATURE_LENGTH;
+    if raw.len() < min_message_size {
+        return Err(Error::UnexpectedlyShortPayload {
+            actual_size: raw.len() as Offset,
+            minimum_size: min_message_size as Offset,
+        });
+    }
+    if raw.version() != PROTOCOL_MAJOR_VERSION {
+        return Err(Error::UnsupportedProtocolVersion { version: PROTOCOL_MAJOR_VERSION });
+    }
+    if raw.message_type() != message_id {
+        return Err(Error::IncorrectMessageType { message_type: message_id });
+    }
+    if raw.service_id() != service_id {
+        return Err(Error::IncorrectServiceId { service_id });
+    }
+    from_bytes(&raw.body()[HEADER_LENGTH..]).map_err(|e| e.in_message(service_id, message_id))
+}
+
+/// Serializes the protobuf message into JSON with the hex-encoded body.
+#[doc(hidden)]
+pub fn message_to_json(raw: &RawMessage) -> Result<Value, Box<StdError + Send + Sync>> {
+    let mut structure = Map::new();
+    structure.insert(
+        "body".to_string(),
+        Value::String(encode_hex(&raw.body()[HEADER_LENGTH..])),
+    );
+    structure.insert("signature".to_string(), raw.signature().serialize_field()?);
+    structure.insert("message_id".to_string(), raw.message_type().serialize_field()?);
+    structure.insert("service_id".to_string(), raw.service_id().serialize_field()?);
+    structure.insert("protocol_version".to_string(), raw.version().serialize_field()?);
+    Ok(Value::Object(structure))
+}
+
+/// Deserializes the protobuf message from JSON with the hex-encoded body.
+#[doc(hidden)]
+pub fn message_from_json<M: MessageStatic>(
+    value: &Value,
+    service_id: u16,
+    message_id: u16,
+) -> Result<(RawMessage, M), Box<StdError>> {
+    let obj = value.as_object().ok_or("Can't cast json as object.")?;
+    let body = obj.get("body")
+        .and_then(Value::as_str)
+        .ok_or("Can't get body from json.")?;
+    let signature: Signature = from_value(
+        obj.get("signature")
+            .ok_or("Can't get signature from json")?
+            .clone(),
+    )?;
+    let json_message_id: u16 = from_value(
+        obj.get("message_id")
+            .ok_or("Can't get message_id from json")?
+            .clone(),
+    )?;
+    let json_service_id: u16 = from_value(
+        obj.get("service_id")
+            .ok_or("Can't get service_id from json")?
+            .clone(),
+    )?;
+    let protocol_version: u8 = from_value(
+        obj.get("protocol_version")
+            .ok_or("Can't get protocol_version from json")?
+            .clone(),
+    )?;
+
+    if json_service_id != service_id {
+        return Err("service_id didn't equal real service_id.".into());
+    }
+    if json_message_id != message_id {
+        return Err("message_id didn't equal real message_id.".into());
+    }
+    if protocol_version != PROTOCOL_MAJOR_VERSION {
+        return Err("protocol_version didn't equal supported protocol version.".into());
+    }
+
+    let bytes = Vec::<u8>::from_hex(body)?;
+    let payload = from_bytes(&bytes)?;
+    let mut writer = MessageWriter::new(protocol_version, service_id, message_id, bytes.len());
+    writer.write_bytes(&bytes, 0);
+    Ok((RawMessage::new(writer.append_signature(&signature)), payload))
+}
+
+/// `protobuf_transactions!` declares a set of transactions of a service with bodies
+/// encoded with protobuf.
+///
+/// Each transaction is declared as a wrapper of a protobuf message generated by
+/// `rust-protobuf`, with an explicit message id. The macro generates the wrappers and
+/// a helper enum implementing [`TransactionSet`], just like [`transactions!`].
+/// You must implement `Transaction` trait for each of the transactions yourself.
+///
+/// For each transaction the macro defines `new` and `new_with_signature` constructors
+/// taking the protobuf message, a `payload` method returning it and a `schema` method
+/// returning the descriptor of the transaction. Each transaction also implements
+/// [`Message`], [`ServiceMessage`], [`ExonumJson`] and [`StorageValue`] traits.
+///
+/// The constructors panic if the protobuf message cannot be serialized canonically,
+/// e.g., if it has map fields.
+///
+/// The macro is available with the `protobuf` feature. See the [`encoding::protobuf`]
+/// module for an example and the details of the encoding.
+///
+/// [`TransactionSet`]: ./blockchain/trait.TransactionSet.html
+/// [`transactions!`]: macro.transactions.html
+/// [`Message`]: ./messages/trait.Message.html
+/// [`ServiceMessage`]: ./messages/trait.ServiceMessage.html
+/// [`ExonumJson`]: ./encoding/serialize/json/trait.ExonumJson.html
+/// [`StorageValue`]: ./storage/trait.StorageValue.html
+/// [`encoding::protobuf`]: ./encoding/protobuf/index.html
+#[macro_export]
+macro_rules! protobuf_transactions {
+    // Variant with the private enum.
+    {
+        $(#[$tx_set_attr:meta])*
+        $transaction_set:ident {
+            const SERVICE_ID = $service_id:expr;
+
+            $(
+                $(#[$tx_attr:meta])*
+                struct $name:ident($payload:ty) = $message_id:expr;
+            )*
+        }
+    } => {
+        protobuf_transactions!(
+            @define (), $(#[$tx_set_attr])* $transaction_set, $service_id,
+            $( $(#[$tx_attr])* $name($payload) = $message_id; )*
+        );
+    };
+    // Variant with the public enum.
+    {
+        $(#[$tx_set_attr:meta])*
+        pub $transaction_set:ident {
+            const SERVICE_ID = $service_id:expr;
+
+            $(
+                $(#[$tx_attr:meta])*
+                struct $name:ident($payload:ty) = $message_id:expr;
+            )*
+        }
+    } => {
+        protobuf_transactions!(
+            @define (pub), $(#[$tx_set_attr])* $transaction_set, $service_id,
+            $( $(#[$tx_attr])* $name($payload) = $message_id; )*
+        );
+    };
+    // Implementation details
+    (
+        @define ($($vis:tt)*), $(#[$tx_set_attr:meta])* $transaction_set:ident, $service_id:expr,
+        $( $(#[$tx_attr:meta])* $name:ident($payload:ty) = $message_id:expr; )*
+    ) => {
+        $(
+            __ex_protobuf_message!($service_id, $message_id, $(#[$tx_attr])* $name($payload));
+        )*
+
+        #[derive(Clone, Debug)]
+        $(#[$tx_set_attr])*
+        $($vis)* enum $transaction_set {
+            $(
+                #[allow(missing_docs)]
+                $name($name),
+            )*
+        }
+
+        transactions!(
+            @implement_set $transaction_set,
+            vec![$($name::schema(),)*],
+            $($name)*
+        );
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_protobuf_message {
+    ($service_id:expr, $message_id:expr, $(#[$attr:meta])* $name:ident($payload:ty)) => {
+        #[derive(Clone, PartialEq)]
+        $(#[$attr])*
+        pub struct $name {
+            raw: $crate::messages::RawMessage,
+            payload: $payload,
+        }
+
+        impl $crate::messages::ServiceMessage for $name {
+            const SERVICE_ID: u16 = $service_id;
+            const MESSAGE_ID: u16 = $message_id;
+        }
+
+        impl $crate::messages::Message for $name {
+            fn from_raw(raw: $crate::messages::RawMessage)
+            -> ::std::result::Result<$name, $crate::encoding::Error> {
+                let payload = $crate::encoding::protobuf::payload_from_raw(
+                    &raw,
+                    <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                )?;
+                Ok($name { raw, payload })
+            }
+
+            fn raw(&self) -> &$crate::messages::RawMessage {
+                &self.raw
+            }
+        }
+
+        impl $name {
+            /// Creates message and signs it.
+            pub fn new(payload: $payload, secret_key: &$crate::crypto::SecretKey) -> $name {
+                let writer = $crate::encoding::protobuf::message_writer(
+                    <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                    &payload,
+                );
+                let raw = $crate::messages::RawMessage::new(writer.sign(secret_key));
+                $name { raw, payload }
+            }
+
+            /// Creates message and appends existing signature.
+            #[allow(dead_code)]
+            pub fn new_with_signature(
+                payload: $payload,
+                signature: &$crate::crypto::Signature,
+            ) -> $name {
+                let writer = $crate::encoding::protobuf::message_writer(
+                    <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                    &payload,
+                );
+                let raw = $crate::messages::RawMessage::new(writer.append_signature(signature));
+                $name { raw, payload }
+            }
+
+            /// Returns the protobuf message in the body of the message.
+            #[allow(dead_code)]
+            pub fn payload(&self) -> &$payload {
+                &self.payload
+            }
+
+            /// Returns the hex representation of the binary data.
+            /// Lower case letters are used (e.g. f9b4ca).
+            #[allow(dead_code)]
+            pub fn to_hex(&self) -> String {
+                $crate::encoding::serialize::encode_hex(self.as_ref())
+            }
+
+            /// Returns the descriptor of the message.
+            #[allow(dead_code)]
+            pub fn schema() -> $crate::encoding::schema::MessageSchema {
+                $crate::encoding::protobuf::message_schema::<$payload>(
+                    stringify!($name),
+                    <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                )
+            }
+        }
+
+        impl AsRef<$crate::messages::RawMessage> for $name {
+            fn as_ref(&self) -> &$crate::messages::RawMessage {
+                $crate::messages::Message::raw(self)
+            }
+        }
+
+        impl $crate::encoding::serialize::FromHex for $name {
+            type Error = $crate::encoding::Error;
+
+            fn from_hex<T: AsRef<[u8]>>(hex: T) -> Result<Self, Self::Error> {
+                let vec = <Vec<u8> as $crate::encoding::serialize::FromHex>::from_hex(hex)
+                    .map_err(|e| $crate::encoding::Error::Other(Box::new(e)))?;
+                if vec.len() < $crate::messages::HEADER_LENGTH {
+                    return Err($crate::encoding::Error::Basic("Hex is too short.".into()));
+                }
+                let buf = $crate::messages::MessageBuffer::from_vec(vec);
+                let raw = $crate::messages::RawMessage::new(buf);
+                $crate::messages::Message::from_raw(raw)
+            }
+        }
+
+        impl $crate::storage::StorageValue for $name {
+            fn into_bytes(self) -> Vec<u8> {
+                self.raw.as_ref().as_ref().to_vec()
+            }
+
+            fn from_bytes(value: ::std::borrow::Cow<[u8]>) -> Self {
+                let raw = $crate::messages::RawMessage::new(
+                    $crate::messages::MessageBuffer::from_vec(value.into_owned()));
+                $crate::messages::Message::from_raw(raw)
+                    .expect("Malformed protobuf message in the storage")
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter)
+                -> Result<(), ::std::fmt::Error> {
+                fmt.debug_struct(stringify!($name))
+                    .field("payload", &self.payload)
+                    .finish()
+            }
+        }
+
+        impl $crate::encoding::serialize::json::ExonumJson for $name {
+            fn deserialize_field<B> (
+                value: &$crate::encoding::serialize::json::reexport::Value,
+                buffer: & mut B,
+                from: $crate::encoding::Offset,
+                to: $crate::encoding::Offset,
+            ) -> ::std::result::Result<(), Box<::std::error::Error>>
+            where B: $crate::encoding::serialize::WriteBufferWrapper
+            {
+                use $crate::encoding::serialize::json::ExonumJsonDeserialize;
+                let structure = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+                buffer.write(from, to, structure);
+                Ok(())
+            }
+
+            fn serialize_field(&self)
+                -> ::std::result::Result<$crate::encoding::serialize::json::reexport::Value,
+                            Box<::std::error::Error + Send + Sync>>
+            {
+                $crate::encoding::protobuf::message_to_json(&self.raw)
+            }
+        }
+
+        impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
+            fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
+                -> ::std::result::Result<Self, Box<::std::error::Error>>
+            {
+                let (raw, payload) = $crate::encoding::protobuf::message_from_json(
+                    value,
+                    <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                )?;
+                Ok($name { raw, payload })
+            }
+        }
+
+        impl<'de> $crate::encoding::serialize::reexport::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: $crate::encoding::serialize::reexport::Deserializer<'de>,
+            {
+                use $crate::encoding::serialize::json::reexport::Value;
+                use $crate::encoding::serialize::reexport::{DeError, Deserialize};
+                let value = <Value as Deserialize>::deserialize(deserializer)?;
+                <Self as $crate::encoding::serialize::json::ExonumJsonDeserialize>::deserialize(
+                    &value).map_err(|e| D::Error::custom(
+                            format!("Can't deserialize a value: {}", e)))
+            }
+        }
+
+        impl $crate::encoding::serialize::reexport::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::encoding::serialize::reexport::Serializer,
+            {
+                use $crate::encoding::serialize::reexport::SerError;
+                use $crate::encoding::serialize::json::ExonumJson;
+                self.serialize_field()
+                    .map_err(|_| S::Error::custom(
+                                concat!("Can not serialize structure: ", stringify!($name))))?
+                    .serialize(serializer)
+            }
+        }
+    };
+}
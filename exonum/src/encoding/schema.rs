@@ -0,0 +1,190 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Descriptors of the binary layout of the types declared with the `encoding_struct!`,
+//! `encoding_enum!`, `messages!` and `transactions!` macros.
+//!
+//! The descriptors are returned by the `schema()` method generated by the macros and
+//! allow tooling written without access to the Rust types, e.g., blockchain explorers,
+//! to decode and display stored values and messages.
+
+use super::Offset;
+
+/// Description of a field in the header of a structure or a message.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldSchema {
+    /// Name of the field.
+    pub name: String,
+    /// Rust type of the field as written in the declaration, e.g. `&PublicKey`
+    /// or `Vec<u8>`.
+    #[serde(rename = "type")]
+    pub type_name: String,
+    /// Start of the field in the header.
+    pub from: Offset,
+    /// End of the field in the header.
+    pub to: Offset,
+}
+
+impl FieldSchema {
+    /// Creates a descriptor of the field. Whitespace in the type name is removed,
+    /// so the name does not depend on the formatting of the declaration.
+    pub fn new(name: &str, type_name: &str, from: Offset, to: Offset) -> Self {
+        FieldSchema {
+            name: name.to_owned(),
+            type_name: type_name.split_whitespace().collect(),
+            from,
+            to,
+        }
+    }
+}
+
+/// Layout of a structure declared with the `encoding_struct!` macro.
+///
+/// Field offsets are relative to the start of the structure buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructSchema {
+    /// Name of the structure.
+    pub name: String,
+    /// Size of the header of the required fields of the structure.
+    pub header_size: Offset,
+    /// Latest layout version for versioned structures or `None` otherwise.
+    pub version: Option<u8>,
+    /// Required fields of the structure.
+    pub fields: Vec<FieldSchema>,
+    /// Optional fields of versioned structures in the order of their addition;
+    /// the `n`-th field is present in the layouts starting from the version `n + 2`.
+    ///
+    /// Optional fields are stored after the data of the required fields, in a block
+    /// starting with the one-byte layout version. Their offsets are relative
+    /// to the end of the version byte.
+    pub optional_fields: Vec<FieldSchema>,
+}
+
+/// Layout of a message declared with the `messages!` or `transactions!` macros.
+///
+/// Field offsets are relative to the start of the message body, that is, they do not
+/// include the message header of [`HEADER_LENGTH`] bytes. Note that positions of segments
+/// stored in the header are relative to the start of the message, as usual.
+///
+/// [`HEADER_LENGTH`]: ../../messages/constant.HEADER_LENGTH.html
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageSchema {
+    /// Name of the message.
+    pub name: String,
+    /// Identifier of the service the message belongs to.
+    pub service_id: u16,
+    /// Identifier of the message within the service.
+    pub message_id: u16,
+    /// Size of the header of the required fields of the message body.
+    pub body_size: Offset,
+    /// Latest layout version for versioned messages or `None` otherwise.
+    pub version: Option<u8>,
+    /// Required fields of the message body.
+    pub fields: Vec<FieldSchema>,
+    /// Optional fields of versioned messages laid out as in [`StructSchema`].
+    ///
+    /// [`StructSchema`]: struct.StructSchema.html#structfield.optional_fields
+    pub optional_fields: Vec<FieldSchema>,
+    /// Structures used by the fields of the message, directly or through other
+    /// structures and enumerations.
+    pub structs: Vec<StructSchema>,
+    /// Enumerations used by the fields of the message, directly or through other
+    /// structures and enumerations.
+    pub enums: Vec<EnumSchema>,
+    /// Full name of the protobuf message in the body for messages declared with
+    /// the `protobuf_transactions!` macro, or `None` for messages with the Exonum
+    /// binary layout. The body of protobuf messages has no fields in the header,
+    /// so their `body_size` is zero and `fields` are empty.
+    pub protobuf_message: Option<String>,
+}
+
+/// Description of a variant of an enumeration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VariantSchema {
+    /// Name of the variant.
+    pub name: String,
+    /// One-byte tag identifying the variant in the binary representation.
+    pub tag: u8,
+    /// Rust type of the variant payload as written in the declaration, or `None`
+    /// for variants without a payload.
+    #[serde(rename = "type")]
+    pub type_name: Option<String>,
+}
+
+impl VariantSchema {
+    /// Creates a descriptor of the variant. Whitespace in the type name is removed,
+    /// as in [`FieldSchema::new`].
+    ///
+    /// [`FieldSchema::new`]: struct.FieldSchema.html#method.new
+    pub fn new(name: &str, tag: u8, type_name: Option<&str>) -> Self {
+        VariantSchema {
+            name: name.to_owned(),
+            tag,
+            type_name: type_name.map(|name| name.split_whitespace().collect()),
+        }
+    }
+}
+
+/// Layout of an enumeration declared with the `encoding_enum!` macro.
+///
+/// An enumeration is stored as a segment starting with the tag of the variant,
+/// which is followed by the payload of the variant, if any.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnumSchema {
+    /// Name of the enumeration.
+    pub name: String,
+    /// Variants of the enumeration in the declaration order.
+    pub variants: Vec<VariantSchema>,
+}
+
+/// Descriptors of the structures and enumerations referenced by a type, gathered
+/// with [`Field::collect_schemas`].
+///
+/// Each type is recorded once, in the order of the first reference.
+///
+/// [`Field::collect_schemas`]: ../trait.Field.html#method.collect_schemas
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TypeSchemas {
+    /// Referenced structures.
+    pub structs: Vec<StructSchema>,
+    /// Referenced enumerations.
+    pub enums: Vec<EnumSchema>,
+}
+
+impl TypeSchemas {
+    /// Creates an empty collection.
+    pub fn new() -> Self {
+        TypeSchemas::default()
+    }
+
+    /// Adds the descriptor of a structure. Returns `false` if a structure with the same
+    /// name has already been added, in which case the descriptor is discarded.
+    pub fn add_struct(&mut self, schema: StructSchema) -> bool {
+        if self.structs.iter().any(|s| s.name == schema.name) {
+            return false;
+        }
+        self.structs.push(schema);
+        true
+    }
+
+    /// Adds the descriptor of an enumeration. Returns `false` if an enumeration with
+    /// the same name has already been added, in which case the descriptor is discarded.
+    pub fn add_enum(&mut self, schema: EnumSchema) -> bool {
+        if self.enums.iter().any(|e| e.name == schema.name) {
+            return false;
+        }
+        self.enums.push(schema);
+        true
+    }
+}
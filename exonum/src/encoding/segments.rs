@@ -17,9 +17,12 @@
 use byteorder::{ByteOrder, LittleEndian};
 use bit_vec::BitVec;
 
+use std::collections::BTreeMap;
+
 use messages::{RawMessage, HEADER_LENGTH, MessageBuffer};
 use crypto::Hash;
 use super::{Result, Error, Field, Offset, CheckedOffset};
+use super::schema::TypeSchemas;
 
 /// Trait for fields, that has unknown `compile-time` size.
 /// Usually important for arrays,
@@ -42,6 +45,11 @@ pub trait SegmentField<'a>: Sized {
         count: CheckedOffset,
         latest_segment: CheckedOffset,
     ) -> Result;
+
+    /// Adds descriptors of the structures and enumerations used by the items
+    /// of the collection to `schemas`.
+    #[allow(unused_variables)]
+    fn collect_schemas(schemas: &mut TypeSchemas) {}
 }
 
 impl<'a, T> Field<'a> for T
@@ -113,6 +121,10 @@ where
 
         Self::check_data(buffer, segment_start, count, latest_segment)
     }
+
+    fn collect_schemas(schemas: &mut TypeSchemas) {
+        <T as SegmentField<'a>>::collect_schemas(schemas);
+    }
 }
 
 impl<'a> SegmentField<'a> for &'a str {
@@ -153,6 +165,34 @@ impl<'a> SegmentField<'a> for &'a str {
     }
 }
 
+/// Owned strings have the same binary representation as `&str`.
+impl<'a> SegmentField<'a> for String {
+    fn item_size() -> Offset {
+        <&str as SegmentField>::item_size()
+    }
+
+    fn count(&self) -> Offset {
+        self.as_str().count()
+    }
+
+    unsafe fn from_buffer(buffer: &'a [u8], from: Offset, count: Offset) -> Self {
+        <&str as SegmentField>::from_buffer(buffer, from, count).to_owned()
+    }
+
+    fn extend_buffer(&self, buffer: &mut Vec<u8>) {
+        self.as_str().extend_buffer(buffer)
+    }
+
+    fn check_data(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        count: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        <&str as SegmentField>::check_data(buffer, from, count, latest_segment)
+    }
+}
+
 impl<'a> SegmentField<'a> for RawMessage {
     fn item_size() -> Offset {
         1
@@ -247,12 +287,146 @@ where
         let mut start = from;
         let mut latest_segment = latest_segment;
 
-        for _ in 0..count.unchecked_offset() {
-            latest_segment = T::check(buffer, start, (start + Self::item_size())?, latest_segment)?;
+        for index in 0..count.unchecked_offset() {
+            latest_segment = T::check(buffer, start, (start + Self::item_size())?, latest_segment)
+                .map_err(|e| e.at_index(index as usize))?;
             start = (start + Self::item_size())?;
         }
         Ok(latest_segment)
     }
+
+    fn collect_schemas(schemas: &mut TypeSchemas) {
+        T::collect_schemas(schemas);
+    }
+}
+
+/// `Option<T>` is stored as a segment containing zero or one `T` item.
+impl<'a, T> SegmentField<'a> for Option<T>
+where
+    T: Field<'a>,
+{
+    fn item_size() -> Offset {
+        T::field_size()
+    }
+
+    fn count(&self) -> Offset {
+        if self.is_some() { 1 } else { 0 }
+    }
+
+    unsafe fn from_buffer(buffer: &'a [u8], from: Offset, count: Offset) -> Self {
+        if count == 0 {
+            None
+        } else {
+            Some(T::read(buffer, from, from + Self::item_size()))
+        }
+    }
+
+    fn extend_buffer(&self, mut buffer: &mut Vec<u8>) {
+        if let Some(ref value) = *self {
+            let start = buffer.len() as Offset;
+            buffer.resize((start + Self::item_size()) as usize, 0);
+            value.write(&mut buffer, start, start + Self::item_size());
+        }
+    }
+
+    fn check_data(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        count: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        match count.unchecked_offset() {
+            0 => Ok(latest_segment),
+            1 => T::check(buffer, from, (from + Self::item_size())?, latest_segment),
+            count => Err(Error::IncorrectSegmentSize {
+                position: from.unchecked_offset(),
+                value: count,
+            }),
+        }
+    }
+
+    fn collect_schemas(schemas: &mut TypeSchemas) {
+        T::collect_schemas(schemas);
+    }
+}
+
+/// `BTreeMap<K, V>` is stored as a segment of key-value pairs sorted by keys.
+/// `check` rejects unsorted and duplicate keys, so the encoding is canonical.
+impl<'a, K, V> SegmentField<'a> for BTreeMap<K, V>
+where
+    K: Field<'a> + Ord,
+    V: Field<'a>,
+{
+    fn item_size() -> Offset {
+        K::field_size() + V::field_size()
+    }
+
+    fn count(&self) -> Offset {
+        self.len() as Offset
+    }
+
+    unsafe fn from_buffer(buffer: &'a [u8], from: Offset, count: Offset) -> Self {
+        let mut map = BTreeMap::new();
+        let mut start = from;
+        for _ in 0..count {
+            let key_end = start + K::field_size();
+            let key = K::read(buffer, start, key_end);
+            let value = V::read(buffer, key_end, start + Self::item_size());
+            map.insert(key, value);
+            start += Self::item_size();
+        }
+        map
+    }
+
+    fn extend_buffer(&self, mut buffer: &mut Vec<u8>) {
+        let mut start = buffer.len() as Offset;
+        buffer.resize((start + self.count() * Self::item_size()) as usize, 0);
+        for (key, value) in self.iter() {
+            let key_end = start + K::field_size();
+            key.write(&mut buffer, start, key_end);
+            value.write(&mut buffer, key_end, start + Self::item_size());
+            start += Self::item_size();
+        }
+    }
+
+    fn check_data(
+        buffer: &'a [u8],
+        from: CheckedOffset,
+        count: CheckedOffset,
+        latest_segment: CheckedOffset,
+    ) -> Result {
+        let mut start = from;
+        let mut latest_segment = latest_segment;
+        let mut prev_key: Option<K> = None;
+
+        for index in 0..count.unchecked_offset() {
+            let key_end = (start + K::field_size())?;
+            latest_segment = K::check(buffer, start, key_end, latest_segment)
+                .map_err(|e| e.at_index(index as usize))?;
+            let key = unsafe {
+                K::read(buffer, start.unchecked_offset(), key_end.unchecked_offset())
+            };
+            if let Some(prev_key) = prev_key {
+                if prev_key >= key {
+                    return Err(Error::IncorrectMapKeyOrder {
+                        position: start.unchecked_offset(),
+                    });
+                }
+            }
+            prev_key = Some(key);
+
+            let value_end = (start + Self::item_size())?;
+            latest_segment = V::check(buffer, key_end, value_end, latest_segment)
+                .map_err(|e| e.at_index(index as usize))?;
+            start = value_end;
+        }
+        Ok(latest_segment)
+    }
+
+    fn collect_schemas(schemas: &mut TypeSchemas) {
+        K::collect_schemas(schemas);
+        V::collect_schemas(schemas);
+    }
 }
 
 impl<'a> SegmentField<'a> for BitVec {
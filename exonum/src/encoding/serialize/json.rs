@@ -12,9 +12,46 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-/// trait `ExonumSerializeJson` implemented for all field that allows serializing in
-/// json format.
-///
+//! JSON representation of the encoding types.
+//!
+//! # Canonical JSON
+//!
+//! All types implementing `ExonumJson` follow the same canonical profile. A value has
+//! exactly one JSON representation, and a JSON value accepted by deserialization is
+//! serialized back to the same text after a round trip through the binary encoding,
+//! provided both texts are written with [`to_canonical_string`].
+//!
+//! | Type | JSON representation |
+//! |------|---------------------|
+//! | `bool` | `true` or `false` |
+//! | `u8`, `i8`, `u16`, `i16`, `u32`, `i32`, `Round`, `ValidatorId` | number |
+//! | `u64`, `i64`, `u128`, `i128`, `Height` | decimal string, e.g. `"18446744073709551615"` |
+//! | `Decimal` | decimal string with `scale` digits after the point, e.g. `"-12.50"` |
+//! | `F32`, `F64` | number with the shortest representation parsed back to the same value |
+//! | `&Hash`, `&PublicKey`, `&Signature`, `&[u8]`, `Vec<u8>`, `[u8; N]` | lowercase hex string |
+//! | `&str` | string |
+//! | `DateTime<Utc>` | `{ "nanos": number, "secs": decimal string }` |
+//! | `SocketAddr` | string, e.g. `"127.0.0.1:8000"` |
+//! | `Uuid` | hyphenated lowercase string |
+//! | `BitVec` | string of `0` and `1` with the length divisible by 8 |
+//! | `Vec<T>`, `&[Hash]`, `Vec<RawMessage>`, `[T; N]` | array |
+//! | `Option<T>` | `null` or the value |
+//! | `BTreeMap<K, V>` | array of `[key, value]` pairs sorted by keys |
+//! | structures and messages | object with sorted keys |
+//! | enumerations | `{ "type": variant, "value": value }` |
+//!
+//! 64-bit integers are represented as strings because many JSON parsers, including
+//! JavaScript ones, store all numbers as doubles and lose precision above 2<sup>53</sup>.
+//! Floating point numbers are available with the `float_serialize` feature only,
+//! and 128-bit integers with the `int128` feature only.
+//!
+//! Deserialization rejects JSON which cannot be produced by serialization: integers
+//! out of range of the type, fractional numbers in place of integers, decimal strings
+//! with a sign `+` or leading zeros, and bit vectors with the length not divisible by 8.
+//! The only exceptions are hexadecimal strings and UUIDs, which are accepted
+//! in upper case as well (and UUIDs also without hyphens).
+//!
+//! [`to_canonical_string`]: fn.to_canonical_string.html
 
 // TODO refer to difference between json serialization and exonum_json (ECR-156).
 // TODO implement Field for float (ECR-153).
@@ -28,8 +65,10 @@ use hex::FromHex;
 use chrono::{DateTime, Utc, TimeZone};
 use uuid::Uuid;
 
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 use std::error::Error;
+use std::str::FromStr;
 
 use crypto::{Hash, PublicKey, Signature};
 use helpers::{Height, Round, ValidatorId};
@@ -43,8 +82,7 @@ macro_rules! impl_default_deserialize_owned {
         impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
             fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
                 -> Result<Self, Box<::std::error::Error>> {
-                use $crate::encoding::serialize::json::reexport::from_value;
-                Ok(from_value(value.clone())?)
+                $crate::encoding::serialize::json::deserialize_owned_field(value)
             }
         }
     };
@@ -69,6 +107,33 @@ pub trait ExonumJson {
         Self: Sized;
     /// serialize field as `json::Value`
     fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>>;
+
+    /// Serializes a vector of values of this type. Vectors are represented as arrays
+    /// except for vectors of bytes, which are represented as hex strings.
+    #[doc(hidden)]
+    fn serialize_vec(items: &[Self]) -> Result<Value, Box<Error + Send + Sync>>
+    where
+        Self: Sized,
+    {
+        let mut vec = Vec::with_capacity(items.len());
+        for item in items {
+            vec.push(item.serialize_field()?);
+        }
+        Ok(Value::Array(vec))
+    }
+
+    /// Deserializes a vector of values of this type represented as in `serialize_vec`.
+    #[doc(hidden)]
+    fn deserialize_vec(value: &Value) -> Result<Vec<Self>, Box<Error>>
+    where
+        Self: Sized + ExonumJsonDeserialize,
+    {
+        let items = value.as_array().ok_or("Can't cast json as array")?;
+        items
+            .iter()
+            .map(<Self as ExonumJsonDeserialize>::deserialize)
+            .collect()
+    }
 }
 
 /// `ExonumJsonDeserialize` is trait for objects that could be constructed from exonum json.
@@ -79,6 +144,107 @@ pub trait ExonumJsonDeserialize {
         Self: Sized;
 }
 
+/// Deserializes a value of an owned field type with `ExonumJson::deserialize_field`.
+///
+/// Unlike `ExonumJsonDeserialize`, the json representation of the value is the same
+/// as the one used for the fields of this type.
+#[allow(unsafe_code)]
+pub fn deserialize_owned_field<T>(value: &Value) -> Result<T, Box<Error>>
+where
+    T: ExonumJson + for<'a> Field<'a>,
+{
+    let size = T::field_size();
+    let mut buffer = vec![0; size as usize];
+    T::deserialize_field(value, &mut buffer, 0, size)?;
+    T::check(&buffer, 0.into(), size.into(), size.into())?;
+    Ok(unsafe { T::read(&buffer, 0, size) })
+}
+
+/// Writes the JSON value in the canonical form: without whitespace and with the keys
+/// of all objects sorted.
+///
+/// Unlike `serde_json::to_string`, the order of keys does not depend on whether
+/// the `preserve_order` feature of `serde_json` is enabled.
+///
+/// # Examples
+///
+/// ```
+/// # #[macro_use] extern crate serde_json;
+/// # extern crate exonum;
+/// use exonum::encoding::serialize::json::to_canonical_string;
+///
+/// # fn main() {
+/// let value = json!({ "to": "01ab", "amount": "10", "seed": 5 });
+/// assert_eq!(
+///     to_canonical_string(&value),
+///     r#"{"amount":"10","seed":5,"to":"01ab"}"#
+/// );
+/// # }
+/// ```
+pub fn to_canonical_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match *value {
+        Value::Array(ref items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(ref map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        _ => out.push_str(&value.to_string()),
+    }
+}
+
+/// Reads an integer represented as a JSON number checking that it lies within `min..=max`.
+fn parse_json_int(value: &Value, min: i64, max: i64, type_name: &str) -> Result<i64, Box<Error>> {
+    let number = value.as_i64().ok_or("Can't cast json as integer")?;
+    if number < min || number > max {
+        Err(format!("Integer {} is out of range of `{}`", number, type_name))?;
+    }
+    Ok(number)
+}
+
+/// Parses an integer represented as a decimal string without a sign `+` and leading zeros.
+fn parse_decimal<T>(string: &str) -> Result<T, Box<Error>>
+where
+    T: FromStr,
+    T::Err: Error + 'static,
+{
+    let digits = if string.starts_with('-') {
+        &string[1..]
+    } else {
+        string
+    };
+    let is_canonical = !digits.is_empty() && digits.bytes().all(|b| b >= b'0' && b <= b'9') &&
+        (digits == "0" || !digits.starts_with('0')) && string != "-0";
+    if !is_canonical {
+        Err(format!("`{}` is not a canonical decimal integer", string))?;
+    }
+    Ok(string.parse()?)
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct TimestampHelper {
     secs: String,
@@ -87,7 +253,8 @@ struct TimestampHelper {
 
 // implementation of deserialization
 macro_rules! impl_deserialize_int {
-    (@impl $typename:ty) => {
+    (@impl $typename:ty) => (impl_deserialize_int!{@impl $typename;});
+    (@impl $typename:ty; $($methods:tt)*) => {
         impl ExonumJson for $typename {
             fn deserialize_field<B: WriteBufferWrapper>(value: &Value,
                                                          buffer: &mut B,
@@ -95,7 +262,12 @@ macro_rules! impl_deserialize_int {
                                                          to: Offset)
                 -> Result<(), Box<Error>>
             {
-                let number = value.as_i64().ok_or("Can't cast json as integer")?;
+                let number = parse_json_int(
+                    value,
+                    <$typename>::min_value() as i64,
+                    <$typename>::max_value() as i64,
+                    stringify!($typename),
+                )?;
                 buffer.write(from, to, number as $typename);
                 Ok(())
             }
@@ -103,6 +275,8 @@ macro_rules! impl_deserialize_int {
             fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
                 Ok(Value::Number((*self).into()))
             }
+
+            $($methods)*
         }
     };
     ($($name:ty);*) => ($(impl_deserialize_int!{@impl $name})*);
@@ -118,7 +292,7 @@ macro_rules! impl_deserialize_bigint {
             -> Result<(), Box<Error>>
             {
                 let string = value.as_str().ok_or("Can't cast json as string")?;
-                let val: $typename =  string.parse()?;
+                let val: $typename = parse_decimal(string)?;
                 buffer.write(from, to, val);
                 Ok(())
             }
@@ -151,15 +325,45 @@ macro_rules! impl_deserialize_hex_segment {
                 Ok(Value::String(hex_str))
             }
         }
+
+        impl ExonumJson for $typename {
+            fn deserialize_field<B: WriteBufferWrapper>(
+                value: &Value,
+                buffer: &mut B,
+                from: Offset,
+                to: Offset,
+            ) -> Result<(), Box<Error>> {
+                <&$typename as ExonumJson>::deserialize_field(value, buffer, from, to)
+            }
+
+            fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+                <&$typename as ExonumJson>::serialize_field(&self)
+            }
+        }
     };
     ($($name:ty);*) => ($(impl_deserialize_hex_segment!{@impl $name})*);
 }
 
-impl_deserialize_int!{u8; u16; u32; i8; i16; i32}
+// Vectors and arrays of bytes are represented as hex strings.
+impl_deserialize_int!{@impl u8;
+    fn serialize_vec(items: &[Self]) -> Result<Value, Box<Error + Send + Sync>> {
+        Ok(Value::String(::encoding::serialize::encode_hex(items)))
+    }
+
+    fn deserialize_vec(value: &Value) -> Result<Vec<Self>, Box<Error>> {
+        let string = value.as_str().ok_or("Can't cast json as string")?;
+        Ok(<Vec<u8> as FromHex>::from_hex(string)?)
+    }
+}
+impl_deserialize_int!{u16; u32; i8; i16; i32}
 impl_deserialize_bigint!{u64; i64}
+#[cfg(feature = "int128")]
+impl_deserialize_bigint!{u128; i128}
 impl_deserialize_hex_segment!{Hash; PublicKey; Signature}
 impl_default_deserialize_owned!{u8; u16; u32; i8; i16; i32; u64; i64;
-                                Hash; PublicKey; Signature; bool}
+                                Hash; PublicKey; Signature; bool; String}
+#[cfg(feature = "int128")]
+impl_default_deserialize_owned!{u128; i128}
 
 impl ExonumJson for bool {
     fn deserialize_field<B: WriteBufferWrapper>(
@@ -178,6 +382,21 @@ impl ExonumJson for bool {
     }
 }
 
+impl ExonumJson for String {
+    fn deserialize_field<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+    ) -> Result<(), Box<Error>> {
+        <&str as ExonumJson>::deserialize_field(value, buffer, from, to)
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        Ok(Value::String(self.clone()))
+    }
+}
+
 impl<'a> ExonumJson for &'a str {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -203,7 +422,10 @@ impl ExonumJson for DateTime<Utc> {
         to: Offset,
     ) -> Result<(), Box<Error>> {
         let helper: TimestampHelper = serde_json::from_value(value.clone())?;
-        let date_time = Utc.timestamp(helper.secs.parse()?, helper.nanos);
+        let secs = parse_decimal(&helper.secs)?;
+        let date_time = Utc.timestamp_opt(secs, helper.nanos).single().ok_or(
+            "Invalid timestamp in json",
+        )?;
         buffer.write(from, to, date_time);
         Ok(())
     }
@@ -225,6 +447,9 @@ impl ExonumJson for SocketAddr {
         to: Offset,
     ) -> Result<(), Box<Error>> {
         let addr: SocketAddr = serde_json::from_value(value.clone())?;
+        if addr.is_ipv6() {
+            Err("IPv6 addresses are not supported")?;
+        }
         buffer.write(from, to, addr);
         Ok(())
     }
@@ -309,18 +534,11 @@ impl ExonumJson for Vec<RawMessage> {
 
 impl<T> ExonumJsonDeserialize for Vec<T>
 where
-    T: ExonumJsonDeserialize,
+    T: ExonumJsonDeserialize + ExonumJson,
     for<'a> Vec<T>: Field<'a>,
 {
     fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
-        let bytes = value.as_array().ok_or("Can't cast json as array")?;
-        let mut vec: Vec<_> = Vec::new();
-        for el in bytes {
-            let obj = T::deserialize(el)?;
-            vec.push(obj);
-        }
-
-        Ok(vec)
+        T::deserialize_vec(value)
     }
 }
 
@@ -337,25 +555,150 @@ where
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let bytes = value.as_array().ok_or("Can't cast json as array")?;
-        let mut vec: Vec<_> = Vec::new();
-        for el in bytes {
-            let obj = T::deserialize(el)?;
-            vec.push(obj);
-        }
+        let vec = T::deserialize_vec(value)?;
         buffer.write(from, to, vec);
         Ok(())
     }
 
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        T::serialize_vec(self)
+    }
+}
+
+impl<T> ExonumJsonDeserialize for Option<T>
+where
+    T: ExonumJson + for<'a> Field<'a>,
+{
+    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
+        if value.is_null() {
+            Ok(None)
+        } else {
+            Ok(Some(deserialize_owned_field(value)?))
+        }
+    }
+}
+
+/// `None` is represented as `null` in json.
+impl<T> ExonumJson for Option<T>
+where
+    T: ExonumJson + for<'a> Field<'a>,
+{
+    fn deserialize_field<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+    ) -> Result<(), Box<Error>> {
+        let value = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+        buffer.write(from, to, value);
+        Ok(())
+    }
+
+    fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+        match *self {
+            Some(ref value) => value.serialize_field(),
+            None => Ok(Value::Null),
+        }
+    }
+}
+
+/// Maps are represented as arrays of `[key, value]` pairs sorted by keys.
+impl<K, V> ExonumJson for BTreeMap<K, V>
+where
+    K: ExonumJson + Ord + for<'a> Field<'a>,
+    V: ExonumJson + for<'a> Field<'a>,
+{
+    fn deserialize_field<B: WriteBufferWrapper>(
+        value: &Value,
+        buffer: &mut B,
+        from: Offset,
+        to: Offset,
+    ) -> Result<(), Box<Error>> {
+        let map = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+        buffer.write(from, to, map);
+        Ok(())
+    }
+
     fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
         let mut vec = Vec::new();
-        for item in self {
-            vec.push(item.serialize_field()?);
+        for (key, value) in self {
+            vec.push(Value::Array(
+                vec![key.serialize_field()?, value.serialize_field()?],
+            ));
         }
         Ok(Value::Array(vec))
     }
 }
 
+impl<K, V> ExonumJsonDeserialize for BTreeMap<K, V>
+where
+    K: ExonumJson + Ord + for<'a> Field<'a>,
+    V: ExonumJson + for<'a> Field<'a>,
+{
+    fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
+        let pairs = value.as_array().ok_or("Can't cast json as array")?;
+        let mut map = BTreeMap::new();
+        for pair in pairs {
+            let pair = pair.as_array().ok_or("Can't cast json as array")?;
+            if pair.len() != 2 {
+                Err("Map entry should be an array of key and value")?;
+            }
+            let key: K = deserialize_owned_field(&pair[0])?;
+            let value: V = deserialize_owned_field(&pair[1])?;
+            if map.insert(key, value).is_some() {
+                Err("Duplicate key in map")?;
+            }
+        }
+        Ok(map)
+    }
+}
+
+macro_rules! impl_array_json {
+    ($($len:tt)*) => ($(
+        impl<T> ExonumJson for [T; $len]
+        where
+            T: ExonumJson + ExonumJsonDeserialize + Copy + Default + for<'a> Field<'a>,
+        {
+            fn deserialize_field<B: WriteBufferWrapper>(
+                value: &Value,
+                buffer: &mut B,
+                from: Offset,
+                _: Offset,
+            ) -> Result<(), Box<Error>> {
+                let items = T::deserialize_vec(value)?;
+                if items.len() != $len {
+                    Err(format!("Expected array of {} items, found {}", $len, items.len()))?;
+                }
+                let mut start = from;
+                for item in items {
+                    buffer.write(start, start + T::field_size(), item);
+                    start += T::field_size();
+                }
+                Ok(())
+            }
+
+            fn serialize_field(&self) -> Result<Value, Box<Error + Send + Sync>> {
+                T::serialize_vec(&self[..])
+            }
+        }
+
+        impl<T> ExonumJsonDeserialize for [T; $len]
+        where
+            T: ExonumJson + ExonumJsonDeserialize + Copy + Default + for<'a> Field<'a>,
+        {
+            fn deserialize(value: &Value) -> Result<Self, Box<Error>> {
+                deserialize_owned_field(value)
+            }
+        }
+    )*)
+}
+
+impl_array_json!{
+    1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16
+    17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32
+    64
+}
+
 impl ExonumJson for BitVec {
     fn deserialize_field<B: WriteBufferWrapper>(
         value: &Value,
@@ -364,8 +707,15 @@ impl ExonumJson for BitVec {
         to: Offset,
     ) -> Result<(), Box<Error>> {
         let string = value.as_str().ok_or("Can't cast json as string")?;
+        // Bit vectors are stored as whole bytes, so shorter ones would not round-trip.
+        if string.len() % 8 != 0 {
+            Err(format!(
+                "BitVec length should be divisible by 8, not {}",
+                string.len()
+            ))?;
+        }
         let mut vec = BitVec::new();
-        for (i, ch) in string.chars().enumerate() {
+        for ch in string.chars() {
             let val = if ch == '1' {
                 true
             } else if ch == '0' {
@@ -373,7 +723,7 @@ impl ExonumJson for BitVec {
             } else {
                 Err(format!("BitVec should contain only 0 or 1, not {}", ch))?
             };
-            vec.set(i, val);
+            vec.push(val);
         }
         buffer.write(from, to, vec);
         Ok(())
@@ -400,7 +750,7 @@ impl ExonumJson for Height {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let val: u64 = value.as_str().ok_or("Can't cast json as string")?.parse()?;
+        let val: u64 = parse_decimal(value.as_str().ok_or("Can't cast json as string")?)?;
         buffer.write(from, to, Height(val));
         Ok(())
     }
@@ -418,7 +768,7 @@ impl ExonumJson for Round {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let number = value.as_i64().ok_or("Can't cast json as integer")?;
+        let number = parse_json_int(value, 0, i64::from(u32::max_value()), "Round")?;
         buffer.write(from, to, Round(number as u32));
         Ok(())
     }
@@ -436,7 +786,7 @@ impl ExonumJson for ValidatorId {
         from: Offset,
         to: Offset,
     ) -> Result<(), Box<Error>> {
-        let number = value.as_i64().ok_or("Can't cast json as integer")?;
+        let number = parse_json_int(value, 0, i64::from(u16::max_value()), "ValidatorId")?;
         buffer.write(from, to, ValidatorId(number as u16));
         Ok(())
     }
@@ -18,7 +18,8 @@
 /// In addition, the macro declares a `new` constructor, which accepts all fields
 /// in the order of their declaration in the macro.
 /// The macro also implements [`Field`], [`ExonumJson`] and [`StorageValue`] traits
-/// for the declared datatype.
+/// for the declared datatype, and a `schema` method returning the [descriptor][schema]
+/// of its binary layout.
 ///
 /// Unlike types created with [`transactions!`], the datatype is mapped to a byte buffer
 /// without any checks; it is assumed that the relevant checks have been performed
@@ -30,10 +31,717 @@
 /// **NB.** `encoding_struct!` uses other macros in the `exonum` crate internally.
 /// Be sure to add them to the global scope.
 ///
+/// Structures declared as ordinary Rust structures can have the same layout
+/// with `#[derive(EncodingStruct)]` from the `exonum-derive` crate.
+///
+/// [`Field`]: ./encoding/trait.Field.html
+/// [`ExonumJson`]: ./encoding/serialize/json/trait.ExonumJson.html
+/// [`StorageValue`]: ./storage/trait.StorageValue.html
+/// [`transactions!`]: macro.transactions.html
+/// [schema]: ./encoding/schema/struct.StructSchema.html
+///
+/// # Examples
+///
+/// ```
+/// #[macro_use] extern crate exonum;
+///
+/// encoding_struct! {
+///     struct SaveTwoIntegers {
+///         first: u64,
+///         second: u64,
+///     }
+/// }
+///
+/// # fn main() {
+/// let s = SaveTwoIntegers::new(1, 2);
+/// println!("Two integers: {:?}", s);
+/// # }
+/// ```
+///
+/// # Schema evolution
+///
+/// Adding a field to a structure changes its layout, so values persisted with
+/// the previous declaration can no longer be read. Structures that are expected to evolve
+/// should be declared in the versioned mode, by adding an `optional` block after
+/// the fields. New fields may be appended to the end of the `optional` block; their
+/// types must implement `Default`, which is returned by the getters for values encoded
+/// with an older layout.
+///
+/// The versioned layout is a strict extension of the layout of a structure declared
+/// without the `optional` block: the required fields are laid out as usual, and
+/// the optional fields follow the data of the required fields as a trailing block.
+/// The block starts with a one-byte layout version equal to one plus the number
+/// of optional fields in the block and is omitted if there are no optional fields.
+/// Hence, an `optional` block may be added to an existing structure, and the values
+/// encoded with the previous layouts stay readable via [`StorageValue::from_bytes`]
+/// and pass [`Field::check`]. `upgrade` re-encodes such values with the latest
+/// layout; note that this changes their hash. The JSON representation includes
+/// only the optional fields present in the layout of the value, so values restored
+/// from JSON keep their layout.
+///
+/// The same applies to messages declared with [`messages!`] and [`transactions!`],
+/// except that signed messages cannot be upgraded.
+///
+/// [`StorageValue::from_bytes`]: ./storage/trait.StorageValue.html#tymethod.from_bytes
+/// [`Field::check`]: ./encoding/trait.Field.html#tymethod.check
+/// [`messages!`]: macro.messages.html
+///
+/// ```
+/// #[macro_use] extern crate exonum;
+///
+/// mod v1 {
+///     encoding_struct! {
+///         struct Wallet {
+///             name: &str,
+///             balance: u64,
+///         }
+///     }
+/// }
+///
+/// encoding_struct! {
+///     struct Wallet {
+///         name: &str,
+///         balance: u64,
+///     }
+///     optional {
+///         frozen: bool,
+///         memo: Option<Vec<u8>>,
+///     }
+/// }
+///
+/// # fn main() {
+/// use exonum::crypto::CryptoHash;
+/// use exonum::storage::StorageValue;
+///
+/// let old = v1::Wallet::new("Alice", 100).into_bytes();
+/// let wallet = Wallet::from_bytes(old.into());
+/// assert_eq!(wallet.version(), 1);
+/// assert_eq!(wallet.balance(), 100);
+/// assert_eq!(wallet.frozen(), false);
+///
+/// let wallet = wallet.upgrade();
+/// assert_eq!(wallet.version(), Wallet::latest_version());
+/// assert_eq!(wallet.memo(), None);
+/// assert_ne!(wallet.hash(), v1::Wallet::new("Alice", 100).hash());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! encoding_struct {
+    (
+    $(#[$attr:meta])*
+    struct $name:ident {
+        $(
+        $(#[$field_attr:meta])*
+        $field_name:ident : $field_type:ty
+        ),*
+        $(,)*
+    }) => (
+        #[derive(Clone, PartialEq)]
+        $(#[$attr])*
+        pub struct $name {
+            raw: Vec<u8>
+        }
+
+        // Re-implement `Field` for `encoding_struct!`
+        // to write fields in place of another structure
+        #[allow(unsafe_code)]
+        impl<'a> $crate::encoding::Field<'a> for $name {
+            unsafe fn read(buffer: &'a [u8],
+                            from: $crate::encoding::Offset,
+                            to: $crate::encoding::Offset) -> Self {
+                let vec: Vec<u8> = $crate::encoding::Field::read(buffer, from, to);
+                $crate::storage::StorageValue::from_bytes(::std::borrow::Cow::Owned(vec))
+            }
+
+            fn write(&self,
+                            buffer: &mut Vec<u8>,
+                            from: $crate::encoding::Offset,
+                            to: $crate::encoding::Offset) {
+                $crate::encoding::Field::write(&self.raw, buffer, from, to);
+            }
+
+            #[allow(unused_variables)]
+            #[allow(unused_comparisons)]
+            fn check(buffer: &'a [u8],
+                        from_st_val: $crate::encoding::CheckedOffset,
+                        to_st_val: $crate::encoding::CheckedOffset,
+                        latest_segment: $crate::encoding::CheckedOffset)
+                -> $crate::encoding::Result
+            {
+                let latest_segment_origin = <&[u8] as $crate::encoding::Field>::check(
+                    buffer, from_st_val, to_st_val, latest_segment)?;
+                let vec: &[u8] = unsafe{ $crate::encoding::Field::read(
+                    buffer,
+                    from_st_val.unchecked_offset(),
+                    to_st_val.unchecked_offset())};
+                let latest_segment: $crate::encoding::CheckedOffset =
+                    $name::__ex_header_size().into();
+
+                if vec.len() < $name::__ex_header_size() as usize {
+                    return Err($crate::encoding::Error::UnexpectedlyShortPayload{
+                        actual_size: vec.len() as $crate::encoding::Offset,
+                        minimum_size: $name::__ex_header_size() as $crate::encoding::Offset
+                    })
+                }
+
+                __ex_for_each_field!(
+                    __ex_struct_check_field, (latest_segment, vec, $name),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                Ok(latest_segment_origin)
+            }
+
+            fn collect_schemas(schemas: &mut $crate::encoding::schema::TypeSchemas) {
+                if schemas.add_struct($name::schema()) {
+                    $(
+                        <$field_type as $crate::encoding::Field>::collect_schemas(schemas);
+                    )*
+                }
+            }
+
+            fn field_size() -> $crate::encoding::Offset {
+                // We write `encoding_struct` as regular buffer,
+                // so real `field_size` is 8.
+                // TODO: maybe we should write it as sub structure in place?
+                // We could get benefit from it: we limit indirection
+                // in deserializing sub fields, by only one calculation (ECR-156).
+
+                // $body as $crate::encoding::Offset
+
+                8 as $crate::encoding::Offset
+            }
+        }
+
+        impl $crate::crypto::CryptoHash for $name {
+            fn hash(&self) -> $crate::crypto::Hash {
+                $crate::crypto::hash(self.raw.as_ref())
+            }
+        }
+
+        impl $crate::storage::StorageValue for $name {
+            fn into_bytes(self) -> Vec<u8> {
+                self.raw
+            }
+
+            fn from_bytes(v: ::std::borrow::Cow<[u8]>) -> Self {
+                $name {
+                    raw: v.into_owned()
+                }
+            }
+        }
+
+        // TODO extract some fields like hash and from_raw into trait (ECR-156)
+        impl $name {
+            #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
+            #[allow(unused_imports, unused_mut)]
+
+            /// Creates a new instance with given parameters.
+            pub fn new($($field_name: $field_type,)*) -> $name {
+                let mut buf = vec![0; $name::__ex_header_size() as usize];
+                __ex_for_each_field!(
+                    __ex_struct_write_field, (buf),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                $name { raw: buf }
+            }
+
+            __ex_for_each_field!(
+                __ex_struct_mk_field, (),
+                $( ($(#[$field_attr])*, $field_name, $field_type) )*
+            );
+
+            fn __ex_header_size() -> $crate::encoding::Offset {
+                __ex_header_size!($($field_type),*)
+            }
+
+            /// Returns the descriptor of the binary layout of the structure.
+            #[allow(unused_mut)]
+            pub fn schema() -> $crate::encoding::schema::StructSchema {
+                let mut fields = Vec::new();
+                __ex_for_each_field!(
+                    __ex_field_schema, (fields),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                $crate::encoding::schema::StructSchema {
+                    name: stringify!($name).to_owned(),
+                    header_size: $name::__ex_header_size(),
+                    version: None,
+                    fields,
+                    optional_fields: Vec::new(),
+                }
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter)
+                -> Result<(), ::std::fmt::Error> {
+                fmt.debug_struct(stringify!($name))
+                 $(.field(stringify!($field_name), &self.$field_name()))*
+                   .finish()
+            }
+        }
+
+        impl $crate::encoding::serialize::json::ExonumJson for $name {
+            #[allow(unused_variables)]
+            fn deserialize_field<B> (value: &$crate::encoding::serialize::json::reexport::Value,
+                                        buffer: & mut B,
+                                        from: $crate::encoding::Offset,
+                                        to: $crate::encoding::Offset )
+                -> Result<(), Box<::std::error::Error>>
+                where B: $crate::encoding::serialize::WriteBufferWrapper
+            {
+                use $crate::encoding::serialize::json::ExonumJsonDeserialize;
+                // deserialize full field
+                let structure = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+                // then write it
+                buffer.write(from, to, structure);
+
+                Ok(())
+            }
+
+
+            #[allow(unused_mut)]
+            fn serialize_field(&self)
+                -> Result<$crate::encoding::serialize::json::reexport::Value,
+                          Box<::std::error::Error + Send + Sync>>
+            {
+                use $crate::encoding::serialize::json::reexport::Value;
+                let mut map = $crate::encoding::serialize::json::reexport::Map::new();
+                $(
+                    map.insert(stringify!($field_name).to_string(),
+                        self.$field_name().serialize_field()?);
+                )*
+                Ok(Value::Object(map))
+            }
+        }
+        impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
+            #[allow(unused_imports, unused_mut)]
+            fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
+                -> Result<Self, Box<::std::error::Error>> {
+                use $crate::encoding::serialize::json::ExonumJson as ExonumJson;
+                let mut buf = vec![0; $name::__ex_header_size() as usize];
+                let _obj = value.as_object().ok_or("Can't cast json as object.")?;
+                __ex_for_each_field!(
+                    __ex_deserialize_field, (_obj, buf),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                Ok($name { raw: buf })
+            }
+        }
+
+        // TODO: Rewrite Deserialize and Serialize implementation (ECR-156)
+        impl<'de> $crate::encoding::serialize::reexport::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where D: $crate::encoding::serialize::reexport::Deserializer<'de>
+            {
+                use $crate::encoding::serialize::json::reexport::Value;
+                use $crate::encoding::serialize::reexport::{DeError, Deserialize};
+                let value = <Value as Deserialize>::deserialize(deserializer)?;
+                <Self as $crate::encoding::serialize::json::ExonumJsonDeserialize>::deserialize(
+                    &value).map_err(|_| D::Error::custom("Can not deserialize value."))
+            }
+        }
+
+        impl $crate::encoding::serialize::reexport::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where S: $crate::encoding::serialize::reexport::Serializer
+            {
+                use $crate::encoding::serialize::reexport::SerError;
+                use $crate::encoding::serialize::json::ExonumJson;
+                self.serialize_field()
+                    .map_err(|_| S::Error::custom(
+                                concat!("Can not serialize structure: ", stringify!($name))))?
+                    .serialize(serializer)
+            }
+        }
+    );
+    (
+    $(#[$attr:meta])*
+    struct $name:ident {
+        $(
+        $(#[$field_attr:meta])*
+        $field_name:ident : $field_type:ty
+        ),*
+        $(,)*
+    }
+    optional {
+        $(
+        $(#[$opt_attr:meta])*
+        $opt_name:ident : $opt_type:ty
+        ),*
+        $(,)*
+    }) => (
+        #[derive(Clone, PartialEq)]
+        $(#[$attr])*
+        pub struct $name {
+            raw: Vec<u8>,
+            // Offset of the version byte, that is, the end of the data of the required fields.
+            required_end: $crate::encoding::Offset,
+        }
+
+        #[allow(unsafe_code)]
+        impl<'a> $crate::encoding::Field<'a> for $name {
+            unsafe fn read(buffer: &'a [u8],
+                            from: $crate::encoding::Offset,
+                            to: $crate::encoding::Offset) -> Self {
+                let vec: Vec<u8> = $crate::encoding::Field::read(buffer, from, to);
+                $crate::storage::StorageValue::from_bytes(::std::borrow::Cow::Owned(vec))
+            }
+
+            fn write(&self,
+                            buffer: &mut Vec<u8>,
+                            from: $crate::encoding::Offset,
+                            to: $crate::encoding::Offset) {
+                $crate::encoding::Field::write(&self.raw, buffer, from, to);
+            }
+
+            #[allow(unused_variables)]
+            fn check(buffer: &'a [u8],
+                        from_st_val: $crate::encoding::CheckedOffset,
+                        to_st_val: $crate::encoding::CheckedOffset,
+                        latest_segment: $crate::encoding::CheckedOffset)
+                -> $crate::encoding::Result
+            {
+                let latest_segment_origin = <&[u8] as $crate::encoding::Field>::check(
+                    buffer, from_st_val, to_st_val, latest_segment)?;
+                let vec: &[u8] = unsafe{ $crate::encoding::Field::read(
+                    buffer,
+                    from_st_val.unchecked_offset(),
+                    to_st_val.unchecked_offset())};
+                $name::__ex_check_layout(vec)?;
+                Ok(latest_segment_origin)
+            }
+
+            fn collect_schemas(schemas: &mut $crate::encoding::schema::TypeSchemas) {
+                if schemas.add_struct($name::schema()) {
+                    $(
+                        <$field_type as $crate::encoding::Field>::collect_schemas(schemas);
+                    )*
+                    $(
+                        <$opt_type as $crate::encoding::Field>::collect_schemas(schemas);
+                    )*
+                }
+            }
+
+            fn field_size() -> $crate::encoding::Offset {
+                8 as $crate::encoding::Offset
+            }
+        }
+
+        impl $crate::crypto::CryptoHash for $name {
+            fn hash(&self) -> $crate::crypto::Hash {
+                $crate::crypto::hash(self.raw.as_ref())
+            }
+        }
+
+        impl $crate::storage::StorageValue for $name {
+            fn into_bytes(self) -> Vec<u8> {
+                self.raw
+            }
+
+            fn from_bytes(v: ::std::borrow::Cow<[u8]>) -> Self {
+                $name::__ex_from_raw(v.into_owned())
+            }
+        }
+
+        impl $name {
+            #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
+            #[allow(unused_imports, unused_mut, unused_variables)]
+
+            /// Creates a new instance with given parameters using the latest layout version.
+            pub fn new($($field_name: $field_type,)* $($opt_name: $opt_type,)*) -> $name {
+                let mut buf = vec![0; $name::__ex_header_size() as usize];
+                __ex_for_each_field!(
+                    __ex_struct_write_field, (buf),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                let required_end = buf.len() as $crate::encoding::Offset;
+                let base = $name::__ex_append_optional_header(&mut buf, $name::latest_version());
+                __ex_for_each_field!(
+                    @inner __ex_struct_write_field (buf) (base);
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                $name { raw: buf, required_end }
+            }
+
+            /// Returns the latest layout version of the structure, that is, one plus
+            /// the number of optional fields.
+            pub fn latest_version() -> u8 {
+                let optional_fields: &[&str] = &[$(stringify!($opt_name)),*];
+                1 + optional_fields.len() as u8
+            }
+
+            /// Returns the layout version the value is encoded with.
+            pub fn version(&self) -> u8 {
+                self.__ex_optional_layout().0
+            }
+
+            /// Re-encodes the value with the latest layout version. Optional fields absent
+            /// in the original layout get their default values.
+            ///
+            /// The upgraded value has a different binary representation and therefore
+            /// a different hash, so upgrading values that are hashed into the state,
+            /// e.g., stored in a `ProofMapIndex`, changes the state hash.
+            pub fn upgrade(&self) -> $name {
+                if self.version() == $name::latest_version() {
+                    return self.clone();
+                }
+                $name::new($(self.$field_name(),)* $(self.$opt_name(),)*)
+            }
+
+            __ex_for_each_field!(
+                __ex_struct_mk_field, (),
+                $( ($(#[$field_attr])*, $field_name, $field_type) )*
+            );
+
+            __ex_for_each_field!(
+                @inner __ex_struct_mk_optional_field () (0);
+                $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+            );
+
+            fn __ex_header_size() -> $crate::encoding::Offset {
+                __ex_header_size!($($field_type),*)
+            }
+
+            // Size of the header of the optional fields present in the given layout version.
+            fn __ex_optional_header_size(version: u8) -> $crate::encoding::Offset {
+                let optional_sizes: &[$crate::encoding::Offset] = &[
+                    $(<$opt_type as $crate::encoding::Field>::field_size()),*
+                ];
+                let present = (version as usize).saturating_sub(1).min(optional_sizes.len());
+                optional_sizes[..present].iter().sum::<$crate::encoding::Offset>()
+            }
+
+            // Appends the version byte and the header of the optional fields present
+            // in the given layout version after the required fields. Returns the offset
+            // of the header. Nothing is appended for the first version.
+            fn __ex_append_optional_header(buf: &mut Vec<u8>, version: u8)
+                -> $crate::encoding::Offset
+            {
+                if version > 1 {
+                    buf.push(version);
+                }
+                let base = buf.len();
+                buf.resize(base + $name::__ex_optional_header_size(version) as usize, 0);
+                base as $crate::encoding::Offset
+            }
+
+            // Wraps raw bytes, locating the end of the required fields once. Bytes with
+            // an invalid layout of the required fields are treated as the first version.
+            fn __ex_from_raw(raw: Vec<u8>) -> $name {
+                let required_end = $name::__ex_check_required_fields(&raw)
+                    .map(|offset| offset.unchecked_offset())
+                    .unwrap_or(raw.len() as $crate::encoding::Offset);
+                $name { raw, required_end }
+            }
+
+            // Returns the layout version of the value and the offset of the header
+            // of its optional fields.
+            fn __ex_optional_layout(&self) -> (u8, $crate::encoding::Offset) {
+                let version = self.raw.get(self.required_end as usize).cloned().unwrap_or(1);
+                (version, self.required_end + 1)
+            }
+
+            // Checks the required fields and returns the end of their data.
+            #[allow(unused_variables)]
+            fn __ex_check_required_fields(vec: &[u8]) -> $crate::encoding::Result {
+                if vec.len() < $name::__ex_header_size() as usize {
+                    return Err($crate::encoding::Error::UnexpectedlyShortPayload{
+                        actual_size: vec.len() as $crate::encoding::Offset,
+                        minimum_size: $name::__ex_header_size(),
+                    })
+                }
+                let latest_segment: $crate::encoding::CheckedOffset =
+                    $name::__ex_header_size().into();
+                __ex_for_each_field!(
+                    __ex_struct_check_field, (latest_segment, vec, $name),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                Ok(latest_segment)
+            }
+
+            // Checks the whole value: the required fields followed by
+            // an optional block, which is absent in the values of the first version.
+            #[allow(unused_variables, unused_mut)]
+            fn __ex_check_layout(vec: &[u8]) -> $crate::encoding::Result {
+                let required_end = $name::__ex_check_required_fields(vec)?.unchecked_offset();
+                let version = match vec.get(required_end as usize) {
+                    Some(&version) => version,
+                    None => return Ok(required_end.into()),
+                };
+                if version < 2 || version > $name::latest_version() {
+                    return Err($crate::encoding::Error::UnsupportedStructVersion{
+                        position: required_end,
+                        version,
+                    })
+                }
+                let base = required_end + 1;
+                let optional_header_size = $name::__ex_optional_header_size(version);
+                let header_end = base + optional_header_size;
+                if vec.len() < header_end as usize {
+                    return Err($crate::encoding::Error::UnexpectedlyShortPayload{
+                        actual_size: vec.len() as $crate::encoding::Offset,
+                        minimum_size: header_end,
+                    })
+                }
+                let mut latest_segment: $crate::encoding::CheckedOffset = header_end.into();
+                __ex_for_each_field!(
+                    @inner __ex_struct_check_optional_field
+                    (latest_segment, vec, base, optional_header_size, $name) (0);
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                Ok(latest_segment)
+            }
+
+            /// Returns the descriptor of the binary layout of the structure.
+            #[allow(unused_mut)]
+            pub fn schema() -> $crate::encoding::schema::StructSchema {
+                let mut fields = Vec::new();
+                __ex_for_each_field!(
+                    __ex_field_schema, (fields),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                let mut optional_fields = Vec::new();
+                __ex_for_each_field!(
+                    __ex_field_schema, (optional_fields),
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                $crate::encoding::schema::StructSchema {
+                    name: stringify!($name).to_owned(),
+                    header_size: $name::__ex_header_size(),
+                    version: Some($name::latest_version()),
+                    fields,
+                    optional_fields,
+                }
+            }
+        }
+
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter)
+                -> Result<(), ::std::fmt::Error> {
+                fmt.debug_struct(stringify!($name))
+                 $(.field(stringify!($field_name), &self.$field_name()))*
+                 $(.field(stringify!($opt_name), &self.$opt_name()))*
+                   .finish()
+            }
+        }
+
+        impl $crate::encoding::serialize::json::ExonumJson for $name {
+            #[allow(unused_variables)]
+            fn deserialize_field<B> (value: &$crate::encoding::serialize::json::reexport::Value,
+                                        buffer: & mut B,
+                                        from: $crate::encoding::Offset,
+                                        to: $crate::encoding::Offset )
+                -> Result<(), Box<::std::error::Error>>
+                where B: $crate::encoding::serialize::WriteBufferWrapper
+            {
+                use $crate::encoding::serialize::json::ExonumJsonDeserialize;
+                let structure = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+                buffer.write(from, to, structure);
+                Ok(())
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn serialize_field(&self)
+                -> Result<$crate::encoding::serialize::json::reexport::Value,
+                          Box<::std::error::Error + Send + Sync>>
+            {
+                use $crate::encoding::serialize::json::reexport::Value;
+                let mut map = $crate::encoding::serialize::json::reexport::Map::new();
+                $(
+                    map.insert(stringify!($field_name).to_string(),
+                        self.$field_name().serialize_field()?);
+                )*
+                // Only the optional fields present in the layout are serialized,
+                // so the value is restored with the same layout.
+                let mut present = self.version().saturating_sub(1);
+                $(
+                    if present > 0 {
+                        present -= 1;
+                        map.insert(stringify!($opt_name).to_string(),
+                            self.$opt_name().serialize_field()?);
+                    }
+                )*
+                Ok(Value::Object(map))
+            }
+        }
+
+        impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
+            #[allow(unused_imports, unused_mut, unused_variables)]
+            fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
+                -> Result<Self, Box<::std::error::Error>> {
+                use $crate::encoding::serialize::json::ExonumJson as ExonumJson;
+                let mut buf = vec![0; $name::__ex_header_size() as usize];
+                let _obj = value.as_object().ok_or("Can't cast json as object.")?;
+                __ex_for_each_field!(
+                    __ex_deserialize_field, (_obj, buf),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                let optional_fields: &[&str] = &[$(stringify!($opt_name)),*];
+                let present = optional_fields
+                    .iter()
+                    .take_while(|name| _obj.contains_key(**name))
+                    .count();
+                if optional_fields[present..].iter().any(|name| _obj.contains_key(*name)) {
+                    return Err("Optional fields should be added in the declaration order.".into());
+                }
+                let version = 1 + present as u8;
+                let optional_header_size = $name::__ex_optional_header_size(version);
+                let required_end = buf.len() as $crate::encoding::Offset;
+                let base = $name::__ex_append_optional_header(&mut buf, version);
+                __ex_for_each_field!(
+                    @inner __ex_deserialize_optional_field
+                    (_obj, buf, base, optional_header_size) (0);
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                Ok($name { raw: buf, required_end })
+            }
+        }
+
+        impl<'de> $crate::encoding::serialize::reexport::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                where D: $crate::encoding::serialize::reexport::Deserializer<'de>
+            {
+                use $crate::encoding::serialize::json::reexport::Value;
+                use $crate::encoding::serialize::reexport::{DeError, Deserialize};
+                let value = <Value as Deserialize>::deserialize(deserializer)?;
+                <Self as $crate::encoding::serialize::json::ExonumJsonDeserialize>::deserialize(
+                    &value).map_err(|_| D::Error::custom("Can not deserialize value."))
+            }
+        }
+
+        impl $crate::encoding::serialize::reexport::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where S: $crate::encoding::serialize::reexport::Serializer
+            {
+                use $crate::encoding::serialize::reexport::SerError;
+                use $crate::encoding::serialize::json::ExonumJson;
+                self.serialize_field()
+                    .map_err(|_| S::Error::custom(
+                                concat!("Can not serialize structure: ", stringify!($name))))?
+                    .serialize(serializer)
+            }
+        }
+    )
+}
+
+/// `encoding_enum!` macro implements a tagged enumeration that can be used as a field
+/// of `encoding_struct!` and `message!` types or saved in the Exonum blockchain directly.
+///
+/// Each variant has an explicit one-byte tag and may hold a single value. Values must be
+/// owned types implementing [`Field`] and [`ExonumJson`], e.g., integers, `Vec<T>`
+/// or types declared with `encoding_struct!`.
+///
+/// The enumeration is stored as a segment field: the segment contains the tag
+/// of the variant followed by its value laid out as a structure with a single field.
+/// [`Field::check`] rejects unknown tags and values of a wrong size.
+///
+/// In JSON the enumeration is represented as an object with the name of the variant
+/// in the `type` field and its value, if any, in the `value` field.
+///
 /// [`Field`]: ./encoding/trait.Field.html
+/// [`Field::check`]: ./encoding/trait.Field.html#tymethod.check
 /// [`ExonumJson`]: ./encoding/serialize/json/trait.ExonumJson.html
-/// [`StorageValue`]: ./storage/trait.StorageValue.html
-/// [`transactions!`]: macro.transactions.html
 ///
 /// # Examples
 ///
@@ -41,150 +749,182 @@
 /// #[macro_use] extern crate exonum;
 ///
 /// encoding_struct! {
-///     struct SaveTwoIntegers {
-///         first: u64,
-///         second: u64,
+///     struct Transfer {
+///         amount: u64,
+///         seed: u64,
+///     }
+/// }
+///
+/// encoding_enum! {
+///     enum Action {
+///         Skip = 0,
+///         Transfer(Transfer) = 1,
+///     }
+/// }
+///
+/// encoding_struct! {
+///     struct Step {
+///         action: Action,
+///         comment: Option<Vec<u8>>,
 ///     }
 /// }
 ///
 /// # fn main() {
-/// let s = SaveTwoIntegers::new(1, 2);
-/// println!("Two integers: {:?}", s);
+/// let step = Step::new(Action::Transfer(Transfer::new(10, 1)), None);
+/// assert_eq!(step.action().tag(), 1);
+/// assert_eq!(step.comment(), None);
 /// # }
 /// ```
 #[macro_export]
-macro_rules! encoding_struct {
+macro_rules! encoding_enum {
     (
     $(#[$attr:meta])*
-    struct $name:ident {
+    enum $name:ident {
         $(
-        $(#[$field_attr:meta])*
-        $field_name:ident : $field_type:ty
+        $(#[$variant_attr:meta])*
+        $variant:ident $(($variant_type:ty))* = $tag:expr
         ),*
         $(,)*
     }) => (
-        #[derive(Clone, PartialEq)]
+        #[derive(Clone, PartialEq, Debug)]
         $(#[$attr])*
-        pub struct $name {
-            raw: Vec<u8>
+        pub enum $name {
+            $(
+            $(#[$variant_attr])*
+            $variant $(($variant_type))*,
+            )*
+        }
+
+        #[cfg_attr(feature = "cargo-clippy", allow(single_match))]
+        #[allow(unreachable_patterns)]
+        impl $name {
+            /// Returns the tag of the variant.
+            pub fn tag(&self) -> u8 {
+                $(
+                    __ex_enum_variant!(@tag (self, $name, $variant, $tag), ($($variant_type)*));
+                )*
+                unreachable!()
+            }
+
+            fn __ex_to_bytes(&self) -> Vec<u8> {
+                let mut bytes = Vec::new();
+                $(
+                    __ex_enum_variant!(
+                        @write (self, bytes, $name, $variant, $tag), ($($variant_type)*)
+                    );
+                )*
+                bytes
+            }
+
+            // Bytes should be checked with `__ex_check_bytes` beforehand.
+            #[allow(unsafe_code)]
+            unsafe fn __ex_from_bytes(bytes: &[u8]) -> Self {
+                let tag = bytes[0];
+                $(
+                    __ex_enum_variant!(
+                        @read (tag, bytes, $name, $variant, $tag), ($($variant_type)*)
+                    );
+                )*
+                panic!("Unknown tag {} of {}", tag, stringify!($name))
+            }
+
+            fn __ex_check_bytes(bytes: &[u8])
+                -> ::std::result::Result<(), $crate::encoding::Error>
+            {
+                if bytes.is_empty() {
+                    return Err($crate::encoding::Error::UnexpectedlyShortPayload {
+                        actual_size: 0,
+                        minimum_size: 1,
+                    });
+                }
+                let tag = bytes[0];
+                $(
+                    __ex_enum_variant!(@check (tag, bytes, $tag), ($($variant_type)*));
+                )*
+                Err($crate::encoding::Error::IncorrectEnumTag { position: 0, value: tag })
+            }
+
+            /// Returns the descriptor of the binary layout of the enumeration.
+            pub fn schema() -> $crate::encoding::schema::EnumSchema {
+                let mut variants = Vec::new();
+                $(
+                    __ex_enum_variant!(
+                        @schema (variants, $variant, $tag), ($($variant_type)*)
+                    );
+                )*
+                $crate::encoding::schema::EnumSchema {
+                    name: stringify!($name).to_owned(),
+                    variants,
+                }
+            }
         }
 
-        // Re-implement `Field` for `encoding_struct!`
-        // to write fields in place of another structure
         #[allow(unsafe_code)]
         impl<'a> $crate::encoding::Field<'a> for $name {
             unsafe fn read(buffer: &'a [u8],
                             from: $crate::encoding::Offset,
                             to: $crate::encoding::Offset) -> Self {
-                let vec: Vec<u8> = $crate::encoding::Field::read(buffer, from, to);
-                $crate::storage::StorageValue::from_bytes(::std::borrow::Cow::Owned(vec))
+                let bytes: &[u8] = $crate::encoding::Field::read(buffer, from, to);
+                $name::__ex_from_bytes(bytes)
             }
 
             fn write(&self,
                             buffer: &mut Vec<u8>,
                             from: $crate::encoding::Offset,
                             to: $crate::encoding::Offset) {
-                $crate::encoding::Field::write(&self.raw, buffer, from, to);
+                $crate::encoding::Field::write(&self.__ex_to_bytes(), buffer, from, to);
             }
 
-            #[allow(unused_variables)]
-            #[allow(unused_comparisons)]
             fn check(buffer: &'a [u8],
-                        from_st_val: $crate::encoding::CheckedOffset,
-                        to_st_val: $crate::encoding::CheckedOffset,
+                        from: $crate::encoding::CheckedOffset,
+                        to: $crate::encoding::CheckedOffset,
                         latest_segment: $crate::encoding::CheckedOffset)
                 -> $crate::encoding::Result
             {
-                let latest_segment_origin = <&[u8] as $crate::encoding::Field>::check(
-                    buffer, from_st_val, to_st_val, latest_segment)?;
-                let vec: &[u8] = unsafe{ $crate::encoding::Field::read(
+                let latest_segment = <&[u8] as $crate::encoding::Field>::check(
+                    buffer, from, to, latest_segment)?;
+                let bytes: &[u8] = unsafe { $crate::encoding::Field::read(
                     buffer,
-                    from_st_val.unchecked_offset(),
-                    to_st_val.unchecked_offset())};
-                let latest_segment: $crate::encoding::CheckedOffset =
-                    $name::__ex_header_size().into();
+                    from.unchecked_offset(),
+                    to.unchecked_offset())};
+                $name::__ex_check_bytes(bytes)?;
+                Ok(latest_segment)
+            }
 
-                if vec.len() < $name::__ex_header_size() as usize {
-                    return Err($crate::encoding::Error::UnexpectedlyShortPayload{
-                        actual_size: vec.len() as $crate::encoding::Offset,
-                        minimum_size: $name::__ex_header_size() as $crate::encoding::Offset
-                    })
+            fn collect_schemas(schemas: &mut $crate::encoding::schema::TypeSchemas) {
+                if schemas.add_enum($name::schema()) {
+                    $(
+                        __ex_enum_variant!(@collect (schemas), ($($variant_type)*));
+                    )*
                 }
-
-                __ex_for_each_field!(
-                    __ex_struct_check_field, (latest_segment, vec),
-                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
-                );
-                Ok(latest_segment_origin)
             }
 
             fn field_size() -> $crate::encoding::Offset {
-                // We write `encoding_struct` as regular buffer,
-                // so real `field_size` is 8.
-                // TODO: maybe we should write it as sub structure in place?
-                // We could get benefit from it: we limit indirection
-                // in deserializing sub fields, by only one calculation (ECR-156).
-
-                // $body as $crate::encoding::Offset
-
+                // Enumeration is written as a regular buffer.
                 8 as $crate::encoding::Offset
             }
         }
 
         impl $crate::crypto::CryptoHash for $name {
             fn hash(&self) -> $crate::crypto::Hash {
-                $crate::crypto::hash(self.raw.as_ref())
+                $crate::crypto::hash(&self.__ex_to_bytes())
             }
         }
 
+        #[allow(unsafe_code)]
         impl $crate::storage::StorageValue for $name {
             fn into_bytes(self) -> Vec<u8> {
-                self.raw
+                self.__ex_to_bytes()
             }
 
             fn from_bytes(v: ::std::borrow::Cow<[u8]>) -> Self {
-                $name {
-                    raw: v.into_owned()
-                }
-            }
-        }
-
-        // TODO extract some fields like hash and from_raw into trait (ECR-156)
-        impl $name {
-            #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
-            #[allow(unused_imports, unused_mut)]
-
-            /// Creates a new instance with given parameters.
-            pub fn new($($field_name: $field_type,)*) -> $name {
-                let mut buf = vec![0; $name::__ex_header_size() as usize];
-                __ex_for_each_field!(
-                    __ex_struct_write_field, (buf),
-                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
-                );
-                $name { raw: buf }
-            }
-
-            __ex_for_each_field!(
-                __ex_struct_mk_field, (),
-                $( ($(#[$field_attr])*, $field_name, $field_type) )*
-            );
-
-            fn __ex_header_size() -> $crate::encoding::Offset {
-                __ex_header_size!($($field_type),*)
-            }
-        }
-
-        impl ::std::fmt::Debug for $name {
-            fn fmt(&self, fmt: &mut ::std::fmt::Formatter)
-                -> Result<(), ::std::fmt::Error> {
-                fmt.debug_struct(stringify!($name))
-                 $(.field(stringify!($field_name), &self.$field_name()))*
-                   .finish()
+                unsafe { $name::__ex_from_bytes(v.as_ref()) }
             }
         }
 
+        #[cfg_attr(feature = "cargo-clippy", allow(single_match))]
+        #[allow(unreachable_patterns)]
         impl $crate::encoding::serialize::json::ExonumJson for $name {
-            #[allow(unused_variables)]
             fn deserialize_field<B> (value: &$crate::encoding::serialize::json::reexport::Value,
                                         buffer: & mut B,
                                         from: $crate::encoding::Offset,
@@ -193,16 +933,11 @@ macro_rules! encoding_struct {
                 where B: $crate::encoding::serialize::WriteBufferWrapper
             {
                 use $crate::encoding::serialize::json::ExonumJsonDeserialize;
-                // deserialize full field
-                let structure = <Self as ExonumJsonDeserialize>::deserialize(value)?;
-                // then write it
-                buffer.write(from, to, structure);
-
+                let value = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+                buffer.write(from, to, value);
                 Ok(())
             }
 
-
-            #[allow(unused_mut)]
             fn serialize_field(&self)
                 -> Result<$crate::encoding::serialize::json::reexport::Value,
                           Box<::std::error::Error + Send + Sync>>
@@ -210,28 +945,30 @@ macro_rules! encoding_struct {
                 use $crate::encoding::serialize::json::reexport::Value;
                 let mut map = $crate::encoding::serialize::json::reexport::Map::new();
                 $(
-                    map.insert(stringify!($field_name).to_string(),
-                        self.$field_name().serialize_field()?);
+                    __ex_enum_variant!(
+                        @serialize (self, map, $name, $variant), ($($variant_type)*)
+                    );
                 )*
                 Ok(Value::Object(map))
             }
         }
+
         impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
-            #[allow(unused_imports, unused_mut)]
             fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
                 -> Result<Self, Box<::std::error::Error>> {
-                use $crate::encoding::serialize::json::ExonumJson as ExonumJson;
-                let mut buf = vec![0; $name::__ex_header_size() as usize];
-                let _obj = value.as_object().ok_or("Can't cast json as object.")?;
-                __ex_for_each_field!(
-                    __ex_deserialize_field, (_obj, buf),
-                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
-                );
-                Ok($name { raw: buf })
+                let obj = value.as_object().ok_or("Can't cast json as object.")?;
+                let variant = obj.get("type")
+                    .and_then(|variant| variant.as_str())
+                    .ok_or("Can't get variant type from json.")?;
+                $(
+                    __ex_enum_variant!(
+                        @deserialize (obj, variant, $name, $variant), ($($variant_type)*)
+                    );
+                )*
+                Err(format!("Unknown variant {} of {}", variant, stringify!($name)).into())
             }
         }
 
-        // TODO: Rewrite Deserialize and Serialize implementation (ECR-156)
         impl<'de> $crate::encoding::serialize::reexport::Deserialize<'de> for $name {
             fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
                 where D: $crate::encoding::serialize::reexport::Deserializer<'de>
@@ -240,7 +977,7 @@ macro_rules! encoding_struct {
                 use $crate::encoding::serialize::reexport::{DeError, Deserialize};
                 let value = <Value as Deserialize>::deserialize(deserializer)?;
                 <Self as $crate::encoding::serialize::json::ExonumJsonDeserialize>::deserialize(
-                    &value).map_err(|_| D::Error::custom("Can not deserialize value."))
+                    &value).map_err(|e| D::Error::custom(e.to_string()))
             }
         }
 
@@ -252,13 +989,174 @@ macro_rules! encoding_struct {
                 use $crate::encoding::serialize::json::ExonumJson;
                 self.serialize_field()
                     .map_err(|_| S::Error::custom(
-                                concat!("Can not serialize structure: ", stringify!($name))))?
+                                concat!("Can not serialize enumeration: ", stringify!($name))))?
                     .serialize(serializer)
             }
         }
     )
 }
 
+// Generates the code handling a single variant of `encoding_enum!`. Variants without
+// a value are matched by the empty type list `()`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_enum_variant {
+    (@schema ($variants:ident, $variant:ident, $tag:expr), ()) => {
+        $variants.push($crate::encoding::schema::VariantSchema::new(
+            stringify!($variant), $tag, None
+        ));
+    };
+    (@schema ($variants:ident, $variant:ident, $tag:expr), ($variant_type:ty)) => {
+        $variants.push($crate::encoding::schema::VariantSchema::new(
+            stringify!($variant), $tag, Some(stringify!($variant_type))
+        ));
+    };
+
+    (@collect ($schemas:ident), ()) => {};
+    (@collect ($schemas:ident), ($variant_type:ty)) => {
+        <$variant_type as $crate::encoding::Field>::collect_schemas($schemas);
+    };
+
+    (@tag ($this:expr, $name:ident, $variant:ident, $tag:expr), ()) => {
+        match *$this {
+            $name::$variant => return $tag,
+            _ => {}
+        }
+    };
+    (@tag ($this:expr, $name:ident, $variant:ident, $tag:expr), ($variant_type:ty)) => {
+        match *$this {
+            $name::$variant(_) => return $tag,
+            _ => {}
+        }
+    };
+
+    (@write ($this:expr, $bytes:ident, $name:ident, $variant:ident, $tag:expr), ()) => {
+        match *$this {
+            $name::$variant => $bytes.push($tag),
+            _ => {}
+        }
+    };
+    (
+        @write ($this:expr, $bytes:ident, $name:ident, $variant:ident, $tag:expr),
+        ($variant_type:ty)
+    ) => {
+        match *$this {
+            $name::$variant(ref value) => {
+                let size = <$variant_type as $crate::encoding::Field>::field_size();
+                let mut payload = vec![0; size as usize];
+                $crate::encoding::Field::write(value, &mut payload, 0, size);
+                $bytes.push($tag);
+                $bytes.extend_from_slice(&payload);
+            }
+            _ => {}
+        }
+    };
+
+    (@read ($tag_var:ident, $bytes:ident, $name:ident, $variant:ident, $tag:expr), ()) => {
+        if $tag_var == $tag {
+            return $name::$variant;
+        }
+    };
+    (
+        @read ($tag_var:ident, $bytes:ident, $name:ident, $variant:ident, $tag:expr),
+        ($variant_type:ty)
+    ) => {
+        if $tag_var == $tag {
+            let size = <$variant_type as $crate::encoding::Field>::field_size();
+            return $name::$variant($crate::encoding::Field::read(&$bytes[1..], 0, size));
+        }
+    };
+
+    (@check ($tag_var:ident, $bytes:ident, $tag:expr), ()) => {
+        if $tag_var == $tag {
+            return if $bytes.len() == 1 {
+                Ok(())
+            } else {
+                Err($crate::encoding::Error::IncorrectEnumValueSize {
+                    position: 1,
+                    actual_size: ($bytes.len() - 1) as $crate::encoding::Offset,
+                    expected_size: 0,
+                })
+            };
+        }
+    };
+    (@check ($tag_var:ident, $bytes:ident, $tag:expr), ($variant_type:ty)) => {
+        if $tag_var == $tag {
+            let payload = &$bytes[1..];
+            let size = <$variant_type as $crate::encoding::Field>::field_size();
+            if payload.len() < size as usize {
+                return Err($crate::encoding::Error::UnexpectedlyShortPayload {
+                    actual_size: payload.len() as $crate::encoding::Offset,
+                    minimum_size: size,
+                });
+            }
+            let end = <$variant_type as $crate::encoding::Field>::check(
+                payload,
+                0.into(),
+                size.into(),
+                size.into(),
+            )?.unchecked_offset();
+            return if end as usize == payload.len() {
+                Ok(())
+            } else {
+                Err($crate::encoding::Error::IncorrectEnumValueSize {
+                    position: 1,
+                    actual_size: payload.len() as $crate::encoding::Offset,
+                    expected_size: end,
+                })
+            };
+        }
+    };
+
+    (@serialize ($this:expr, $map:ident, $name:ident, $variant:ident), ()) => {
+        match *$this {
+            $name::$variant => {
+                $map.insert(
+                    "type".to_owned(),
+                    $crate::encoding::serialize::json::reexport::Value::String(
+                        stringify!($variant).to_owned(),
+                    ),
+                );
+            }
+            _ => {}
+        }
+    };
+    (@serialize ($this:expr, $map:ident, $name:ident, $variant:ident), ($variant_type:ty)) => {
+        match *$this {
+            $name::$variant(ref value) => {
+                $map.insert(
+                    "type".to_owned(),
+                    $crate::encoding::serialize::json::reexport::Value::String(
+                        stringify!($variant).to_owned(),
+                    ),
+                );
+                $map.insert(
+                    "value".to_owned(),
+                    $crate::encoding::serialize::json::ExonumJson::serialize_field(value)?,
+                );
+            }
+            _ => {}
+        }
+    };
+
+    (@deserialize ($obj:ident, $variant_var:ident, $name:ident, $variant:ident), ()) => {
+        if $variant_var == stringify!($variant) {
+            return Ok($name::$variant);
+        }
+    };
+    (
+        @deserialize ($obj:ident, $variant_var:ident, $name:ident, $variant:ident),
+        ($variant_type:ty)
+    ) => {
+        if $variant_var == stringify!($variant) {
+            let value = $obj.get("value").ok_or("Can't get variant value from json.")?;
+            let value: $variant_type =
+                $crate::encoding::serialize::json::deserialize_owned_field(value)?;
+            return Ok($name::$variant(value));
+        }
+    };
+}
+
 /// This macro checks bounds of fields for structs with custom layout.
 #[macro_export]
 macro_rules! check_bounds {
@@ -359,7 +1257,7 @@ macro_rules! __ex_for_each_field {
 #[macro_export]
 macro_rules! __ex_struct_check_field {
     (
-        ($latest_segment:ident, $vec:ident),
+        ($latest_segment:ident, $vec:ident, $name:ident),
         $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
     ) => {
         let $latest_segment = <$field_type as $crate::encoding::Field>::check(
@@ -367,7 +1265,7 @@ macro_rules! __ex_struct_check_field {
             $from.into(),
             $to.into(),
             $latest_segment,
-        )?;
+        ).map_err(|e| e.in_field(stringify!($name), stringify!($field_name)))?;
     }
 }
 
@@ -412,3 +1310,85 @@ macro_rules! __ex_deserialize_field {
         <$field_type as ExonumJson>::deserialize_field(val, &mut $writer, $from, $to)?;
     }
 }
+
+// Checks an optional field of a versioned structure if it is present in the layout
+// of the value. `$from` and `$to` are relative to the header of the optional fields
+// starting at `$base`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_struct_check_optional_field {
+    (
+        ($latest_segment:ident, $vec:ident, $base:ident, $header_size:ident, $name:ident),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
+    ) => {
+        if $to <= $header_size {
+            $latest_segment = <$field_type as $crate::encoding::Field>::check(
+                &$vec,
+                ($base + $from).into(),
+                ($base + $to).into(),
+                $latest_segment,
+            ).map_err(|e| e.in_field(stringify!($name), stringify!($field_name)))?;
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_struct_mk_optional_field {
+    (
+        (),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
+    ) => {
+        $(#[$field_attr])*
+        ///
+        /// Returns the default value if the field is absent in the layout of the value.
+        #[allow(unsafe_code)]
+        pub fn $field_name(&self) -> $field_type {
+            use $crate::encoding::Field;
+            let (version, base) = self.__ex_optional_layout();
+            if $to <= Self::__ex_optional_header_size(version) {
+                unsafe {
+                    Field::read(&self.raw, base + $from, base + $to)
+                }
+            } else {
+                <$field_type as Default>::default()
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_deserialize_optional_field {
+    (
+        ($obj:ident, $writer:ident, $base:ident, $header_size:ident),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
+    ) => {
+        if $to <= $header_size {
+            let val = $obj.get(stringify!($field_name))
+                          .ok_or("Can't get object from json.")?;
+            <$field_type as ExonumJson>::deserialize_field(
+                val,
+                &mut $writer,
+                $base + $from,
+                $base + $to,
+            )?;
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_field_schema {
+    (
+        ($fields:ident),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
+    ) => {
+        $fields.push($crate::encoding::schema::FieldSchema::new(
+            stringify!($field_name),
+            stringify!($field_type),
+            $from,
+            $to,
+        ));
+    }
+}
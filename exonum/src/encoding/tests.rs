@@ -18,14 +18,17 @@ use bit_vec::BitVec;
 use chrono::Utc;
 use uuid::Uuid;
 
+use std::collections::BTreeMap;
 use std::net::SocketAddr;
 
-use crypto::{hash, gen_keypair};
+use crypto::{hash, gen_keypair, CryptoHash};
+use storage::{Database, MapIndex, MemoryDB, StorageValue};
 use blockchain::{self, BlockProof, Block};
 use messages::{RawMessage, Message, Connect, Propose, Prevote, Precommit, Status, BlockResponse,
                BlockRequest};
 use helpers::{Height, Round, ValidatorId, user_agent};
-use super::{Field, Offset};
+use super::{Error, Field, Offset};
+use super::serialize::json::{to_canonical_string, ExonumJson, ExonumJsonDeserialize};
 
 static VALIDATOR: ValidatorId = ValidatorId(65_123);
 static HEIGHT: Height = Height(123_123_123);
@@ -35,6 +38,8 @@ static ROUND: Round = Round(321_321_312);
 // This structures used to test deserialization,
 // so we should ignore unused `new` method.
 mod ignore_new {
+    use std::collections::BTreeMap;
+
     use crypto::Hash;
     encoding_struct! {
         struct Parent {
@@ -47,12 +52,78 @@ mod ignore_new {
             child: &Hash,
         }
     }
+
+    encoding_struct! {
+        struct Payment {
+            amount: u64,
+            memo: Option<Vec<u8>>,
+        }
+    }
+
+    encoding_enum! {
+        enum Action {
+            Skip = 0,
+            Pay(Payment) = 1,
+            Wait(u32) = 7,
+        }
+    }
+
+    encoding_struct! {
+        struct Step {
+            action: Action,
+            next: Option<Action>,
+        }
+    }
+
+    encoding_struct! {
+        struct Attributes {
+            salt: [u8; 4],
+            values: BTreeMap<u16, u64>,
+        }
+    }
+
+    encoding_struct! {
+        struct Entry {
+            flag: bool,
+            amount: u64,
+        }
+    }
+
+    encoding_struct! {
+        struct Wallet {
+            owner: &Hash,
+            history: Vec<Entry>,
+        }
+    }
+
+    pub mod v1 {
+        use crypto::Hash;
+
+        encoding_struct! {
+            struct Account {
+                owner: &Hash,
+                name: &str,
+            }
+        }
+    }
+
+    encoding_struct! {
+        struct Account {
+            owner: &Hash,
+            name: &str,
+        }
+        optional {
+            frozen: bool,
+            memo: Option<Vec<u8>>,
+        }
+    }
 }
 
 use self::ignore_new::*;
 
 #[test]
-#[should_panic(expected = "Found error in check: UnexpectedlyShortPayload")]
+#[should_panic(expected = "Found error in check: InField { path: Parent.child, \
+                           error: UnexpectedlyShortPayload")]
 fn test_zero_size_segment() {
     let buf = vec![8,0,0,0, // not overlap
                    0,0,0,0,0]; // but with zero size
@@ -61,7 +132,8 @@ fn test_zero_size_segment() {
 }
 
 #[test]
-#[should_panic(expected = "Found error in check: UnexpectedlyShortPayload")]
+#[should_panic(expected = "Found error in check: InField { path: Parent.child, \
+                           error: UnexpectedlyShortPayload")]
 fn test_incorrect_pointer() {
     let buf = vec![8,0,0,0, // not overlap
                    0,0,0,0,0]; // but with zero size
@@ -191,6 +263,433 @@ fn test_segments_of_arrays() {
     assert_eq!(buf.len(), 64 + v1.len() + v2.len() + v3.len() + 3 * 8);
 }
 
+#[test]
+fn test_option_segment() {
+    assert_write_check_read(Some(5u64), 8);
+    assert_write_check_read(None::<u64>, 8);
+    assert_write_check_read(Some(vec![1u8, 2, 3]), 8);
+    assert_write_check_read(Some(Some(Height(1))), 8);
+    assert_write_check_read(vec![None, Some(1u32), None], 8);
+
+    let mut buf = vec![0; 8];
+    Field::write(&Some(5u64), &mut buf, 0, 8);
+    assert_eq!(buf, vec![8, 0, 0, 0, 1, 0, 0, 0, 5, 0, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_option_incorrect_count() {
+    let buf = vec![8, 0, 0, 0, 2, 0, 0, 0, 1, 2];
+    match <Option<u8> as Field>::check(&buf, 0.into(), 8.into(), 8.into()) {
+        Err(Error::IncorrectSegmentSize { position: 8, value: 2 }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_option_json() {
+    let value = Some(Round(5)).serialize_field().unwrap();
+    assert_eq!(value, json!(5));
+    assert_eq!(None::<Round>.serialize_field().unwrap(), json!(null));
+    assert_eq!(Option::<Round>::deserialize(&value).unwrap(), Some(Round(5)));
+    assert_eq!(Option::<Round>::deserialize(&json!(null)).unwrap(), None);
+
+    // `u64` is represented as a string in fields.
+    let value = Some(Height(10)).serialize_field().unwrap();
+    assert_eq!(value, json!("10"));
+    assert_eq!(Option::<Height>::deserialize(&value).unwrap(), Some(Height(10)));
+    assert!(Option::<Height>::deserialize(&json!(true)).is_err());
+}
+
+#[test]
+fn test_enum_segment() {
+    assert_write_check_read(Action::Skip, 8);
+    assert_write_check_read(Action::Wait(100), 8);
+    assert_write_check_read(Action::Pay(Payment::new(10, Some(vec![1, 2]))), 8);
+    assert_write_check_read(Action::Pay(Payment::new(10, None)), 8);
+    assert_write_check_read(vec![Action::Skip, Action::Wait(1)], 8);
+
+    let step = Step::new(Action::Wait(3), Some(Action::Skip));
+    assert_eq!(step.action(), Action::Wait(3));
+    assert_eq!(step.next(), Some(Action::Skip));
+    assert_eq!(step.action().tag(), 7);
+
+    let mut buf = vec![0; 8];
+    Field::write(&Action::Wait(3), &mut buf, 0, 8);
+    assert_eq!(buf, vec![8, 0, 0, 0, 5, 0, 0, 0, 7, 3, 0, 0, 0]);
+}
+
+#[test]
+fn test_enum_incorrect_data() {
+    fn check(data: &[u8]) -> Result<(), Error> {
+        let mut buf = vec![8, 0, 0, 0, data.len() as u8, 0, 0, 0];
+        buf.extend_from_slice(data);
+        <Action as Field>::check(&buf, 0.into(), 8.into(), 8.into()).map(drop)
+    }
+
+    assert!(check(&[7, 3, 0, 0, 0]).is_ok());
+    match check(&[]) {
+        Err(Error::UnexpectedlyShortPayload { actual_size: 0, minimum_size: 1 }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+    match check(&[2]) {
+        Err(Error::IncorrectEnumTag { value: 2, .. }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+    match check(&[0, 1]) {
+        Err(Error::IncorrectEnumValueSize { actual_size: 1, expected_size: 0, .. }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+    match check(&[7, 3, 0]) {
+        Err(Error::UnexpectedlyShortPayload { actual_size: 2, minimum_size: 4 }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+    match check(&[7, 3, 0, 0, 0, 1]) {
+        Err(Error::IncorrectEnumValueSize { actual_size: 5, expected_size: 4, .. }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_enum_json() {
+    let action = Action::Pay(Payment::new(10, Some(vec![1, 2])));
+    let value = action.serialize_field().unwrap();
+    assert_eq!(
+        value,
+        json!({ "type": "Pay", "value": { "amount": "10", "memo": "0102" } })
+    );
+    assert_eq!(Action::deserialize(&value).unwrap(), action);
+
+    let value = Action::Skip.serialize_field().unwrap();
+    assert_eq!(value, json!({ "type": "Skip" }));
+    assert_eq!(Action::deserialize(&value).unwrap(), Action::Skip);
+
+    let step = Step::new(Action::Wait(3), None);
+    let value = step.serialize_field().unwrap();
+    assert_eq!(
+        value,
+        json!({ "action": { "type": "Wait", "value": 3 }, "next": null })
+    );
+    assert_eq!(Step::deserialize(&value).unwrap(), step);
+
+    assert!(Action::deserialize(&json!({ "type": "Jump" })).is_err());
+    assert!(Action::deserialize(&json!({ "type": "Wait" })).is_err());
+}
+
+#[test]
+fn test_array_field() {
+    assert_write_check_read([1u8, 2, 3, 4], 4);
+    assert_write_check_read([Height(1), Height(2)], 16);
+    assert_write_check_read([7u8; 32], 32);
+    assert_write_check_read(vec![[1u16, 2], [3, 4]], 8);
+    assert_write_check_read(Some([true, false]), 8);
+
+    let mut buf = vec![0; 4];
+    Field::write(&[1u16, 2], &mut buf, 0, 4);
+    assert_eq!(buf, vec![1, 0, 2, 0]);
+
+    let buf = vec![1, 2];
+    assert!(<[bool; 2] as Field>::check(&buf, 0.into(), 2.into(), 2.into()).is_err());
+}
+
+#[test]
+fn test_map_segment() {
+    let mut map = BTreeMap::new();
+    map.insert(3u32, vec![1u8, 2]);
+    map.insert(1, vec![]);
+    map.insert(2, vec![3]);
+    assert_write_check_read(map, 8);
+    assert_write_check_read(BTreeMap::<u64, bool>::new(), 8);
+
+    let mut map = BTreeMap::new();
+    map.insert(2u8, 20u8);
+    map.insert(1, 10);
+    let mut buf = vec![0; 8];
+    Field::write(&map, &mut buf, 0, 8);
+    assert_eq!(buf, vec![8, 0, 0, 0, 2, 0, 0, 0, 1, 10, 2, 20]);
+}
+
+#[test]
+fn test_map_incorrect_order() {
+    fn check(data: &[u8]) -> Result<(), Error> {
+        let mut buf = vec![8, 0, 0, 0, (data.len() / 2) as u8, 0, 0, 0];
+        buf.extend_from_slice(data);
+        <BTreeMap<u8, u8> as Field>::check(&buf, 0.into(), 8.into(), 8.into()).map(drop)
+    }
+
+    assert!(check(&[1, 10, 2, 20]).is_ok());
+    match check(&[2, 20, 1, 10]) {
+        Err(Error::IncorrectMapKeyOrder { position: 10 }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+    match check(&[1, 10, 1, 20]) {
+        Err(Error::IncorrectMapKeyOrder { position: 10 }) => {}
+        other => panic!("Unexpected check result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_map_and_array_json() {
+    let mut values = BTreeMap::new();
+    values.insert(2, 20);
+    values.insert(1, 10);
+    let attributes = Attributes::new([1, 2, 3, 4], values);
+
+    let value = attributes.serialize_field().unwrap();
+    assert_eq!(
+        value,
+        json!({ "salt": "01020304", "values": [[1, "10"], [2, "20"]] })
+    );
+    assert_eq!(Attributes::deserialize(&value).unwrap(), attributes);
+
+    let value = json!({ "salt": "010203", "values": [] });
+    assert!(Attributes::deserialize(&value).is_err());
+    let value = json!({ "salt": "01020304", "values": [[1, "10"], [1, "20"]] });
+    assert!(Attributes::deserialize(&value).is_err());
+    let value = json!({ "salt": "01020304", "values": [[1]] });
+    assert!(Attributes::deserialize(&value).is_err());
+}
+
+#[test]
+fn test_versioned_struct_upgrade() {
+    let owner = hash(&[1, 2, 3]);
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    {
+        let mut accounts = MapIndex::new("accounts", &mut fork);
+        accounts.put(&1_u64, v1::Account::new(&owner, "Alice"));
+    }
+    db.merge(fork.into_patch()).unwrap();
+
+    let snapshot = db.snapshot();
+    let accounts: MapIndex<_, u64, Account> = MapIndex::new("accounts", &snapshot);
+    let account = accounts.get(&1).unwrap();
+    assert_eq!(account.version(), 1);
+    assert_eq!(account.owner(), &owner);
+    assert_eq!(account.name(), "Alice");
+    assert_eq!(account.frozen(), false);
+    assert_eq!(account.memo(), None);
+
+    let upgraded = account.upgrade();
+    assert_eq!(upgraded.version(), 3);
+    assert_eq!(Account::latest_version(), 3);
+    assert_eq!(upgraded.owner(), &owner);
+    assert_eq!(upgraded.name(), "Alice");
+    assert_eq!(upgraded.frozen(), false);
+    assert_eq!(upgraded.memo(), None);
+    assert_ne!(upgraded.hash(), account.hash());
+
+    let account = Account::new(&owner, "Bob", true, Some(vec![1, 2]));
+    assert_eq!(account.upgrade(), account);
+    assert_eq!(account.frozen(), true);
+    assert_eq!(account.memo(), Some(vec![1, 2]));
+}
+
+#[test]
+fn test_versioned_struct_layout() {
+    let owner = hash(&[1, 2, 3]);
+    let old = v1::Account::new(&owner, "Bob").into_bytes();
+    let bytes = Account::new(&owner, "Bob", true, Some(vec![1, 2])).into_bytes();
+    // The optional fields follow the data of the required ones.
+    assert!(bytes.starts_with(&old));
+    // The block starts with the layout version followed by the `frozen` field.
+    assert_eq!(&bytes[old.len()..old.len() + 2], &[3, 1]);
+    // The previous declaration reads the required fields of new values.
+    assert_eq!(v1::Account::from_bytes(bytes.clone().into()).name(), "Bob");
+
+    // Values read back from bytes locate their optional fields as well.
+    let account = Account::from_bytes(bytes.into());
+    assert_eq!(account.version(), 3);
+    assert_eq!(account.frozen(), true);
+    assert_eq!(account.memo(), Some(vec![1, 2]));
+    // Malformed bytes do not panic and are read as the first version.
+    let malformed = Account::from_bytes(vec![1, 2, 3].into());
+    assert_eq!(malformed.version(), 1);
+    assert_eq!(malformed.memo(), None);
+}
+
+#[test]
+fn test_versioned_struct_check() {
+    let owner = hash(&[1, 2, 3]);
+    assert_write_check_read(v1::Account::new(&owner, "Alice"), 8);
+    assert_write_check_read(Account::new(&owner, "Bob", true, Some(vec![1, 2])), 8);
+
+    let old = v1::Account::new(&owner, "Alice");
+    let mut buffer = vec![0; 8];
+    Field::write(&old, &mut buffer, 0, 8);
+    <Account as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap();
+
+    let check = |trailer: &[u8]| {
+        let mut bytes = old.clone().into_bytes();
+        bytes.extend_from_slice(trailer);
+        let mut buffer = vec![0; 8];
+        Field::write(&bytes, &mut buffer, 0, 8);
+        <Account as Field>::check(&buffer, 0.into(), 8.into(), 8.into())
+    };
+    for version in &[1, 4] {
+        match check(&[*version]) {
+            Err(Error::UnsupportedStructVersion { position: 45, version: v })
+                if v == *version => {}
+            other => panic!("Unexpected check result: {:?}", other),
+        }
+    }
+    // Version 3 requires a longer header than the one written by version 2.
+    assert!(check(&[3, 1]).is_err());
+    check(&[2, 1]).unwrap();
+
+    let mut bytes = old.clone().into_bytes();
+    bytes.extend_from_slice(&[2, 1]);
+    let account = Account::from_bytes(bytes.into());
+    assert_eq!(account.version(), 2);
+    assert_eq!(account.frozen(), true);
+    assert_eq!(account.memo(), None);
+}
+
+#[test]
+fn test_struct_schema() {
+    use super::schema::FieldSchema;
+
+    let schema = Step::schema();
+    assert_eq!(schema.name, "Step");
+    assert_eq!(schema.header_size, 16);
+    assert_eq!(schema.version, None);
+    assert_eq!(
+        schema.fields,
+        vec![
+            FieldSchema::new("action", "Action", 0, 8),
+            FieldSchema::new("next", "Option<Action>", 8, 16),
+        ]
+    );
+    assert!(schema.optional_fields.is_empty());
+
+    let schema = Account::schema();
+    assert_eq!(schema.header_size, 40);
+    assert_eq!(schema.version, Some(3));
+    assert_eq!(
+        schema.fields,
+        vec![
+            FieldSchema::new("owner", "&Hash", 0, 32),
+            FieldSchema::new("name", "&str", 32, 40),
+        ]
+    );
+    assert_eq!(
+        schema.optional_fields,
+        vec![
+            FieldSchema::new("frozen", "bool", 0, 1),
+            FieldSchema::new("memo", "Option<Vec<u8>>", 1, 9),
+        ]
+    );
+}
+
+#[test]
+fn test_collect_schemas() {
+    use super::schema::TypeSchemas;
+
+    let mut schemas = TypeSchemas::new();
+    <Option<Step> as Field>::collect_schemas(&mut schemas);
+    assert_eq!(schemas.structs, vec![Step::schema(), Payment::schema()]);
+    assert_eq!(schemas.enums, vec![Action::schema()]);
+
+    // Already collected types are skipped.
+    <Vec<Payment> as Field>::collect_schemas(&mut schemas);
+    assert_eq!(schemas.structs.len(), 2);
+
+    let mut schemas = TypeSchemas::new();
+    <BTreeMap<u16, Attributes> as Field>::collect_schemas(&mut schemas);
+    assert_eq!(schemas.structs, vec![Attributes::schema()]);
+    assert!(schemas.enums.is_empty());
+}
+
+#[test]
+fn test_versioned_struct_json() {
+    let owner = hash(&[1, 2, 3]);
+    let account = Account::new(&owner, "Bob", true, None);
+    let value = account.serialize_field().unwrap();
+    assert_eq!(
+        value,
+        json!({ "owner": owner, "name": "Bob", "frozen": true, "memo": null })
+    );
+    assert_eq!(Account::deserialize(&value).unwrap(), account);
+
+    // Values restored from JSON keep their layout.
+    let old = v1::Account::new(&owner, "Alice");
+    let value = old.serialize_field().unwrap();
+    let account = Account::deserialize(&value).unwrap();
+    assert_eq!(account.version(), 1);
+    assert_eq!(account.serialize_field().unwrap(), value);
+    assert_eq!(account.into_bytes(), old.into_bytes());
+
+    let value = json!({ "owner": owner, "name": "Alice", "frozen": true });
+    assert_eq!(Account::deserialize(&value).unwrap().version(), 2);
+    let value = json!({ "owner": owner, "name": "Alice", "memo": null });
+    assert!(Account::deserialize(&value).is_err());
+}
+
+#[test]
+fn test_canonical_json() {
+    // 64-bit integers are strings both in fields and in vectors.
+    let vec = vec![0, u64::max_value()];
+    let value = vec.serialize_field().unwrap();
+    assert_eq!(value, json!(["0", "18446744073709551615"]));
+    assert_eq!(Vec::<u64>::deserialize(&value).unwrap(), vec);
+    assert_eq!(u64::deserialize(&json!("10")).unwrap(), 10);
+    assert!(u64::deserialize(&json!(10)).is_err());
+
+    // Binary data is hex-encoded.
+    let value = vec![1u8, 255].serialize_field().unwrap();
+    assert_eq!(value, json!("01ff"));
+    assert_eq!(Vec::<u8>::deserialize(&value).unwrap(), vec![1, 255]);
+    assert_eq!(<[u8; 2]>::deserialize(&json!("01FF")).unwrap(), [1, 255]);
+
+    // Non-canonical values are rejected rather than truncated or normalized.
+    assert!(u8::deserialize(&json!(256)).is_err());
+    assert!(i8::deserialize(&json!(-129)).is_err());
+    assert!(u16::deserialize(&json!(1.5)).is_err());
+    assert!(Option::<Round>::deserialize(&json!(-1)).is_err());
+    for string in &["+1", "01", "-0", "", "1e3", " 1"] {
+        assert!(i64::deserialize(&json!(string)).is_err(), "{}", string);
+    }
+    assert!(Option::<BitVec>::deserialize(&json!("101")).is_err());
+    let value = json!("10100000");
+    let bits = Option::<BitVec>::deserialize(&value).unwrap();
+    assert_eq!(bits.serialize_field().unwrap(), value);
+
+    let value = json!({ "b": [{ "d": 1, "c": "2" }], "a": null });
+    assert_eq!(
+        to_canonical_string(&value),
+        r#"{"a":null,"b":[{"c":"2","d":1}]}"#
+    );
+}
+
+#[test]
+fn test_error_field_path() {
+    let entry = |flag: u8| Entry::from_bytes(vec![flag, 10, 0, 0, 0, 0, 0, 0, 0].into());
+    let wallet = Wallet::new(&hash(&[1]), vec![entry(1), entry(0), entry(1), entry(2)]);
+    let mut buffer = vec![0; 8];
+    Field::write(&wallet, &mut buffer, 0, 8);
+
+    let error = <Wallet as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap_err();
+    {
+        let path = error.field_path().unwrap();
+        assert_eq!(path.to_string(), "Wallet.history[3].flag");
+        assert_eq!(path.root(), "Wallet");
+        assert_eq!(path.field(), Some("flag"));
+    }
+    match *error.root_cause() {
+        Error::IncorrectBoolean { value: 2, .. } => {}
+        ref other => panic!("Unexpected error: {:?}", other),
+    }
+    assert!(error.to_string().starts_with(
+        "Error in field `Wallet.history[3].flag`: Incorrect boolean value",
+    ));
+
+    // Errors in collections outside of structures start with the index.
+    let entries = vec![entry(1), entry(3)];
+    let mut buffer = vec![0; 8];
+    Field::write(&entries, &mut buffer, 0, 8);
+    let error = <Vec<Entry> as Field>::check(&buffer, 0.into(), 8.into(), 8.into()).unwrap_err();
+    assert_eq!(error.field_path().unwrap().to_string(), "[1].flag");
+}
+
 fn assert_write_check_read<T>(input: T, header_size: Offset)
 where
     T: for<'r> Field<'r> + PartialEq + ::std::fmt::Debug,
@@ -24,7 +24,7 @@ use super::internal::{CollectedCommand, Feedback};
 use super::clap_backend::ClapBackend;
 use super::ServiceFactory;
 use super::details::{Run, RunDev, Finalize, GenerateNodeConfig, GenerateCommonConfig,
-                     GenerateTestnet};
+                     GenerateTestnet, ConsensusTimeline};
 use super::keys;
 use super::CommandName;
 
@@ -128,6 +128,10 @@ impl NodeBuilder {
             CollectedCommand::new(Box::new(GenerateCommonConfig)),
         );
         commands.insert(Finalize::name(), CollectedCommand::new(Box::new(Finalize)));
+        commands.insert(
+            ConsensusTimeline::name(),
+            CollectedCommand::new(Box::new(ConsensusTimeline)),
+        );
         commands
     }
 }
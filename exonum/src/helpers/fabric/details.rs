@@ -26,9 +26,10 @@ use toml::Value;
 
 use blockchain::GenesisConfig;
 use blockchain::config::ValidatorKeys;
-use helpers::generate_testnet_config;
+use helpers::{generate_testnet_config, Height};
 use helpers::config::ConfigFile;
 use node::{NodeApiConfig, NodeConfig};
+use node::tracing;
 use storage::{Database, RocksDB, DbOptions};
 use crypto;
 use super::internal::{CollectedCommand, Command, Feedback};
@@ -425,6 +426,7 @@ impl Command for GenerateNodeConfig {
         let validator_keys = ValidatorKeys {
             consensus_key: consensus_public_key,
             service_key: service_public_key,
+            voting_power: None,
         };
         let node_pub_config = NodePublicConfig {
             addr: addr.0,
@@ -616,6 +618,7 @@ impl Command for Finalize {
                 mempool: Default::default(),
                 services_configs: Default::default(),
                 database: Default::default(),
+                consensus_trace: None,
             }
         };
 
@@ -716,3 +719,70 @@ impl Command for GenerateTestnet {
         Feedback::None
     }
 }
+
+/// Command for the reconstruction of the consensus timeline of a height
+/// from the trace files of several nodes.
+pub struct ConsensusTimeline;
+
+impl ConsensusTimeline {
+    /// Returns the name of the `ConsensusTimeline` command.
+    pub fn name() -> CommandName {
+        "consensus-timeline"
+    }
+}
+
+impl Command for ConsensusTimeline {
+    fn args(&self) -> Vec<Argument> {
+        vec![
+            Argument::new_named(
+                "HEIGHT",
+                true,
+                "Height to reconstruct the timeline of.",
+                None,
+                "height",
+                false
+            ),
+            Argument::new_named(
+                "TRACE_FILES",
+                true,
+                "Path to the consensus trace file of a node.",
+                "t",
+                "trace",
+                true
+            ),
+        ]
+    }
+
+    fn name(&self) -> CommandName {
+        Self::name()
+    }
+
+    fn about(&self) -> &str {
+        "Prints consensus events of the given height from the trace files of several nodes"
+    }
+
+    fn execute(
+        &self,
+        _commands: &HashMap<CommandName, CollectedCommand>,
+        context: Context,
+        _: &Fn(Context) -> Context,
+    ) -> Feedback {
+        let height = context.arg::<u64>("HEIGHT").expect("height as int");
+        let paths = context.arg_multiple::<String>("TRACE_FILES").expect(
+            "expected trace files",
+        );
+
+        let mut events = Vec::new();
+        for path in paths {
+            let file_events = tracing::read_events_from_file(&path).unwrap_or_else(|e| {
+                panic!("Unable to read consensus trace file {}: {}", path, e)
+            });
+            events.extend(file_events);
+        }
+
+        for event in tracing::timeline(events, Height(height)) {
+            println!("{}", event);
+        }
+        Feedback::None
+    }
+}
@@ -25,7 +25,8 @@ use failure;
 use blockchain::Service;
 
 pub use self::builder::NodeBuilder;
-pub use self::details::{Run, Finalize, GenerateNodeConfig, GenerateCommonConfig, GenerateTestnet};
+pub use self::details::{Run, Finalize, GenerateNodeConfig, GenerateCommonConfig, GenerateTestnet,
+                        ConsensusTimeline};
 pub use self::shared::{AbstractConfig, NodePublicConfig, CommonConfigTemplate, NodePrivateConfig};
 pub use self::context_key::ContextKey;
 
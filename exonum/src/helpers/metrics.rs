@@ -13,18 +13,523 @@
 // limitations under the License.
 
 //! Utilities for collecting metrics.
+//!
+//! Metrics are collected in the global [`Registry`] and exported in the
+//! [Prometheus text format][format] at the `/metrics` endpoint of the private API.
+//! The registry supports counters, gauges and histograms; each metric may have several
+//! series distinguished by their labels.
+//!
+//! A series is registered once and updated through the returned handle, i.e.,
+//! [`Counter`], [`Gauge`] or [`Histogram`], so updates neither lock the registry nor
+//! allocate. Counters and gauges are updated with atomic operations; a histogram locks
+//! only its own series. The handles of the metrics reported by the node are
+//! available via [`node_metrics`].
+//!
+//! [`Registry`]: struct.Registry.html
+//! [`Counter`]: struct.Counter.html
+//! [`Gauge`]: struct.Gauge.html
+//! [`Histogram`]: struct.Histogram.html
+//! [`node_metrics`]: fn.node_metrics.html
+//! [format]: https://prometheus.io/docs/instrumenting/exposition_formats/
+//!
+//! # Examples
+//!
+//! ```
+//! use exonum::helpers::metrics::Registry;
+//!
+//! let registry = Registry::global();
+//! let requests = registry.counter(
+//!     "my_service_requests_total",
+//!     "Number of processed requests",
+//!     &[("method", "transfer")],
+//! );
+//! requests.inc();
+//! assert!(registry.encode().contains("my_service_requests_total{method=\"transfer\"} 1"));
+//! ```
 
-use std::time::SystemTime;
+use std::collections::BTreeMap;
+use std::f64;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Number of transactions in the pool.
+pub const MEMPOOL_SIZE: &str = "exonum_node_mempool_size";
+/// Height of the blockchain, i.e. the height of the next block.
+pub const HEIGHT: &str = "exonum_node_height";
+/// Current consensus round.
+pub const ROUND: &str = "exonum_node_round";
+/// Number of connected peers.
+pub const PEERS: &str = "exonum_node_peers";
+/// Time from the start of a height to the commit of the block.
+pub const BLOCK_COMMIT_LATENCY: &str = "exonum_node_block_commit_latency_seconds";
+/// Time of the execution of a single transaction, labeled by `service_id`.
+pub const TX_EXECUTION_TIME: &str = "exonum_blockchain_tx_execution_seconds";
+/// Time of writing a patch to RocksDB.
+pub const ROCKSDB_WRITE_TIME: &str = "exonum_storage_rocksdb_write_seconds";
+/// Number of messages received from the network, labeled by `message_type`.
+pub const MESSAGES_RECEIVED: &str = "exonum_network_messages_received_total";
+/// Number of messages sent to the network, labeled by `message_type`.
+pub const MESSAGES_SENT: &str = "exonum_network_messages_sent_total";
+
+/// Values of the `message_type` label of the network metrics.
+pub const MESSAGE_TYPES: &[&str] = &[
+    "connect",
+    "status",
+    "propose",
+    "prevote",
+    "precommit",
+    "block_response",
+    "propose_request",
+    "transactions_request",
+    "prevotes_request",
+    "peers_request",
+    "block_request",
+    "transaction",
+    "unknown",
+];
+
+/// Default buckets of histograms measuring durations, in seconds.
+pub const DEFAULT_BUCKETS: &[f64] = &[
+    0.0001,
+    0.0005,
+    0.001,
+    0.005,
+    0.01,
+    0.05,
+    0.1,
+    0.5,
+    1.0,
+    5.0,
+    10.0,
+];
+
+lazy_static! {
+    static ref GLOBAL_REGISTRY: Registry = Registry::new();
+    static ref NODE_METRICS: NodeMetrics = NodeMetrics::new(&GLOBAL_REGISTRY);
+}
+
+/// Returns the handles of the metrics reported by the node, which are registered
+/// in the global registry.
+pub fn node_metrics() -> &'static NodeMetrics {
+    &NODE_METRICS
+}
+
+/// Handles of the metrics reported by the node.
+#[derive(Debug)]
+pub struct NodeMetrics {
+    /// Number of transactions in the pool.
+    pub mempool_size: Gauge,
+    /// Height of the blockchain, i.e. the height of the next block.
+    pub height: Gauge,
+    /// Current consensus round.
+    pub round: Gauge,
+    /// Number of connected peers.
+    pub peers: Gauge,
+    /// Time from the start of a height to the commit of the block.
+    pub block_commit_latency: Histogram,
+    /// Time of writing a patch to RocksDB.
+    pub rocksdb_write_time: Histogram,
+    /// Number of messages received from the network.
+    pub messages_received: MessageCounters,
+    /// Number of messages sent to the network.
+    pub messages_sent: MessageCounters,
+}
+
+impl NodeMetrics {
+    fn new(registry: &Registry) -> Self {
+        NodeMetrics {
+            mempool_size: registry.gauge(MEMPOOL_SIZE, "Number of transactions in the pool", &[]),
+            height: registry.gauge(HEIGHT, "Height of the next block", &[]),
+            round: registry.gauge(ROUND, "Current consensus round", &[]),
+            peers: registry.gauge(PEERS, "Number of connected peers", &[]),
+            block_commit_latency: registry.histogram(
+                BLOCK_COMMIT_LATENCY,
+                "Time from the start of a height to the commit of the block",
+                DEFAULT_BUCKETS,
+                &[],
+            ),
+            rocksdb_write_time: registry.histogram(
+                ROCKSDB_WRITE_TIME,
+                "Time of writing a patch to RocksDB",
+                DEFAULT_BUCKETS,
+                &[],
+            ),
+            messages_received: MessageCounters::new(
+                registry,
+                MESSAGES_RECEIVED,
+                "Number of messages received from the network",
+            ),
+            messages_sent: MessageCounters::new(
+                registry,
+                MESSAGES_SENT,
+                "Number of messages sent to the network",
+            ),
+        }
+    }
+
+    /// Returns the histogram of the execution time of transactions of the given service.
+    ///
+    /// The histogram is registered on each call, so the handle should be kept by the caller.
+    pub fn tx_execution_time(&self, service_id: u16) -> Histogram {
+        Registry::global().histogram(
+            TX_EXECUTION_TIME,
+            "Time of the execution of a single transaction",
+            DEFAULT_BUCKETS,
+            &[("service_id", &service_id.to_string())],
+        )
+    }
+}
+
+/// Counters of network messages labeled by their type.
+#[derive(Debug)]
+pub struct MessageCounters {
+    counters: Vec<Counter>,
+}
+
+impl MessageCounters {
+    fn new(registry: &Registry, name: &str, help: &str) -> Self {
+        let counters = MESSAGE_TYPES
+            .iter()
+            .map(|&message_type| {
+                registry.counter(name, help, &[("message_type", message_type)])
+            })
+            .collect();
+        MessageCounters { counters }
+    }
+
+    /// Increments the counter of the given message type, which is one of
+    /// [`MESSAGE_TYPES`](constant.MESSAGE_TYPES.html); other types are counted as `unknown`.
+    pub fn inc(&self, message_type: &str) {
+        let index = MESSAGE_TYPES
+            .iter()
+            .position(|&known| known == message_type)
+            .unwrap_or(MESSAGE_TYPES.len() - 1);
+        self.counters[index].inc();
+    }
+}
+
+/// Kind of a metric.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricKind {
+    /// Monotonically increasing value.
+    Counter,
+    /// Value that can go up and down.
+    Gauge,
+    /// Distribution of observed values over the buckets with the given upper bounds.
+    Histogram(Vec<f64>),
+}
+
+impl MetricKind {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            MetricKind::Counter => "counter",
+            MetricKind::Gauge => "gauge",
+            MetricKind::Histogram(..) => "histogram",
+        }
+    }
+}
+
+/// Handle of a counter series.
+#[derive(Debug, Clone, Default)]
+pub struct Counter {
+    value: Arc<AtomicUsize>,
+}
+
+impl Counter {
+    /// Increments the counter by one.
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    /// Increments the counter by the given value.
+    pub fn inc_by(&self, value: u64) {
+        self.value.fetch_add(value as usize, Ordering::Relaxed);
+    }
+
+    /// Returns the current value of the counter.
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed) as u64
+    }
+}
+
+/// Handle of a gauge series.
+#[derive(Debug, Clone, Default)]
+pub struct Gauge {
+    value: Arc<AtomicIsize>,
+}
+
+impl Gauge {
+    /// Sets the value of the gauge.
+    pub fn set(&self, value: i64) {
+        self.value.store(value as isize, Ordering::Relaxed);
+    }
+
+    /// Returns the current value of the gauge.
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed) as i64
+    }
+}
+
+#[derive(Debug)]
+struct HistogramData {
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Handle of a histogram series.
+#[derive(Debug, Clone)]
+pub struct Histogram {
+    bounds: Arc<Vec<f64>>,
+    data: Arc<Mutex<HistogramData>>,
+}
+
+impl Histogram {
+    fn new(bounds: &[f64]) -> Self {
+        Histogram {
+            bounds: Arc::new(bounds.to_vec()),
+            data: Arc::new(Mutex::new(HistogramData {
+                buckets: vec![0; bounds.len()],
+                sum: 0.0,
+                count: 0,
+            })),
+        }
+    }
+
+    /// Adds the value to the histogram.
+    pub fn observe(&self, value: f64) {
+        let mut data = self.data.lock().unwrap();
+        for (bucket, bound) in data.buckets.iter_mut().zip(self.bounds.iter()) {
+            if value <= *bound {
+                *bucket += 1;
+            }
+        }
+        data.sum += value;
+        data.count += 1;
+    }
+
+    /// Adds the duration in seconds to the histogram.
+    pub fn observe_duration(&self, duration: Duration) {
+        let seconds = duration.as_secs() as f64 + f64::from(duration.subsec_nanos()) * 1e-9;
+        self.observe(seconds);
+    }
+}
+
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug)]
+enum Series {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+#[derive(Debug)]
+struct Family {
+    help: String,
+    kind: MetricKind,
+    series: BTreeMap<Labels, Series>,
+}
+
+/// Registry of metrics.
+///
+/// Metrics are identified by their names, and their series by the labels. Registering
+/// a series that already exists returns the handle of the existing series.
+///
+/// # Panics
+///
+/// Registration methods panic if a metric with the same name is already registered
+/// with a different kind.
+#[derive(Debug, Default)]
+pub struct Registry {
+    families: Mutex<BTreeMap<String, Family>>,
+}
+
+impl Registry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Registry::default()
+    }
+
+    /// Returns the global registry with the metrics of the node.
+    pub fn global() -> &'static Registry {
+        // Node metrics are exported even if they have not been updated yet.
+        ::lazy_static::initialize(&NODE_METRICS);
+        &GLOBAL_REGISTRY
+    }
+
+    /// Registers a counter series with the given labels and returns its handle.
+    pub fn counter(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Counter {
+        let series = self.register(name, help, MetricKind::Counter, labels, || {
+            Series::Counter(Counter::default())
+        });
+        match series {
+            Series::Counter(counter) => counter,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Registers a gauge series with the given labels and returns its handle.
+    pub fn gauge(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Gauge {
+        let series = self.register(name, help, MetricKind::Gauge, labels, || {
+            Series::Gauge(Gauge::default())
+        });
+        match series {
+            Series::Gauge(gauge) => gauge,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Registers a histogram series with the given upper bounds of the buckets and labels,
+    /// and returns its handle. The bounds of an existing histogram are not changed.
+    pub fn histogram(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: &[f64],
+        labels: &[(&str, &str)],
+    ) -> Histogram {
+        let kind = MetricKind::Histogram(buckets.to_vec());
+        let series = self.register(name, help, kind, labels, || {
+            Series::Histogram(Histogram::new(buckets))
+        });
+        match series {
+            Series::Histogram(histogram) => histogram,
+            _ => unreachable!(),
+        }
+    }
+
+    fn register<F>(
+        &self,
+        name: &str,
+        help: &str,
+        kind: MetricKind,
+        labels: &[(&str, &str)],
+        new_series: F,
+    ) -> Series
+    where
+        F: FnOnce() -> Series,
+    {
+        let mut families = self.families.lock().unwrap();
+        let family = families.entry(name.to_owned()).or_insert_with(|| {
+            Family {
+                help: help.to_owned(),
+                kind: kind.clone(),
+                series: BTreeMap::new(),
+            }
+        });
+        assert_eq!(
+            family.kind.as_str(),
+            kind.as_str(),
+            "Metric {} is already registered as a {}",
+            name,
+            family.kind.as_str()
+        );
+
+        let mut labels: Labels = labels
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect();
+        labels.sort();
+
+        let series = family.series.entry(labels).or_insert_with(new_series);
+        match *series {
+            Series::Counter(ref counter) => Series::Counter(counter.clone()),
+            Series::Gauge(ref gauge) => Series::Gauge(gauge.clone()),
+            Series::Histogram(ref histogram) => Series::Histogram(histogram.clone()),
+        }
+    }
+
+    /// Encodes all metrics in the Prometheus text format.
+    pub fn encode(&self) -> String {
+        let families = self.families.lock().unwrap();
+        let mut output = String::new();
+        for (name, family) in families.iter() {
+            if !family.help.is_empty() {
+                writeln!(output, "# HELP {} {}", name, escape_help(&family.help)).unwrap();
+            }
+            writeln!(output, "# TYPE {} {}", name, family.kind.as_str()).unwrap();
+
+            for (labels, series) in &family.series {
+                match *series {
+                    Series::Histogram(ref histogram) => {
+                        let data = histogram.data.lock().unwrap();
+                        for (bucket, bound) in data.buckets.iter().zip(histogram.bounds.iter()) {
+                            let le = format_value(*bound);
+                            let labels = format_labels(labels, Some(&le));
+                            writeln!(output, "{}_bucket{} {}", name, labels, bucket).unwrap();
+                        }
+                        let inf_labels = format_labels(labels, Some("+Inf"));
+                        writeln!(output, "{}_bucket{} {}", name, inf_labels, data.count).unwrap();
+                        let labels = format_labels(labels, None);
+                        let sum = format_value(data.sum);
+                        writeln!(output, "{}_sum{} {}", name, labels, sum).unwrap();
+                        writeln!(output, "{}_count{} {}", name, labels, data.count).unwrap();
+                    }
+                    Series::Counter(ref counter) => {
+                        let labels = format_labels(labels, None);
+                        writeln!(output, "{}{} {}", name, labels, counter.get()).unwrap();
+                    }
+                    Series::Gauge(ref gauge) => {
+                        let labels = format_labels(labels, None);
+                        writeln!(output, "{}{} {}", name, labels, gauge.get()).unwrap();
+                    }
+                }
+            }
+        }
+        output
+    }
+}
+
+fn format_value(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_owned()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_owned()
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn format_labels(labels: &[(String, String)], le: Option<&str>) -> String {
+    let le = le.map(|le| ("le", le));
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|&(ref name, ref value)| (name.as_str(), value.as_str()))
+        .chain(le)
+        .map(|(name, value)| {
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"", name, value)
+        })
+        .collect();
+
+    if pairs.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", pairs.join(","))
+    }
+}
 
 /// Adds given metric with given value.
 ///
 /// Metric name should be in the following format: `module_name.metric_name`, where `module_name`
 /// is a high level name. For example `storage` or `node` (not `storage_proof_list_index`).
+/// The value is stored in the global [`Registry`](helpers/metrics/struct.Registry.html)
+/// as the gauge named `exonum_{module_name}_{metric_name}`. The gauge is looked up by its name
+/// on each call, so the macro should not be used on hot paths; register a [`Gauge`] instead.
 ///
-/// Value is a string and can be formatted similar to the `println!`. See `std::fmt` and example
-/// for details.
+/// Additionally, the value is written to the log if the `metrics-log` feature is enabled.
 ///
-/// Metrics output direction is determined by the corresponding `metrics-...` feature. If
+/// [`Gauge`]: helpers/metrics/struct.Gauge.html
 ///
 /// # Examples
 ///
@@ -45,13 +550,122 @@ macro_rules! metric {
 
 // Do not use directly, use `metric!` macro instead.
 #[doc(hidden)]
-#[allow(unused_variables)]
 pub fn add_metric(metric_name: &str, value: i64) {
-    // TODO: Convert time to some meaningful format.
-    let time = format!("{:?}", SystemTime::now());
+    let name = format!("exonum_{}", metric_name.replace('.', "_"));
+    Registry::global().gauge(&name, "", &[]).set(value);
 
     #[cfg(feature = "metrics-log")]
     {
-        trace!("{} {} {}", metric_name, value, time);
+        trace!("{} {}", metric_name, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_counters_and_gauges() {
+        let registry = Registry::new();
+        let propose = registry.counter(
+            "messages_total",
+            "Number of messages",
+            &[("message_type", "propose")],
+        );
+        propose.inc();
+        propose.inc();
+        registry
+            .counter("messages_total", "", &[("message_type", "prevote")])
+            .inc_by(3);
+        let height = registry.gauge("height", "", &[]);
+        height.set(10);
+        height.set(12);
+
+        assert_eq!(
+            registry.encode(),
+            "# TYPE height gauge\n\
+             height 12\n\
+             # HELP messages_total Number of messages\n\
+             # TYPE messages_total counter\n\
+             messages_total{message_type=\"prevote\"} 3\n\
+             messages_total{message_type=\"propose\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_histogram() {
+        let registry = Registry::new();
+        let latency = registry.histogram("latency_seconds", "Latency", &[0.1, 1.0], &[]);
+        latency.observe(0.0625);
+        latency.observe(0.5);
+        latency.observe_duration(Duration::from_secs(2));
+
+        assert_eq!(
+            registry.encode(),
+            "# HELP latency_seconds Latency\n\
+             # TYPE latency_seconds histogram\n\
+             latency_seconds_bucket{le=\"0.1\"} 1\n\
+             latency_seconds_bucket{le=\"1\"} 2\n\
+             latency_seconds_bucket{le=\"+Inf\"} 3\n\
+             latency_seconds_sum 2.5625\n\
+             latency_seconds_count 3\n"
+        );
+    }
+
+    #[test]
+    fn test_labels_escaping() {
+        let registry = Registry::new();
+        registry.gauge("value", "", &[("b", "2"), ("a", "x\"y")]).set(1);
+        assert_eq!(
+            registry.encode(),
+            "# TYPE value gauge\nvalue{a=\"x\\\"y\",b=\"2\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn test_registered_series_are_shared() {
+        let registry = Registry::new();
+        let first = registry.counter("requests_total", "", &[("a", "1"), ("b", "2")]);
+        let second = registry.counter("requests_total", "", &[("b", "2"), ("a", "1")]);
+        first.inc();
+        second.inc();
+        assert_eq!(first.get(), 2);
+        assert_eq!(second.get(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "already registered as a counter")]
+    fn test_register_with_different_kind() {
+        let registry = Registry::new();
+        registry.counter("requests_total", "", &[]);
+        registry.gauge("requests_total", "", &[]);
+    }
+
+    #[test]
+    fn test_message_counters() {
+        let registry = Registry::new();
+        let counters = MessageCounters::new(&registry, "messages_total", "");
+        counters.inc("propose");
+        counters.inc("something_else");
+        let output = registry.encode();
+        assert!(output.contains("messages_total{message_type=\"propose\"} 1\n"));
+        assert!(output.contains("messages_total{message_type=\"unknown\"} 1\n"));
+        assert!(output.contains("messages_total{message_type=\"prevote\"} 0\n"));
+    }
+
+    #[test]
+    fn test_node_metrics_are_registered() {
+        node_metrics().height.set(3);
+        let output = Registry::global().encode();
+        assert!(output.contains("# TYPE exonum_node_block_commit_latency_seconds histogram\n"));
+        assert!(output.contains("exonum_node_height 3\n"));
+    }
+
+    #[test]
+    fn test_metric_macro() {
+        metric!("test.metric_macro", 5);
+        assert!(Registry::global().encode().contains(
+            "exonum_test_metric_macro 5\n",
+        ));
     }
 }
@@ -58,6 +58,7 @@ pub fn generate_testnet_config(count: u8, start_port: u16) -> Vec<NodeConfig> {
         ValidatorKeys {
             consensus_key: (x.0).0,
             service_key: (x.1).0,
+            voting_power: None,
         }
     }));
     let peers = (0..validators.len())
@@ -88,6 +89,7 @@ pub fn generate_testnet_config(count: u8, start_port: u16) -> Vec<NodeConfig> {
                 mempool: Default::default(),
                 services_configs: Default::default(),
                 database: Default::default(),
+                consensus_trace: None,
             }
         })
         .collect::<Vec<_>>()
@@ -22,6 +22,7 @@
 #![cfg_attr(feature = "flame_profile", feature(plugin, custom_attribute))]
 #![cfg_attr(feature = "flame_profile", plugin(exonum_flamer))]
 #![cfg_attr(feature = "long_benchmarks", feature(test))]
+#![cfg_attr(feature = "int128", feature(i128_type))]
 
 extern crate exonum_sodiumoxide as sodiumoxide;
 extern crate exonum_rocksdb as rocksdb;
@@ -47,6 +48,7 @@ extern crate chrono;
 #[macro_use(crate_version, crate_authors)]
 extern crate clap;
 extern crate hyper;
+extern crate hyper_native_tls;
 extern crate iron;
 extern crate iron_cors;
 extern crate router;
@@ -66,14 +68,16 @@ extern crate os_info;
 extern crate failure;
 extern crate bodyparser;
 extern crate uuid;
+extern crate ws;
+#[macro_use]
+extern crate lazy_static;
+#[cfg(feature = "protobuf")]
+extern crate protobuf;
 
 // Test dependencies.
 #[cfg(all(test, feature = "long_benchmarks"))]
 extern crate test;
 #[cfg(test)]
-#[macro_use]
-extern crate lazy_static;
-#[cfg(test)]
 extern crate tempdir;
 
 #[macro_use]
@@ -137,6 +137,12 @@ impl MessageBuffer {
         LittleEndian::read_u16(&self.raw[2..4])
     }
 
+    /// Returns the length of the message declared in the header, including the header
+    /// and the signature.
+    pub fn payload_length(&self) -> usize {
+        LittleEndian::read_u32(&self.raw[6..10]) as usize
+    }
+
     /// Returns message body without signature.
     pub fn body(&self) -> &[u8] {
         &self.raw[..self.raw.len() - SIGNATURE_LENGTH]
@@ -231,6 +237,23 @@ impl MessageWriter {
         );
     }
 
+    /// Writes the given bytes to the payload starting from the given offset.
+    pub fn write_bytes(&mut self, bytes: &[u8], from: Offset) {
+        let from = from as usize + HEADER_LENGTH;
+        self.raw[from..from + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Appends the block of optional fields with the given layout `version` after
+    /// the data written so far and reserves `header_size` bytes for the fixed-size parts
+    /// of the fields. Returns the offset of the reserved space, which should be used
+    /// to write the optional fields.
+    pub fn append_optional_header(&mut self, version: u8, header_size: Offset) -> Offset {
+        self.raw.push(version);
+        let from = self.raw.len();
+        self.raw.resize(from + header_size as usize, 0);
+        (from - HEADER_LENGTH) as Offset
+    }
+
     /// Signs the message with the given secret key.
     pub fn sign(mut self, secret_key: &SecretKey) -> MessageBuffer {
         let payload_length = self.raw.len() + SIGNATURE_LENGTH;
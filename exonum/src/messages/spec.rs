@@ -14,6 +14,11 @@
 
 /// A low-level versions of `transactions!` macro, which generates structs for messages,
 /// but does not require the messages to implement `Transaction`.
+///
+/// As with [`encoding_struct!`], a message may be declared with an `optional` block
+/// of fields, which may be extended in later versions of the message.
+///
+/// [`encoding_struct!`]: macro.encoding_struct.html#schema-evolution
 #[macro_export]
 macro_rules! messages {
     {
@@ -22,12 +27,11 @@ macro_rules! messages {
         $(
             $(#[$tx_attr:meta])*
             struct $name:ident {
-            $(
-                $(#[$field_attr:meta])*
-                $field_name:ident : $field_type:ty
-            ),*
-            $(,)* // optional trailing comma
+                $($def:tt)*
             }
+            $(optional {
+                $($opt:tt)*
+            })*
         )*
     }
 
@@ -40,11 +44,11 @@ macro_rules! messages {
             $(
                 $(#[$tx_attr])*
                 struct $name {
-                $(
-                    $(#[$field_attr])*
-                    $field_name: $field_type
-                ),*
+                    $($def)*
                 }
+                $(optional {
+                    $($opt)*
+                })*
             )*
         );
     };
@@ -63,116 +67,386 @@ macro_rules! __ex_message {
             $field_name:ident : $field_type:ty
         ),*
         $(,)*
-       }
+        }
+        optional {
+        $(
+            $(#[$opt_attr:meta])*
+            $opt_name:ident : $opt_type:ty
+        ),*
+        $(,)*
+        }
 
         $($tt:tt)*
     } => (
         #[derive(Clone, PartialEq)]
         $(#[$attr])*
         pub struct $name {
-            raw: $crate::messages::RawMessage
+            raw: $crate::messages::RawMessage,
+            // Offset of the version byte, that is, the end of the data of the required fields.
+            required_end: $crate::encoding::Offset,
         }
 
-        impl $crate::messages::ServiceMessage for $name {
-            const SERVICE_ID: u16 = $service_id;
-            const MESSAGE_ID: u16 = $message_id;
-        }
+        __ex_message_common!($service_id, $message_id, $name);
 
-        impl $crate::messages::Message for $name {
-            fn from_raw(raw: $crate::messages::RawMessage)
-            -> ::std::result::Result<$name, $crate::encoding::Error> {
-                let min_message_size = $name::__ex_header_size() as usize
-                            + $crate::messages::HEADER_LENGTH as usize
-                            + $crate::crypto::SIGNATURE_LENGTH as usize;
-                if raw.len() < min_message_size {
-                    return Err($crate::encoding::Error::UnexpectedlyShortPayload {
-                        actual_size: raw.len() as $crate::encoding::Offset,
-                        minimum_size: min_message_size as $crate::encoding::Offset,
-                    });
-                }
+        impl $name {
+            #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
+            /// Creates message with the latest layout version and signs it.
+            pub fn new($($field_name: $field_type,)* $($opt_name: $opt_type,)*
+                       secret_key: &$crate::crypto::SecretKey) -> $name {
+                use $crate::messages::RawMessage;
+                let writer = $name::__ex_write_fields($($field_name,)* $($opt_name,)*);
+                $name::__ex_from_raw(RawMessage::new(writer.sign(secret_key)))
+            }
 
-                // Check identifiers
-                if raw.version() != $crate::messages::PROTOCOL_MAJOR_VERSION {
-                    return Err($crate::encoding::Error::UnsupportedProtocolVersion {
-                        version: $crate::messages::PROTOCOL_MAJOR_VERSION
-                    });
+            /// Creates message with the latest layout version and appends existing signature.
+            #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
+            #[allow(dead_code)]
+            pub fn new_with_signature($($field_name: $field_type,)* $($opt_name: $opt_type,)*
+                                      signature: &$crate::crypto::Signature) -> $name {
+                use $crate::messages::RawMessage;
+                let writer = $name::__ex_write_fields($($field_name,)* $($opt_name,)*);
+                $name::__ex_from_raw(RawMessage::new(writer.append_signature(signature)))
+            }
+
+            /// Returns the latest layout version of the message body, that is, one plus
+            /// the number of optional fields. Not to be confused with the protocol version
+            /// stored in the message header.
+            pub fn latest_version() -> u8 {
+                let optional_fields: &[&str] = &[$(stringify!($opt_name)),*];
+                1 + optional_fields.len() as u8
+            }
+
+            /// Returns the layout version the message body is encoded with.
+            pub fn version(&self) -> u8 {
+                self.__ex_optional_layout().0
+            }
+
+            #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
+            #[allow(unused_mut, unused_variables)]
+            fn __ex_write_fields($($field_name: $field_type,)* $($opt_name: $opt_type,)*)
+                -> $crate::messages::MessageWriter
+            {
+                let mut writer = $crate::messages::MessageWriter::new(
+                    $crate::messages::PROTOCOL_MAJOR_VERSION,
+                    <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                    $name::__ex_header_size() as usize,
+                );
+                __ex_for_each_field!(
+                    __ex_message_write_field, (writer),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                let base = $name::__ex_append_optional_header(
+                    &mut writer,
+                    $name::latest_version(),
+                );
+                __ex_for_each_field!(
+                    @inner __ex_message_write_field (writer) (base);
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                writer
+            }
+
+            // Appends the version byte and the header of the optional fields present
+            // in the given layout version after the required fields. Returns the offset
+            // of the header. Nothing is appended for the first version.
+            fn __ex_append_optional_header(
+                writer: &mut $crate::messages::MessageWriter,
+                version: u8,
+            ) -> $crate::encoding::Offset {
+                if version > 1 {
+                    writer.append_optional_header(
+                        version,
+                        $name::__ex_optional_header_size(version),
+                    )
+                } else {
+                    0
                 }
-                if raw.message_type() != <Self as $crate::messages::ServiceMessage>::MESSAGE_ID {
-                    return Err($crate::encoding::Error::IncorrectMessageType {
-                        message_type: <Self as $crate::messages::ServiceMessage>::MESSAGE_ID
+            }
+
+            // Checks the required fields and returns the end of their data.
+            #[allow(unused_variables)]
+            fn __ex_check_required_fields(raw_message: &$crate::messages::RawMessage)
+            -> $crate::encoding::Result {
+                let header_length =
+                    $crate::messages::HEADER_LENGTH as $crate::encoding::Offset;
+                let latest_segment = ($name::__ex_header_size() + header_length).into();
+                __ex_for_each_field!(
+                    __ex_message_check_field, (latest_segment, raw_message, $name),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                Ok(latest_segment)
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn check_fields(raw_message: &$crate::messages::RawMessage)
+            -> $crate::encoding::Result {
+                let required_end = $name::__ex_check_required_fields(raw_message)?
+                    .unchecked_offset();
+                let body = raw_message.body();
+                let version = match body.get(required_end as usize) {
+                    Some(&version) => version,
+                    None => return Ok(required_end.into()),
+                };
+                if version < 2 || version > $name::latest_version() {
+                    return Err($crate::encoding::Error::UnsupportedStructVersion {
+                        position: required_end,
+                        version,
                     });
                 }
-                if raw.service_id() != <Self as $crate::messages::ServiceMessage>::SERVICE_ID {
-                    return Err($crate::encoding::Error::IncorrectServiceId {
-                        service_id: <Self as $crate::messages::ServiceMessage>::SERVICE_ID
+                let header_length =
+                    $crate::messages::HEADER_LENGTH as $crate::encoding::Offset;
+                let base = required_end + 1 - header_length;
+                let optional_header_size = $name::__ex_optional_header_size(version);
+                let header_end = required_end + 1 + optional_header_size;
+                if body.len() < header_end as usize {
+                    return Err($crate::encoding::Error::UnexpectedlyShortPayload {
+                        actual_size: body.len() as $crate::encoding::Offset,
+                        minimum_size: header_end,
                     });
                 }
+                let mut latest_segment: $crate::encoding::CheckedOffset = header_end.into();
+                __ex_for_each_field!(
+                    @inner __ex_message_check_optional_field
+                    (latest_segment, raw_message, base, optional_header_size, $name) (0);
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                Ok(latest_segment)
+            }
 
-                // Check body
-                let body_len = <Self>::check_fields(&raw)?;
-                if body_len.unchecked_offset() as usize +
-                    $crate::crypto::SIGNATURE_LENGTH as usize != raw.len()  {
-                    return Err("Incorrect raw message length.".into())
-                }
+            // Wraps a raw message, locating the end of the required fields once. Messages
+            // with an invalid layout of the required fields are treated as the first version.
+            fn __ex_from_raw(raw: $crate::messages::RawMessage) -> $name {
+                let required_end = $name::__ex_check_required_fields(&raw)
+                    .map(|offset| offset.unchecked_offset())
+                    .unwrap_or(raw.body().len() as $crate::encoding::Offset);
+                $name { raw, required_end }
+            }
 
-                Ok($name { raw })
+            // Returns the layout version of the message body and the offset of the header
+            // of its optional fields.
+            fn __ex_optional_layout(&self) -> (u8, $crate::encoding::Offset) {
+                let version = self.raw.body().get(self.required_end as usize).cloned().unwrap_or(1);
+                let header_length =
+                    $crate::messages::HEADER_LENGTH as $crate::encoding::Offset;
+                (version, self.required_end + 1 - header_length)
             }
 
+            __ex_for_each_field!(
+                __ex_message_mk_field, (),
+                $( ($(#[$field_attr])*, $field_name, $field_type) )*
+            );
 
-            fn raw(&self) -> &$crate::messages::RawMessage {
-                &self.raw
-            }
-        }
+            __ex_for_each_field!(
+                @inner __ex_message_mk_optional_field () (0);
+                $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+            );
 
-        #[allow(unsafe_code)]
-        impl<'a> $crate::encoding::SegmentField<'a> for $name {
+            #[doc(hidden)]
+            fn __ex_header_size() -> $crate::encoding::Offset {
+                __ex_header_size!($($field_type),*)
+            }
 
-            fn item_size() -> $crate::encoding::Offset {
-                1
+            // Size of the header of the optional fields present in the given layout version.
+            fn __ex_optional_header_size(version: u8) -> $crate::encoding::Offset {
+                let optional_sizes: &[$crate::encoding::Offset] = &[
+                    $(<$opt_type as $crate::encoding::Field>::field_size()),*
+                ];
+                let present = (version as usize).saturating_sub(1).min(optional_sizes.len());
+                optional_sizes[..present].iter().sum::<$crate::encoding::Offset>()
             }
 
-            fn count(&self) -> $crate::encoding::Offset {
-                self.raw.len() as $crate::encoding::Offset
+            /// Returns the descriptor of the binary layout of the message.
+            #[allow(dead_code, unused_mut)]
+            pub fn schema() -> $crate::encoding::schema::MessageSchema {
+                let mut fields = Vec::new();
+                __ex_for_each_field!(
+                    __ex_field_schema, (fields),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                let mut optional_fields = Vec::new();
+                __ex_for_each_field!(
+                    __ex_field_schema, (optional_fields),
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                let mut types = $crate::encoding::schema::TypeSchemas::new();
+                $(
+                    <$field_type as $crate::encoding::Field>::collect_schemas(&mut types);
+                )*
+                $(
+                    <$opt_type as $crate::encoding::Field>::collect_schemas(&mut types);
+                )*
+                $crate::encoding::schema::MessageSchema {
+                    name: stringify!($name).to_owned(),
+                    service_id: <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    message_id: <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                    body_size: $name::__ex_header_size(),
+                    version: Some($name::latest_version()),
+                    fields,
+                    optional_fields,
+                    structs: types.structs,
+                    enums: types.enums,
+                    protobuf_message: None,
+                }
             }
+        }
 
-            fn extend_buffer(&self, buffer: &mut Vec<u8>) {
-                buffer.extend_from_slice(self.raw.as_ref().as_ref())
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, fmt: &mut ::std::fmt::Formatter)
+                -> Result<(), ::std::fmt::Error> {
+                fmt.debug_struct(stringify!($name))
+                 $(.field(stringify!($field_name), &self.$field_name()))*
+                 $(.field(stringify!($opt_name), &self.$opt_name()))*
+                   .finish()
             }
+        }
 
-            unsafe fn from_buffer(
-                buffer: &'a [u8],
+        impl $crate::encoding::serialize::json::ExonumJson for $name {
+            fn deserialize_field<B> (
+                value: &$crate::encoding::serialize::json::reexport::Value,
+                buffer: & mut B,
                 from: $crate::encoding::Offset,
-                count: $crate::encoding::Offset
-            ) -> Self {
-                let raw_message: $crate::messages::RawMessage =
-                                    $crate::encoding::SegmentField::from_buffer(buffer,
-                                                                from,
-                                                                count);
-                $crate::messages::Message::from_raw(raw_message).unwrap()
+                to: $crate::encoding::Offset,
+            ) -> ::std::result::Result<(), Box<::std::error::Error>>
+            where B: $crate::encoding::serialize::WriteBufferWrapper
+            {
+                use $crate::encoding::serialize::json::ExonumJsonDeserialize;
+                // deserialize full field
+                let structure = <Self as ExonumJsonDeserialize>::deserialize(value)?;
+                // then write it
+                buffer.write(from, to, structure);
+                Ok(())
             }
 
-            fn check_data(
-                buffer: &'a [u8],
-                from: $crate::encoding::CheckedOffset,
-                count: $crate::encoding::CheckedOffset,
-                latest_segment: $crate::encoding::CheckedOffset
-            ) -> $crate::encoding::Result {
-                let latest_segment_origin = <$crate::messages::RawMessage as
-                                $crate::encoding::SegmentField>::check_data(buffer,
-                                                                from,
-                                                                count,
-                                                                latest_segment)?;
-                // TODO: remove this allocation,
-                // by allowing creating message from borrowed data (ECR-156)
-                let raw_message: $crate::messages::RawMessage =
-                                    unsafe { $crate::encoding::SegmentField::from_buffer(buffer,
-                                                                from.unchecked_offset(),
-                                                                count.unchecked_offset())};
-                let _: $name = $crate::messages::Message::from_raw(raw_message)?;
-                Ok(latest_segment_origin)
+            #[allow(unused_mut, unused_variables)]
+            fn serialize_field(&self)
+                -> ::std::result::Result<$crate::encoding::serialize::json::reexport::Value,
+                            Box<::std::error::Error + Send + Sync>>
+            {
+                use $crate::encoding::serialize::json::reexport::Value;
+                use $crate::encoding::serialize::json::reexport::Map;
+                let mut body = Map::new();
+                $(
+                    body.insert(stringify!($field_name).to_string(),
+                        self.$field_name().serialize_field()?);
+                )*
+                // Only the optional fields present in the layout are serialized,
+                // so the signed message is restored with the same layout.
+                let mut present = self.version().saturating_sub(1);
+                $(
+                    if present > 0 {
+                        present -= 1;
+                        body.insert(stringify!($opt_name).to_string(),
+                            self.$opt_name().serialize_field()?);
+                    }
+                )*
+                let mut structure = Map::new();
+                structure.insert("body".to_string(), Value::Object(body));
+                structure.insert("signature".to_string(),
+                                    self.raw.signature().serialize_field()?);
+                structure.insert("message_id".to_string(),
+                                    self.raw.message_type().serialize_field()?);
+                structure.insert("service_id".to_string(),
+                                    self.raw.service_id().serialize_field()?);
+                structure.insert("protocol_version".to_string(),
+                                    self.raw.version().serialize_field()?);
+                Ok(Value::Object(structure))
             }
         }
 
+        impl $crate::encoding::serialize::json::ExonumJsonDeserialize for $name {
+            #[allow(unused_imports, unused_variables, unused_mut)]
+            fn deserialize(value: &$crate::encoding::serialize::json::reexport::Value)
+                -> ::std::result::Result<Self, Box<::std::error::Error>>
+            {
+                use $crate::encoding::serialize::json::ExonumJson;
+                use $crate::encoding::serialize::json::reexport::from_value;
+                use $crate::messages::{RawMessage, MessageWriter};
+
+                // if we could deserialize values, try append signature
+                let obj = value.as_object().ok_or("Can't cast json as object.")?;
+
+                let body = obj.get("body").ok_or("Can't get body from json.")?;
+
+                let signature = from_value(obj.get("signature")
+                                    .ok_or("Can't get signature from json")?.clone())?;
+                let message_id = from_value(obj.get("message_id")
+                                    .ok_or("Can't get message_id from json")?.clone())?;
+                let service_id = from_value(obj.get("service_id")
+                                    .ok_or("Can't get service_id from json")?.clone())?;
+
+                let protocol_version = from_value(obj.get("protocol_version")
+                                        .ok_or("Can't get protocol_version from json")?.clone())?;
+
+                if service_id != <Self as $crate::messages::ServiceMessage>::SERVICE_ID {
+                    return Err("service_id didn't equal real service_id.".into())
+                }
+
+                if message_id != <Self as $crate::messages::ServiceMessage>::MESSAGE_ID {
+                    return Err("message_id didn't equal real message_id.".into())
+                }
+
+                let mut writer = MessageWriter::new(
+                    protocol_version,
+                    service_id,
+                    message_id,
+                    $name::__ex_header_size() as usize,
+                );
+                let obj = body.as_object().ok_or("Can't cast body as object.")?;
+                __ex_for_each_field!(
+                    __ex_deserialize_field, (obj, writer),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                let optional_fields: &[&str] = &[$(stringify!($opt_name)),*];
+                let present = optional_fields
+                    .iter()
+                    .take_while(|name| obj.contains_key(**name))
+                    .count();
+                if optional_fields[present..].iter().any(|name| obj.contains_key(*name)) {
+                    return Err("Optional fields should be added in the declaration order.".into());
+                }
+                let version = 1 + present as u8;
+                let optional_header_size = $name::__ex_optional_header_size(version);
+                let base = $name::__ex_append_optional_header(&mut writer, version);
+                __ex_for_each_field!(
+                    @inner __ex_deserialize_optional_field
+                    (obj, writer, base, optional_header_size) (0);
+                    $( ($(#[$opt_attr])*, $opt_name, $opt_type) )*
+                );
+                Ok($name::__ex_from_raw(RawMessage::new(writer.append_signature(&signature))))
+            }
+        }
+
+        __ex_message!(
+            $service_id,
+            $message_id + 1,
+            $($tt)*
+        );
+    );
+
+    {
+        $service_id:expr,
+        $message_id:expr,
+
+        $(#[$attr:meta])*
+        struct $name:ident {
+        $(
+            $(#[$field_attr:meta])*
+            $field_name:ident : $field_type:ty
+        ),*
+        $(,)*
+       }
+
+        $($tt:tt)*
+    } => (
+        #[derive(Clone, PartialEq)]
+        $(#[$attr])*
+        pub struct $name {
+            raw: $crate::messages::RawMessage
+        }
+
+        __ex_message_common!($service_id, $message_id, $name);
+
         impl $name {
             #[cfg_attr(feature="cargo-clippy", allow(too_many_arguments))]
             /// Creates message and signs it.
@@ -219,63 +493,53 @@ macro_rules! __ex_message {
                     $crate::messages::HEADER_LENGTH as $crate::encoding::Offset;
                 let latest_segment = ($name::__ex_header_size() + header_length).into();
                 __ex_for_each_field!(
-                    __ex_message_check_field, (latest_segment, raw_message),
+                    __ex_message_check_field, (latest_segment, raw_message, $name),
                     $( ($(#[$field_attr])*, $field_name, $field_type) )*
                 );
                 Ok(latest_segment)
             }
 
-            /// Returns the hex representation of the binary data.
-            /// Lower case letters are used (e.g. f9b4ca).
-            #[allow(dead_code)]
-            pub fn to_hex(&self) -> String {
-                $crate::encoding::serialize::encode_hex(self.as_ref())
-            }
-
             __ex_for_each_field!(
                 __ex_message_mk_field, (),
                 $( ($(#[$field_attr])*, $field_name, $field_type) )*
             );
 
+            fn __ex_from_raw(raw: $crate::messages::RawMessage) -> $name {
+                $name { raw }
+            }
+
             #[doc(hidden)]
             fn __ex_header_size() -> $crate::encoding::Offset {
                 __ex_header_size!($($field_type),*)
             }
-        }
-
-        impl AsRef<$crate::messages::RawMessage> for $name {
-            fn as_ref(&self) -> &$crate::messages::RawMessage {
-                $crate::messages::Message::raw(self)
-            }
-        }
-
-        impl $crate::encoding::serialize::FromHex for $name {
-            type Error = $crate::encoding::Error;
 
-            fn from_hex<T: AsRef<[u8]>>(hex: T) -> Result<Self, Self::Error> {
-                let vec = Vec::<u8>::from_hex(hex)
-                    .map_err(|e| $crate::encoding::Error::Other(Box::new(e)))?;
-                if vec.len() < $crate::messages::HEADER_LENGTH {
-                    return Err($crate::encoding::Error::Basic("Hex is too short.".into()));
+            /// Returns the descriptor of the binary layout of the message.
+            #[allow(dead_code, unused_mut)]
+            pub fn schema() -> $crate::encoding::schema::MessageSchema {
+                let mut fields = Vec::new();
+                __ex_for_each_field!(
+                    __ex_field_schema, (fields),
+                    $( ($(#[$field_attr])*, $field_name, $field_type) )*
+                );
+                let mut types = $crate::encoding::schema::TypeSchemas::new();
+                $(
+                    <$field_type as $crate::encoding::Field>::collect_schemas(&mut types);
+                )*
+                $crate::encoding::schema::MessageSchema {
+                    name: stringify!($name).to_owned(),
+                    service_id: <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    message_id: <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                    body_size: $name::__ex_header_size(),
+                    version: None,
+                    fields,
+                    optional_fields: Vec::new(),
+                    structs: types.structs,
+                    enums: types.enums,
+                    protobuf_message: None,
                 }
-                let buf = $crate::messages::MessageBuffer::from_vec(vec);
-                let raw = $crate::messages::RawMessage::new(buf);
-                $crate::messages::Message::from_raw(raw)
             }
         }
 
-        impl $crate::storage::StorageValue for $name {
-            fn into_bytes(self) -> Vec<u8> {
-                self.raw.as_ref().as_ref().to_vec()
-            }
-
-            fn from_bytes(value: ::std::borrow::Cow<[u8]>) -> Self {
-                $name {
-                    raw: $crate::messages::RawMessage::new(
-                        $crate::messages::MessageBuffer::from_vec(value.into_owned()))
-                }
-            }
-        }
 
         impl ::std::fmt::Debug for $name {
             fn fmt(&self, fmt: &mut ::std::fmt::Formatter)
@@ -377,6 +641,166 @@ macro_rules! __ex_message {
             }
         }
 
+        __ex_message!(
+            $service_id,
+            $message_id + 1,
+            $($tt)*
+        );
+    );
+
+    { $service_id:expr, $message_id:expr, } => ();
+}
+
+// Implements the traits and methods of a message declared with `__ex_message!`,
+// which do not depend on the fields of the message.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_message_common {
+    ($service_id:expr, $message_id:expr, $name:ident) => (
+        impl $crate::messages::ServiceMessage for $name {
+            const SERVICE_ID: u16 = $service_id;
+            const MESSAGE_ID: u16 = $message_id;
+        }
+
+        impl $crate::messages::Message for $name {
+            fn from_raw(raw: $crate::messages::RawMessage)
+            -> ::std::result::Result<$name, $crate::encoding::Error> {
+                let min_message_size = $name::__ex_header_size() as usize
+                            + $crate::messages::HEADER_LENGTH as usize
+                            + $crate::crypto::SIGNATURE_LENGTH as usize;
+                if raw.len() < min_message_size {
+                    return Err($crate::encoding::Error::UnexpectedlyShortPayload {
+                        actual_size: raw.len() as $crate::encoding::Offset,
+                        minimum_size: min_message_size as $crate::encoding::Offset,
+                    });
+                }
+
+                // Check identifiers
+                if raw.version() != $crate::messages::PROTOCOL_MAJOR_VERSION {
+                    return Err($crate::encoding::Error::UnsupportedProtocolVersion {
+                        version: $crate::messages::PROTOCOL_MAJOR_VERSION
+                    });
+                }
+                if raw.message_type() != <Self as $crate::messages::ServiceMessage>::MESSAGE_ID {
+                    return Err($crate::encoding::Error::IncorrectMessageType {
+                        message_type: <Self as $crate::messages::ServiceMessage>::MESSAGE_ID
+                    });
+                }
+                if raw.service_id() != <Self as $crate::messages::ServiceMessage>::SERVICE_ID {
+                    return Err($crate::encoding::Error::IncorrectServiceId {
+                        service_id: <Self as $crate::messages::ServiceMessage>::SERVICE_ID
+                    });
+                }
+
+                // Check body
+                let body_len = <Self>::check_fields(&raw).map_err(|e| e.in_message(
+                    <Self as $crate::messages::ServiceMessage>::SERVICE_ID,
+                    <Self as $crate::messages::ServiceMessage>::MESSAGE_ID,
+                ))?;
+                if body_len.unchecked_offset() as usize +
+                    $crate::crypto::SIGNATURE_LENGTH as usize != raw.len()  {
+                    return Err("Incorrect raw message length.".into())
+                }
+
+                Ok($name::__ex_from_raw(raw))
+            }
+
+
+            fn raw(&self) -> &$crate::messages::RawMessage {
+                &self.raw
+            }
+        }
+
+        #[allow(unsafe_code)]
+        impl<'a> $crate::encoding::SegmentField<'a> for $name {
+
+            fn item_size() -> $crate::encoding::Offset {
+                1
+            }
+
+            fn count(&self) -> $crate::encoding::Offset {
+                self.raw.len() as $crate::encoding::Offset
+            }
+
+            fn extend_buffer(&self, buffer: &mut Vec<u8>) {
+                buffer.extend_from_slice(self.raw.as_ref().as_ref())
+            }
+
+            unsafe fn from_buffer(
+                buffer: &'a [u8],
+                from: $crate::encoding::Offset,
+                count: $crate::encoding::Offset
+            ) -> Self {
+                let raw_message: $crate::messages::RawMessage =
+                                    $crate::encoding::SegmentField::from_buffer(buffer,
+                                                                from,
+                                                                count);
+                $crate::messages::Message::from_raw(raw_message).unwrap()
+            }
+
+            fn check_data(
+                buffer: &'a [u8],
+                from: $crate::encoding::CheckedOffset,
+                count: $crate::encoding::CheckedOffset,
+                latest_segment: $crate::encoding::CheckedOffset
+            ) -> $crate::encoding::Result {
+                let latest_segment_origin = <$crate::messages::RawMessage as
+                                $crate::encoding::SegmentField>::check_data(buffer,
+                                                                from,
+                                                                count,
+                                                                latest_segment)?;
+                // TODO: remove this allocation,
+                // by allowing creating message from borrowed data (ECR-156)
+                let raw_message: $crate::messages::RawMessage =
+                                    unsafe { $crate::encoding::SegmentField::from_buffer(buffer,
+                                                                from.unchecked_offset(),
+                                                                count.unchecked_offset())};
+                let _: $name = $crate::messages::Message::from_raw(raw_message)?;
+                Ok(latest_segment_origin)
+            }
+        }
+
+        impl $name {
+            /// Returns the hex representation of the binary data.
+            /// Lower case letters are used (e.g. f9b4ca).
+            #[allow(dead_code)]
+            pub fn to_hex(&self) -> String {
+                $crate::encoding::serialize::encode_hex(self.as_ref())
+            }
+        }
+
+        impl AsRef<$crate::messages::RawMessage> for $name {
+            fn as_ref(&self) -> &$crate::messages::RawMessage {
+                $crate::messages::Message::raw(self)
+            }
+        }
+
+        impl $crate::encoding::serialize::FromHex for $name {
+            type Error = $crate::encoding::Error;
+
+            fn from_hex<T: AsRef<[u8]>>(hex: T) -> Result<Self, Self::Error> {
+                let vec = Vec::<u8>::from_hex(hex)
+                    .map_err(|e| $crate::encoding::Error::Other(Box::new(e)))?;
+                if vec.len() < $crate::messages::HEADER_LENGTH {
+                    return Err($crate::encoding::Error::Basic("Hex is too short.".into()));
+                }
+                let buf = $crate::messages::MessageBuffer::from_vec(vec);
+                let raw = $crate::messages::RawMessage::new(buf);
+                $crate::messages::Message::from_raw(raw)
+            }
+        }
+
+        impl $crate::storage::StorageValue for $name {
+            fn into_bytes(self) -> Vec<u8> {
+                self.raw.as_ref().as_ref().to_vec()
+            }
+
+            fn from_bytes(value: ::std::borrow::Cow<[u8]>) -> Self {
+                $name::__ex_from_raw($crate::messages::RawMessage::new(
+                    $crate::messages::MessageBuffer::from_vec(value.into_owned())))
+            }
+        }
+
         // TODO: Rewrite Deserialize and Serialize implementation (ECR-156)
         impl<'de> $crate::encoding::serialize::reexport::Deserialize<'de> for $name {
             #[allow(unused_mut)]
@@ -406,17 +830,7 @@ macro_rules! __ex_message {
                     .serialize(serializer)
             }
         }
-
-
-        __ex_message!(
-            $service_id,
-            $message_id + 1,
-            $($tt)*
-        );
-
-    );
-
-    { $service_id:expr, $message_id:expr, } => ();
+    )
 }
 
 #[doc(hidden)]
@@ -449,13 +863,55 @@ macro_rules! __ex_message_write_field {
 #[macro_export]
 macro_rules! __ex_message_check_field {
     (
-        ($latest_segment:ident, $raw_message:ident),
+        ($latest_segment:ident, $raw_message:ident, $name:ident),
         $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
     ) => {
         let $latest_segment = $raw_message.check::<$field_type>(
             $from.into(),
             $to.into(),
             $latest_segment
-        )?;
+        ).map_err(|e| e.in_field(stringify!($name), stringify!($field_name)))?;
+    }
+}
+
+// Checks an optional field of a versioned message if it is present in the layout
+// of the message. `$from` and `$to` are relative to the header of the optional fields
+// starting at `$base`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_message_check_optional_field {
+    (
+        ($latest_segment:ident, $raw_message:ident, $base:ident, $header_size:ident, $name:ident),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
+    ) => {
+        if $to <= $header_size {
+            $latest_segment = $raw_message.check::<$field_type>(
+                ($base + $from).into(),
+                ($base + $to).into(),
+                $latest_segment
+            ).map_err(|e| e.in_field(stringify!($name), stringify!($field_name)))?;
+        }
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __ex_message_mk_optional_field {
+    (
+        (),
+        $(#[$field_attr:meta])*, $field_name:ident, $field_type:ty, $from:expr, $to:expr
+    ) => {
+        $(#[$field_attr])*
+        ///
+        /// Returns the default value if the field is absent in the layout of the message.
+        #[allow(unsafe_code)]
+        pub fn $field_name(&self) -> $field_type {
+            let (version, base) = self.__ex_optional_layout();
+            if $to <= Self::__ex_optional_header_size(version) {
+                unsafe { self.raw.read::<$field_type>(base + $from, base + $to) }
+            } else {
+                <$field_type as Default>::default()
+            }
+        }
     }
 }
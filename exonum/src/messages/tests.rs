@@ -26,6 +26,85 @@ messages! {
     }
 }
 
+mod versioned {
+    use crypto::PublicKey;
+
+    messages! {
+        const SERVICE_ID = 0;
+
+        struct TxSimple {
+            public_key: &PublicKey,
+            msg: &str,
+        }
+        optional {
+            amount: u64,
+            memo: Option<Vec<u8>>,
+        }
+    }
+}
+
+#[test]
+fn test_message_schema() {
+    use encoding::schema::FieldSchema;
+
+    let schema = TxSimple::schema();
+    assert_eq!(schema.name, "TxSimple");
+    assert_eq!(schema.service_id, 0);
+    assert_eq!(schema.message_id, 0);
+    assert_eq!(schema.body_size, 40);
+    assert_eq!(
+        schema.fields,
+        vec![
+            FieldSchema::new("public_key", "&PublicKey", 0, 32),
+            FieldSchema::new("msg", "&str", 32, 40),
+        ]
+    );
+}
+
+#[test]
+fn test_message_schema_referenced_types() {
+    use encoding::schema::VariantSchema;
+
+    encoding_struct! {
+        struct Point {
+            x: u32,
+            y: u32,
+        }
+    }
+
+    encoding_enum! {
+        enum Shape {
+            Empty = 0,
+            Dot(Point) = 1,
+            Path(Vec<Point>) = 2,
+        }
+    }
+
+    messages! {
+        const SERVICE_ID = 0;
+
+        struct TxDraw {
+            shape: Shape,
+            origin: Point,
+        }
+    }
+
+    let schema = TxDraw::schema();
+    assert!(TxSimple::schema().structs.is_empty());
+    // Each type is described once, even if it is referenced several times.
+    assert_eq!(schema.structs, vec![Point::schema()]);
+    assert_eq!(schema.enums, vec![Shape::schema()]);
+    assert_eq!(schema.enums[0].name, "Shape");
+    assert_eq!(
+        schema.enums[0].variants,
+        vec![
+            VariantSchema::new("Empty", 0, None),
+            VariantSchema::new("Dot", 1, Some("Point")),
+            VariantSchema::new("Path", 2, Some("Vec<Point>")),
+        ]
+    );
+}
+
 #[test]
 fn test_message_without_fields() {
     messages! {
@@ -106,6 +185,29 @@ fn test_hex_wrong_body_into_message() {
     let _msg = TxSimple::from_hex(hex).unwrap();
 }
 
+#[test]
+fn test_message_error_field_path() {
+    use encoding::Error;
+
+    let tx = TxSimple::new_with_signature(&PublicKey::zero(), "My little pony", &Signature::zero());
+    let mut vec = tx.as_ref().as_ref().to_vec();
+    // Point the `msg` segment to the start of the message.
+    vec[10 + 32] = 0;
+    let error = TxSimple::from_raw(RawMessage::from_vec(vec)).unwrap_err();
+    match error {
+        Error::InMessage { service_id: 0, message_id: 0, .. } => {}
+        ref other => panic!("Unexpected error: {:?}", other),
+    }
+    assert_eq!(error.field_path().unwrap().to_string(), "TxSimple.msg");
+    match *error.root_cause() {
+        Error::OverlappingSegment { last_end: 50, start: 0 } => {}
+        ref other => panic!("Unexpected error: {:?}", other),
+    }
+    assert!(error.to_string().starts_with(
+        "Error in field `TxSimple.msg` of message 0 of service 0: Overlapping segments",
+    ));
+}
+
 #[test]
 #[allow(dead_code)]
 #[should_panic(expected = "IncorrectMessageType")]
@@ -142,3 +244,87 @@ fn test_hex_wrong_type_into_message() {
     let hex = msg.to_hex();
     let _msg = TxOtherType::from_hex(hex).unwrap();
 }
+
+#[test]
+fn test_versioned_message() {
+    use crypto::CryptoHash;
+    use messages::HEADER_LENGTH;
+
+    let (public_key, secret_key) = gen_keypair();
+    // Messages signed with the layout without optional fields stay valid.
+    let old = TxSimple::new(&public_key, "Hello", &secret_key);
+    let tx = versioned::TxSimple::from_raw(old.raw().clone()).unwrap();
+    assert_eq!(tx.version(), 1);
+    assert_eq!(tx.msg(), "Hello");
+    assert_eq!(tx.amount(), 0);
+    assert_eq!(tx.memo(), None);
+    assert_eq!(tx.hash(), old.hash());
+    assert!(tx.verify_signature(&public_key));
+
+    let tx = versioned::TxSimple::new(&public_key, "Hello", 10, Some(vec![1, 2]), &secret_key);
+    assert_eq!(versioned::TxSimple::latest_version(), 3);
+    assert_eq!(tx.version(), 3);
+    assert_eq!(tx.msg(), "Hello");
+    assert_eq!(tx.amount(), 10);
+    assert_eq!(tx.memo(), Some(vec![1, 2]));
+    assert!(tx.verify_signature(&public_key));
+    assert_eq!(versioned::TxSimple::from_raw(tx.raw().clone()).unwrap(), tx);
+    // Optional fields follow the fields of the previous layout.
+    assert!(tx.raw().body()[HEADER_LENGTH..].starts_with(&old.raw().body()[HEADER_LENGTH..]));
+
+    let schema = versioned::TxSimple::schema();
+    assert_eq!(schema.body_size, 40);
+    assert_eq!(schema.version, Some(3));
+    assert_eq!(schema.optional_fields.len(), 2);
+}
+
+#[test]
+fn test_versioned_message_json() {
+    use crypto::CryptoHash;
+    use serde_json;
+
+    let (public_key, secret_key) = gen_keypair();
+    let old = TxSimple::new(&public_key, "Hello", &secret_key);
+    let value = serde_json::to_value(&old).unwrap();
+    let tx: versioned::TxSimple = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(tx.hash(), old.hash());
+    assert_eq!(serde_json::to_value(&tx).unwrap(), value);
+
+    let tx = versioned::TxSimple::new(&public_key, "Hello", 10, None, &secret_key);
+    let value = serde_json::to_value(&tx).unwrap();
+    assert_eq!(value["body"]["amount"], "10");
+    assert_eq!(value["body"]["memo"], serde_json::Value::Null);
+    let parsed: versioned::TxSimple = serde_json::from_value(value).unwrap();
+    assert_eq!(parsed, tx);
+}
+
+#[test]
+fn test_versioned_message_errors() {
+    use crypto::SIGNATURE_LENGTH;
+    use encoding::Error;
+
+    let (public_key, secret_key) = gen_keypair();
+    let old = TxSimple::new(&public_key, "Hello", &secret_key);
+    let with_trailer = |trailer: &[u8]| {
+        let mut vec = old.raw().as_ref().to_vec();
+        let signature_start = vec.len() - SIGNATURE_LENGTH;
+        for (i, byte) in trailer.iter().enumerate() {
+            vec.insert(signature_start + i, *byte);
+        }
+        versioned::TxSimple::from_raw(RawMessage::from_vec(vec))
+    };
+
+    for version in &[1, 4] {
+        let error = with_trailer(&[*version]).unwrap_err();
+        match *error.root_cause() {
+            Error::UnsupportedStructVersion { position: 55, version: v } if v == *version => {}
+            ref other => panic!("Unexpected error: {:?}", other),
+        }
+    }
+    // The header of the optional fields of the version 2 is truncated.
+    assert!(with_trailer(&[2, 1, 0]).is_err());
+    let tx = with_trailer(&[2, 1, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+    assert_eq!(tx.version(), 2);
+    assert_eq!(tx.amount(), 1);
+    assert_eq!(tx.memo(), None);
+}
@@ -19,7 +19,8 @@ use rand::{self, Rng};
 
 use messages::{Any, RawMessage, Connect, Status, Message, PeersRequest};
 use helpers::Height;
-use super::{NodeHandler, RequestData};
+use helpers::metrics;
+use super::{message_type, NodeHandler, RequestData};
 
 impl NodeHandler {
     /// Redirects message to the corresponding `handle_...` function.
@@ -30,6 +31,8 @@ impl NodeHandler {
         //         return;
         //     }
 
+        metrics::node_metrics().messages_received.inc(message_type(&raw));
+
         match Any::from_raw(raw) {
             Ok(Any::Connect(msg)) => self.handle_connect(msg),
             Ok(Any::Status(msg)) => self.handle_status(&msg),
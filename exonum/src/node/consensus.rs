@@ -13,14 +13,17 @@
 // limitations under the License.
 
 use std::collections::HashSet;
+use std::time::Duration;
 
 use crypto::{Hash, CryptoHash, PublicKey};
 use blockchain::{Schema, Transaction};
 use messages::{BlockRequest, BlockResponse, ConsensusMessage, Message, Precommit, Prevote,
                PrevotesRequest, Propose, ProposeRequest, RawTransaction, TransactionsRequest};
 use helpers::{Height, Round, ValidatorId};
+use helpers::metrics;
 use storage::Patch;
-use node::{NodeHandler, RequestData};
+use node::{NodeHandler, NodeTimeout, RequestData};
+use node::tracing::ConsensusEventType;
 use events::InternalRequest;
 use std::error::Error;
 
@@ -87,6 +90,20 @@ impl NodeHandler {
             }
         };
 
+        if let Some(evidence) = self.state.check_equivocation(&msg) {
+            let (validator, height, round) =
+                (evidence.validator(), evidence.height(), evidence.round());
+            // Replayed conflicting messages produce the same evidence, which is saved once.
+            if self.blockchain.save_equivocation(evidence) {
+                warn!(
+                    "Validator {} sent conflicting consensus messages: height={}, round={}",
+                    validator,
+                    height,
+                    round
+                );
+            }
+        }
+
         trace!("Handle message={:?}", msg);
         match msg {
             ConsensusMessage::Propose(msg) => self.handle_propose(key, &msg),
@@ -136,6 +153,14 @@ impl NodeHandler {
         };
 
         let hash = msg.hash();
+        let height = self.state.height();
+        self.trace_consensus_event(
+            height,
+            msg.round(),
+            ConsensusEventType::ProposeReceived,
+            Some(hash),
+            None,
+        );
 
         // Remove request info
         let known_nodes = self.remove_request(&RequestData::Propose(hash));
@@ -347,6 +372,14 @@ impl NodeHandler {
     /// Locks to the propose by calling `lock`. This function is called when node receives
     /// +2/3 pre-votes.
     pub fn has_majority_prevotes(&mut self, prevote_round: Round, propose_hash: &Hash) {
+        let height = self.state.height();
+        self.trace_consensus_event(
+            height,
+            prevote_round,
+            ConsensusEventType::PrevoteMajority,
+            Some(*propose_hash),
+            None,
+        );
         // Remove request info
         self.remove_request(&RequestData::Prevotes(prevote_round, *propose_hash));
         // Lock to propose
@@ -362,6 +395,15 @@ impl NodeHandler {
         propose_hash: &Hash,
         block_hash: &Hash,
     ) {
+        let height = self.state.height();
+        self.trace_consensus_event(
+            height,
+            round,
+            ConsensusEventType::PrecommitMajority,
+            Some(*propose_hash),
+            Some(*block_hash),
+        );
+
         // Check if propose is known.
         if self.state.propose(propose_hash).is_none() {
             self.state.add_unknown_propose_with_precommits(
@@ -425,6 +467,14 @@ impl NodeHandler {
                 self.blockchain.save_messages(round, raw_messages);
 
                 self.state.lock(round, propose_hash);
+                let height = self.state.height();
+                self.trace_consensus_event(
+                    height,
+                    round,
+                    ConsensusEventType::Lock,
+                    Some(propose_hash),
+                    None,
+                );
                 // Send precommit
                 if self.state.is_validator() && !self.state.have_incompatible_prevotes() {
                     // Execute block and get state hash
@@ -484,6 +534,15 @@ impl NodeHandler {
         round: Option<Round>,
     ) {
         trace!("COMMIT {:?}", block_hash);
+        let height = self.state.height();
+        let current_round = self.state.round();
+        self.trace_consensus_event(
+            height,
+            round.unwrap_or(current_round),
+            ConsensusEventType::Commit,
+            None,
+            Some(block_hash),
+        );
 
         // Merge changes into storage
         let (committed_txs, proposer) = {
@@ -496,6 +555,12 @@ impl NodeHandler {
             self.state.update_config(
                 Schema::new(&self.blockchain.snapshot()).actual_configuration(),
             );
+            if let Ok(latency) = self.system_state.current_time().duration_since(
+                self.state.height_start_time(),
+            )
+            {
+                metrics::node_metrics().block_commit_latency.observe_duration(latency);
+            }
             // Update state to new height
             let block_hash = self.blockchain.last_hash();
             self.state.new_height(
@@ -508,7 +573,7 @@ impl NodeHandler {
         let schema = Schema::new(&snapshot);
         let pool_len = schema.transactions_pool_len();
 
-        metric!("node.mempool", pool_len);
+        metrics::node_metrics().mempool_size.set(pool_len as i64);
 
         let height = self.state.height();
         info!(
@@ -527,8 +592,10 @@ impl NodeHandler {
         self.broadcast_status();
         self.add_status_timeout();
 
-        // Adjust propose timeout after accepting a new block.
-        self.state.adjust_timeout(&*self.blockchain.snapshot());
+        // Adjust propose timeout and leader election after accepting a new block.
+        let snapshot = self.blockchain.snapshot();
+        self.state.adjust_timeout(&*snapshot);
+        self.state.update_leader_election(&*snapshot);
 
         // Add timeout for first round
         self.add_round_timeout();
@@ -554,7 +621,7 @@ impl NodeHandler {
             match self.blockchain.tx_from_raw(msg.clone()) {
                 Ok(tx) => tx,
                 Err(e) => {
-                    error!("{}, service_id={}", e.description(), service_id);
+                    error!("{}, service_id={}", e, service_id);
                     return;
                 }
             }
@@ -580,6 +647,7 @@ impl NodeHandler {
         self.blockchain.merge(fork.into_patch()).expect(
             "Unable to save transaction to persistent pool.",
         );
+        self.blockchain.notifier().notify_in_pool(&hash);
 
         let full_proposes = self.state.check_incomplete_proposes(hash);
         // Go to has full propose if we get last transaction
@@ -587,6 +655,8 @@ impl NodeHandler {
             self.remove_request(&RequestData::Transactions(hash));
             self.has_full_propose(hash, round);
         }
+
+        self.handle_postponed_propose();
     }
 
     /// Handles external boxed transaction. Additionally transaction will be broadcast to the
@@ -603,6 +673,7 @@ impl NodeHandler {
         self.blockchain.merge(fork.into_patch()).expect(
             "Unable to save transaction to persistent pool.",
         );
+        self.blockchain.notifier().notify_in_pool(&hash);
         // Broadcast transaction to validators
         trace!("Broadcast transactions: {:?}", msg.raw());
         self.broadcast(msg.raw());
@@ -613,6 +684,8 @@ impl NodeHandler {
             self.remove_request(&RequestData::Transactions(hash));
             self.has_full_propose(hash, round);
         }
+
+        self.handle_postponed_propose();
     }
 
     /// Handle new round, after jump.
@@ -662,6 +735,7 @@ impl NodeHandler {
             return;
         }
         warn!("ROUND TIMEOUT height={}, round={}", height, round);
+        self.trace_consensus_event(height, round, ConsensusEventType::RoundTimeout, None, None);
 
         // Update state to new round
         self.state.new_round();
@@ -696,6 +770,19 @@ impl NodeHandler {
 
             info!("LEADER: pool = {}", pool_len);
 
+            if pool_len == 0 {
+                if let Some(max_idle_time) = self.state.consensus_config().max_idle_time {
+                    let idle_until = self.state.height_start_time() +
+                        Duration::from_millis(max_idle_time);
+                    if self.system_state.current_time() < idle_until {
+                        trace!("Postpone propose until {:?}", idle_until);
+                        self.state.postpone_propose();
+                        self.add_timeout(NodeTimeout::Propose(height, round), idle_until);
+                        return;
+                    }
+                }
+            }
+
             let round = self.state.round();
             let max_count = ::std::cmp::min(self.txs_block_limit() as usize, pool_len);
 
@@ -726,6 +813,15 @@ impl NodeHandler {
         }
     }
 
+    /// Sends the propose postponed because of the empty transactions pool
+    /// (see `ConsensusConfig::max_idle_time`).
+    fn handle_postponed_propose(&mut self) {
+        if self.state.take_postponed_propose() {
+            let (height, round) = (self.state.height(), self.state.round());
+            self.handle_propose_timeout(height, round);
+        }
+    }
+
     /// Handles request timeout by sending the corresponding request message to a peer.
     pub fn handle_request_timeout(&mut self, data: &RequestData, peer: Option<PublicKey>) {
         trace!("HANDLE REQUEST TIMEOUT");
@@ -927,14 +1023,17 @@ impl NodeHandler {
         self.broadcast(precommit.raw());
     }
 
-    /// Checks that pre-commits count is correct and calls `verify_precommit` for each of them.
+    /// Checks that pre-commits voting power is sufficient and calls `verify_precommit` for each
+    /// of them.
     fn verify_precommits(
         &self,
         precommits: &[Precommit],
         block_hash: &Hash,
         block_height: Height,
     ) -> Result<(), String> {
-        if precommits.len() < self.state.majority_count() {
+        let voters: HashSet<_> = precommits.iter().map(|p| p.validator()).collect();
+        let voting_power: u64 = voters.iter().map(|id| self.state.voting_power_of(*id)).sum();
+        if voting_power < self.state.majority_count() {
             return Err("Received block without consensus".to_string());
         } else if precommits.len() > self.state.validators().len() {
             return Err("Wrong precommits count in block".to_string());
@@ -17,10 +17,12 @@
 //! For details about consensus message handling see messages module documentation.
 // spell-checker:ignore cors
 
+use std::cmp;
 use std::io;
 use std::sync::Arc;
 use std::thread;
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::time::{Duration, SystemTime};
 use std::collections::BTreeMap;
 use std::collections::HashSet;
@@ -29,21 +31,33 @@ use std::fmt;
 use toml::Value;
 use router::Router;
 use mount::Mount;
-use iron::{Chain, Iron};
+use iron::{status, Chain, Iron, IronResult, Request, Response};
+use hyper::header::ContentType;
 use iron_cors::CorsMiddleware;
+use hyper_native_tls::NativeTlsServer;
 use serde::{de, ser};
-use futures::{Future, Sink};
+use futures::{stream, Future, Sink};
 use futures::sync::mpsc;
 use tokio_core::reactor::Core;
+use chrono::{DateTime, Utc};
 
 use crypto::{self, CryptoHash, Hash, PublicKey, SecretKey};
 use blockchain::{Blockchain, GenesisConfig, Schema, Service, SharedNodeState, Transaction};
 use api::{private, public, Api};
-use messages::{Connect, Message, RawMessage};
+use api::public::WebSocketConfig;
+use api::private::{AuthConfig, AuthMiddleware};
+use api::openapi::{self, ApiDocument, Endpoint};
+use messages::{Connect, Message, RawMessage, CONSENSUS, CONNECT_MESSAGE_ID, STATUS_MESSAGE_ID,
+               PROPOSE_MESSAGE_ID, PREVOTE_MESSAGE_ID, PRECOMMIT_MESSAGE_ID,
+               BLOCK_RESPONSE_MESSAGE_ID, PROPOSE_REQUEST_MESSAGE_ID,
+               TRANSACTIONS_REQUEST_MESSAGE_ID, PREVOTES_REQUEST_MESSAGE_ID,
+               PEERS_REQUEST_MESSAGE_ID, BLOCK_REQUEST_MESSAGE_ID};
 use events::{HandlerPart, InternalEvent, InternalPart, InternalRequest, NetworkConfiguration,
              NetworkEvent, NetworkPart, NetworkRequest, SyncSender, TimeoutRequest};
 use events::error::{into_other, log_error, other_error, LogError};
 use helpers::{user_agent, Height, Milliseconds, Round, ValidatorId};
+use helpers::metrics;
+use self::tracing::{ConsensusEvent, ConsensusEventType, ConsensusTracer, JsonLinesTracer};
 use storage::{Database, DbOptions};
 
 pub use self::state::{RequestData, State, ValidatorState};
@@ -56,6 +70,7 @@ mod requests;
 mod whitelist;
 pub mod state; // TODO: temporary solution to get access to WAIT constants (ECR-167)
 pub mod timeout_adjuster;
+pub mod tracing;
 
 /// External messages.
 #[derive(Debug)]
@@ -117,6 +132,8 @@ pub struct NodeHandler {
     pub peer_discovery: Vec<SocketAddr>,
     /// Does this node participate in the consensus?
     is_enabled: bool,
+    /// Receiver of the consensus events.
+    consensus_tracer: Option<Box<ConsensusTracer>>,
 }
 
 /// Service configuration.
@@ -157,6 +174,21 @@ pub struct NodeApiConfig {
     ///
     /// [cors]: https://developer.mozilla.org/en-US/docs/Web/HTTP/CORS
     pub allow_origin: Option<AllowOrigin>,
+    /// TLS options of the private api. If specified, the private api is served
+    /// over HTTPS only.
+    #[serde(default)]
+    pub private_api_tls: Option<TlsConfig>,
+    /// Authentication options of the private api. If specified, each request to
+    /// the private api must carry a bearer token allowed to access the endpoint.
+    #[serde(default)]
+    pub private_api_auth: Option<AuthConfig>,
+    /// Listen address for the WebSocket api with subscriptions to blocks, transactions
+    /// and service events. The api is not started if the address is not specified.
+    #[serde(default)]
+    pub websocket_address: Option<SocketAddr>,
+    /// Limits of the WebSocket api.
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
 }
 
 impl Default for NodeApiConfig {
@@ -167,10 +199,38 @@ impl Default for NodeApiConfig {
             public_api_address: None,
             private_api_address: None,
             allow_origin: None,
+            private_api_tls: None,
+            private_api_auth: None,
+            websocket_address: None,
+            websocket: WebSocketConfig::default(),
         }
     }
 }
 
+/// TLS options of an api listen address.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// Path to the PKCS #12 archive with the certificate chain and the private key
+    /// of the server.
+    pub identity: PathBuf,
+    /// Password of the archive.
+    #[serde(default)]
+    pub password: String,
+}
+
+impl TlsConfig {
+    /// Loads the identity and creates TLS server for `iron`.
+    pub fn server(&self) -> io::Result<NativeTlsServer> {
+        NativeTlsServer::new(&self.identity, &self.password).map_err(|e| {
+            other_error(format!(
+                "Unable to load TLS identity from {}: {}",
+                self.identity.display(),
+                e
+            ))
+        })
+    }
+}
+
 /// CORS header specification
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AllowOrigin {
@@ -348,6 +408,10 @@ pub struct NodeConfig {
     /// Optional database configuration.
     #[serde(default)]
     pub database: DbOptions,
+    /// Path to the file the consensus events are written to as JSON lines.
+    /// Tracing is disabled if the path is not set.
+    #[serde(default)]
+    pub consensus_trace: Option<PathBuf>,
 }
 
 /// Configuration for the `NodeHandler`.
@@ -379,13 +443,14 @@ pub struct NodeSender {
 impl NodeHandler {
     /// Creates `NodeHandler` using specified `Configuration`.
     pub fn new(
-        blockchain: Blockchain,
+        mut blockchain: Blockchain,
         external_address: SocketAddr,
         sender: NodeSender,
         system_state: Box<SystemStateProvider>,
         config: Configuration,
         api_state: SharedNodeState,
     ) -> Self {
+        blockchain.set_node_state(api_state.clone());
         // FIXME: remove unwraps here, use FATAL log level instead
         let (last_hash, last_height) = {
             let block = blockchain.last_block();
@@ -433,6 +498,7 @@ impl NodeHandler {
 
         // Adjust propose timeout for the first time.
         state.adjust_timeout(&*snapshot);
+        state.update_leader_election(&*snapshot);
 
         NodeHandler {
             blockchain,
@@ -442,6 +508,36 @@ impl NodeHandler {
             channel: sender,
             peer_discovery: config.peer_discovery,
             is_enabled: true,
+            consensus_tracer: None,
+        }
+    }
+
+    /// Installs the receiver of the consensus events, replacing the previous one.
+    pub fn set_consensus_tracer(&mut self, tracer: Box<ConsensusTracer>) {
+        self.consensus_tracer = Some(tracer);
+    }
+
+    /// Reports the consensus event to the tracer, if any.
+    fn trace_consensus_event(
+        &mut self,
+        height: Height,
+        round: Round,
+        event: ConsensusEventType,
+        propose_hash: Option<Hash>,
+        block_hash: Option<Hash>,
+    ) {
+        if let Some(ref mut tracer) = self.consensus_tracer {
+            let event = ConsensusEvent {
+                time: DateTime::<Utc>::from(self.system_state.current_time()),
+                node: *self.state.consensus_public_key(),
+                validator: self.state.validator_id(),
+                height,
+                round,
+                event,
+                propose_hash,
+                block_hash,
+            };
+            tracer.trace(&event);
         }
     }
 
@@ -528,6 +624,7 @@ impl NodeHandler {
         if let Some(conn) = self.state.peers().get(&public_key) {
             let address = conn.addr();
             trace!("Send to address: {}", address);
+            count_sent_message(message);
             let request = NetworkRequest::SendMessage(address, message.clone());
             self.channel.network_requests.send(request).log_error();
         } else {
@@ -538,6 +635,7 @@ impl NodeHandler {
     /// Sends `RawMessage` to the specified address.
     pub fn send_to_addr(&mut self, address: &SocketAddr, message: &RawMessage) {
         trace!("Send to address: {}", address);
+        count_sent_message(message);
         let request = NetworkRequest::SendMessage(*address, message.clone());
         self.channel.network_requests.send(request).log_error();
     }
@@ -547,6 +645,7 @@ impl NodeHandler {
         for conn in self.state.peers().values() {
             let address = conn.addr();
             trace!("Send to address: {}", address);
+            count_sent_message(message);
             let request = NetworkRequest::SendMessage(address, message.clone());
             self.channel.network_requests.send(request).log_error();
         }
@@ -591,8 +690,11 @@ impl NodeHandler {
     /// Adds `NodeTimeout::Propose` timeout to the channel.
     pub fn add_propose_timeout(&mut self) {
         let adjusted_timeout = self.state.propose_timeout();
-        let time = self.round_start_time(self.state.round()) +
-            Duration::from_millis(adjusted_timeout);
+        let min_block_interval = self.state.consensus_config().min_block_interval;
+        let time = cmp::max(
+            self.round_start_time(self.state.round()) + Duration::from_millis(adjusted_timeout),
+            self.state.height_start_time() + Duration::from_millis(min_block_interval),
+        );
 
         trace!(
             "ADD PROPOSE TIMEOUT: time={:?}, height={}, round={}",
@@ -675,6 +777,18 @@ impl ApiSender {
         self.send_external_message(msg)
     }
 
+    /// Sends several transactions to the node at once. Unlike `TransactionSend::send`,
+    /// the transactions are not verified.
+    pub fn send_batch(&self, txs: Vec<Box<Transaction>>) -> io::Result<()> {
+        let messages = txs.into_iter().map(ExternalMessage::Transaction);
+        self.0
+            .clone()
+            .send_all(stream::iter_ok(messages))
+            .wait()
+            .map(drop)
+            .map_err(into_other)
+    }
+
     /// Sends an external message.
     pub fn send_external_message(&self, message: ExternalMessage) -> io::Result<()> {
         self.0.clone().send(message).wait().map(drop).map_err(
@@ -816,7 +930,7 @@ impl Node {
         let api_state = SharedNodeState::new(node_cfg.api.state_update_timeout as u64);
         let system_state = Box::new(DefaultSystemState(node_cfg.listen_address));
         let network_config = config.network;
-        let handler = NodeHandler::new(
+        let mut handler = NodeHandler::new(
             blockchain,
             external_address,
             channel.node_sender(),
@@ -824,6 +938,16 @@ impl Node {
             config,
             api_state,
         );
+        if let Some(ref path) = node_cfg.consensus_trace {
+            let tracer = JsonLinesTracer::open(path).unwrap_or_else(|e| {
+                panic!(
+                    "Unable to open consensus trace file {}: {}",
+                    path.display(),
+                    e
+                )
+            });
+            handler.set_consensus_tracer(Box::new(tracer));
+        }
         Node {
             api_options: node_cfg.api,
             handler,
@@ -848,7 +972,7 @@ impl Node {
             );
             let network_handler = network_part.run(&core.handle());
             core.run(network_handler).map(drop).map_err(|e| {
-                other_error(&format!("An error in the `Network` thread occurred: {}", e))
+                other_error(format!("An error in the `Network` thread occurred: {}", e))
             })
         });
 
@@ -864,25 +988,49 @@ impl Node {
     /// Explorer api prefix is `/api/explorer`
     /// Public api prefix is `/api/services/{service_name}`
     /// Private api prefix is `/api/services/{service_name}`
+    /// WebSocket api is served on a separate `websocket_address`, if it is specified
     pub fn run(self) -> io::Result<()> {
         let blockchain = self.handler().blockchain.clone();
         let api_sender = self.channel();
 
         let private_config_api_thread = match self.api_options.private_api_address {
             Some(listen_address) => {
-                let handler = create_private_api_handler(
+                let mut handler = create_private_api_handler(
                     blockchain.clone(),
                     self.handler().api_state().clone(),
                     api_sender,
                 );
+                if let Some(ref auth) = self.api_options.private_api_auth {
+                    handler.link_before(AuthMiddleware::new(auth.clone()));
+                }
+                let tls_server = match self.api_options.private_api_tls {
+                    Some(ref tls) => Some(tls.server()?),
+                    None => None,
+                };
                 let thread = thread::spawn(move || {
-                    info!("Private exonum api started on {}", listen_address);
-                    Iron::new(handler).http(listen_address).unwrap();
+                    let iron = Iron::new(handler);
+                    match tls_server {
+                        Some(tls_server) => {
+                            info!("Private exonum api started on https://{}", listen_address);
+                            iron.https(listen_address, tls_server).unwrap();
+                        }
+                        None => {
+                            info!("Private exonum api started on {}", listen_address);
+                            iron.http(listen_address).unwrap();
+                        }
+                    }
                 });
                 Some(thread)
             }
             None => None,
         };
+        if let Some(listen_address) = self.api_options.websocket_address {
+            public::start_websocket_api(
+                blockchain.clone(),
+                listen_address,
+                &self.api_options.websocket,
+            ).map_err(into_other)?;
+        }
         let public_config_api_thread = match self.api_options.public_api_address {
             Some(listen_address) => {
                 let handler = create_public_api_handler(
@@ -954,12 +1102,44 @@ impl Node {
         &self.handler
     }
 
+    /// Installs the receiver of the consensus events, replacing the one
+    /// configured by `consensus_trace`.
+    pub fn set_consensus_tracer(&mut self, tracer: Box<ConsensusTracer>) {
+        self.handler.set_consensus_tracer(tracer);
+    }
+
     /// Returns channel.
     pub fn channel(&self) -> ApiSender {
         ApiSender::new(self.channel.api_requests.0.clone())
     }
 }
 
+/// Returns the name of the message type used as a label of the network metrics,
+/// which is one of `metrics::MESSAGE_TYPES`.
+fn message_type(raw: &RawMessage) -> &'static str {
+    if raw.service_id() != CONSENSUS {
+        return "transaction";
+    }
+    match raw.message_type() {
+        CONNECT_MESSAGE_ID => "connect",
+        STATUS_MESSAGE_ID => "status",
+        PROPOSE_MESSAGE_ID => "propose",
+        PREVOTE_MESSAGE_ID => "prevote",
+        PRECOMMIT_MESSAGE_ID => "precommit",
+        BLOCK_RESPONSE_MESSAGE_ID => "block_response",
+        PROPOSE_REQUEST_MESSAGE_ID => "propose_request",
+        TRANSACTIONS_REQUEST_MESSAGE_ID => "transactions_request",
+        PREVOTES_REQUEST_MESSAGE_ID => "prevotes_request",
+        PEERS_REQUEST_MESSAGE_ID => "peers_request",
+        BLOCK_REQUEST_MESSAGE_ID => "block_request",
+        _ => "unknown",
+    }
+}
+
+fn count_sent_message(message: &RawMessage) {
+    metrics::node_metrics().messages_sent.inc(message_type(message));
+}
+
 /// Public for testing
 #[doc(hidden)]
 pub fn create_public_api_handler(
@@ -970,18 +1150,29 @@ pub fn create_public_api_handler(
     let mut mount = Mount::new();
     mount.mount("api/services", blockchain.mount_public_api());
 
+    let mut document = ApiDocument::new("Exonum public API");
+    let context = blockchain.api_context();
+    for service in blockchain.service_map().values() {
+        let prefix = format!("/api/services/{}", service.service_name());
+        document.add_endpoints(&prefix, &service.public_api_endpoints(&context));
+    }
+
     if config.enable_blockchain_explorer {
         let mut router = Router::new();
         let explorer_api = public::ExplorerApi::new(blockchain.clone());
         explorer_api.wire(&mut router);
+        document.add_endpoints("/api/explorer", &explorer_api.endpoints());
         mount.mount("api/explorer", router);
     }
 
     let mut router = Router::new();
     let system_api = public::SystemApi::new(blockchain, shared_api_state);
     system_api.wire(&mut router);
+    document.add_endpoints("/api/system", &system_api.endpoints());
     mount.mount("api/system", router);
 
+    mount.mount("api", openapi_router(&document));
+
     let mut chain = Chain::new(mount);
     if let Some(ref allow_origin) = config.allow_origin {
         chain.link_around(CorsMiddleware::from(allow_origin.clone()));
@@ -999,11 +1190,44 @@ pub fn create_private_api_handler(
     let mut mount = Mount::new();
     mount.mount("api/services", blockchain.mount_private_api());
 
+    let mut document = ApiDocument::new("Exonum private API");
+    let context = blockchain.api_context();
+    for service in blockchain.service_map().values() {
+        let prefix = format!("/api/services/{}", service.service_name());
+        document.add_endpoints(&prefix, &service.private_api_endpoints(&context));
+    }
+
     let mut router = Router::new();
     let node_info = private::NodeInfo::new(blockchain.service_map().iter().map(|(_, s)| s));
     let system_api = private::SystemApi::new(node_info, blockchain, shared_api_state, api_sender);
     system_api.wire(&mut router);
+    document.add_endpoints("/api/system", &system_api.endpoints());
     mount.mount("api/system", router);
 
+    mount.mount("metrics", private::metrics_handler);
+    document.add_endpoints(
+        "/metrics",
+        &[
+            Endpoint::get("/", "Returns metrics of the node in the Prometheus text format")
+                .response(openapi::string()),
+        ],
+    );
+
+    mount.mount("api", openapi_router(&document));
+
     Chain::new(mount)
 }
+
+/// Returns a router serving the given OpenAPI document at `/openapi.json`.
+fn openapi_router(document: &ApiDocument) -> Router {
+    let body = ::serde_json::to_string_pretty(&document.to_json()).unwrap();
+    let openapi = move |_: &mut Request| -> IronResult<Response> {
+        let mut response = Response::with((status::Ok, body.clone()));
+        response.headers.set(ContentType::json());
+        Ok(response)
+    };
+
+    let mut router = Router::new();
+    router.get("/openapi.json", openapi, "openapi");
+    router
+}
@@ -26,10 +26,14 @@ use bit_vec::BitVec;
 use messages::{RawMessage, Message, Propose, Prevote, Precommit, ConsensusMessage, Connect};
 use crypto::{CryptoHash, PublicKey, SecretKey, Hash};
 use storage::{Patch, Snapshot, MapIndex, KeySetIndex};
-use blockchain::{ValidatorKeys, ConsensusConfig, StoredConfiguration, TimeoutAdjusterConfig};
+use blockchain::{ValidatorKeys, ConsensusConfig, Equivocation, StoredConfiguration,
+                 TimeoutAdjusterConfig};
+use blockchain::evidence::is_conflicting;
 use helpers::{Height, Round, ValidatorId, Milliseconds};
+use helpers::metrics;
 use node::whitelist::Whitelist;
 use node::timeout_adjuster::{TimeoutAdjuster, Constant, Dynamic, MovingAverage};
+use blockchain::leader_election::{LeaderElection, make_leader_election};
 
 // TODO: move request timeouts into node configuration (ECR-171)
 
@@ -87,8 +91,16 @@ pub struct State {
 
     validators_rounds: BTreeMap<ValidatorId, Round>,
 
+    // first consensus message of each type received from a validator in a round
+    // of the current height; used to detect equivocation
+    validators_messages: HashMap<(ValidatorId, Round, u16), ConsensusMessage>,
+
+    // round in which our propose is postponed until a transaction appears in the pool
+    postponed_propose: Option<Round>,
+
     timeout_adjuster: Box<TimeoutAdjuster>,
     propose_timeout: Milliseconds,
+    leader_election: Box<LeaderElection>,
 }
 
 /// State of a validator-node.
@@ -166,6 +178,7 @@ pub struct Votes<T: VoteMessage> {
     messages: Vec<T>,
     validators: BitVec,
     count: usize,
+    voting_power: u64,
 }
 
 impl ValidatorState {
@@ -210,14 +223,17 @@ where
             messages: Vec::new(),
             validators: BitVec::from_elem(validators_len, false),
             count: 0,
+            voting_power: 0,
         }
     }
 
-    /// Inserts a new message if it hasn't been inserted yet.
-    pub fn insert(&mut self, message: &T) {
+    /// Inserts a new message with the given voting power of its author if it hasn't been
+    /// inserted yet.
+    pub fn insert(&mut self, message: &T, voting_power: u64) {
         let voter: usize = message.validator().into();
         if !self.validators[voter] {
             self.count += 1;
+            self.voting_power += voting_power;
             self.validators.set(voter, true);
             self.messages.push(message.clone());
         }
@@ -233,6 +249,11 @@ where
         self.count
     }
 
+    /// Returns total voting power of the validators that sent contained messages.
+    pub fn voting_power(&self) -> u64 {
+        self.voting_power
+    }
+
     /// Returns messages.
     pub fn messages(&self) -> &Vec<T> {
         &self.messages
@@ -400,6 +421,8 @@ impl State {
 
             nodes_max_height: BTreeMap::new(),
             validators_rounds: BTreeMap::new(),
+            validators_messages: HashMap::new(),
+            postponed_propose: None,
 
             our_connect_message: connect,
 
@@ -407,6 +430,7 @@ impl State {
 
             timeout_adjuster: make_timeout_adjuster(&stored.consensus),
             propose_timeout: 0,
+            leader_election: make_leader_election(&stored.consensus),
             config: stored,
         }
     }
@@ -502,6 +526,7 @@ impl State {
         trace!("Validator={:#?}", self.validator_state());
 
         self.timeout_adjuster = make_timeout_adjuster(&config.consensus);
+        self.leader_election = make_leader_election(&config.consensus);
         self.config = config;
     }
 
@@ -511,6 +536,12 @@ impl State {
         self.propose_timeout = timeout;
     }
 
+    /// Updates leader election for the current height (see `LeaderElection` for the details).
+    pub fn update_leader_election(&mut self, snapshot: &Snapshot) {
+        let height = self.height();
+        self.leader_election.update(snapshot, height);
+    }
+
     /// Returns adjusted (see `TimeoutAdjuster` for the details) value of the propose timeout.
     pub fn propose_timeout(&self) -> Milliseconds {
         self.propose_timeout
@@ -519,13 +550,16 @@ impl State {
     /// Adds the public key, address, and `Connect` message of a validator.
     pub fn add_peer(&mut self, pubkey: PublicKey, msg: Connect) -> bool {
         self.connections.insert(msg.addr(), pubkey);
-        self.peers.insert(pubkey, msg).is_none()
+        let is_new = self.peers.insert(pubkey, msg).is_none();
+        self.update_peers_metric();
+        is_new
     }
 
     /// Removes a peer by the socket address.
     pub fn remove_peer_with_addr(&mut self, addr: &SocketAddr) -> bool {
         if let Some(pubkey) = self.connections.remove(addr) {
             self.peers.remove(&pubkey);
+            self.update_peers_metric();
             return self.config.validator_keys.iter().any(|x| {
                 x.consensus_key == pubkey
             });
@@ -533,6 +567,10 @@ impl State {
         false
     }
 
+    fn update_peers_metric(&self) {
+        metrics::node_metrics().peers.set(self.peers.len() as i64);
+    }
+
     /// Returns the keys of known peers with their `Connect` messages.
     pub fn peers(&self) -> &HashMap<PublicKey, Connect> {
         &self.peers
@@ -566,9 +604,11 @@ impl State {
 
     /// Returns the leader id for the specified round and current height.
     pub fn leader(&self, round: Round) -> ValidatorId {
-        let height: u64 = self.height().into();
-        let round: u64 = round.into();
-        ValidatorId(((height + round) % (self.validators().len() as u64)) as u16)
+        self.leader_election.leader(
+            self.height(),
+            round,
+            self.validators().len(),
+        )
     }
 
     /// Updates known round for a validator and returns
@@ -590,21 +630,24 @@ impl State {
             }
             *known_round = round;
         }
-        let max_byzantine_count = self.validators().len() / 3;
-        if self.validators_rounds.len() <= max_byzantine_count {
-            trace!("Count of validators, lower then max byzantine count.");
-            return None;
-        }
-
-        let mut rounds: Vec<_> = self.validators_rounds.iter().map(|(_, v)| v).collect();
+        let max_byzantine_power = self.config.total_voting_power() / 3;
+        let mut rounds: Vec<_> = self.validators_rounds
+            .iter()
+            .map(|(id, round)| (*round, self.voting_power_of(*id)))
+            .collect();
         rounds.sort_unstable_by(|a, b| b.cmp(a));
 
-        if rounds[max_byzantine_count] > &self.round {
-            Some(*rounds[max_byzantine_count])
-        } else {
-            None
+        // Find the highest round known by validators with more than 1/3 of the voting power.
+        let mut voting_power = 0;
+        for (round, power) in rounds {
+            voting_power += power;
+            if voting_power > max_byzantine_power {
+                return if round > self.round { Some(round) } else { None };
+            }
         }
 
+        trace!("Voting power of validators, lower then max byzantine voting power.");
+        None
     }
 
     /// Returns the height for a validator identified by the public key.
@@ -628,12 +671,19 @@ impl State {
             .collect()
     }
 
-    /// Returns sufficient number of votes for current validators number.
-    pub fn majority_count(&self) -> usize {
-        State::byzantine_majority_count(self.validators().len())
+    /// Returns sufficient voting power for the current validators (see
+    /// `StoredConfiguration::majority_voting_power`).
+    pub fn majority_count(&self) -> u64 {
+        self.config.majority_voting_power()
+    }
+
+    /// Returns voting power of the validator with the given id.
+    pub fn voting_power_of(&self, id: ValidatorId) -> u64 {
+        self.config.voting_power_of(id)
     }
 
-    /// Returns sufficient number of votes for the given validators number.
+    /// Returns sufficient number of votes for the given validators number, assuming
+    /// that each validator has the voting power of `1`.
     pub fn byzantine_majority_count(total: usize) -> usize {
         total * 2 / 3 + 1
     }
@@ -698,11 +748,17 @@ impl State {
     /// Updates mode's round.
     pub fn jump_round(&mut self, round: Round) {
         self.round = round;
+        self.update_round_metric();
     }
 
     /// Increments node's round by one.
     pub fn new_round(&mut self) {
         self.round.increment();
+        self.update_round_metric();
+    }
+
+    fn update_round_metric(&self) {
+        metrics::node_metrics().round.set(i64::from(u32::from(self.round)));
     }
 
     /// Increments the node height by one and resets previous height data.
@@ -720,10 +776,48 @@ impl State {
         self.prevotes.clear();
         self.precommits.clear();
         self.validators_rounds.clear();
+        self.validators_messages.clear();
+        self.postponed_propose = None;
         if let Some(ref mut validator_state) = self.validator_state {
             validator_state.clear();
         }
         self.requests.clear(); // FIXME: clear all timeouts (ECR-171)
+
+        let height: u64 = self.height.into();
+        metrics::node_metrics().height.set(height as i64);
+        self.update_round_metric();
+    }
+
+    /// Remembers the consensus message and returns evidence of equivocation if the same
+    /// validator has already sent a conflicting message of the same type for the same round
+    /// of the current height.
+    pub fn check_equivocation(&mut self, msg: &ConsensusMessage) -> Option<Equivocation> {
+        let key = (msg.validator(), msg.round(), msg.raw().message_type());
+        let known = self.validators_messages.entry(key).or_insert_with(
+            || msg.clone(),
+        );
+        if is_conflicting(known, msg) {
+            Some(Equivocation::from_messages(known, msg))
+        } else {
+            None
+        }
+    }
+
+    /// Marks the propose for the current round as postponed until a transaction appears
+    /// in the pool (see `ConsensusConfig::max_idle_time`).
+    pub fn postpone_propose(&mut self) {
+        self.postponed_propose = Some(self.round);
+    }
+
+    /// Returns `true` and resets the postponed propose if it has been postponed in
+    /// the current round.
+    pub fn take_postponed_propose(&mut self) -> bool {
+        if self.postponed_propose == Some(self.round) {
+            self.postponed_propose = None;
+            true
+        } else {
+            false
+        }
     }
 
     /// Returns a list of queued consensus messages.
@@ -870,7 +964,7 @@ impl State {
         }
     }
 
-    /// Adds pre-vote. Returns `true` there are +2/3 pre-votes.
+    /// Adds pre-vote. Returns `true` there are pre-votes with +2/3 of voting power.
     ///
     /// # Panics
     ///
@@ -898,17 +992,19 @@ impl State {
 
         let key = (msg.round(), *msg.propose_hash());
         let validators_len = self.validators().len();
+        let voting_power = self.voting_power_of(msg.validator());
         let votes = self.prevotes.entry(key).or_insert_with(
             || Votes::new(validators_len),
         );
-        votes.insert(msg);
-        votes.count() >= majority_count
+        votes.insert(msg, voting_power);
+        votes.voting_power() >= majority_count
     }
 
-    /// Returns `true` if there are +2/3 pre-votes for the specified round and hash.
+    /// Returns `true` if there are pre-votes with +2/3 of voting power for the specified round
+    /// and hash.
     pub fn has_majority_prevotes(&self, round: Round, propose_hash: Hash) -> bool {
         match self.prevotes.get(&(round, propose_hash)) {
-            Some(votes) => votes.count() >= self.majority_count(),
+            Some(votes) => votes.voting_power() >= self.majority_count(),
             None => false,
         }
     }
@@ -931,7 +1027,7 @@ impl State {
             .unwrap_or_else(|| BitVec::from_elem(len, false))
     }
 
-    /// Adds pre-commit. Returns `true` there are +2/3 pre-commits.
+    /// Adds pre-commit. Returns `true` there are pre-commits with +2/3 of voting power.
     ///
     /// # Panics
     ///
@@ -959,11 +1055,12 @@ impl State {
 
         let key = (msg.round(), *msg.block_hash());
         let validators_len = self.validators().len();
+        let voting_power = self.voting_power_of(msg.validator());
         let votes = self.precommits.entry(key).or_insert_with(
             || Votes::new(validators_len),
         );
-        votes.insert(msg);
-        votes.count() >= majority_count
+        votes.insert(msg, voting_power);
+        votes.voting_power() >= majority_count
     }
 
     /// Adds unknown (for this node) propose.
@@ -986,10 +1083,11 @@ impl State {
             .unwrap_or_default()
     }
 
-    /// Returns true if the node has +2/3 pre-commits for the specified round and block hash.
+    /// Returns true if the node has pre-commits with +2/3 of voting power for the specified round
+    /// and block hash.
     pub fn has_majority_precommits(&self, round: Round, block_hash: Hash) -> bool {
         match self.precommits.get(&(round, block_hash)) {
-            Some(votes) => votes.count() >= self.majority_count(),
+            Some(votes) => votes.voting_power() >= self.majority_count(),
             None => false,
         }
     }
@@ -0,0 +1,260 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Structured tracing of the consensus state machine.
+//!
+//! The node reports every consensus state transition as a [`ConsensusEvent`] to
+//! the [`ConsensusTracer`] installed with [`Node::set_consensus_tracer`] or configured
+//! by [`NodeConfig::consensus_trace`]. [`JsonLinesTracer`] writes events to a file,
+//! one JSON object per line; [`timeline`] merges such files from several nodes
+//! into the timeline of a single height.
+//!
+//! [`ConsensusEvent`]: struct.ConsensusEvent.html
+//! [`ConsensusTracer`]: trait.ConsensusTracer.html
+//! [`Node::set_consensus_tracer`]: ../struct.Node.html#method.set_consensus_tracer
+//! [`NodeConfig::consensus_trace`]: ../struct.NodeConfig.html#structfield.consensus_trace
+//! [`JsonLinesTracer`]: struct.JsonLinesTracer.html
+//! [`timeline`]: fn.timeline.html
+
+use chrono::{DateTime, Utc};
+use serde_json;
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crypto::{Hash, PublicKey};
+use encoding::serialize::encode_hex;
+use helpers::{Height, Round, ValidatorId};
+
+/// Type of a consensus state transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsensusEventType {
+    /// A valid propose for the current height has been received.
+    ProposeReceived,
+    /// The node has collected +2/3 prevotes for a propose.
+    PrevoteMajority,
+    /// The node has locked on a propose.
+    Lock,
+    /// The node has collected +2/3 precommits for a propose.
+    PrecommitMajority,
+    /// The block has been committed.
+    Commit,
+    /// The round has timed out.
+    RoundTimeout,
+}
+
+/// Consensus state transition of a node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsensusEvent {
+    /// Time of the event according to the clock of the node.
+    pub time: DateTime<Utc>,
+    /// Consensus key of the node.
+    pub node: PublicKey,
+    /// Validator id of the node or `None` if the node is an auditor.
+    pub validator: Option<ValidatorId>,
+    /// Height the event relates to.
+    pub height: Height,
+    /// Round the event relates to.
+    pub round: Round,
+    /// Type of the event.
+    pub event: ConsensusEventType,
+    /// Hash of the propose the event relates to, if any.
+    pub propose_hash: Option<Hash>,
+    /// Hash of the committed block for `commit` events.
+    pub block_hash: Option<Hash>,
+}
+
+/// Receiver of the consensus events of a node.
+pub trait ConsensusTracer: Send + fmt::Debug {
+    /// Records the event. Called from the consensus thread, so the implementations
+    /// should not block for a long time.
+    fn trace(&mut self, event: &ConsensusEvent);
+}
+
+/// Tracer writing events to a file as JSON lines.
+#[derive(Debug)]
+pub struct JsonLinesTracer {
+    writer: BufWriter<File>,
+}
+
+impl JsonLinesTracer {
+    /// Creates a tracer appending events to the file with the given path.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(JsonLinesTracer { writer: BufWriter::new(file) })
+    }
+
+    fn write(&mut self, event: &ConsensusEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.writer, event)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()
+    }
+}
+
+impl ConsensusTracer for JsonLinesTracer {
+    fn trace(&mut self, event: &ConsensusEvent) {
+        if let Err(e) = self.write(event) {
+            error!("Unable to write consensus trace event: {}", e);
+        }
+    }
+}
+
+/// Reads events written by `JsonLinesTracer`. Empty lines are skipped.
+pub fn read_events<R: BufRead>(reader: R) -> io::Result<Vec<ConsensusEvent>> {
+    let mut events = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str(&line).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid event on line {}: {}", index + 1, e),
+            )
+        })?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Reads events from the file written by `JsonLinesTracer`.
+pub fn read_events_from_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<ConsensusEvent>> {
+    read_events(BufReader::new(File::open(path)?))
+}
+
+/// Returns the events of the given height from several nodes ordered by time.
+/// Events with the same time are ordered by round and then by the node.
+///
+/// Note that the events are ordered by the clocks of the nodes, so the order
+/// of the events of different nodes is only as precise as the clocks are synchronized.
+pub fn timeline<I>(events: I, height: Height) -> Vec<ConsensusEvent>
+where
+    I: IntoIterator<Item = ConsensusEvent>,
+{
+    let mut events: Vec<_> = events
+        .into_iter()
+        .filter(|event| event.height == height)
+        .collect();
+    events.sort_by(|a, b| {
+        (a.time, a.round, a.node).cmp(&(b.time, b.round, b.node))
+    });
+    events
+}
+
+impl fmt::Display for ConsensusEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let node = self.validator.map_or_else(
+            || format!("auditor {}", encode_hex(&self.node)),
+            |id| format!("validator {}", id),
+        );
+        write!(
+            f,
+            "{} {} height={} round={} {:?}",
+            self.time.format("%H:%M:%S%.3f"),
+            node,
+            self.height,
+            self.round,
+            self.event
+        )?;
+        if let Some(ref hash) = self.propose_hash {
+            write!(f, " propose={}", encode_hex(hash))?;
+        }
+        if let Some(ref hash) = self.block_hash {
+            write!(f, " block={}", encode_hex(hash))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use crypto::{gen_keypair, hash};
+    use super::*;
+
+    fn event(
+        node: PublicKey,
+        millis: i64,
+        height: u64,
+        round: u32,
+        event: ConsensusEventType,
+    ) -> ConsensusEvent {
+        ConsensusEvent {
+            time: Utc.ymd(2018, 3, 1).and_hms(12, 0, 0) + Duration::milliseconds(millis),
+            node,
+            validator: Some(ValidatorId(0)),
+            height: Height(height),
+            round: Round(round),
+            event,
+            propose_hash: Some(hash(&[1, 2, 3])),
+            block_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_event_json_lines() {
+        let (node, _) = gen_keypair();
+        let events = vec![
+            event(node, 0, 1, 1, ConsensusEventType::ProposeReceived),
+            event(node, 10, 1, 1, ConsensusEventType::Commit),
+        ];
+
+        let mut buffer = Vec::new();
+        for event in &events {
+            serde_json::to_writer(&mut buffer, event).unwrap();
+            buffer.extend_from_slice(b"\n\n");
+        }
+        assert_eq!(read_events(&buffer[..]).unwrap(), events);
+
+        let json = serde_json::to_value(&events[0]).unwrap();
+        assert_eq!(json["event"], "propose_received");
+        assert_eq!(json["height"], 1);
+        assert_eq!(json["round"], 1);
+        assert_eq!(json["validator"], 0);
+
+        assert!(read_events(&b"{}\n"[..]).is_err());
+    }
+
+    #[test]
+    fn test_timeline() {
+        let (first, _) = gen_keypair();
+        let (second, _) = gen_keypair();
+        let first_events = vec![
+            event(first, 0, 1, 1, ConsensusEventType::ProposeReceived),
+            event(first, 30, 1, 1, ConsensusEventType::PrevoteMajority),
+            event(first, 50, 2, 1, ConsensusEventType::ProposeReceived),
+        ];
+        let second_events = vec![
+            event(second, 10, 1, 1, ConsensusEventType::ProposeReceived),
+            event(second, 20, 1, 1, ConsensusEventType::RoundTimeout),
+        ];
+
+        let timeline = timeline(first_events.into_iter().chain(second_events), Height(1));
+        let types: Vec<_> = timeline.iter().map(|e| (e.node, e.event)).collect();
+        assert_eq!(
+            types,
+            vec![
+                (first, ConsensusEventType::ProposeReceived),
+                (second, ConsensusEventType::ProposeReceived),
+                (second, ConsensusEventType::RoundTimeout),
+                (first, ConsensusEventType::PrevoteMajority),
+            ]
+        );
+    }
+}
@@ -28,7 +28,9 @@ use node;
 use node::state::{PREVOTES_REQUEST_TIMEOUT, PROPOSE_REQUEST_TIMEOUT, TRANSACTIONS_REQUEST_TIMEOUT};
 use helpers::{Height, Round, user_agent};
 use super::timestamping::{TimestampTx, TimestampingTxGenerator, TIMESTAMPING_SERVICE};
-use super::sandbox::{timestamping_sandbox, sandbox_with_services_uninitialized};
+use super::sandbox::{timestamping_sandbox, sandbox_with_services_uninitialized,
+                     sandbox_consensus_config, timestamping_sandbox_with_consensus,
+                     weighted_timestamping_sandbox};
 use super::sandbox_tests_helper::*;
 use super::config_updater::TxConfig;
 
@@ -1026,6 +1028,55 @@ fn request_propose_when_get_prevote() {
     sandbox.add_time(Duration::from_millis(0));
 }
 
+/// idea of the test is to verify that conflicting prevotes from the same validator
+/// are stored as evidence of equivocation
+#[test]
+fn save_evidence_when_get_conflicting_prevotes() {
+    let sandbox = timestamping_sandbox();
+
+    let prevote = Prevote::new(
+        VALIDATOR_2,
+        HEIGHT_ONE,
+        ROUND_ONE,
+        &empty_hash(),
+        LOCK_ZERO,
+        sandbox.s(VALIDATOR_2),
+    );
+    let conflicting_prevote = Prevote::new(
+        VALIDATOR_2,
+        HEIGHT_ONE,
+        ROUND_ONE,
+        &::crypto::hash(&[1]),
+        LOCK_ZERO,
+        sandbox.s(VALIDATOR_2),
+    );
+
+    sandbox.recv(&prevote);
+    sandbox.recv(&prevote);
+    assert_eq!(
+        Schema::new(&sandbox.blockchain_ref().snapshot())
+            .equivocations()
+            .values()
+            .count(),
+        0
+    );
+
+    sandbox.recv(&conflicting_prevote);
+    let evidence: Vec<_> = Schema::new(&sandbox.blockchain_ref().snapshot())
+        .equivocations()
+        .values()
+        .collect();
+    assert_eq!(evidence.len(), 1);
+    assert_eq!(evidence[0].validator(), VALIDATOR_2);
+    assert_eq!(evidence[0].height(), HEIGHT_ONE);
+    assert_eq!(evidence[0].round(), ROUND_ONE);
+    assert_eq!(
+        evidence[0].messages(),
+        vec![prevote.raw().clone(), conflicting_prevote.raw().clone()]
+    );
+    assert!(evidence[0].verify(&sandbox.p(VALIDATOR_2)));
+}
+
 /// idea of the test is to verify request transaction scenario: other node requests
 /// transaction from our node
 // TODO: add scenario with manually created and signed tx
@@ -1963,6 +2014,146 @@ fn handle_precommit_positive_scenario_commit() {
     sandbox.add_time(Duration::from_millis(0));
 }
 
+/// scenario: // HANDLE PRECOMMIT with weighted validators
+/// - validator 0 (us) has voting power 4, others have 1; total is 7, majority is 5
+/// - precommits from 3 of 4 validators are not enough to commit
+#[test]
+fn handle_precommit_weighted_majority_not_reached() {
+    let sandbox = weighted_timestamping_sandbox([Some(4), None, None, None]);
+
+    let tx = gen_timestamping_tx();
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_duration_since_sandbox_time(sandbox.propose_timeout())
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    let block = BlockBuilder::new(&sandbox)
+        .with_duration_since_sandbox_time(sandbox.propose_timeout())
+        .with_tx_hash(&tx.hash())
+        .with_state_hash(&sandbox.compute_state_hash(&[tx.raw().clone()]))
+        .build();
+
+    let precommit_1 = Precommit::new(
+        VALIDATOR_1,
+        HEIGHT_ONE,
+        ROUND_ONE,
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(VALIDATOR_1),
+    );
+    let precommit_2 = Precommit::new(
+        VALIDATOR_2,
+        HEIGHT_ONE,
+        ROUND_ONE,
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(VALIDATOR_2),
+    );
+    let precommit_3 = Precommit::new(
+        VALIDATOR_3,
+        HEIGHT_ONE,
+        ROUND_ONE,
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(VALIDATOR_3),
+    );
+
+    sandbox.recv(&precommit_1);
+    sandbox.add_time(Duration::from_millis(PROPOSE_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.a(VALIDATOR_1),
+        &make_request_propose_from_precommit(&sandbox, &precommit_1),
+    );
+    sandbox.send(
+        sandbox.a(VALIDATOR_1),
+        &make_request_prevote_from_precommit(&sandbox, &precommit_1),
+    );
+
+    sandbox.recv(&precommit_2);
+    sandbox.add_time(Duration::from_millis(PROPOSE_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.a(VALIDATOR_2),
+        &make_request_propose_from_precommit(&sandbox, &precommit_2),
+    );
+    sandbox.send(
+        sandbox.a(VALIDATOR_2),
+        &make_request_prevote_from_precommit(&sandbox, &precommit_2),
+    );
+    sandbox.recv(&propose);
+    sandbox.recv(&tx);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+
+    // 3 of 4 validators precommit, but their voting power is only 3 of 7.
+    sandbox.recv(&precommit_3);
+    sandbox.assert_state(HEIGHT_ONE, ROUND_ONE);
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// scenario: // HANDLE PRECOMMIT with weighted validators
+/// - validator 3 has voting power 4, others have 1; total is 7, majority is 5
+/// - precommits from 2 of 4 validators are enough to commit
+#[test]
+fn handle_precommit_weighted_majority_reached() {
+    let sandbox = weighted_timestamping_sandbox([None, None, None, Some(4)]);
+
+    let tx = gen_timestamping_tx();
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_duration_since_sandbox_time(sandbox.propose_timeout())
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    let block = BlockBuilder::new(&sandbox)
+        .with_duration_since_sandbox_time(sandbox.propose_timeout())
+        .with_tx_hash(&tx.hash())
+        .with_state_hash(&sandbox.compute_state_hash(&[tx.raw().clone()]))
+        .build();
+
+    let precommit_1 = Precommit::new(
+        VALIDATOR_1,
+        HEIGHT_ONE,
+        ROUND_ONE,
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(VALIDATOR_1),
+    );
+    let precommit_3 = Precommit::new(
+        VALIDATOR_3,
+        HEIGHT_ONE,
+        ROUND_ONE,
+        &propose.hash(),
+        &block.hash(),
+        sandbox.time().into(),
+        sandbox.s(VALIDATOR_3),
+    );
+
+    sandbox.recv(&precommit_1);
+    sandbox.add_time(Duration::from_millis(PROPOSE_REQUEST_TIMEOUT));
+    sandbox.send(
+        sandbox.a(VALIDATOR_1),
+        &make_request_propose_from_precommit(&sandbox, &precommit_1),
+    );
+    sandbox.send(
+        sandbox.a(VALIDATOR_1),
+        &make_request_prevote_from_precommit(&sandbox, &precommit_1),
+    );
+    // Make sandbox time >= propose time; requests to validator 1 are not repeated.
+    sandbox.add_time(Duration::from_millis(PROPOSE_REQUEST_TIMEOUT));
+    sandbox.recv(&propose);
+    sandbox.recv(&tx);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.assert_state(HEIGHT_ONE, ROUND_ONE);
+
+    // Only 2 of 4 validators precommit, but their voting power is 5 of 7.
+    sandbox.recv(&precommit_3);
+    sandbox.assert_state(HEIGHT_TWO, ROUND_ONE);
+    sandbox.check_broadcast_status(HEIGHT_TWO, &block.hash());
+    sandbox.add_time(Duration::from_millis(0));
+}
+
 /// LOCK
 /// - Send precommit during the lock   // covered in `lock_to_propose_when_get_2_3_prevote_positive`
 /// - if there aren't incompatible prevotes
@@ -3080,6 +3271,133 @@ fn test_send_propose_and_prevote_when_we_are_leader() {
     sandbox.add_time(Duration::from_millis(0));
 }
 
+/// HANDLE PROPOSE TIMEOUT:
+/// - do not send propose earlier than `min_block_interval` since the start of the height
+/// idea:
+///  - reach the height where we are the leader in the first round
+///  - observe that propose is not sent after `propose_timeout`
+///  - observe propose and prevote after `min_block_interval`
+#[test]
+fn test_postpone_propose_till_min_block_interval() {
+    let min_block_interval = 500;
+    let mut consensus = sandbox_consensus_config();
+    consensus.min_block_interval = min_block_interval;
+    let sandbox = timestamping_sandbox_with_consensus(consensus);
+    let sandbox_state = SandboxState::new();
+
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(HEIGHT_THREE, ROUND_ONE);
+    assert!(sandbox.is_leader());
+
+    sandbox.add_time(Duration::from_millis(sandbox.propose_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        min_block_interval - sandbox.propose_timeout() - 1,
+    ));
+    sandbox.add_time(Duration::from_millis(1));
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// HANDLE PROPOSE TIMEOUT:
+/// - do not send empty propose until `max_idle_time` passes
+/// idea:
+///  - reach the height where we are the leader in the first round with an empty pool
+///  - observe that propose is not sent after `propose_timeout`
+///  - observe empty propose and prevote after `max_idle_time`
+#[test]
+fn test_postpone_empty_propose_till_max_idle_time() {
+    let max_idle_time = 800;
+    let mut consensus = sandbox_consensus_config();
+    consensus.max_idle_time = Some(max_idle_time);
+    let sandbox = timestamping_sandbox_with_consensus(consensus);
+    let sandbox_state = SandboxState::new();
+
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(HEIGHT_THREE, ROUND_ONE);
+    assert!(sandbox.is_leader());
+
+    sandbox.add_time(Duration::from_millis(sandbox.propose_timeout()));
+    sandbox.add_time(Duration::from_millis(
+        max_idle_time - sandbox.propose_timeout() - 1,
+    ));
+    sandbox.add_time(Duration::from_millis(1));
+
+    let propose = ProposeBuilder::new(&sandbox).build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// HANDLE POSTPONED PROPOSE:
+/// - send postponed propose as soon as a transaction appears in the pool
+/// - do not send another propose after `max_idle_time`
+#[test]
+fn test_send_postponed_propose_on_new_tx() {
+    let max_idle_time = 800;
+    let mut consensus = sandbox_consensus_config();
+    consensus.max_idle_time = Some(max_idle_time);
+    let sandbox = timestamping_sandbox_with_consensus(consensus);
+    let sandbox_state = SandboxState::new();
+
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(HEIGHT_THREE, ROUND_ONE);
+    assert!(sandbox.is_leader());
+
+    sandbox.add_time(Duration::from_millis(sandbox.propose_timeout()));
+
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+
+    sandbox.add_time(Duration::from_millis(
+        max_idle_time - sandbox.propose_timeout(),
+    ));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
+/// HANDLE PROPOSE TIMEOUT:
+/// - do not postpone propose if the pool is not empty
+/// - do not send another propose after `max_idle_time`
+#[test]
+fn test_do_not_postpone_propose_with_nonempty_pool() {
+    let max_idle_time = 800;
+    let mut consensus = sandbox_consensus_config();
+    consensus.max_idle_time = Some(max_idle_time);
+    let sandbox = timestamping_sandbox_with_consensus(consensus);
+    let sandbox_state = SandboxState::new();
+
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+    sandbox.assert_state(HEIGHT_THREE, ROUND_ONE);
+    assert!(sandbox.is_leader());
+
+    let tx = gen_timestamping_tx();
+    sandbox.recv(&tx);
+    sandbox.add_time(Duration::from_millis(sandbox.propose_timeout()));
+
+    let propose = ProposeBuilder::new(&sandbox)
+        .with_tx_hashes(&[tx.hash()])
+        .build();
+    sandbox.broadcast(&propose);
+    sandbox.broadcast(&make_prevote_from_propose(&sandbox, &propose));
+
+    sandbox.add_time(Duration::from_millis(
+        max_idle_time - sandbox.propose_timeout(),
+    ));
+    sandbox.add_time(Duration::from_millis(0));
+}
+
 /// HANDLE ROUND TIMEOUT:
 /// - send prevote if locked to propose
 /// idea:
@@ -3288,6 +3606,49 @@ fn test_schema_config_changes() {
     );
 }
 
+/// - Check validators statistics computed from committed blocks
+/// - idea of the test is that sandbox node proposes each block in the first round
+///   where it is the leader, and precommits are received from validators 0, 1 and 2
+#[test]
+fn test_validators_statistics() {
+    use std::f64::EPSILON;
+    use blockchain::ValidatorsStatistics;
+    use helpers::ValidatorId;
+
+    let sandbox = timestamping_sandbox();
+    let sandbox_state = SandboxState::new();
+
+    // Blocks are committed in rounds 3, 2 and 1 correspondingly.
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+    add_one_height(&sandbox, &sandbox_state);
+
+    let stats = ValidatorsStatistics::compute(&*sandbox.blockchain_ref().snapshot(), 0..4);
+    assert_eq!(stats.range, 1..4);
+    assert!((stats.average_rounds - 2.0).abs() < EPSILON);
+    assert_eq!(stats.validators.len(), sandbox.n_validators());
+
+    let expected = [
+        // (precommits, leader rounds, proposed blocks)
+        (3, 3, 3),
+        (3, 0, 0),
+        (3, 1, 0),
+        (0, 2, 0),
+    ];
+    for (i, &(precommits, leader_rounds, proposed_blocks)) in expected.iter().enumerate() {
+        let validator = &stats.validators[i];
+        assert_eq!(validator.consensus_key, sandbox.p(ValidatorId(i as u16)));
+        assert_eq!(validator.blocks, 3);
+        assert_eq!(validator.precommits, precommits);
+        assert_eq!(validator.leader_rounds, leader_rounds);
+        assert_eq!(validator.proposed_blocks, proposed_blocks);
+    }
+    assert!((stats.validators[0].precommit_participation - 1.0).abs() < EPSILON);
+    assert!((stats.validators[0].proposal_success_rate - 1.0).abs() < EPSILON);
+    assert!(stats.validators[3].precommit_participation.abs() < EPSILON);
+    assert!(stats.validators[3].proposal_success_rate.abs() < EPSILON);
+}
+
 // - lock to propose when get +2/3 prevote
 //     - only if propose is known     - covered in request_propose_when_get_prevote()
 //     - only if all txs is known     - covered in request_txs_when_get_propose_or_prevote()
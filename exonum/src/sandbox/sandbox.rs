@@ -684,6 +684,31 @@ pub fn sandbox_with_services(services: Vec<Box<Service>>) -> Sandbox {
 
 /// Constructs an uninitialized instance of a `Sandbox`.
 pub fn sandbox_with_services_uninitialized(services: Vec<Box<Service>>) -> Sandbox {
+    sandbox_with_config_uninitialized(services, sandbox_consensus_config(), [None; 4])
+}
+
+/// Returns the consensus configuration used by the sandbox by default.
+pub fn sandbox_consensus_config() -> ConsensusConfig {
+    ConsensusConfig {
+        round_timeout: 1000,
+        status_timeout: 600_000,
+        peers_timeout: 600_000,
+        txs_block_limit: 1000,
+        max_message_len: 1024 * 1024,
+        min_block_interval: 0,
+        max_idle_time: None,
+        timeout_adjuster: TimeoutAdjusterConfig::Constant { timeout: 200 },
+        leader_election: Default::default(),
+    }
+}
+
+/// Constructs an uninitialized instance of a `Sandbox` with the given consensus
+/// configuration and voting power of each of the four validators.
+pub fn sandbox_with_config_uninitialized(
+    services: Vec<Box<Service>>,
+    consensus: ConsensusConfig,
+    voting_power: [Option<u64>; 4],
+) -> Sandbox {
     let validators = vec![
         gen_keypair_from_seed(&Seed::new([12; 32])),
         gen_keypair_from_seed(&Seed::new([13; 32])),
@@ -709,22 +734,19 @@ pub fn sandbox_with_services_uninitialized(services: Vec<Box<Service>>) -> Sandb
         ApiSender::new(api_channel.0.clone()),
     );
 
-    let consensus = ConsensusConfig {
-        round_timeout: 1000,
-        status_timeout: 600_000,
-        peers_timeout: 600_000,
-        txs_block_limit: 1000,
-        max_message_len: 1024 * 1024,
-        timeout_adjuster: TimeoutAdjusterConfig::Constant { timeout: 200 },
-    };
     let genesis = GenesisConfig::new_with_consensus(
         consensus,
-        validators.iter().zip(service_keys.iter()).map(|x| {
-            ValidatorKeys {
-                consensus_key: (x.0).0,
-                service_key: (x.1).0,
-            }
-        }),
+        validators
+            .iter()
+            .zip(service_keys.iter())
+            .zip(voting_power.iter())
+            .map(|((consensus, service), voting_power)| {
+                ValidatorKeys {
+                    consensus_key: consensus.0,
+                    service_key: service.0,
+                    voting_power: *voting_power,
+                }
+            }),
     );
     blockchain.initialize(genesis).unwrap();
 
@@ -802,6 +824,31 @@ pub fn timestamping_sandbox() -> Sandbox {
     ])
 }
 
+/// Constructs a timestamping sandbox with the given voting power of validators.
+pub fn weighted_timestamping_sandbox(voting_power: [Option<u64>; 4]) -> Sandbox {
+    timestamping_sandbox_with_config(sandbox_consensus_config(), voting_power)
+}
+
+/// Constructs a timestamping sandbox with the given consensus configuration.
+pub fn timestamping_sandbox_with_consensus(consensus: ConsensusConfig) -> Sandbox {
+    timestamping_sandbox_with_config(consensus, [None; 4])
+}
+
+fn timestamping_sandbox_with_config(
+    consensus: ConsensusConfig,
+    voting_power: [Option<u64>; 4],
+) -> Sandbox {
+    let services: Vec<Box<Service>> = vec![
+        Box::new(TimestampingService::new()),
+        Box::new(ConfigUpdateService::new()),
+    ];
+    let mut sandbox = sandbox_with_config_uninitialized(services, consensus, voting_power);
+    let time = sandbox.time();
+    let validators_count = sandbox.validators_map.len();
+    sandbox.initialize(time, 1, validators_count);
+    sandbox
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1010,4 +1057,22 @@ mod tests {
         let tx = TxAfterCommit::new_with_height(Height(1));
         sandbox.broadcast(&tx);
     }
+
+    #[test]
+    fn test_sandbox_api_state_update() {
+        let s = timestamping_sandbox();
+        s.add_time(Duration::from_millis(1000));
+        s.assert_state(HEIGHT_ONE, ROUND_TWO);
+
+        let api_state = s.inner.borrow().handler.api_state.clone();
+        assert_eq!(api_state.height_start_time(), None);
+        api_state.update_node_state(&s.node_state());
+        assert_eq!(api_state.height(), HEIGHT_ONE);
+        assert_eq!(api_state.max_known_height(), HEIGHT_ONE);
+        assert_eq!(api_state.round(), ROUND_TWO);
+        assert_eq!(
+            api_state.height_start_time(),
+            Some(s.node_state().height_start_time())
+        );
+    }
 }
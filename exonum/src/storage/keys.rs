@@ -21,6 +21,7 @@ use chrono::{DateTime, Utc, NaiveDateTime};
 use uuid::Uuid;
 
 use crypto::{Hash, PublicKey, HASH_SIZE, PUBLIC_KEY_LENGTH};
+use encoding::Decimal;
 
 /// A type that can be (de)serialized as a key in the blockchain storage.
 ///
@@ -176,6 +177,8 @@ macro_rules! storage_key_for_ints {
 storage_key_for_ints!{u16, i16, 2, read_u16, write_u16}
 storage_key_for_ints!{u32, i32, 4, read_u32, write_u32}
 storage_key_for_ints!{u64, i64, 8, read_u64, write_u64}
+#[cfg(feature = "int128")]
+storage_key_for_ints!{u128, i128, 16, read_u128, write_u128}
 
 impl StorageKey for Hash {
     fn size(&self) -> usize {
@@ -299,6 +302,96 @@ impl StorageKey for Uuid {
     }
 }
 
+/// `Decimal` uses 17 bytes in the storage, so that keys are sorted in the same order
+/// as by the `Ord` implementation. The first 16 bytes contain the number multiplied
+/// by `10^(MAX_SCALE - scale)`, that is, converted to the maximum scale. It is stored
+/// as a 128-bit integer mapped to the unsigned format in big-endian encoding.
+/// The last byte contains the scale, so that numerically equal keys are sorted by it.
+impl StorageKey for Decimal {
+    fn size(&self) -> usize {
+        17
+    }
+
+    fn write(&self, buffer: &mut [u8]) {
+        let factor = decimal_factor(self.scale());
+        // `wrapping_abs` leaves `i64::min_value()` intact, and it is then cast
+        // to its absolute value.
+        let (mut high, mut low) = mul_wide(self.mantissa().wrapping_abs() as u64, factor);
+        if self.mantissa() < 0 {
+            let (neg_high, neg_low) = neg_wide(high, low);
+            high = neg_high;
+            low = neg_low;
+        }
+        BigEndian::write_u64(&mut buffer[0..8], high ^ SIGN_BIT);
+        BigEndian::write_u64(&mut buffer[8..16], low);
+        buffer[16] = self.scale();
+    }
+
+    fn read(buffer: &[u8]) -> Self::Owned {
+        let scale = buffer[16];
+        Decimal::check_scale(scale, 16).expect("Malformed decimal key in the storage");
+        let high = BigEndian::read_u64(&buffer[0..8]) ^ SIGN_BIT;
+        let low = BigEndian::read_u64(&buffer[8..16]);
+        let mantissa = if high & SIGN_BIT == 0 {
+            div_wide(high, low, decimal_factor(scale)) as i64
+        } else {
+            let (high, low) = neg_wide(high, low);
+            // The magnitude is at most `2^63`, which is mapped to `i64::min_value()`.
+            (div_wide(high, low, decimal_factor(scale)) as i64).wrapping_neg()
+        };
+        Decimal::new(mantissa, scale)
+    }
+}
+
+const SIGN_BIT: u64 = 1 << 63;
+
+// Returns `10^(MAX_SCALE - scale)`.
+fn decimal_factor(scale: u8) -> u64 {
+    10_u64.pow(u32::from(Decimal::MAX_SCALE - scale))
+}
+
+// Multiplies two numbers into a 128-bit number represented by its high and low halves.
+fn mul_wide(lhs: u64, rhs: u64) -> (u64, u64) {
+    const MASK: u64 = 0xffff_ffff;
+    let (lhs_high, lhs_low) = (lhs >> 32, lhs & MASK);
+    let (rhs_high, rhs_low) = (rhs >> 32, rhs & MASK);
+
+    let low_low = lhs_low * rhs_low;
+    let high_low = lhs_high * rhs_low;
+    let low_high = lhs_low * rhs_high;
+    let high_high = lhs_high * rhs_high;
+
+    // Cannot overflow: the sum does not exceed `(2^32 - 1)^2 + 2 * (2^32 - 1)`.
+    let middle = (low_low >> 32) + (high_low & MASK) + low_high;
+    let high = high_high + (high_low >> 32) + (middle >> 32);
+    let low = (middle << 32) | (low_low & MASK);
+    (high, low)
+}
+
+// Negates a 128-bit number in the two's complement representation.
+fn neg_wide(high: u64, low: u64) -> (u64, u64) {
+    let low = (!low).wrapping_add(1);
+    let carry = if low == 0 { 1 } else { 0 };
+    ((!high).wrapping_add(carry), low)
+}
+
+// Divides a 128-bit number by `divisor`, which should be less than `2^63`.
+// The quotient should fit into 64 bits, that is, `high` should be less than `divisor`.
+fn div_wide(high: u64, low: u64, divisor: u64) -> u64 {
+    debug_assert!(high < divisor && divisor < SIGN_BIT);
+    let mut remainder = high;
+    let mut quotient = 0;
+    for bit in (0..64).rev() {
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if remainder >= divisor {
+            remainder -= divisor;
+            quotient |= 1;
+        }
+    }
+    quotient
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -373,6 +466,29 @@ mod tests {
     test_storage_key_for_int_type!{fuzz u64, 8 => test_storage_key_for_u64}
     test_storage_key_for_int_type!{fuzz i64, 8 => test_storage_key_for_i64}
 
+    #[test]
+    #[cfg(feature = "int128")]
+    fn storage_key_for_int128() {
+        let values = [
+            i128::min_value(),
+            -(1 << 64),
+            -1,
+            0,
+            1,
+            1 << 64,
+            i128::max_value(),
+        ];
+        let (mut x_buffer, mut y_buffer) = ([0u8; 16], [0u8; 16]);
+        for w in values.windows(2) {
+            let (x, y) = (w[0], w[1]);
+            x.write(&mut x_buffer);
+            y.write(&mut y_buffer);
+            assert!(x_buffer < y_buffer);
+            assert_eq!(i128::read(&x_buffer), x);
+            assert_eq!(u128::read(&y_buffer[..]), y.wrapping_sub(i128::min_value()) as u128);
+        }
+    }
+
     #[test]
     fn signed_int_key_in_index() {
         use storage::{Database, MapIndex, MemoryDB};
@@ -584,6 +700,84 @@ mod tests {
         }
     }
 
+    #[test]
+    fn decimal_key_order() {
+        // Sorted as per `Ord`, including numerically equal values with different scales.
+        let values = [
+            Decimal::new(i64::min_value(), 0),
+            Decimal::new(i64::min_value(), 18),
+            Decimal::new(-15, 1),
+            Decimal::new(-1_500, 3),
+            Decimal::new(-1, 18),
+            Decimal::new(0, 0),
+            Decimal::new(0, 2),
+            Decimal::new(1, 18),
+            Decimal::new(15, 1),
+            Decimal::new(150, 2),
+            Decimal::new(151, 2),
+            Decimal::new(2, 0),
+            Decimal::new(i64::max_value(), 18),
+            Decimal::new(i64::max_value(), 0),
+        ];
+
+        let mut buffers = Vec::new();
+        for value in values.iter() {
+            let mut buffer = get_buffer(value);
+            value.write(&mut buffer);
+            assert_eq!(Decimal::read(&buffer), *value);
+            buffers.push(buffer);
+        }
+        for i in 1..values.len() {
+            assert!(values[i - 1] < values[i]);
+            assert!(
+                buffers[i - 1] < buffers[i],
+                "Keys of {} and {} are not ordered",
+                values[i - 1],
+                values[i]
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Malformed decimal key in the storage")]
+    fn decimal_key_incorrect_scale() {
+        let mut buffer = [0u8; 17];
+        Decimal::new(1, 18).write(&mut buffer);
+        buffer[16] = 19;
+        Decimal::read(&buffer);
+    }
+
+    #[test]
+    fn decimal_key_in_index() {
+        use storage::{Database, MapIndex, MemoryDB};
+
+        let db: Box<Database> = Box::new(MemoryDB::new());
+        let amounts: Vec<Decimal> = ["-12.50", "0.00", "0.01", "3.14", "-0.01"]
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        let mut fork = db.fork();
+        {
+            let mut index: MapIndex<_, Decimal, u64> = MapIndex::new("test_index", &mut fork);
+            for (i, amount) in amounts.iter().enumerate() {
+                index.put(amount, i as u64);
+            }
+        }
+        db.merge(fork.into_patch()).unwrap();
+
+        let snapshot = db.snapshot();
+        let index: MapIndex<_, Decimal, u64> = MapIndex::new("test_index", snapshot);
+        let mut sorted = amounts.clone();
+        sorted.sort();
+        assert_eq!(index.keys().collect::<Vec<_>>(), sorted);
+        assert_eq!(index.get(&Decimal::new(314, 2)), Some(3));
+        assert_eq!(index.get(&Decimal::new(3_140, 3)), None);
+        assert_eq!(
+            index.iter_from(&Decimal::new(0, 2)).collect::<Vec<_>>(),
+            vec![(Decimal::new(0, 2), 1), (Decimal::new(1, 2), 2), (Decimal::new(314, 2), 3)]
+        );
+    }
+
     fn get_buffer<T: StorageKey + ?Sized>(key: &T) -> Vec<u8> {
         vec![0; key.size()]
     }
@@ -26,9 +26,11 @@ use std::path::Path;
 use std::fmt;
 use std::error::Error;
 use std::iter::Peekable;
+use std::time::Instant;
 
 use storage::{self, DbOptions, Database, Iterator, Iter, Snapshot, Patch};
 use storage::db::Change;
+use helpers::metrics;
 
 pub use rocksdb::WriteOptions as RocksDBWriteOptions;
 pub use rocksdb::BlockBasedOptions as RocksBlockOptions;
@@ -99,7 +101,10 @@ impl RocksDB {
                 }
             }
         }
-        self.db.write_opt(batch, w_opts).map_err(Into::into)
+        let start = Instant::now();
+        let result = self.db.write_opt(batch, w_opts).map_err(Into::into);
+        metrics::node_metrics().rocksdb_write_time.observe_duration(start.elapsed());
+        result
     }
 }
 
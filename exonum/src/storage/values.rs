@@ -22,6 +22,7 @@ use std::mem;
 use std::borrow::Cow;
 
 use crypto::{Hash, PublicKey};
+use encoding::Decimal;
 use messages::{RawMessage, MessageBuffer};
 use helpers::Round;
 use super::UniqueHash;
@@ -212,6 +213,50 @@ impl StorageValue for i64 {
     }
 }
 
+/// Uses little-endian encoding.
+#[cfg(feature = "int128")]
+impl StorageValue for u128 {
+    fn into_bytes(self) -> Vec<u8> {
+        let mut v = vec![0; 16];
+        LittleEndian::write_u128(&mut v, self);
+        v
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        LittleEndian::read_u128(value.as_ref())
+    }
+}
+
+/// Uses little-endian encoding.
+#[cfg(feature = "int128")]
+impl StorageValue for i128 {
+    fn into_bytes(self) -> Vec<u8> {
+        let mut v = vec![0; 16];
+        LittleEndian::write_i128(&mut v, self);
+        v
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        LittleEndian::read_i128(value.as_ref())
+    }
+}
+
+/// Uses the same encoding as the `Field` implementation: the mantissa in little endian
+/// followed by the scale.
+impl StorageValue for Decimal {
+    fn into_bytes(self) -> Vec<u8> {
+        let mut v = vec![0; 9];
+        LittleEndian::write_i64(&mut v[0..8], self.mantissa());
+        v[8] = self.scale();
+        v
+    }
+
+    fn from_bytes(value: Cow<[u8]>) -> Self {
+        Decimal::check_scale(value[8], 8).expect("Malformed decimal value in the storage");
+        Decimal::new(LittleEndian::read_i64(&value[0..8]), value[8])
+    }
+}
+
 impl StorageValue for Hash {
     fn into_bytes(self) -> Vec<u8> {
         self.as_ref().to_vec()
@@ -378,6 +423,44 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg(feature = "int128")]
+    fn int128_round_trip() {
+        let values = [u128::min_value(), 1, u128::max_value()];
+        for value in values.iter() {
+            let bytes = value.into_bytes();
+            assert_eq!(*value, u128::from_bytes(Cow::Borrowed(&bytes)));
+        }
+        let values = [i128::min_value(), -1, 0, 1, i128::max_value()];
+        for value in values.iter() {
+            let bytes = value.into_bytes();
+            assert_eq!(*value, i128::from_bytes(Cow::Borrowed(&bytes)));
+        }
+    }
+
+    #[test]
+    fn decimal_round_trip() {
+        let values = [
+            Decimal::new(0, 0),
+            Decimal::new(-1_250, 2),
+            Decimal::new(i64::min_value(), 18),
+            Decimal::new(i64::max_value(), 0),
+        ];
+        for value in values.iter() {
+            let bytes = value.into_bytes();
+            assert_eq!(bytes.len(), 9);
+            assert_eq!(*value, Decimal::from_bytes(Cow::Borrowed(&bytes)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Malformed decimal value in the storage")]
+    fn decimal_incorrect_scale() {
+        let mut bytes = Decimal::new(1, 18).into_bytes();
+        bytes[8] = 19;
+        Decimal::from_bytes(Cow::Borrowed(&bytes));
+    }
+
     #[test]
     fn bool_round_trip() {
         let values = [false, true];
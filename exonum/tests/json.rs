@@ -0,0 +1,327 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Property testing for the canonical JSON representation of the encoding types.
+//!
+//! Each test generates values of structures covering all field types and checks that
+//! the canonical JSON of a value is decoded into the same binary representation
+//! and then encoded back into the same JSON text.
+//!
+//! To adjust the number of test cases for each test, set the `PROPTEST_CASES` environment
+//! variable as per `proptest` docs.
+
+// cspell:ignore proptest
+
+#![cfg_attr(feature = "int128", feature(i128_type))]
+
+extern crate bit_vec;
+extern crate chrono;
+#[macro_use]
+extern crate exonum;
+#[macro_use]
+extern crate proptest;
+extern crate serde_json;
+extern crate uuid;
+
+use bit_vec::BitVec;
+use chrono::{DateTime, TimeZone, Utc};
+use exonum::crypto::{Hash, PublicKey, Signature};
+use exonum::encoding::serialize::json::{to_canonical_string, ExonumJson,
+                                        ExonumJsonDeserialize};
+use exonum::helpers::{Height, Round, ValidatorId};
+use exonum::messages::RawMessage;
+use exonum::storage::StorageValue;
+use proptest::prelude::*;
+use proptest::collection::{btree_map, vec};
+use proptest::option;
+use uuid::Uuid;
+
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+encoding_struct! {
+    struct Primitives {
+        flag: bool,
+        byte: u8,
+        signed_byte: i8,
+        short: u16,
+        signed_short: i16,
+        int: u32,
+        signed_int: i32,
+        long: u64,
+        signed_long: i64,
+        height: Height,
+        round: Round,
+        validator: ValidatorId,
+        time: DateTime<Utc>,
+        addr: SocketAddr,
+        id: Uuid,
+        salt: [u8; 4],
+        weights: [u16; 2],
+    }
+}
+
+encoding_struct! {
+    struct Segments {
+        hash: &Hash,
+        key: &PublicKey,
+        signature: &Signature,
+        text: &str,
+        data: &[u8],
+        bytes: Vec<u8>,
+        longs: Vec<u64>,
+        hashes: &[Hash],
+        maybe: Option<i64>,
+        map: BTreeMap<u32, u64>,
+        bits: BitVec,
+        messages: Vec<RawMessage>,
+    }
+}
+
+encoding_enum! {
+    enum Shape {
+        Empty = 0,
+        Size(u64) = 1,
+        Tagged(Primitives) = 2,
+    }
+}
+
+encoding_struct! {
+    struct Nested {
+        shape: Shape,
+        first: Primitives,
+        rest: Vec<Segments>,
+    }
+}
+
+/// Checks that the value survives JSON -> binary -> JSON conversion bit-exactly.
+fn check_round_trip<T>(value: &T)
+where
+    T: ExonumJson + ExonumJsonDeserialize + StorageValue + Clone,
+{
+    let text = to_canonical_string(&value.serialize_field().unwrap());
+    let json = serde_json::from_str(&text).unwrap();
+    let decoded = T::deserialize(&json).unwrap();
+    assert_eq!(decoded.clone().into_bytes(), value.clone().into_bytes());
+    assert_eq!(to_canonical_string(&decoded.serialize_field().unwrap()), text);
+}
+
+fn bytes(len: usize) -> BoxedStrategy<Vec<u8>> {
+    vec(any::<u8>(), len..len + 1).boxed()
+}
+
+fn hashes() -> BoxedStrategy<Hash> {
+    bytes(32).prop_map(|b| Hash::from_slice(&b).unwrap()).boxed()
+}
+
+fn primitives() -> BoxedStrategy<Primitives> {
+    let integers = (
+        any::<u8>(),
+        any::<i8>(),
+        any::<u16>(),
+        any::<i16>(),
+        any::<u32>(),
+        any::<i32>(),
+        any::<u64>(),
+        any::<i64>(),
+    );
+    let others = (
+        any::<bool>(),
+        (any::<u64>(), any::<u32>(), any::<u16>()),
+        (-(1_i64 << 40)..1_i64 << 40, 0..1_000_000_000_u32),
+        (any::<u32>(), any::<u16>()),
+        bytes(16),
+        bytes(4),
+        (any::<u16>(), any::<u16>()),
+    );
+    (integers, others)
+        .prop_map(|(integers, others)| {
+            let (byte, signed_byte, short, signed_short, int, signed_int, long, signed_long) =
+                integers;
+            let (flag, (height, round, validator), (secs, nanos), (ip, port), id, salt, weights) =
+                others;
+            let mut salt_array = [0; 4];
+            salt_array.copy_from_slice(&salt);
+            Primitives::new(
+                flag,
+                byte,
+                signed_byte,
+                short,
+                signed_short,
+                int,
+                signed_int,
+                long,
+                signed_long,
+                Height(height),
+                Round(round),
+                ValidatorId(validator),
+                Utc.timestamp(secs, nanos),
+                SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::from(ip), port)),
+                Uuid::from_bytes(&id).unwrap(),
+                salt_array,
+                [weights.0, weights.1],
+            )
+        })
+        .boxed()
+}
+
+fn raw_messages() -> BoxedStrategy<Vec<RawMessage>> {
+    let message = vec(any::<u8>(), 10..50).prop_map(|mut raw| {
+        // Only the declared length is checked in segments of raw messages.
+        let len = raw.len() as u8;
+        raw[6..10].copy_from_slice(&[len, 0, 0, 0]);
+        RawMessage::from_vec(raw)
+    });
+    vec(message, 0..3).boxed()
+}
+
+fn segments() -> BoxedStrategy<Segments> {
+    (
+        (hashes(), bytes(32), bytes(64)),
+        ("\\PC*", vec(any::<u8>(), 0..20), vec(any::<u8>(), 0..20)),
+        (vec(any::<u64>(), 0..5), vec(hashes(), 0..3)),
+        (
+            option::of(any::<i64>()),
+            btree_map(any::<u32>(), any::<u64>(), 0..5),
+            vec(any::<u8>(), 0..4),
+            raw_messages(),
+        ),
+    ).prop_map(|(keys, data, lists, others)| {
+            let (hash, key, signature) = keys;
+            let (text, data, bytes) = data;
+            let (longs, hashes) = lists;
+            let (maybe, map, bits, messages) = others;
+            Segments::new(
+                &hash,
+                &PublicKey::from_slice(&key).unwrap(),
+                &Signature::from_slice(&signature).unwrap(),
+                &text,
+                &data,
+                bytes,
+                longs,
+                &hashes,
+                maybe,
+                map,
+                BitVec::from_bytes(&bits),
+                messages,
+            )
+        })
+        .boxed()
+}
+
+fn shapes() -> BoxedStrategy<Shape> {
+    prop_oneof![
+        Just(Shape::Empty),
+        any::<u64>().prop_map(Shape::Size),
+        primitives().prop_map(Shape::Tagged),
+    ].boxed()
+}
+
+proptest! {
+    #[test]
+    fn primitives_round_trip(value in primitives()) {
+        check_round_trip(&value);
+    }
+
+    #[test]
+    fn segments_round_trip(value in segments()) {
+        check_round_trip(&value);
+    }
+
+    #[test]
+    fn nested_round_trip(
+        shape in shapes(),
+        first in primitives(),
+        rest in vec(segments(), 0..3)
+    ) {
+        check_round_trip(&Nested::new(shape, first, rest));
+    }
+}
+
+#[cfg(feature = "float_serialize")]
+mod float {
+    use exonum::encoding::{F32, F64};
+    use proptest::num;
+
+    use super::check_round_trip;
+
+    encoding_struct! {
+        struct Floats {
+            single: F32,
+            double: F64,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn floats_round_trip(
+            single in num::f32::NORMAL,
+            double in num::f64::NORMAL
+        ) {
+            let single = F32::try_from(single).unwrap();
+            let double = F64::try_from(double).unwrap();
+            check_round_trip(&Floats::new(single, double));
+        }
+    }
+}
+
+mod decimal {
+    use exonum::encoding::Decimal;
+    use proptest::prelude::*;
+    use proptest::collection::vec;
+
+    use super::check_round_trip;
+
+    encoding_struct! {
+        struct Amounts {
+            amount: Decimal,
+            history: Vec<Decimal>,
+        }
+    }
+
+    fn decimals() -> BoxedStrategy<Decimal> {
+        (any::<i64>(), 0..Decimal::MAX_SCALE + 1)
+            .prop_map(|(mantissa, scale)| Decimal::new(mantissa, scale))
+            .boxed()
+    }
+
+    proptest! {
+        #[test]
+        fn decimals_round_trip(amount in decimals(), history in vec(decimals(), 0..5)) {
+            check_round_trip(&Amounts::new(amount, history));
+        }
+    }
+}
+
+#[cfg(feature = "int128")]
+mod int128 {
+    use proptest::prelude::*;
+
+    use super::check_round_trip;
+
+    encoding_struct! {
+        struct Wide {
+            unsigned: u128,
+            signed: i128,
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn int128_round_trip(high in any::<u64>(), low in any::<u64>()) {
+            let unsigned = (u128::from(high) << 64) | u128::from(low);
+            check_round_trip(&Wide::new(unsigned, unsigned as i128));
+        }
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright 2018 The Exonum Team
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tests for the protobuf encoding of transactions and storage values.
+
+#![cfg(feature = "protobuf")]
+
+#[macro_use]
+extern crate exonum;
+extern crate protobuf;
+extern crate serde_json;
+
+use exonum::blockchain::{ExecutionResult, Transaction, TransactionSet};
+use exonum::crypto::{self, CryptoHash};
+use exonum::encoding::Error;
+use exonum::encoding::protobuf::{from_bytes, to_bytes, ProtobufValue};
+use exonum::messages::{Message, RawMessage, HEADER_LENGTH};
+use exonum::storage::{Database, Fork, MapIndex, MemoryDB, StorageValue};
+use protobuf::well_known_types::{ListValue, StringValue, Struct, Timestamp, Type, Value};
+
+protobuf_transactions! {
+    pub NoteTransactions {
+        const SERVICE_ID = 7;
+
+        /// Publishes a note.
+        struct TxNote(StringValue) = 0;
+        /// Marks the time.
+        struct TxMark(Timestamp) = 3;
+    }
+}
+
+impl Transaction for TxNote {
+    fn verify(&self) -> bool {
+        !self.payload().get_value().is_empty()
+    }
+
+    fn execute(&self, _: &mut Fork) -> ExecutionResult {
+        Ok(())
+    }
+}
+
+impl Transaction for TxMark {
+    fn verify(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, _: &mut Fork) -> ExecutionResult {
+        Ok(())
+    }
+}
+
+fn timestamp(seconds: i64, nanos: i32) -> Timestamp {
+    let mut timestamp = Timestamp::new();
+    timestamp.set_seconds(seconds);
+    timestamp.set_nanos(nanos);
+    timestamp
+}
+
+fn note(value: &str) -> StringValue {
+    let mut note = StringValue::new();
+    note.set_value(value.to_owned());
+    note
+}
+
+#[test]
+fn test_canonical_bytes() {
+    let bytes = to_bytes(&timestamp(5, 7)).unwrap();
+    assert_eq!(bytes, vec![0x08, 0x05, 0x10, 0x07]);
+    assert_eq!(from_bytes::<Timestamp>(&bytes).unwrap(), timestamp(5, 7));
+    assert_eq!(to_bytes(&Timestamp::new()).unwrap(), Vec::<u8>::new());
+}
+
+#[test]
+fn test_non_canonical_bytes() {
+    let cases: &[&[u8]] = &[
+        // Fields in the reverse order.
+        &[0x10, 0x07, 0x08, 0x05],
+        // Varint which is longer than necessary.
+        &[0x08, 0x85, 0x00],
+        // Default value of a `proto3` field.
+        &[0x08, 0x00],
+        // Unknown field.
+        &[0x08, 0x05, 0x18, 0x01],
+        // Truncated message.
+        &[0x08],
+    ];
+    for bytes in cases {
+        assert!(
+            from_bytes::<Timestamp>(bytes).is_err(),
+            "Accepted non-canonical bytes {:?}",
+            bytes
+        );
+    }
+}
+
+#[test]
+fn test_nested_unknown_fields() {
+    // `Type` with `SourceContext { file_name: "a" }`.
+    let bytes = [0x2a, 0x03, 0x0a, 0x01, b'a'];
+    let parsed = from_bytes::<Type>(&bytes).unwrap();
+    assert_eq!(parsed.get_source_context().get_file_name(), "a");
+    // The same value with an unknown field in `SourceContext`.
+    let bytes = [0x2a, 0x05, 0x0a, 0x01, b'a', 0x10, 0x01];
+    assert!(from_bytes::<Type>(&bytes).is_err());
+
+    // `ListValue` with a single `Value { bool_value: true }`.
+    let bytes = [0x0a, 0x02, 0x20, 0x01];
+    assert_eq!(from_bytes::<ListValue>(&bytes).unwrap().get_values().len(), 1);
+    // The same value with an unknown field in the item.
+    let bytes = [0x0a, 0x04, 0x20, 0x01, 0x38, 0x01];
+    assert!(from_bytes::<ListValue>(&bytes).is_err());
+}
+
+#[test]
+fn test_map_fields() {
+    // `Struct` consists of a map field.
+    assert!(to_bytes(&Struct::new()).is_err());
+    assert!(from_bytes::<Struct>(&[]).is_err());
+
+    // Nested messages with map fields are rejected as well.
+    let mut value = Value::new();
+    value.set_struct_value(Struct::new());
+    assert!(to_bytes(&value).is_err());
+    value.set_bool_value(true);
+    assert_eq!(to_bytes(&value).unwrap(), vec![0x20, 0x01]);
+}
+
+#[test]
+fn test_storage_value() {
+    let value = ProtobufValue(timestamp(1_500_000_000, 1));
+    let bytes = value.clone().into_bytes();
+    assert_eq!(bytes, to_bytes(&value.0).unwrap());
+    assert_eq!(value.hash(), crypto::hash(&bytes));
+    assert_eq!(ProtobufValue::from_bytes(bytes.into()), value);
+
+    let db = MemoryDB::new();
+    let mut fork = db.fork();
+    let mut index = MapIndex::new("timestamps", &mut fork);
+    index.put(&1_u64, value.clone());
+    assert_eq!(index.get(&1_u64), Some(value));
+}
+
+#[test]
+fn test_tx_from_raw() {
+    let (_, secret_key) = crypto::gen_keypair();
+    let tx = TxNote::new(note("Hello"), &secret_key);
+    assert_eq!(&tx.raw().body()[HEADER_LENGTH..], &b"\x0a\x05Hello"[..]);
+
+    match NoteTransactions::tx_from_raw(tx.raw().clone()).unwrap() {
+        NoteTransactions::TxNote(parsed) => assert_eq!(parsed, tx),
+        other => panic!("Unexpected transaction: {:?}", other),
+    }
+
+    let tx = TxMark::new(timestamp(10, 0), &secret_key);
+    match NoteTransactions::tx_from_raw(tx.raw().clone()).unwrap() {
+        NoteTransactions::TxMark(parsed) => assert_eq!(parsed.payload(), &timestamp(10, 0)),
+        other => panic!("Unexpected transaction: {:?}", other),
+    }
+    let tx: Box<Transaction> = NoteTransactions::TxMark(tx).into();
+    assert!(tx.verify());
+}
+
+#[test]
+fn test_tx_from_raw_errors() {
+    let (_, secret_key) = crypto::gen_keypair();
+    let tx = TxMark::new(timestamp(10, 0), &secret_key);
+
+    // Unknown message id.
+    let mut bytes = tx.raw().as_ref().to_vec();
+    bytes[2] = 1;
+    match NoteTransactions::tx_from_raw(RawMessage::from_vec(bytes)) {
+        Err(Error::IncorrectMessageType { message_type: 1 }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+
+    // Overlong varint in the body.
+    let mut bytes = tx.raw().as_ref().to_vec();
+    bytes.truncate(HEADER_LENGTH);
+    bytes.extend_from_slice(&[0x08, 0x8a, 0x00]);
+    bytes.extend_from_slice(&[0; crypto::SIGNATURE_LENGTH]);
+    match NoteTransactions::tx_from_raw(RawMessage::from_vec(bytes)) {
+        Err(Error::InMessage { service_id: 7, message_id: 3, .. }) => {}
+        other => panic!("Unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn test_json() {
+    let (_, secret_key) = crypto::gen_keypair();
+    let tx = TxNote::new(note("Hello"), &secret_key);
+
+    let json = serde_json::to_value(&tx).unwrap();
+    assert_eq!(json["body"], "0a0548656c6c6f");
+    assert_eq!(json["service_id"], 7);
+    assert_eq!(json["message_id"], 0);
+
+    let parsed: NoteTransactions = serde_json::from_value(json.clone()).unwrap();
+    match parsed {
+        NoteTransactions::TxNote(parsed) => assert_eq!(parsed, tx),
+        other => panic!("Unexpected transaction: {:?}", other),
+    }
+
+    let mut json = json;
+    json["protocol_version"] = 1.into();
+    assert!(serde_json::from_value::<TxNote>(json).is_err());
+}
+
+#[test]
+fn test_message_schemas() {
+    let schemas = NoteTransactions::message_schemas();
+    assert_eq!(schemas.len(), 2);
+    assert_eq!(schemas[0], TxNote::schema());
+    assert_eq!(schemas[0].name, "TxNote");
+    assert_eq!(schemas[0].service_id, 7);
+    assert_eq!(schemas[0].message_id, 0);
+    assert_eq!(schemas[0].body_size, 0);
+    assert!(schemas[0].fields.is_empty());
+    assert_eq!(
+        schemas[0].protobuf_message,
+        Some("google.protobuf.StringValue".to_owned())
+    );
+    assert_eq!(schemas[1].message_id, 3);
+    assert_eq!(
+        schemas[1].protobuf_message,
+        Some("google.protobuf.Timestamp".to_owned())
+    );
+}
@@ -14,14 +14,15 @@
 
 use bodyparser;
 use exonum::api::{Api, ApiError};
+use exonum::api::openapi::{self, DocumentedRouter, Endpoint};
 use exonum::crypto::{CryptoHash, PublicKey, SecretKey, Hash};
 use exonum::blockchain::{ApiContext, Blockchain, StoredConfiguration, Schema as CoreSchema};
 use exonum::encoding::serialize::json::reexport as serde_json;
+use exonum::encoding::serialize::json::reexport::Value as JsonValue;
 use exonum::helpers::Height;
 use exonum::node::{ApiSender, TransactionSend};
 use exonum::storage::StorageValue;
 use iron::prelude::*;
-use router::Router;
 
 use super::{ProposeData, Propose, Vote, Schema};
 
@@ -171,17 +172,19 @@ impl PublicApi {
         committed_configs
     }
 
-    fn handle_actual_config(self, router: &mut Router) {
+    fn handle_actual_config(self, router: &mut DocumentedRouter) {
         let actual_config = move |_: &mut Request| -> IronResult<Response> {
             let config = CoreSchema::new(self.blockchain.snapshot()).actual_configuration();
             let config = self.config_with_proofs(config);
             self.ok_response(&serde_json::to_value(config).unwrap())
         };
 
-        router.get("/v1/configs/actual", actual_config, "actual_config");
+        let endpoint = Endpoint::get("/v1/configs/actual", "Returns the actual configuration")
+            .response(config_hash_info_schema());
+        router.route(endpoint, actual_config, "actual_config");
     }
 
-    fn handle_following_config(self, router: &mut Router) {
+    fn handle_following_config(self, router: &mut DocumentedRouter) {
         let following_config = move |_: &mut Request| -> IronResult<Response> {
             let config = CoreSchema::new(self.blockchain.snapshot())
                 .following_configuration()
@@ -189,14 +192,14 @@ impl PublicApi {
             self.ok_response(&serde_json::to_value(config).unwrap())
         };
 
-        router.get(
+        let endpoint = Endpoint::get(
             "/v1/configs/following",
-            following_config,
-            "following_config",
-        );
+            "Returns the scheduled following configuration",
+        ).response(openapi::nullable(config_hash_info_schema()));
+        router.route(endpoint, following_config, "following_config");
     }
 
-    fn handle_config_by_hash(self, router: &mut Router) {
+    fn handle_config_by_hash(self, router: &mut DocumentedRouter) {
         let config_by_hash = move |req: &mut Request| -> IronResult<Response> {
             let hash: Hash = self.url_fragment(req, "hash")?;
 
@@ -212,21 +215,30 @@ impl PublicApi {
             }).unwrap())
         };
 
-        router.get("/v1/configs/:hash", config_by_hash, "config_by_hash");
+        let endpoint = Endpoint::get(
+            "/v1/configs/:hash",
+            "Returns the committed or proposed configuration with the given hash",
+        ).path_param("hash", "Hash of the configuration", openapi::hash())
+            .response(openapi::object(&[
+                ("committed_config", openapi::nullable(stored_configuration_schema())),
+                ("propose", openapi::nullable(propose_data_schema())),
+            ]));
+        router.route(endpoint, config_by_hash, "config_by_hash");
     }
 
-    fn handle_votes_for_propose(self, router: &mut Router) {
+    fn handle_votes_for_propose(self, router: &mut DocumentedRouter) {
         let votes_for_propose = move |req: &mut Request| -> IronResult<Response> {
             let config_hash: Hash = self.url_fragment(req, "hash")?;
             let votes = self.votes_for_propose(&config_hash);
             self.ok_response(&serde_json::to_value(votes).unwrap())
         };
 
-        router.get(
+        let endpoint = Endpoint::get(
             "/v1/configs/:hash/votes",
-            votes_for_propose,
-            "votes_for_propose",
-        );
+            "Returns votes for the proposed configuration",
+        ).path_param("hash", "Hash of the configuration", openapi::hash())
+            .response(votes_schema());
+        router.route(endpoint, votes_for_propose, "votes_for_propose");
     }
 
     fn retrieve_filter(&self, request: &mut Request) -> Result<Filter, ApiError> {
@@ -238,26 +250,33 @@ impl PublicApi {
         })
     }
 
-    fn handle_proposed_configs(self, router: &mut Router) {
+    fn handle_proposed_configs(self, router: &mut DocumentedRouter) {
         let proposed_configs = move |req: &mut Request| -> IronResult<Response> {
             let proposes = self.proposed_configs(&self.retrieve_filter(req)?);
             self.ok_response(&serde_json::to_value(proposes).unwrap())
         };
 
-        router.get("/v1/configs/proposed", proposed_configs, "proposed_configs");
+        let endpoint = filter_params(Endpoint::get(
+            "/v1/configs/proposed",
+            "Returns proposed configurations",
+        )).response(openapi::array(openapi::object(&[
+            ("hash", openapi::hash()),
+            ("propose_data", propose_data_schema()),
+        ])));
+        router.route(endpoint, proposed_configs, "proposed_configs");
     }
 
-    fn handle_committed_configs(self, router: &mut Router) {
+    fn handle_committed_configs(self, router: &mut DocumentedRouter) {
         let committed_configs = move |req: &mut Request| -> IronResult<Response> {
             let configs = self.committed_configs(&self.retrieve_filter(req)?);
             self.ok_response(&serde_json::to_value(configs).unwrap())
         };
 
-        router.get(
+        let endpoint = filter_params(Endpoint::get(
             "/v1/configs/committed",
-            committed_configs,
-            "committed_configs",
-        );
+            "Returns committed configurations",
+        )).response(openapi::array(config_hash_info_schema()));
+        router.route(endpoint, committed_configs, "committed_configs");
     }
 }
 
@@ -269,7 +288,7 @@ impl PrivateApi {
         }
     }
 
-    fn handle_propose(self, router: &mut Router) {
+    fn handle_propose(self, router: &mut DocumentedRouter) {
         let post_propose = move |req: &mut Request| -> IronResult<Response> {
             let config = match req.get::<bodyparser::Struct<StoredConfiguration>>() {
                 Ok(Some(config)) => config,
@@ -293,10 +312,18 @@ impl PrivateApi {
             self.ok_response(&serde_json::to_value(response).unwrap())
         };
 
-        router.post("/v1/configs/postpropose", post_propose, "post_propose");
+        let endpoint = Endpoint::post(
+            "/v1/configs/postpropose",
+            "Proposes the configuration on behalf of the node",
+        ).request(stored_configuration_schema())
+            .response(openapi::object(&[
+                ("tx_hash", openapi::hash()),
+                ("cfg_hash", openapi::hash()),
+            ]));
+        router.route(endpoint, post_propose, "post_propose");
     }
 
-    fn handle_vote(self, router: &mut Router) {
+    fn handle_vote(self, router: &mut DocumentedRouter) {
         let post_vote = move |req: &mut Request| -> IronResult<Response> {
             let cfg_hash: Hash = self.url_fragment(req, "hash")?;
 
@@ -309,12 +336,17 @@ impl PrivateApi {
             self.ok_response(&serde_json::to_value(response).unwrap())
         };
 
-        router.post("/v1/configs/:hash/postvote", post_vote, "post_vote");
+        let endpoint = Endpoint::post(
+            "/v1/configs/:hash/postvote",
+            "Votes for the proposed configuration on behalf of the node",
+        ).path_param("hash", "Hash of the configuration", openapi::hash())
+            .response(openapi::object(&[("tx_hash", openapi::hash())]));
+        router.route(endpoint, post_vote, "post_vote");
     }
 }
 
 impl Api for PublicApi {
-    fn wire(&self, router: &mut Router) {
+    fn wire_documented(&self, router: &mut DocumentedRouter) {
         self.clone().handle_actual_config(router);
         self.clone().handle_following_config(router);
         self.clone().handle_config_by_hash(router);
@@ -325,8 +357,56 @@ impl Api for PublicApi {
 }
 
 impl Api for PrivateApi {
-    fn wire(&self, router: &mut Router) {
+    fn wire_documented(&self, router: &mut DocumentedRouter) {
         self.clone().handle_propose(router);
         self.clone().handle_vote(router);
     }
 }
+
+fn filter_params(endpoint: Endpoint) -> Endpoint {
+    endpoint
+        .query_param(
+            "previous_cfg_hash",
+            "Hash of the previous configuration",
+            false,
+            openapi::hash(),
+        )
+        .query_param(
+            "actual_from",
+            "Minimal height, from which the configuration becomes actual",
+            false,
+            openapi::integer(),
+        )
+}
+
+fn stored_configuration_schema() -> JsonValue {
+    openapi::object(&[
+        ("previous_cfg_hash", openapi::hash()),
+        ("actual_from", openapi::integer_string()),
+        ("validator_keys", openapi::array(openapi::any())),
+        ("consensus", openapi::any()),
+        ("majority_count", openapi::nullable(openapi::integer())),
+        ("services", openapi::dictionary(openapi::any())),
+    ])
+}
+
+fn propose_data_schema() -> JsonValue {
+    openapi::object(&[
+        ("tx_propose", openapi::any()),
+        ("votes_history_hash", openapi::hash()),
+        ("num_validators", openapi::integer_string()),
+    ])
+}
+
+fn votes_schema() -> JsonValue {
+    openapi::nullable(openapi::array(openapi::nullable(openapi::any())))
+}
+
+fn config_hash_info_schema() -> JsonValue {
+    openapi::object(&[
+        ("hash", openapi::hash()),
+        ("config", stored_configuration_schema()),
+        ("propose", openapi::nullable(openapi::hash())),
+        ("votes", votes_schema()),
+    ])
+}
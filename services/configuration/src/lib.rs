@@ -78,9 +78,11 @@ extern crate exonum_testkit;
 extern crate pretty_assertions;
 
 use exonum::api::Api;
+use exonum::api::openapi::Endpoint;
 use exonum::blockchain::{self, Transaction, ApiContext};
 use exonum::crypto::Hash;
 use exonum::encoding::Error as EncodingError;
+use exonum::encoding::schema::MessageSchema;
 use exonum::helpers::fabric::{self, Context};
 use exonum::messages::RawTransaction;
 use exonum::storage::Snapshot;
@@ -123,6 +125,10 @@ impl blockchain::Service for Service {
         transactions::tx_from_raw(raw)
     }
 
+    fn message_schemas(&self) -> Vec<MessageSchema> {
+        transactions::message_schemas()
+    }
+
     fn public_api_handler(&self, ctx: &ApiContext) -> Option<Box<Handler>> {
         let mut router = Router::new();
         let api = api::PublicApi::new(ctx);
@@ -136,6 +142,14 @@ impl blockchain::Service for Service {
         api.wire(&mut router);
         Some(Box::new(router))
     }
+
+    fn public_api_endpoints(&self, ctx: &ApiContext) -> Vec<Endpoint> {
+        api::PublicApi::new(ctx).endpoints()
+    }
+
+    fn private_api_endpoints(&self, ctx: &ApiContext) -> Vec<Endpoint> {
+        api::PrivateApi::new(ctx).endpoints()
+    }
 }
 
 /// A configuration service creator for the `NodeBuilder`.
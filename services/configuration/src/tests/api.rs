@@ -17,6 +17,7 @@
 use exonum::blockchain::{Schema, StoredConfiguration};
 use exonum::helpers::{Height, ValidatorId};
 use exonum::crypto::{CryptoHash, Hash};
+use exonum::encoding::serialize::json::reexport as serde_json;
 use exonum_testkit::{ApiKind, TestKit, TestKitApi};
 
 use super::ConfigurationSchema;
@@ -275,7 +276,7 @@ fn test_votes_for_propose() {
     let response = api.votes_for_propose(&new_cfg.hash()).expect(
         "Votes for config is absent",
     );
-    for entry in response.into_iter().take(testkit.majority_count()) {
+    for entry in response.into_iter().take(testkit.majority_count() as usize) {
         let tx = entry.expect("Vote for config is absent");
         assert!(
             Schema::new(&testkit.snapshot()).transactions().contains(
@@ -460,3 +461,18 @@ fn test_post_vote_tx() {
     assert_eq!(tx.hash(), info.tx_hash);
     assert!(testkit.is_tx_in_pool(&info.tx_hash));
 }
+
+#[test]
+fn test_documented_endpoints() {
+    let testkit: TestKit = TestKit::configuration_default();
+    let api = testkit.api();
+
+    let document: serde_json::Value = api.get(ApiKind::Root, "openapi.json");
+    let paths = document["paths"].as_object().unwrap();
+    assert!(paths["/api/services/configuration/v1/configs/{hash}"]["get"].is_object());
+    let document: serde_json::Value = api.get_private(ApiKind::Root, "openapi.json");
+    let paths = document["paths"].as_object().unwrap();
+    assert!(paths["/api/services/configuration/v1/configs/postpropose"]["post"].is_object());
+
+    api.check_documented_endpoints();
+}
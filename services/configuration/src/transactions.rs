@@ -17,8 +17,8 @@
 use exonum::blockchain::{ExecutionResult, Schema as CoreSchema, StoredConfiguration, Transaction};
 use exonum::crypto::{CryptoHash, Hash, PublicKey};
 use exonum::encoding::Error as EncodingError;
+use exonum::encoding::schema::MessageSchema;
 use exonum::messages::{Message, RawTransaction};
-use exonum::node::State;
 use exonum::storage::{Fork, Snapshot};
 
 use errors::Error as ServiceError;
@@ -92,13 +92,18 @@ fn enough_votes_to_commit(snapshot: &Snapshot, cfg_hash: &Hash) -> bool {
 
     let schema = Schema::new(snapshot);
     let votes = schema.votes_by_config_hash(cfg_hash);
-    let votes_count = votes.iter().filter(|vote| vote.is_some()).count();
+    let voting_power: u64 = votes
+        .iter()
+        .zip(&actual_config.validator_keys)
+        .filter(|&(ref vote, _)| vote.is_some())
+        .map(|(_, keys)| keys.voting_power())
+        .sum();
     let majority_count = match actual_config.majority_count {
-        Some(majority_count) => majority_count as usize,
-        _ => State::byzantine_majority_count(actual_config.validator_keys.len()),
+        Some(majority_count) => majority_count,
+        _ => actual_config.majority_voting_power(),
     };
 
-    votes_count >= majority_count
+    voting_power >= majority_count
 }
 
 impl Propose {
@@ -151,16 +156,15 @@ impl Propose {
         }
 
         if let Some(proposed_majority_count) = candidate.majority_count {
-            let proposed_majority_count = proposed_majority_count as usize;
-            let validators_num = candidate.validator_keys.len();
-            let min_votes_count = State::byzantine_majority_count(validators_num);
+            let total_voting_power = candidate.total_voting_power();
+            let min_voting_power = candidate.majority_voting_power();
 
-            if proposed_majority_count < min_votes_count ||
-                proposed_majority_count > validators_num
+            if proposed_majority_count < min_voting_power ||
+                proposed_majority_count > total_voting_power
             {
                 return Err(InvalidMajorityCount {
-                    min: min_votes_count,
-                    max: validators_num,
+                    min: min_voting_power,
+                    max: total_voting_power,
                     proposed: proposed_majority_count,
                 });
             }
@@ -332,3 +336,10 @@ pub(crate) fn tx_from_raw(raw: RawTransaction) -> Result<Box<Transaction>, Encod
 
     Any::tx_from_raw(raw).map(Any::into)
 }
+
+/// Returns descriptors of the binary layout of the service transactions.
+pub(crate) fn message_schemas() -> Vec<MessageSchema> {
+    use exonum::blockchain::TransactionSet;
+
+    Any::message_schemas()
+}
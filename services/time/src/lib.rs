@@ -49,6 +49,7 @@ use exonum::encoding::serialize::json::reexport::Value;
 use exonum::storage::{Fork, Snapshot, ProofMapIndex, Entry};
 use exonum::crypto::{Hash, PublicKey};
 use exonum::encoding;
+use exonum::encoding::schema::MessageSchema;
 use exonum::helpers::fabric::{ServiceFactory, Context};
 use exonum::api::Api;
 
@@ -460,6 +461,10 @@ impl Service for TimeService {
         Ok(tx.into())
     }
 
+    fn message_schemas(&self) -> Vec<MessageSchema> {
+        TimeTransactions::message_schemas()
+    }
+
     fn initialize(&self, _fork: &mut Fork) -> Value {
         Value::Null
     }
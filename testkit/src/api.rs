@@ -20,6 +20,8 @@ use iron::{Response, IronError, Handler, Chain};
 use iron::headers::{ContentType, Headers};
 use iron::status::{self, StatusClass};
 use iron_test::{request, response};
+use mount::NoMatch;
+use router::NoRoute;
 use serde::{Deserialize, Serialize};
 use serde_json;
 use serde_json::Value as JsonValue;
@@ -35,6 +37,8 @@ use super::TestKit;
 /// [`TestKitApi`]: struct.TestKitApi.html
 #[derive(Debug)]
 pub enum ApiKind {
+    /// `api` endpoints shared by all APIs, such as the OpenAPI document.
+    Root,
     /// `api/system` endpoints of the built-in Exonum REST API.
     System,
     /// `api/explorer` endpoints of the built-in Exonum REST API.
@@ -46,6 +50,7 @@ pub enum ApiKind {
 impl ApiKind {
     fn into_prefix(self) -> String {
         match self {
+            ApiKind::Root => "api".to_string(),
             ApiKind::System => "api/system".to_string(),
             ApiKind::Explorer => "api/explorer".to_string(),
             ApiKind::Service(name) => format!("api/services/{}", name),
@@ -280,6 +285,50 @@ impl TestKitApi {
         )
     }
 
+    /// Checks that every endpoint described in the OpenAPI documents of the public and private
+    /// APIs is routed by the corresponding handler, e.g., that a service returns the endpoints
+    /// of the same API it wires into its handlers.
+    ///
+    /// # Panics
+    ///
+    /// - Panics if a described endpoint is not routed.
+    pub fn check_documented_endpoints(&self) {
+        let document: JsonValue = self.get(ApiKind::Root, "openapi.json");
+        TestKitApi::check_routes(&self.public_handler, &document);
+        let document: JsonValue = self.get_private(ApiKind::Root, "openapi.json");
+        TestKitApi::check_routes(&self.private_handler, &document);
+    }
+
+    fn check_routes<H: Handler>(handler: &H, document: &JsonValue) {
+        let paths = document["paths"].as_object().expect(
+            "No paths in the OpenAPI document",
+        );
+        for (path, endpoints) in paths {
+            // Path parameters are replaced with an arbitrary value; the handlers may reject it,
+            // but the request still has to reach them.
+            let segments: Vec<&str> = path.split('/')
+                .map(|segment| if segment.starts_with('{') { "0" } else { segment })
+                .collect();
+            let url = format!("http://localhost:3000{}", segments.join("/"));
+
+            for method in endpoints.as_object().expect("Invalid path item").keys() {
+                let response = match method.as_str() {
+                    "get" => request::get(&url, Headers::new(), handler),
+                    "post" => request::post(&url, Headers::new(), "{}", handler),
+                    _ => panic!("Unexpected method {} of {}", method, path),
+                };
+                if let Err(IronError { ref error, .. }) = response {
+                    assert!(
+                        !error.is::<NoRoute>() && !error.is::<NoMatch>(),
+                        "Endpoint {} {} is documented, but not routed",
+                        method.to_uppercase(),
+                        path
+                    );
+                }
+            }
+        }
+    }
+
     /// Converts iron Response to ApiError.
     ///
     /// # Panics
@@ -127,6 +127,7 @@ extern crate exonum;
 extern crate futures;
 extern crate iron;
 extern crate iron_test;
+extern crate mount;
 extern crate router;
 extern crate serde;
 extern crate serde_json;
@@ -146,7 +147,7 @@ use exonum::blockchain::{Blockchain, Schema as CoreSchema, Service, StoredConfig
                          Transaction};
 use exonum::crypto::{self, Hash};
 use exonum::helpers::{Height, ValidatorId};
-use exonum::node::{ApiSender, ExternalMessage, State as NodeState, NodeApiConfig};
+use exonum::node::{ApiSender, ExternalMessage, NodeApiConfig};
 use exonum::storage::{MemoryDB, Patch, Snapshot};
 use exonum::messages::RawMessage;
 
@@ -723,9 +724,10 @@ impl TestKit {
         &self.network.validators()[id.0 as usize]
     }
 
-    /// Returns sufficient number of validators for the Byzantine Fault Tolerance consensus.
-    pub fn majority_count(&self) -> usize {
-        NodeState::byzantine_majority_count(self.network().validators().len())
+    /// Returns sufficient voting power for the Byzantine Fault Tolerance consensus,
+    /// i.e., +2/3 of the total voting power of the actual validators.
+    pub fn majority_count(&self) -> u64 {
+        self.actual_configuration().majority_voting_power()
     }
 
     /// Returns the leader on the current height. At the moment first validator.
@@ -202,6 +202,7 @@ impl TestNode {
         ValidatorKeys {
             consensus_key: self.consensus_public_key,
             service_key: self.service_public_key,
+            voting_power: None,
         }
     }
 
@@ -283,7 +284,7 @@ impl TestNetworkConfiguration {
 
     /// Modifies number of votes required to accept a new consensus configuration
     /// (see majority_count field of the StoredConfiguration documentation).
-    pub fn set_majority_count(&mut self, majority_count: Option<u16>) {
+    pub fn set_majority_count(&mut self, majority_count: Option<u64>) {
         self.stored_configuration.majority_count = majority_count;
     }
 
@@ -25,6 +25,7 @@ use exonum::storage::{Entry, Fork, Snapshot};
 use exonum::crypto::{Hash, PublicKey};
 use exonum::encoding;
 use exonum::api::{Api, ApiError};
+use exonum::api::openapi::{self, DocumentedRouter, Endpoint};
 use self::iron::Handler;
 use self::iron::prelude::*;
 use self::router::Router;
@@ -182,28 +183,38 @@ impl CounterApi {
         }
     }
 
-    fn wire_private(&self, router: &mut Router) {
+    fn wire_private(&self, router: &mut DocumentedRouter) {
         let self_ = self.clone();
         let reset = move |req: &mut Request| self_.reset(req);
-        router.post("/reset", reset, "reset");
+        let endpoint = Endpoint::post("/reset", "Resets the counter")
+            .request(openapi::any())
+            .response(openapi::object(&[("tx_hash", openapi::hash())]));
+        router.route(endpoint, reset, "reset");
 
         // Expose `get_count` as both private and public endpoint
         // in order to test private gets as well.
         let self_ = self.clone();
         let get_count = move |req: &mut Request| self_.get_count(req);
-        router.get("/count", get_count, "get_count");
+        let endpoint =
+            Endpoint::get("/count", "Returns the counter value").response(openapi::integer());
+        router.route(endpoint, get_count, "get_count");
     }
 }
 
 impl Api for CounterApi {
-    fn wire(&self, router: &mut Router) {
+    fn wire_documented(&self, router: &mut DocumentedRouter) {
         let self_ = self.clone();
         let increment = move |req: &mut Request| self_.increment(req);
-        router.post("/count", increment, "increment");
+        let endpoint = Endpoint::post("/count", "Increments the counter")
+            .request(openapi::any())
+            .response(openapi::object(&[("tx_hash", openapi::hash())]));
+        router.route(endpoint, increment, "increment");
 
         let self_ = self.clone();
         let get_count = move |req: &mut Request| self_.get_count(req);
-        router.get("/count", get_count, "get_count");
+        let endpoint =
+            Endpoint::get("/count", "Returns the counter value").response(openapi::integer());
+        router.route(endpoint, get_count, "get_count");
     }
 }
 
@@ -247,7 +258,23 @@ impl Service for CounterService {
             channel: ctx.node_channel().clone(),
             blockchain: ctx.blockchain().clone(),
         };
-        api.wire_private(&mut router);
+        api.wire_private(&mut DocumentedRouter::new(&mut router));
         Some(Box::new(router))
     }
+
+    fn public_api_endpoints(&self, ctx: &ApiContext) -> Vec<Endpoint> {
+        let api = CounterApi {
+            channel: ctx.node_channel().clone(),
+            blockchain: ctx.blockchain().clone(),
+        };
+        api.endpoints()
+    }
+
+    fn private_api_endpoints(&self, ctx: &ApiContext) -> Vec<Endpoint> {
+        let api = CounterApi {
+            channel: ctx.node_channel().clone(),
+            blockchain: ctx.blockchain().clone(),
+        };
+        openapi::endpoints_of(|router| api.wire_private(router))
+    }
 }
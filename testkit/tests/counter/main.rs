@@ -521,7 +521,12 @@ fn test_explorer_single_block() {
         validators.insert(precommit.validator());
     }
 
-    assert!(validators.len() >= testkit.majority_count());
+    let config = testkit.actual_configuration();
+    let voting_power: u64 = validators
+        .into_iter()
+        .map(|id| config.voting_power_of(id))
+        .sum();
+    assert!(voting_power >= testkit.majority_count());
 }
 
 #[test]
@@ -653,6 +658,169 @@ fn test_explorer_transaction_statuses() {
     );
 }
 
+#[test]
+fn test_explorer_post_raw_transaction() {
+    use exonum::api::public::{TransactionHash, TransactionHex};
+    use exonum::encoding::serialize::ToHex;
+
+    let (mut testkit, api) = init_testkit();
+
+    let tx = {
+        let (pubkey, key) = crypto::gen_keypair();
+        TxIncrement::new(&pubkey, 5, &key)
+    };
+    let body = TransactionHex { tx_body: tx.raw().as_ref().to_hex() };
+    let info: TransactionHash = api.post(ApiKind::Explorer, "v1/transactions", &body);
+    assert_eq!(info.tx_hash, tx.hash());
+
+    testkit.create_block();
+    let counter: u64 = api.get(ApiKind::Service("counter"), "count");
+    assert_eq!(counter, 5);
+
+    // Truncated transaction is rejected.
+    let body = TransactionHex { tx_body: tx.raw().as_ref()[..20].to_hex() };
+    let error: Value = api.post(ApiKind::Explorer, "v1/transactions", &body);
+    assert_eq!(error["description"], "Bad request: Transaction is too short: 20 bytes");
+
+    // Transaction with a corrupted signature is rejected.
+    let mut bytes = tx.raw().as_ref().to_vec();
+    *bytes.last_mut().unwrap() ^= 1;
+    let body = TransactionHex { tx_body: bytes.to_hex() };
+    let error: Value = api.post(ApiKind::Explorer, "v1/transactions", &body);
+    assert_eq!(error["description"], "Bad request: Transaction verification failed");
+}
+
+#[test]
+fn test_explorer_post_transactions_batch() {
+    use exonum::api::public::{SubmissionResult, TransactionHex};
+    use exonum::encoding::serialize::ToHex;
+
+    let (mut testkit, api) = init_testkit();
+
+    let txs: Vec<_> = (1..4)
+        .map(|by| {
+            let (pubkey, key) = crypto::gen_keypair();
+            TxIncrement::new(&pubkey, by, &key)
+        })
+        .collect();
+    let mut body: Vec<_> = txs.iter()
+        .map(|tx| TransactionHex { tx_body: tx.raw().as_ref().to_hex() })
+        .collect();
+    body.push(TransactionHex { tx_body: "invalid".to_owned() });
+
+    let results: Vec<SubmissionResult> =
+        api.post(ApiKind::Explorer, "v1/transactions/batch", &body);
+    assert_eq!(results.len(), 4);
+    for (result, tx) in results.iter().zip(&txs) {
+        assert_eq!(*result, SubmissionResult::Accepted { tx_hash: tx.hash() });
+    }
+    assert_matches!(results[3], SubmissionResult::Rejected { .. });
+
+    testkit.create_block();
+    let counter: u64 = api.get(ApiKind::Service("counter"), "count");
+    assert_eq!(counter, 6);
+}
+
+#[test]
+fn test_explorer_pagination() {
+    use std::collections::HashSet;
+    use exonum::api::public::{CommittedTransaction, Page};
+    use exonum::blockchain::Block;
+
+    fn tx_hashes(page: &Page<CommittedTransaction>) -> HashSet<crypto::Hash> {
+        page.items.iter().map(|tx| tx.tx_hash).collect()
+    }
+
+    let (mut testkit, api) = init_testkit();
+
+    let (pubkey, key) = crypto::gen_keypair();
+    let tx1 = TxIncrement::new(&pubkey, 5, &key);
+    let error_tx = TxIncrement::new(&pubkey, 0, &key);
+    let tx2 = TxIncrement::new(&pubkey, 3, &key);
+    testkit.create_block_with_transactions(txvec![tx1.clone(), error_tx.clone()]);
+    testkit.create_block_with_transactions(txvec![tx2.clone()]);
+
+    let page: Page<Block> = api.get(
+        ApiKind::Explorer,
+        "v1/blocks/page?limit=2&direction=forward",
+    );
+    let heights: Vec<_> = page.items.iter().map(Block::height).collect();
+    assert_eq!(heights, vec![Height(0), Height(1)]);
+    assert_eq!(page.next_cursor, Some(2));
+
+    let page: Page<Block> = api.get(
+        ApiKind::Explorer,
+        "v1/blocks/page?limit=2&direction=forward&cursor=2",
+    );
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.next_cursor, None);
+
+    let page: Page<Block> = api.get(ApiKind::Explorer, "v1/blocks/page?limit=2");
+    let heights: Vec<_> = page.items.iter().map(Block::height).collect();
+    assert_eq!(heights, vec![Height(2), Height(1)]);
+    assert_eq!(page.next_cursor, Some(0));
+
+    let page: Page<CommittedTransaction> = api.get(
+        ApiKind::Explorer,
+        "v1/blocks/1/transactions?limit=1",
+    );
+    assert_eq!(page.items.len(), 1);
+    assert_eq!(page.next_cursor, Some(1));
+
+    let page: Page<CommittedTransaction> =
+        api.get(ApiKind::Explorer, "v1/transactions?limit=10");
+    assert_eq!(
+        tx_hashes(&page),
+        [tx1.hash(), error_tx.hash(), tx2.hash()].iter().cloned().collect()
+    );
+    assert_eq!(page.items[0].tx_hash, tx2.hash());
+
+    let page: Page<CommittedTransaction> =
+        api.get(ApiKind::Explorer, "v1/transactions?limit=10&status=error");
+    assert_eq!(tx_hashes(&page), [error_tx.hash()].iter().cloned().collect());
+
+    let page: Page<CommittedTransaction> = api.get(
+        ApiKind::Explorer,
+        "v1/transactions?limit=10&service_id=1&message_type=0&status=success",
+    );
+    assert_eq!(
+        tx_hashes(&page),
+        [tx1.hash(), tx2.hash()].iter().cloned().collect()
+    );
+
+    let page: Page<CommittedTransaction> =
+        api.get(ApiKind::Explorer, "v1/transactions?limit=10&service_id=2");
+    assert!(page.items.is_empty());
+}
+
+#[test]
+fn test_openapi_document() {
+    let (_, api) = init_testkit();
+
+    let document: Value = api.get(ApiKind::Root, "openapi.json");
+    assert_eq!(document["openapi"], "3.0.0");
+    let paths = document["paths"].as_object().unwrap();
+    let increment = &paths["/api/services/counter/count"]["post"];
+    assert_eq!(increment["summary"], "Increments the counter");
+    assert!(paths["/api/services/counter/count"]["get"].is_object());
+    assert!(paths["/api/explorer/v1/blocks/{height}"]["get"].is_object());
+    assert!(paths["/api/system/v1/mempool"]["get"].is_object());
+    assert!(!paths.contains_key("/api/services/counter/reset"));
+    assert!(!paths.contains_key("/api/system/v1/peers"));
+
+    let document: Value = api.get_private(ApiKind::Root, "openapi.json");
+    let paths = document["paths"].as_object().unwrap();
+    assert!(paths["/api/services/counter/reset"]["post"].is_object());
+    assert!(paths["/api/system/v1/peers"]["post"].is_object());
+    assert!(!paths.contains_key("/api/explorer/v1/blocks/{height}"));
+
+    // Service endpoints are still reachable alongside the document.
+    let counter: u64 = api.get(ApiKind::Service("counter"), "count");
+    assert_eq!(counter, 0);
+
+    api.check_documented_endpoints();
+}
+
 // Make sure that boxed transaction can be used in the `TestKitApi::send`.
 #[test]
 fn test_boxed_tx() {
@@ -12,21 +12,86 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[macro_use]
 extern crate exonum;
 extern crate exonum_testkit;
 #[macro_use]
 extern crate pretty_assertions;
 
 use exonum_testkit::{ApiKind, TestKitBuilder};
-use exonum::api::public::HealthCheckInfo;
-use exonum::helpers::user_agent;
+use exonum::api::public::{HealthCheckInfo, ServiceSchemaInfo};
+use exonum::blockchain::{ExecutionResult, Service, Transaction, TransactionSet};
+use exonum::crypto::{Hash, PublicKey};
+use exonum::encoding;
+use exonum::encoding::schema::{FieldSchema, MessageSchema};
+use exonum::helpers::{user_agent, Height, Round};
+use exonum::messages::RawTransaction;
+use exonum::storage::{Fork, Snapshot};
+
+const SERVICE_ID: u16 = 512;
+
+transactions! {
+    GreetingTransactions {
+        const SERVICE_ID = SERVICE_ID;
+
+        struct TxGreet {
+            author: &PublicKey,
+            text: &str,
+        }
+    }
+}
+
+impl Transaction for TxGreet {
+    fn verify(&self) -> bool {
+        true
+    }
+
+    fn execute(&self, _: &mut Fork) -> ExecutionResult {
+        Ok(())
+    }
+}
+
+struct GreetingService;
+
+impl Service for GreetingService {
+    fn service_id(&self) -> u16 {
+        SERVICE_ID
+    }
+
+    fn service_name(&self) -> &str {
+        "greeting"
+    }
+
+    fn state_hash(&self, _: &Snapshot) -> Vec<Hash> {
+        Vec::new()
+    }
+
+    fn tx_from_raw(&self, raw: RawTransaction) -> Result<Box<Transaction>, encoding::Error> {
+        let tx = GreetingTransactions::tx_from_raw(raw)?;
+        Ok(tx.into())
+    }
+
+    fn message_schemas(&self) -> Vec<MessageSchema> {
+        GreetingTransactions::message_schemas()
+    }
+}
 
 #[test]
 fn test_healthcheck_connectivity_false() {
     let testkit = TestKitBuilder::validator().with_validators(2).create();
     let api = testkit.api();
     let info: HealthCheckInfo = api.get(ApiKind::System, "v1/healthcheck");
-    let expected = HealthCheckInfo { connectivity: false };
+    let expected = HealthCheckInfo {
+        connectivity: false,
+        height: Height::zero(),
+        max_known_height: Height::zero(),
+        in_sync: true,
+        round: Round::zero(),
+        since_last_commit: None,
+        consensus_enabled: true,
+        database_writable: true,
+        healthy: true,
+    };
     assert_eq!(info, expected);
 }
 
@@ -38,3 +103,36 @@ fn test_user_agent_info() {
     let expected = user_agent::get();
     assert_eq!(info, expected);
 }
+
+#[test]
+fn test_schemas_info() {
+    let testkit = TestKitBuilder::validator()
+        .with_service(GreetingService)
+        .create();
+    let api = testkit.api();
+    let info: Vec<ServiceSchemaInfo> = api.get(ApiKind::System, "v1/schemas");
+    let expected = vec![
+        ServiceSchemaInfo {
+            service_id: SERVICE_ID,
+            service_name: "greeting".to_owned(),
+            messages: vec![
+                MessageSchema {
+                    name: "TxGreet".to_owned(),
+                    service_id: SERVICE_ID,
+                    message_id: 0,
+                    body_size: 40,
+                    version: None,
+                    fields: vec![
+                        FieldSchema::new("author", "&PublicKey", 0, 32),
+                        FieldSchema::new("text", "&str", 32, 40),
+                    ],
+                    optional_fields: Vec::new(),
+                    structs: Vec::new(),
+                    enums: Vec::new(),
+                    protobuf_message: None,
+                },
+            ],
+        },
+    ];
+    assert_eq!(info, expected);
+}